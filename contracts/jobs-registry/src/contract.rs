@@ -5,10 +5,11 @@ use crate::querier::deduct_tax;
 use crate::state::{Config, Job, CONFIG, COUNT, CREDITS, JOBS};
 use cosmwasm_std::{
     entry_point, to_binary, Addr, Api, BankMsg, Binary, Coin, CosmosMsg, Deps, DepsMut, Env,
-    MessageInfo, Response, StdResult,
+    MessageInfo, Response, StdError, StdResult, Uint128,
 };
 use cw0::NativeBalance;
 use cw2::set_contract_version;
+use suberra_core::migration::migrate_contract_version;
 
 use crate::error::ContractError;
 use crate::msg::{CountResponse, ExecuteMsg, InstantiateMsg, QueryMsg};
@@ -66,9 +67,12 @@ pub fn execute(
             let contract_addr = deps.api.addr_validate(&contract_address)?;
             try_add_credits(deps, info, contract_addr)
         }
-        ExecuteMsg::WorkReceipt { worker_address } => {
+        ExecuteMsg::WorkReceipt {
+            worker_address,
+            units,
+        } => {
             let worker_addr = deps.api.addr_validate(&worker_address)?;
-            try_work_receipt(deps, info, worker_addr)
+            try_work_receipt(deps, info, worker_addr, units.unwrap_or(1))
         }
         ExecuteMsg::UpdateAdmins { admins } => try_update_admins(deps, info, admins),
         ExecuteMsg::SetBaseFee { base_fee } => try_set_base_fee(deps, info, base_fee),
@@ -77,8 +81,16 @@ pub fn execute(
 
 #[allow(dead_code)]
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
-    Ok(Response::default())
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let previous_version =
+        migrate_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION, &[])?;
+
+    // no storage changes to apply yet. Upgrade steps are gated on `previous_version`
+
+    Ok(Response::new()
+        .add_attribute("method", "migrate")
+        .add_attribute("previous_version", previous_version.to_string())
+        .add_attribute("version", CONTRACT_VERSION))
 }
 
 pub fn try_remove_job(
@@ -109,14 +121,30 @@ pub fn try_remove_job(
 
 /// try_work_receipt : called when the work is completed by Workers
 /// This transfers the credits applicable to the worker_address.
+/// The reward is the base fee multiplied by the number of `units` of work completed (eg. number of charges in a batch)
 pub fn try_work_receipt(
     deps: DepsMut,
     info: MessageInfo,
     worker_address: Addr,
+    units: u64,
 ) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
 
-    let base_fee = &config.base_fee;
+    if units == 0 {
+        return Err(ContractError::InvalidParam);
+    }
+
+    // scale the base fee by the number of units of work done
+    let mut base_fee: Vec<Coin> = vec![];
+    for coin in config.base_fee.iter() {
+        base_fee.push(Coin {
+            denom: coin.denom.clone(),
+            amount: coin
+                .amount
+                .checked_mul(Uint128::from(units))
+                .map_err(StdError::from)?,
+        });
+    }
 
     let job = JOBS.may_load(deps.storage, &info.sender)?;
     match job {
@@ -168,7 +196,8 @@ pub fn try_work_receipt(
                 .map(|coin: &Coin| coin.to_string())
                 .collect::<Vec<String>>()
                 .join(","),
-        ))
+        )
+        .add_attribute("units", units.to_string()))
 }
 
 /// try_set_base_fee sets the base fee that can be claimable by the Workers. Once set, jobs must be funded with credits.
@@ -22,6 +22,8 @@ pub enum ExecuteMsg {
     },
     WorkReceipt {
         worker_address: String,
+        /// number of units of work completed in this call. Defaults to 1 if not provided
+        units: Option<u64>,
     },
     SetBaseFee {
         base_fee: Vec<Coin>,
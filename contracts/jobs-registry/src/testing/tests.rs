@@ -10,6 +10,7 @@ use crate::msg::JobInfo;
 use crate::msg::QueryMsg;
 use cosmwasm_std::testing::{mock_env, mock_info};
 use cosmwasm_std::Coin;
+use cosmwasm_std::{BankMsg, CosmosMsg};
 use cosmwasm_std::Decimal;
 use cosmwasm_std::Uint128;
 use cosmwasm_std::{coins, from_binary};
@@ -302,6 +303,7 @@ fn work_receipt() {
     let info = mock_info("job1", &vec![]);
     let msg = ExecuteMsg::WorkReceipt {
         worker_address: String::from("worker1"),
+        units: None,
     };
     let res = execute(deps.as_mut(), mock_env(), info, msg);
     match res {
@@ -320,6 +322,7 @@ fn work_receipt() {
     let info = mock_info("job1", &vec![]);
     let msg = ExecuteMsg::WorkReceipt {
         worker_address: String::from("worker1"),
+        units: None,
     };
     let res = execute(deps.as_mut(), mock_env(), info, msg);
     match res {
@@ -338,6 +341,7 @@ fn work_receipt() {
     let info = mock_info("job1", &vec![]);
     let msg = ExecuteMsg::WorkReceipt {
         worker_address: String::from("worker1"),
+        units: None,
     };
     let res = execute(deps.as_mut(), mock_env(), info, msg);
     match res {
@@ -361,6 +365,7 @@ fn work_receipt() {
     let info = mock_info("job1", &vec![]);
     let msg = ExecuteMsg::WorkReceipt {
         worker_address: String::from("worker1"),
+        units: None,
     };
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
@@ -384,6 +389,7 @@ fn work_receipt() {
     let info = mock_info("job1", &vec![]);
     let msg = ExecuteMsg::WorkReceipt {
         worker_address: String::from("worker1"),
+        units: None,
     };
     let res = execute(deps.as_mut(), mock_env(), info, msg);
     match res {
@@ -391,3 +397,81 @@ fn work_receipt() {
         _ => panic!("Should not be able to work without sufficient credits"),
     }
 }
+
+#[test]
+fn work_receipt_with_units() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("creator", &[]);
+    instantiate(deps.as_mut(), mock_env(), info.clone(), InstantiateMsg {}).unwrap();
+
+    let msg = ExecuteMsg::AddJob {
+        contract_address: String::from("job1"),
+        name: String::from("TestJob1"),
+    };
+    execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    // set base_fee to 0.1 ust
+    let msg = ExecuteMsg::SetBaseFee {
+        base_fee: vec![Coin::new(100000, "uusd")],
+    };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // credits for 3 units of work
+    let info = mock_info("merchant", &coins(300000, "uusd"));
+    let msg = ExecuteMsg::AddCredits {
+        contract_address: String::from("job1"),
+    };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    deps.querier.with_tax(
+        Decimal::zero(),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+
+    // zero units of work is not allowed
+    let info = mock_info("job1", &[]);
+    let msg = ExecuteMsg::WorkReceipt {
+        worker_address: String::from("worker1"),
+        units: Some(0),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    match res {
+        Err(ContractError::InvalidParam {}) => {}
+        _ => panic!("Should not be able to submit zero units of work"),
+    }
+
+    // more units than the credits can pay for
+    let msg = ExecuteMsg::WorkReceipt {
+        worker_address: String::from("worker1"),
+        units: Some(4),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    match res {
+        Err(ContractError::JobInsufficientCredits {}) => {}
+        _ => panic!("Should not be able to work without sufficient credits"),
+    }
+
+    // reward is scaled by the number of units
+    let msg = ExecuteMsg::WorkReceipt {
+        worker_address: String::from("worker1"),
+        units: Some(3),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: String::from("worker1"),
+            amount: coins(300000, "uusd"),
+        })
+    );
+    assert_eq!("300000uusd", res.attributes[3].value);
+    assert_eq!("3", res.attributes[4].value);
+
+    let msg = QueryMsg::GetJobCredits {
+        contract_address: String::from("job1"),
+    };
+    let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+    let value: NativeBalance = from_binary(&res).unwrap();
+    assert!(value.is_empty());
+}
@@ -13,9 +13,11 @@ use cosmwasm_std::{
 use cw2::set_contract_version;
 use cw_storage_plus::{Bound, U64Key};
 use protobuf::Message;
-use suberra_core::msg::{JobsRegistryExecuteMsg, MigrateMsg, ProductInstantiateMsg};
+use suberra_core::migration::{migrate_contract_version, Version};
+use suberra_core::msg::{JobsRegistryExecuteMsg, ProductInstantiateMsg};
 use suberra_core::product_factory::{
-    ConfigResponse, CreateProductExecuteMsg, ExecuteMsg, InstantiateMsg, ProductsResponse, QueryMsg,
+    ConfigResponse, CreateProductExecuteMsg, ExecuteMsg, InstantiateMsg, MigrateMsg,
+    ProductsResponse, QueryMsg,
 };
 
 // version info for migration info
@@ -49,8 +51,10 @@ pub fn instantiate(
         is_restricted: true, // is_restricted is turned on by default. Only release it when platform and automation is more stablised
         min_amount_per_interval: msg.min_amount_per_interval,
         min_unit_interval_hour: msg.min_unit_interval_hour,
+        min_price_change_notice_hour: msg.min_price_change_notice_hour,
         fee_address: deps.api.addr_validate(&msg.fee_address)?,
         job_registry_address: deps.api.addr_validate(&msg.job_registry_address)?,
+        subwallet_factory_address: Some(deps.api.addr_validate(&msg.subwallet_factory_address)?),
     };
 
     CONFIG.save(deps.storage, &config)?;
@@ -79,8 +83,10 @@ pub fn execute(
             new_min_protocol_fee,
             new_min_amount_per_interval,
             new_min_unit_interval_hour,
+            new_min_price_change_notice_hour,
             new_fee_address,
             new_job_registry_address,
+            new_subwallet_factory_address,
         } => update_config(
             deps,
             env,
@@ -92,8 +98,10 @@ pub fn execute(
             new_min_protocol_fee,
             new_min_amount_per_interval,
             new_min_unit_interval_hour,
+            new_min_price_change_notice_hour,
             new_fee_address,
             new_job_registry_address,
+            new_subwallet_factory_address,
         ),
     }
 }
@@ -115,8 +123,10 @@ pub fn update_config(
     new_min_protocol_fee: Option<Uint256>,
     new_min_amount_per_interval: Option<Uint256>,
     new_min_unit_interval_hour: Option<u64>,
+    new_min_price_change_notice_hour: Option<u64>,
     new_fee_address: Option<String>,
     new_job_registry_address: Option<String>,
+    new_subwallet_factory_address: Option<String>,
 ) -> Result<Response, ContractError> {
     let mut config: Config = CONFIG.load(deps.storage)?;
     // permission check
@@ -172,6 +182,14 @@ pub fn update_config(
         ));
     }
 
+    if let Some(new_min_price_change_notice_hour) = new_min_price_change_notice_hour {
+        config.min_price_change_notice_hour = new_min_price_change_notice_hour;
+        attributes.push(attr(
+            "new_min_price_change_notice_hour",
+            new_min_price_change_notice_hour.to_string(),
+        ));
+    }
+
     if let Some(new_product_code_id) = new_product_code_id {
         config.product_code_id = new_product_code_id;
         attributes.push(attr("new_product_code_id", new_product_code_id.to_string()));
@@ -187,6 +205,17 @@ pub fn update_config(
         attributes.push(attr("new_job_registry_address", new_job_registry_address));
     }
 
+    if let Some(new_subwallet_factory_address) = new_subwallet_factory_address {
+        config.subwallet_factory_address = Some(
+            deps.api
+                .addr_validate(new_subwallet_factory_address.as_str())?,
+        );
+        attributes.push(attr(
+            "new_subwallet_factory_address",
+            new_subwallet_factory_address,
+        ));
+    }
+
     CONFIG.save(deps.storage, &config)?;
 
     Ok(Response::new().add_attributes(attributes))
@@ -220,12 +249,34 @@ pub fn execute_create_product(
         return Err(ContractError::InvalidParam {});
     }
 
+    // every additional plan offered by the product is subjected to the same minimums
+    if let Some(plans) = &param.plans {
+        if plans.iter().any(|plan| {
+            plan.unit_amount < config.min_amount_per_interval
+                || plan.unit_interval_hour < config.min_unit_interval_hour
+        }) {
+            return Err(ContractError::InvalidParam {});
+        }
+    }
+
     let product: ProductInstantiateMsg = ProductInstantiateMsg {
         receiver_address: param.receiver_address,
         unit_amount: param.unit_amount,
         initial_amount: param.initial_amount,
         unit_interval_hour: param.unit_interval_hour,
+        trial_period_hour: param.trial_period_hour,
+        plans: param.plans,
+        max_amount_chargeable: param.max_amount_chargeable,
+        usage_tiers: param.usage_tiers,
+        membership_nft: param.membership_nft,
+        billing_asset: param.billing_asset,
+        revenue_splits: param.revenue_splits,
+        billing_calendar: param.billing_calendar,
+        accept_any_payer: param.accept_any_payer,
         additional_grace_period_hour: param.additional_grace_period_hour,
+        retry_schedule_hour: param.retry_schedule_hour,
+        max_pause_duration_hour: param.max_pause_duration_hour,
+        max_pauses_per_year: param.max_pauses_per_year,
         uri: param.uri,
         owner: info.sender.to_string(),
         admins: param.admins,
@@ -381,20 +432,48 @@ fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
         min_protocol_fee: config.min_protocol_fee,
         min_amount_per_interval: config.min_amount_per_interval,
         min_unit_interval_hour: config.min_unit_interval_hour,
+        min_price_change_notice_hour: config.min_price_change_notice_hour,
         fee_address: config.fee_address.to_string(),
         job_registry_address: config.job_registry_address.to_string(),
+        subwallet_factory_address: config.subwallet_factory_address.map(|v| v.to_string()),
     };
     Ok(resp)
 }
 
-/// Used for migration of contract. Returns the default object of type [`Response`].
+/// Used for migration of contract. Applies the upgrade steps of every version after the stored contract version.
+/// Refuses to migrate to an older version.
 /// ## Params
-/// * **_deps** is the object of type [`Deps`].
+/// * **deps** is the object of type [`DepsMut`].
 ///
 /// * **_env** is the object of type [`Env`].
 ///
-/// * **_msg** is the object of type [`MigrateMsg`].
+/// * **msg** is the object of type [`MigrateMsg`]. Sets the subwallet factory that products check their payers against, if given
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
-    Ok(Response::default())
+pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> StdResult<Response> {
+    let previous_version =
+        migrate_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION, &[])?;
+
+    let mut config = CONFIG.load(deps.storage)?;
+    let mut attributes = vec![
+        attr("method", "migrate"),
+        attr("previous_version", previous_version.to_string()),
+        attr("version", CONTRACT_VERSION),
+    ];
+
+    if let Some(subwallet_factory_address) = msg.subwallet_factory_address {
+        config.subwallet_factory_address =
+            Some(deps.api.addr_validate(&subwallet_factory_address)?);
+        CONFIG.save(deps.storage, &config)?;
+        attributes.push(attr("subwallet_factory_address", subwallet_factory_address));
+    }
+
+    // products reject every payer while the factory has no subwallet factory, so factories from before
+    // the subwallet check was introduced must be given one
+    if previous_version < Version::new(0, 3, 0) && config.subwallet_factory_address.is_none() {
+        return Err(StdError::generic_err(
+            "subwallet_factory_address must be set when migrating from before 0.3.0",
+        ));
+    }
+
+    Ok(Response::new().add_attributes(attributes))
 }
@@ -22,10 +22,16 @@ pub struct Config {
     pub min_amount_per_interval: Uint256,
     /// minimum unit interval in hours
     pub min_unit_interval_hour: u64,
+    /// minimum notice in hours between scheduling a price change of a product and the change taking effect
+    #[serde(default)]
+    pub min_price_change_notice_hour: u64,
     /// address that will be receiving the protocol fees
     pub fee_address: Addr,
     /// address for the job registry contract
     pub job_registry_address: Addr,
+    /// address for the subwallet factory contract. Unset for factories instantiated before it was introduced
+    #[serde(default)]
+    pub subwallet_factory_address: Option<Addr>,
 }
 
 pub const CONFIG: Item<Config> = Item::new("config");
@@ -1,10 +1,16 @@
 use crate::contract;
 use crate::contract::instantiate;
 use crate::error::ContractError;
+use crate::state::CONFIG;
 use cosmwasm_bignumber::Uint256;
-use cosmwasm_std::attr;
 use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-use suberra_core::product_factory::{CreateProductExecuteMsg, ExecuteMsg, InstantiateMsg};
+use cosmwasm_std::{attr, from_binary, Addr, CosmosMsg, StdError, StdResult, WasmMsg};
+use cw2::set_contract_version;
+use suberra_core::msg::ProductInstantiateMsg;
+use suberra_core::product_factory::{
+    CreateProductExecuteMsg, ExecuteMsg, InstantiateMsg, MigrateMsg,
+};
+use suberra_core::subscriptions::PlanInfo;
 
 #[test]
 fn update_config() {
@@ -16,8 +22,10 @@ fn update_config() {
         min_amount_per_interval: Uint256::from(100u64),
         min_protocol_fee: Uint256::zero(),
         min_unit_interval_hour: 168, // one week
+        min_price_change_notice_hour: 168,
         fee_address: "owner".to_string(),
         job_registry_address: "jobs".to_string(),
+        subwallet_factory_address: "subwallet_factory".to_string(),
     };
 
     let info = mock_info("deployer", &[]);
@@ -28,10 +36,21 @@ fn update_config() {
     let product = CreateProductExecuteMsg {
         receiver_address: "receiver".to_string(),
         additional_grace_period_hour: None,
+        retry_schedule_hour: None,
+        max_pause_duration_hour: None,
+        max_pauses_per_year: None,
         unit_amount: Uint256::from(123u128),
         initial_amount: Uint256::from(123u128),
         unit_interval_hour: 2592000u64,
+        trial_period_hour: None,
+        plans: None,
         max_amount_chargeable: Some(Uint256::from(123u128)),
+        usage_tiers: None,
+        membership_nft: None,
+        billing_asset: None,
+        revenue_splits: None,
+        billing_calendar: None,
+        accept_any_payer: None,
         admins: Vec::new(),
         mutable: false,
         uri: "{\"image_url\": \"www.google.com\" }".to_string(),
@@ -54,9 +73,11 @@ fn update_config() {
         new_owner: None,
         new_fee_address: None,
         new_job_registry_address: None,
+        new_subwallet_factory_address: None,
         new_min_amount_per_interval: None,
         new_min_protocol_fee: None,
         new_min_unit_interval_hour: None,
+        new_min_price_change_notice_hour: None,
         new_product_code_id: None,
         new_protocol_fee_bps: None,
         new_is_restricted: Some(false),
@@ -68,3 +89,179 @@ fn update_config() {
         contract::execute(deps.as_mut(), mock_env(), alice_info.clone(), msg.clone()).unwrap();
     assert_eq!(res.attributes, vec![attr("action", "create_product")]);
 }
+
+#[test]
+fn create_product_with_invalid_plan() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        product_code_id: 1u64,
+        protocol_fee_bps: 100,
+        min_amount_per_interval: Uint256::from(100u64),
+        min_protocol_fee: Uint256::zero(),
+        min_unit_interval_hour: 168, // one week
+        min_price_change_notice_hour: 168,
+        fee_address: "owner".to_string(),
+        job_registry_address: "jobs".to_string(),
+        subwallet_factory_address: "subwallet_factory".to_string(),
+    };
+
+    let info = mock_info("deployer", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    // additional plan has a billing cycle that is shorter than the minimum
+    let product = CreateProductExecuteMsg {
+        receiver_address: "receiver".to_string(),
+        additional_grace_period_hour: None,
+        retry_schedule_hour: None,
+        max_pause_duration_hour: None,
+        max_pauses_per_year: None,
+        unit_amount: Uint256::from(123u128),
+        initial_amount: Uint256::from(123u128),
+        unit_interval_hour: 2592000u64,
+        trial_period_hour: None,
+        plans: Some(vec![PlanInfo {
+            plan_id: 1,
+            unit_amount: Uint256::from(123u128),
+            initial_amount: Uint256::zero(),
+            unit_interval_hour: 24u64,
+            trial_period_hour: None,
+            billing_calendar: None,
+        }]),
+        max_amount_chargeable: None,
+        usage_tiers: None,
+        membership_nft: None,
+        billing_asset: None,
+        revenue_splits: None,
+        billing_calendar: None,
+        accept_any_payer: None,
+        admins: Vec::new(),
+        mutable: false,
+        uri: "{\"image_url\": \"www.google.com\" }".to_string(),
+    };
+    let msg = ExecuteMsg::CreateProduct {
+        product_info: product,
+    };
+
+    let res = contract::execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::InvalidParam {}) => {}
+        _ => panic!("Contract should return an invalid param error"),
+    }
+}
+
+#[test]
+fn create_product_with_max_amount_chargeable() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        product_code_id: 1u64,
+        protocol_fee_bps: 100,
+        min_amount_per_interval: Uint256::from(100u64),
+        min_protocol_fee: Uint256::zero(),
+        min_unit_interval_hour: 168, // one week
+        min_price_change_notice_hour: 168,
+        fee_address: "owner".to_string(),
+        job_registry_address: "jobs".to_string(),
+        subwallet_factory_address: "subwallet_factory".to_string(),
+    };
+
+    let info = mock_info("deployer", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    let product = CreateProductExecuteMsg {
+        receiver_address: "receiver".to_string(),
+        additional_grace_period_hour: None,
+        retry_schedule_hour: None,
+        max_pause_duration_hour: None,
+        max_pauses_per_year: None,
+        unit_amount: Uint256::from(123u128),
+        initial_amount: Uint256::from(123u128),
+        unit_interval_hour: 2592000u64,
+        trial_period_hour: None,
+        plans: None,
+        max_amount_chargeable: Some(Uint256::from(1230u128)),
+        usage_tiers: None,
+        membership_nft: None,
+        billing_asset: None,
+        revenue_splits: None,
+        billing_calendar: None,
+        accept_any_payer: None,
+        admins: Vec::new(),
+        mutable: false,
+        uri: "{\"image_url\": \"www.google.com\" }".to_string(),
+    };
+    let msg = ExecuteMsg::CreateProduct {
+        product_info: product,
+    };
+
+    // the cap is passed on to the product contract
+    let res = contract::execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let product_msg: ProductInstantiateMsg = match &res.messages[0].msg {
+        CosmosMsg::Wasm(WasmMsg::Instantiate { msg, .. }) => from_binary(msg).unwrap(),
+        _ => panic!("Must instantiate the product"),
+    };
+    assert_eq!(
+        product_msg.max_amount_chargeable,
+        Some(Uint256::from(1230u128))
+    );
+}
+
+#[test]
+fn migrate_sets_subwallet_factory() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        product_code_id: 1u64,
+        protocol_fee_bps: 100,
+        min_amount_per_interval: Uint256::from(100u64),
+        min_protocol_fee: Uint256::zero(),
+        min_unit_interval_hour: 168, // one week
+        min_price_change_notice_hour: 168,
+        fee_address: "owner".to_string(),
+        job_registry_address: "jobs".to_string(),
+        subwallet_factory_address: "subwallet_factory".to_string(),
+    };
+    let info = mock_info("deployer", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // rewind to a factory from before the subwallet factory was introduced
+    CONFIG
+        .update(&mut deps.storage, |mut config| -> StdResult<_> {
+            config.subwallet_factory_address = None;
+            Ok(config)
+        })
+        .unwrap();
+    set_contract_version(&mut deps.storage, "crates.io:product-factory", "0.2.0").unwrap();
+
+    // the subwallet factory must be given when migrating from before 0.3.0
+    let res = contract::migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            subwallet_factory_address: None,
+        },
+    );
+    match res {
+        Err(StdError::GenericErr { .. }) => {}
+        _ => panic!("Must return generic error"),
+    }
+
+    let res = contract::migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            subwallet_factory_address: Some("subwallet_factory".to_string()),
+        },
+    )
+    .unwrap();
+    assert!(res
+        .attributes
+        .contains(&attr("subwallet_factory_address", "subwallet_factory")));
+
+    let config = CONFIG.load(&deps.storage).unwrap();
+    assert_eq!(
+        config.subwallet_factory_address,
+        Some(Addr::unchecked("subwallet_factory"))
+    );
+}
@@ -47,8 +47,10 @@ fn proper_initialization_factory() {
         min_amount_per_interval: Uint256::from(100u64),
         min_protocol_fee: Uint256::zero(),
         min_unit_interval_hour: 168, // one week
+        min_price_change_notice_hour: 168,
         fee_address: "owner".to_string(),
         job_registry_address: "jobs".to_string(),
+        subwallet_factory_address: "subwallet_factory".to_string(),
     };
 
     let factory_instance = app
@@ -92,10 +94,21 @@ fn factory_creates_product() {
     let product = CreateProductExecuteMsg {
         receiver_address: "receiver".to_string(),
         additional_grace_period_hour: None,
+        retry_schedule_hour: None,
+        max_pause_duration_hour: None,
+        max_pauses_per_year: None,
         unit_amount: Uint256::from(123u128),
         initial_amount: Uint256::from(123u128),
         unit_interval_hour: 2592000u64,
+        trial_period_hour: None,
+        plans: None,
         max_amount_chargeable: Some(Uint256::from(123u128)),
+        usage_tiers: None,
+        membership_nft: None,
+        billing_asset: None,
+        revenue_splits: None,
+        billing_calendar: None,
+        accept_any_payer: None,
         admins: Vec::new(),
         mutable: false,
         uri: "{\"image_url\": \"www.google.com\" }".to_string(),
@@ -166,8 +179,10 @@ fn test_update_config() {
         min_amount_per_interval: Uint256::from(100u64),
         min_protocol_fee: Uint256::zero(),
         min_unit_interval_hour: 168, // one week
+        min_price_change_notice_hour: 168,
         fee_address: "owner".to_string(),
         job_registry_address: "jobs".to_string(),
+        subwallet_factory_address: "subwallet_factory".to_string(),
     };
 
     let factory_instance = app
@@ -187,9 +202,11 @@ fn test_update_config() {
         new_is_restricted: None,
         new_fee_address: Some("fee2".to_string()),
         new_job_registry_address: Some("jobs2".to_string()),
+        new_subwallet_factory_address: Some("subwallet_factory2".to_string()),
         new_product_code_id: Some(5u64),
         new_protocol_fee_bps: Some(500u64),
         new_min_unit_interval_hour: Some(24u64),
+        new_min_price_change_notice_hour: Some(72u64),
         new_min_protocol_fee: Some(Uint256::zero()),
         new_min_amount_per_interval: None,
     };
@@ -210,9 +227,11 @@ fn test_update_config() {
             attr("new_protocol_fee_bps", "500"),
             attr("new_min_protocol_fee", "0"),
             attr("new_min_unit_interval_hour", "24"),
+            attr("new_min_price_change_notice_hour", "72"),
             attr("new_product_code_id", "5"),
             attr("new_fee_address", "fee2"),
-            attr("new_job_registry_address", "jobs2")
+            attr("new_job_registry_address", "jobs2"),
+            attr("new_subwallet_factory_address", "subwallet_factory2")
         ]
     );
 
@@ -232,8 +251,10 @@ fn test_update_config() {
         min_amount_per_interval: Uint256::from(100u64),
         min_protocol_fee: Uint256::zero(),
         min_unit_interval_hour: 24,
+        min_price_change_notice_hour: 72,
         fee_address: "fee2".to_string(),
         job_registry_address: "jobs2".to_string(),
+        subwallet_factory_address: Some("subwallet_factory2".to_string()),
     };
 
     assert_eq!(res, expected);
@@ -269,10 +290,21 @@ fn test_query_products() {
     let product = CreateProductExecuteMsg {
         receiver_address: "receiver".to_string(),
         additional_grace_period_hour: None,
+        retry_schedule_hour: None,
+        max_pause_duration_hour: None,
+        max_pauses_per_year: None,
         unit_amount: Uint256::from(123u128),
         initial_amount: Uint256::from(123u128),
         unit_interval_hour: 2592000u64,
+        trial_period_hour: None,
+        plans: None,
         max_amount_chargeable: Some(Uint256::from(123u128)),
+        usage_tiers: None,
+        membership_nft: None,
+        billing_asset: None,
+        revenue_splits: None,
+        billing_calendar: None,
+        accept_any_payer: None,
         admins: Vec::new(),
         mutable: false,
         uri: "{\"image_url\": \"www.google.com\" }".to_string(),
@@ -373,8 +405,10 @@ fn instantiate_contracts(app: &mut TerraApp, owner: Addr) -> (Addr, Addr, u64) {
         min_amount_per_interval: Uint256::from(100u64),
         min_protocol_fee: Uint256::zero(),
         min_unit_interval_hour: 168u64,
+        min_price_change_notice_hour: 168,
         fee_address: owner.to_string(),
         job_registry_address: jobs_instance.to_string(),
+        subwallet_factory_address: "subwallet_factory".to_string(),
     };
 
     let factory_instance = app
@@ -405,11 +439,14 @@ fn store_factory_code(app: &mut TerraApp) -> u64 {
 }
 
 fn store_product_code(app: &mut TerraApp) -> u64 {
-    let product_contract = Box::new(ContractWrapper::new_with_empty(
-        sub1_fixed_recurring_subscriptions::contract::execute,
-        sub1_fixed_recurring_subscriptions::contract::instantiate,
-        sub1_fixed_recurring_subscriptions::contract::query,
-    ));
+    let product_contract = Box::new(
+        ContractWrapper::new_with_empty(
+            sub1_fixed_recurring_subscriptions::contract::execute,
+            sub1_fixed_recurring_subscriptions::contract::instantiate,
+            sub1_fixed_recurring_subscriptions::contract::query,
+        )
+        .with_reply_empty(sub1_fixed_recurring_subscriptions::contract::reply),
+    );
 
     app.store_code(product_contract)
 }
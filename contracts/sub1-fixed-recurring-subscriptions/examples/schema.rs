@@ -4,10 +4,13 @@ use std::fs::create_dir_all;
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
 use sub1_fixed_recurring_subscriptions::msg::{
-    ConfigResponse, ExecuteMsg, QueryMsg, SubscriptionInfoResponse, SubscriptionsResponse,
+    AllNftInfoResponse, ConfigResponse, ContractInfoResponse, CouponResponse, CouponsResponse,
+    Cw20HookMsg, DueSubscriptionsResponse, ExecuteMsg, NftInfoResponse, NumTokensResponse,
+    OwnerOfResponse, PaymentsResponse, PlanResponse, PlansResponse, QueryMsg, StatsResponse,
+    SubscriptionInfoResponse, SubscriptionsResponse, TokensResponse, UsageResponse,
 };
 use sub1_fixed_recurring_subscriptions::state::Config;
-use suberra_core::msg::ProductInstantiateMsg;
+use suberra_core::msg::{ProductInstantiateMsg, SubscriptionHookExecuteMsg};
 
 fn main() {
     let mut out_dir = current_dir().unwrap();
@@ -17,9 +20,25 @@ fn main() {
 
     export_schema(&schema_for!(ProductInstantiateMsg), &out_dir);
     export_schema(&schema_for!(ExecuteMsg), &out_dir);
+    export_schema(&schema_for!(Cw20HookMsg), &out_dir);
+    export_schema(&schema_for!(SubscriptionHookExecuteMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
     export_schema(&schema_for!(Config), &out_dir);
     export_schema(&schema_for!(SubscriptionInfoResponse), &out_dir);
     export_schema(&schema_for!(ConfigResponse), &out_dir);
     export_schema(&schema_for!(SubscriptionsResponse), &out_dir);
+    export_schema(&schema_for!(DueSubscriptionsResponse), &out_dir);
+    export_schema(&schema_for!(PlanResponse), &out_dir);
+    export_schema(&schema_for!(PlansResponse), &out_dir);
+    export_schema(&schema_for!(CouponResponse), &out_dir);
+    export_schema(&schema_for!(CouponsResponse), &out_dir);
+    export_schema(&schema_for!(UsageResponse), &out_dir);
+    export_schema(&schema_for!(PaymentsResponse), &out_dir);
+    export_schema(&schema_for!(StatsResponse), &out_dir);
+    export_schema(&schema_for!(OwnerOfResponse), &out_dir);
+    export_schema(&schema_for!(NumTokensResponse), &out_dir);
+    export_schema(&schema_for!(ContractInfoResponse), &out_dir);
+    export_schema(&schema_for!(NftInfoResponse), &out_dir);
+    export_schema(&schema_for!(AllNftInfoResponse), &out_dir);
+    export_schema(&schema_for!(TokensResponse), &out_dir);
 }
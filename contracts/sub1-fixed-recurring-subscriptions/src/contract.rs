@@ -1,21 +1,35 @@
 use crate::error::ContractError;
-use crate::msg::WorkPayload;
 use crate::msg::{
-    ConfigResponse, ExecuteMsg, JobsRegistryExecuteMsg, QueryMsg, SubscriptionInfoResponse,
-    SubscriptionsResponse,
+    AllNftInfoResponse, ConfigResponse, ContractInfoResponse, CouponResponse, CouponsResponse,
+    DueSubscriptionsResponse, ExecuteMsg, JobsRegistryExecuteMsg, MembershipMetadata, MigrateMsg,
+    NftInfoResponse, NumTokensResponse, OwnerOfResponse, PaymentResponse, PaymentsResponse,
+    PlanResponse, PlansResponse, PriceChangeResponse, QueryMsg, StatsResponse,
+    SubscriptionInfoResponse, SubscriptionsResponse, TeamResponse, TokensResponse,
+    UsageRecordResponse, UsageResponse,
 };
+use crate::msg::{BatchWorkPayload, Cw20HookMsg, WorkPayload};
 use crate::querier::get_job_registry;
 use crate::state::{
-    create_subscription, read_config, store_config, Config, SubscriptionInfo, SUBSCRIPTIONS,
+    burn_membership, create_subscription, memberships, migrate_legacy_config, mint_membership,
+    read_config, read_plan, rebuild_stats, record_payment, record_revenue, remove_team,
+    store_config, subscription_due_time, subscription_status_key, subscriptions,
+    update_subscriber_stats, Config, Coupon, MembershipToken, Payment, PendingCharge, Plan,
+    PriceChange, RevenueSplit, Stats, SubscriptionInfo, SubscriptionStatus, UsageRecord,
+    ACTIVE_STATUS_KEY, CANCELLED_STATUS_KEY, COMPLETED_STATUS_KEY, COUPONS, DEFAULT_RETRY_SCHEDULE,
+    MEMBERSHIP_COUNT, PAST_DUE_STATUS_KEY, PAYMENTS, PENDING_CHARGES, PLANS, PLAN_SUBSCRIBERS,
+    PREPAID_BALANCES, REMOVED_SUBSCRIPTIONS, STATS, STATUS_KEYS, TEAMS, TEAM_MEMBERS, TRIALS,
 };
 use cosmwasm_bignumber::{Decimal256, Uint256};
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    attr, from_binary, to_binary, Addr, Api, Binary, CosmosMsg, Deps, DepsMut, Empty, Env,
-    MessageInfo, Order, Response, StdResult, Storage, Timestamp, Uint128, WasmMsg,
+    attr, coins, from_binary, to_binary, Addr, Api, Attribute, BankMsg, Binary, Coin,
+    ContractResult, CosmosMsg, Deps, DepsMut, Empty, Env, Event, MessageInfo, Order, Reply,
+    Response, StdError, StdResult, Storage, SubMsg, Timestamp, Uint128, WasmMsg,
 };
-use cw_storage_plus::Bound;
+use cw2::set_contract_version;
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use cw_storage_plus::{Bound, PrimaryKey, U64Key};
 
 use admin_core::{
     contract::{instantiate as whitelist_instantiate, query_admin_list, query_owner},
@@ -24,19 +38,56 @@ use admin_core::{
     state::ADMIN_CONFIG,
 };
 
-use suberra_core::msg::{MigrateMsg, ProductInstantiateMsg, SubWalletExecuteMsg};
-use suberra_core::querier::query_product_factory_config;
-use suberra_core::subscriptions::Discount;
+use suberra_core::asset::AssetInfo;
+use suberra_core::migration::{migrate_contract_version, Version};
+use suberra_core::msg::{ProductInstantiateMsg, SubWalletExecuteMsg, SubscriptionHookMsg};
+use suberra_core::product_factory::ConfigResponse as ProductFactoryConfigResponse;
+use suberra_core::querier::{query_product_factory_config, query_subwallet_owner};
+use suberra_core::subscriptions::{
+    BillingCalendar, Discount, DiscountType, PlanInfo, PriceTier, RevenueSplitInfo, DEFAULT_PLAN_ID,
+};
 use suberra_core::util::optional_addr_validate;
 
+const CONTRACT_NAME: &str = "crates.io:sub1-fixed-recurring-subscriptions";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+// contracts instantiated before sub1 stored its own contract version only have the version stored by admin-core
+const LEGACY_CONTRACT_NAME: &str = "crates.io:admin-core";
+
 const DEFAULT_LIMIT: u32 = 10;
 const MAX_FEE_DECIMAL: u64 = 10_000u64; // constant for 100%
 const MAX_LIMIT: u32 = 30;
 const DEFAULT_GRACE_PERIOD: u64 = 86400; // 24 hours in seconds
+const UST_DENOM: &str = "uusd"; // refunds and prepaid balances of products without a billing asset are in UST, the denomination that charges are priced in
 
 // hard cap of 10 admins to prevent uncapped arrays
 const MAXIMUM_ADMIN_LIST_SIZE: usize = 10;
 
+// hard cap of 10 retries for a failed charge
+const MAXIMUM_RETRY_SCHEDULE_SIZE: usize = 10;
+
+// hard cap of 10 usage tiers and 10 usage reporters for metered products
+const MAXIMUM_USAGE_TIERS: usize = 10;
+const MAXIMUM_USAGE_REPORTERS: usize = 10;
+
+// hard cap of 10 co-receivers for the revenue of a product
+const MAXIMUM_REVENUE_SPLITS: usize = 10;
+
+// the monthly recurring revenue is normalized to months of 30 days
+const SECONDS_PER_MONTH: u64 = 30 * 24 * 60 * 60;
+
+// hard cap of 100 seats on a team subscription, which bounds the list of its members
+const MAXIMUM_SEATS: u64 = 100;
+
+// hard cap of 10 hook contracts, which are called on every subscription change
+const MAXIMUM_HOOKS: usize = 10;
+
+// reply id of the hook callbacks. Payments reply with the index of their pending charge instead
+const HOOK_REPLY_ID: u64 = u64::MAX;
+
+// window over which the pauses of a subscriber are counted against `max_pauses_per_year`
+const ONE_YEAR_IN_SECONDS: u64 = 365 * 86400;
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     mut deps: DepsMut,
@@ -57,6 +108,9 @@ pub fn instantiate(
         },
     )?;
 
+    // replaces the contract version stored by admin-core
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
     // unit_interval needs to be converted from hours to Timestamp
     let unit_interval = Timestamp::from_seconds(msg.unit_interval_hour * 60 * 60);
 
@@ -65,11 +119,59 @@ pub fn instantiate(
         None => 0,
     };
 
+    let trial_period = match msg.trial_period_hour {
+        Some(v) => v * 60 * 60,
+        None => 0,
+    };
+
+    // a cap of zero would prevent any charges from being made
+    if matches!(msg.max_amount_chargeable, Some(max_amount) if max_amount.is_zero()) {
+        return Err(AdminCoreContractError::InvalidParams {});
+    }
+
+    let retry_schedule = match msg.retry_schedule_hour {
+        Some(retry_schedule_hour) => match to_retry_schedule(retry_schedule_hour) {
+            Some(retry_schedule) => retry_schedule,
+            None => return Err(AdminCoreContractError::InvalidParams {}),
+        },
+        None => DEFAULT_RETRY_SCHEDULE.to_vec(),
+    };
+
+    let usage_tiers = msg.usage_tiers.unwrap_or_default();
+    if !is_valid_usage_tiers(&usage_tiers) {
+        return Err(AdminCoreContractError::InvalidParams {});
+    }
+
+    if !is_valid_billing_calendar(&msg.billing_calendar) {
+        return Err(AdminCoreContractError::InvalidParams {});
+    }
+
+    let max_pause_duration = match msg.max_pause_duration_hour {
+        Some(v) => v * 60 * 60,
+        None => 0,
+    };
+
+    // membership tokens need a collection name and symbol to be listed by wallets
+    if matches!(&msg.membership_nft, Some(membership_nft) if membership_nft.name.is_empty() || membership_nft.symbol.is_empty())
+    {
+        return Err(AdminCoreContractError::InvalidParams {});
+    }
+
+    if let Some(billing_asset) = &msg.billing_asset {
+        billing_asset.check(deps.api)?;
+    }
+
+    let receiver_address = deps.api.addr_validate(&msg.receiver_address)?;
+    let revenue_splits = to_revenue_splits(deps.api, msg.revenue_splits.unwrap_or_default())?;
+    if !is_valid_revenue_splits(&receiver_address, &revenue_splits) {
+        return Err(AdminCoreContractError::InvalidParams {});
+    }
+
     store_config(
         deps.storage,
         &Config {
             owner_address: owner_address.clone(),
-            receiver_address: deps.api.addr_validate(&msg.receiver_address)?,
+            receiver_address,
             additional_grace_period,
             unit_interval,
             unit_amount: msg.unit_amount,
@@ -78,9 +180,41 @@ pub fn instantiate(
             is_paused: false,
             is_frozen: false,
             factory_address: deps.api.addr_validate(&msg.factory_address)?,
+            trial_period,
+            max_amount_chargeable: msg.max_amount_chargeable,
+            retry_schedule,
+            max_pause_duration,
+            max_pauses_per_year: msg.max_pauses_per_year.unwrap_or(0),
+            usage_tiers,
+            usage_reporters: vec![],
+            membership_nft: msg.membership_nft,
+            billing_asset: msg.billing_asset,
+            revenue_splits,
+            hooks: vec![],
+            billing_calendar: msg.billing_calendar,
+            price_changes: vec![],
+            accept_any_payer: msg.accept_any_payer.unwrap_or(false),
         },
     )?;
 
+    // the default plan is described by the config. Additional plans are stored separately
+    for plan in msg.plans.unwrap_or_default() {
+        if plan.plan_id == DEFAULT_PLAN_ID
+            || PLANS.has(deps.storage, U64Key::from(plan.plan_id))
+            || !is_valid_billing_calendar(&plan.billing_calendar)
+        {
+            return Err(AdminCoreContractError::InvalidParams {});
+        }
+
+        PLANS.save(
+            deps.storage,
+            U64Key::from(plan.plan_id),
+            &to_plan(plan, trial_period),
+        )?;
+    }
+
+    STATS.save(deps.storage, &Stats::default())?;
+
     Ok(Response::new()
         .add_attribute("method", "instantiate")
         // required attribute for factory to register owner
@@ -108,8 +242,13 @@ pub fn execute(
         ExecuteMsg::UpdateConfig {
             receiver_address,
             additional_grace_period_hour,
+            retry_schedule_hour,
+            max_pause_duration_hour,
+            max_pauses_per_year,
             initial_amount,
             uri,
+            revenue_splits,
+            accept_any_payer,
         } => {
             let api = deps.api;
             update_config(
@@ -119,12 +258,65 @@ pub fn execute(
                 optional_addr_validate(api, receiver_address)?,
                 initial_amount,
                 additional_grace_period_hour,
+                retry_schedule_hour,
+                max_pause_duration_hour,
+                max_pauses_per_year,
                 uri,
+                revenue_splits,
+                accept_any_payer,
             )
         }
         ExecuteMsg::UpdateAdmins { admins } => execute_update_admins(deps, env, info, admins),
-        ExecuteMsg::Subscribe {} => execute_subscribe(deps, info, env),
+        ExecuteMsg::UpdateUsageReporters { reporters } => {
+            execute_update_usage_reporters(deps, info, env, reporters)
+        }
+        ExecuteMsg::UpdateHooks { hooks } => execute_update_hooks(deps, info, env, hooks),
+        ExecuteMsg::SchedulePriceChange {
+            plan_id,
+            new_unit_amount,
+            effective_at,
+        } => execute_schedule_price_change(deps, info, env, plan_id, new_unit_amount, effective_at),
+        ExecuteMsg::ReportUsage {
+            subscriber,
+            quantity,
+        } => execute_report_usage(deps, info, env, api.addr_validate(&subscriber)?, quantity),
+        ExecuteMsg::Subscribe {
+            plan_id,
+            coupon,
+            billing_cycles,
+            end_at,
+            beneficiary,
+            seats,
+        } => execute_subscribe(
+            deps,
+            info,
+            env,
+            plan_id,
+            coupon,
+            billing_cycles,
+            end_at,
+            optional_addr_validate(api, beneficiary)?,
+            seats,
+        ),
+        ExecuteMsg::ChangePlan { plan_id } => execute_change_plan(deps, info, env, plan_id),
         ExecuteMsg::Cancel {} => execute_cancel(deps, info, env),
+        ExecuteMsg::TopUp { subscriber } => {
+            execute_top_up(deps, info, env, optional_addr_validate(api, subscriber)?)
+        }
+        ExecuteMsg::RefundPrepaidBalance { subscriber } => {
+            execute_refund_prepaid_balance(deps, env, api.addr_validate(&subscriber)?)
+        }
+        ExecuteMsg::UpdateSeats { seats } => execute_update_seats(deps, info, env, seats),
+        ExecuteMsg::AssignSeat { member } => {
+            execute_assign_seat(deps, info, env, api.addr_validate(&member)?)
+        }
+        ExecuteMsg::UnassignSeat { member } => {
+            execute_unassign_seat(deps, info, env, api.addr_validate(&member)?)
+        }
+        ExecuteMsg::TakeOverPayment {} => execute_take_over_payment(deps, info, env),
+        ExecuteMsg::PauseSubscription { resume_at } => {
+            execute_pause_subscription(deps, info, env, resume_at)
+        }
         ExecuteMsg::TogglePause {} => execute_toggle_pause(deps, info, env),
         ExecuteMsg::ToggleFreeze {} => execute_toggle_freeze(deps, info, env),
         ExecuteMsg::ModifySubscriber {
@@ -148,28 +340,129 @@ pub fn execute(
         ExecuteMsg::RemoveSubscriber { subscriber } => {
             execute_remove_subscriber(deps, info, env, api.addr_validate(&subscriber)?)
         }
+        ExecuteMsg::AddPlan { plan } => execute_add_plan(deps, info, env, plan),
+        ExecuteMsg::ArchivePlan { plan_id } => execute_archive_plan(deps, info, env, plan_id),
+        ExecuteMsg::CreateCoupon {
+            code,
+            discount,
+            max_redemptions,
+            redeem_by,
+        } => execute_create_coupon(deps, info, env, code, discount, max_redemptions, redeem_by),
+        ExecuteMsg::RemoveCoupon { code } => execute_remove_coupon(deps, info, env, code),
         ExecuteMsg::Charge { payer_address } => {
             execute_charge(deps, env, api.addr_validate(&payer_address)?, None)
         }
-        ExecuteMsg::Work { payload } => {
-            let work_payload: WorkPayload = from_binary(&payload).unwrap();
-            execute_work(
+        ExecuteMsg::Work { payload } => match from_binary::<WorkPayload>(&payload) {
+            Ok(work_payload) => execute_work(
                 deps,
                 info,
                 env,
                 api.addr_validate(&work_payload.payer_address)?,
-            )
-        }
+            ),
+            Err(_) => {
+                let batch_payload: BatchWorkPayload = from_binary(&payload)?;
+                execute_batch_work(deps, info, env, batch_payload.payer_addresses)
+            }
+        },
+        ExecuteMsg::CollectPayment {
+            subscriber,
+            payer,
+            amount,
+        } => execute_collect_payment(
+            deps,
+            info,
+            env,
+            api.addr_validate(&subscriber)?,
+            api.addr_validate(&payer)?,
+            amount,
+        ),
+        ExecuteMsg::SendWorkReceipt {
+            worker_address,
+            units,
+        } => execute_send_work_receipt(deps, info, env, worker_address, units),
+        ExecuteMsg::Refund {
+            subscriber,
+            amount,
+            charge_id,
+        } => execute_refund(
+            deps,
+            info,
+            env,
+            api.addr_validate(&subscriber)?,
+            amount,
+            charge_id,
+        ),
+        ExecuteMsg::TransferNft {
+            recipient,
+            token_id,
+        } => execute_transfer_nft(
+            deps,
+            info,
+            env,
+            api.addr_validate(&recipient)?,
+            api.addr_validate(&token_id)?,
+        ),
+        ExecuteMsg::Receive(msg) => execute_receive(deps, info, env, msg),
     }
 }
 
 #[allow(dead_code)]
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
-    Ok(Response::default())
+pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let previous_version = migrate_contract_version(
+        deps.storage,
+        CONTRACT_NAME,
+        CONTRACT_VERSION,
+        &[LEGACY_CONTRACT_NAME],
+    )?;
+
+    // upgrade steps of every version after the one that is migrated from, oldest first
+    if previous_version < Version::new(0, 2, 1) {
+        migrate_legacy_config(deps.storage)?;
+    }
+
+    if previous_version < Version::new(0, 3, 0) {
+        // subscriptions stored before the indexes were introduced are saved again to populate the indexes
+        let existing_subscriptions = subscriptions()
+            .range(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+
+        for (subscriber, subscription) in existing_subscriptions {
+            let subscriber =
+                Addr::unchecked(String::from_utf8(subscriber).map_err(StdError::from)?);
+            subscriptions().save(deps.storage, &subscriber, &subscription)?;
+        }
+    }
+
+    if previous_version < Version::new(0, 3, 0) {
+        // products instantiated before the running counters were introduced rebuild them
+        rebuild_stats(deps.storage)?;
+    }
+
+    let mut attributes = vec![
+        attr("method", "migrate"),
+        attr("previous_version", previous_version.to_string()),
+        attr("version", CONTRACT_VERSION),
+    ];
+
+    match msg.accept_any_payer {
+        Some(accept_any_payer) => {
+            let mut config = read_config(deps.storage)?;
+            config.accept_any_payer = accept_any_payer;
+            store_config(deps.storage, &config)?;
+            attributes.push(attr("accept_any_payer", accept_any_payer.to_string()));
+        }
+        // products from before 0.3.0 accepted any payer, so whether they keep doing so must be chosen explicitly
+        None if previous_version < Version::new(0, 3, 0) => {
+            return Err(ContractError::AcceptAnyPayerNotSet {});
+        }
+        None => {}
+    }
+
+    Ok(Response::new().add_attributes(attributes))
 }
 
-/// Updates the generate settings.
+/// Updates the generate settings. `initial_amount` only applies to the default plan.
 ///
 /// ## Executor
 /// Only owner or admin can execute this function
@@ -181,7 +474,12 @@ pub fn update_config(
     receiver_address: Option<Addr>,
     initial_amount: Option<Uint256>,
     additional_grace_period_hour: Option<u64>,
+    retry_schedule_hour: Option<Vec<u64>>,
+    max_pause_duration_hour: Option<u64>,
+    max_pauses_per_year: Option<u32>,
     uri: Option<String>,
+    revenue_splits: Option<Vec<RevenueSplitInfo>>,
+    accept_any_payer: Option<bool>,
 ) -> Result<Response, ContractError> {
     let mut config: Config = read_config(deps.storage)?;
     let mut attributes = Vec::from([
@@ -208,10 +506,39 @@ pub fn update_config(
         config.additional_grace_period = additional_grace_period_hour * 60 * 60
     }
 
+    if let Some(retry_schedule_hour) = retry_schedule_hour {
+        config.retry_schedule = match to_retry_schedule(retry_schedule_hour) {
+            Some(retry_schedule) => retry_schedule,
+            None => return Err(ContractError::InvalidParam {}),
+        };
+    }
+
+    if let Some(max_pause_duration_hour) = max_pause_duration_hour {
+        config.max_pause_duration = max_pause_duration_hour * 60 * 60
+    }
+
+    if let Some(max_pauses_per_year) = max_pauses_per_year {
+        config.max_pauses_per_year = max_pauses_per_year
+    }
+
     if let Some(uri) = uri {
         config.uri = uri
     }
 
+    if let Some(revenue_splits) = revenue_splits {
+        config.revenue_splits = to_revenue_splits(deps.api, revenue_splits)?;
+    }
+
+    if let Some(accept_any_payer) = accept_any_payer {
+        config.accept_any_payer = accept_any_payer;
+        attributes.push(attr("new_accept_any_payer", accept_any_payer.to_string()));
+    }
+
+    // a new primary receiver must not also be a co-receiver
+    if !is_valid_revenue_splits(&config.receiver_address, &config.revenue_splits) {
+        return Err(ContractError::InvalidParam {});
+    }
+
     store_config(deps.storage, &config)?;
 
     Ok(Response::new().add_attributes(attributes))
@@ -244,8 +571,231 @@ pub fn execute_update_admins(
     }
 }
 
+/// Sets the addresses that can report the usage of subscribers of a metered product. Replaces the existing reporters.
+///
+/// ## Executor
+/// Only owner or admin can execute this function
+pub fn execute_update_usage_reporters(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    reporters: Vec<String>,
+) -> Result<Response, ContractError> {
+    let mut config: Config = read_config(deps.storage)?;
+
+    // Only owner or admin can call this function
+    let cfg = ADMIN_CONFIG.load(deps.storage)?;
+    if !cfg.is_admin(info.sender.as_ref()) && !cfg.is_owner(info.sender.as_ref()) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if !config.is_metered() {
+        return Err(ContractError::NotMetered {});
+    }
+
+    if reporters.len() > MAXIMUM_USAGE_REPORTERS {
+        return Err(ContractError::InvalidParam {});
+    }
+
+    config.usage_reporters = map_validate(deps.api, &reporters)?;
+    store_config(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("method", "update_usage_reporters"),
+        attr("module_contract_address", env.contract.address.to_string()),
+    ]))
+}
+
+/// Sets the contracts that receive a [`SubscriptionHookMsg`] when a subscription changes. Replaces the existing hooks.
+/// Callbacks are best-effort: a failing hook does not revert the subscription change
+///
+/// ## Executor
+/// Only owner or admin can execute this function
+pub fn execute_update_hooks(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    hooks: Vec<String>,
+) -> Result<Response, ContractError> {
+    let mut config: Config = read_config(deps.storage)?;
+
+    // Only owner or admin can call this function
+    let cfg = ADMIN_CONFIG.load(deps.storage)?;
+    if !cfg.is_admin(info.sender.as_ref()) && !cfg.is_owner(info.sender.as_ref()) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if hooks.len() > MAXIMUM_HOOKS {
+        return Err(ContractError::InvalidParam {});
+    }
+
+    config.hooks = map_validate(deps.api, &hooks)?;
+    store_config(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("method", "update_hooks"),
+        attr("module_contract_address", env.contract.address.to_string()),
+    ]))
+}
+
+/// Schedules a change of the `unit_amount` of the plan, or of the default plan if `plan_id` is unset. Every subscriber of the plan
+/// is billed the new unit amount from its first interval that starts at or after `effective_at`, which must be at least the
+/// minimum notice of the product factory away. Replaces the change that is pending, if any. Changes that have taken effect
+/// are kept, so that the intervals that started before them are billed the previous unit amount.
+///
+/// ## Executor
+/// Only owner or admin can execute this function
+pub fn execute_schedule_price_change(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    plan_id: Option<u64>,
+    new_unit_amount: Uint256,
+    effective_at: u64,
+) -> Result<Response, ContractError> {
+    let mut config: Config = read_config(deps.storage)?;
+
+    // Only owner or admin can call this function
+    let cfg = ADMIN_CONFIG.load(deps.storage)?;
+    if !cfg.is_admin(info.sender.as_ref()) && !cfg.is_owner(info.sender.as_ref()) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let factory_config =
+        query_product_factory_config(&deps.querier, config.factory_address.clone())?;
+    if new_unit_amount < factory_config.min_amount_per_interval {
+        return Err(ContractError::InvalidParam {});
+    }
+
+    let effective_at = Timestamp::from_seconds(effective_at);
+    if effective_at
+        < env
+            .block
+            .time
+            .plus_seconds(factory_config.min_price_change_notice_hour * 60 * 60)
+    {
+        return Err(ContractError::PriceChangeNoticeTooShort {});
+    }
+
+    // archived plans still bill their subscribers, so their price can be changed as well
+    let mut plan = match read_plan(deps.storage, plan_id.unwrap_or(DEFAULT_PLAN_ID))? {
+        Some(v) => v,
+        None => return Err(ContractError::PlanNotFound {}),
+    };
+    plan.schedule_price_change(
+        PriceChange {
+            unit_amount: new_unit_amount,
+            effective_at,
+        },
+        env.block.time,
+    );
+
+    // the default plan is described by the pricing fields of the config
+    if plan.plan_id == DEFAULT_PLAN_ID {
+        config.price_changes = plan.price_changes;
+        store_config(deps.storage, &config)?;
+    } else {
+        PLANS.save(deps.storage, U64Key::from(plan.plan_id), &plan)?;
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("method", "schedule_price_change"),
+        attr("module_contract_address", env.contract.address.to_string()),
+        attr("plan_id", plan.plan_id.to_string()),
+        attr("new_unit_amount", new_unit_amount.to_string()),
+        attr("effective_at", effective_at.seconds().to_string()),
+    ]))
+}
+
+/// Adds `quantity` units of usage to the interval of the subscriber that is running at the current block time.
+/// The usage of an interval is billed with the `usage_tiers` of the [`Config`] when the subscriber is charged at the end of the interval.
+///
+/// ## Executor
+/// Only usage reporters can execute this function
+pub fn execute_report_usage(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    subscriber: Addr,
+    quantity: u64,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+
+    if config.is_frozen {
+        return Err(ContractError::Frozen {});
+    }
+
+    if !config.is_metered() {
+        return Err(ContractError::NotMetered {});
+    }
+
+    if !config.usage_reporters.contains(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if quantity == 0 {
+        return Err(ContractError::InvalidParam {});
+    }
+
+    let mut subscription = match subscriptions().may_load(deps.storage, &subscriber)? {
+        Some(v) => v,
+        None => return Err(ContractError::SubscriptionNotFound {}),
+    };
+
+    // usage can only be billed while the subscription continues to be charged
+    if subscription.is_cancelled {
+        return Err(ContractError::SubscriptionCancelled {});
+    }
+
+    if subscription.is_paused(env.block.time) {
+        return Err(ContractError::SubscriptionPaused {});
+    }
+
+    if !is_subscription_active(deps.storage, env.clone(), subscription.clone()) {
+        return Err(ContractError::SubscriptionInactive {});
+    }
+
+    // usage reported after the end of an interval that has not been charged yet belongs to the following intervals
+    let plan = read_subscription_plan(deps.storage, &subscription)?;
+    let interval_end_at = match env.block.time < subscription.interval_end_at {
+        true => subscription.interval_end_at,
+        false => plan.interval_end_after(
+            subscription.interval_end_at,
+            plan.intervals_starting_before(
+                subscription.interval_end_at,
+                env.block.time.plus_seconds(1),
+            ),
+        ),
+    };
+
+    match subscription
+        .usage
+        .iter_mut()
+        .find(|record| record.interval_end_at == interval_end_at)
+    {
+        Some(record) => record.quantity += quantity,
+        None => subscription.usage.push(UsageRecord {
+            interval_end_at,
+            quantity,
+        }),
+    }
+
+    subscriptions().save(deps.storage, &subscriber, &subscription)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("method", "report_usage"),
+        attr("module_contract_address", env.contract.address.to_string()),
+        attr("subscriber", subscriber.into_string()),
+        attr("quantity", quantity.to_string()),
+        attr("interval_end_at", interval_end_at.seconds().to_string()),
+    ]))
+}
+
 /// Creates a subscription object whenever the user subscribes to the product.
-/// If the `initial_amount` is set to a non-zero value, this function should process the payment from subscriber to merchant (and protocol, if applicable)
+/// If the `initial_amount` of the plan is set to a non-zero value, this function should process the payment from subscriber to merchant (and protocol, if applicable)
+///
+/// The sender pays for the subscription. A gift or sponsored subscription is created for another `beneficiary`, which owns the subscription
+/// while every charge is made from the subwallet of the sender
 ///
 /// * **deps** is the object of [`DepsMut`]
 ///
@@ -253,10 +803,27 @@ pub fn execute_update_admins(
 ///
 ///  * **_env** is the object of type [`Env`]
 ///
+///  * **plan_id** is the plan to subscribe to. Defaults to the default plan if unspecified
+///
+///  * **coupon** is an optional coupon code to redeem. The [`Discount`] of the coupon is applied to the new subscription
+///
+///  * **billing_cycles** is an optional number of billing cycles after which a fixed-term subscription ends
+///
+///  * **end_at** is an optional unix timestamp by which a fixed-term subscription ends. The term is made of the whole billing cycles that end by `end_at`
+///
+///  * **beneficiary** is the optional address that is subscribed. Defaults to the sender
+///
+#[allow(clippy::too_many_arguments)]
 pub fn execute_subscribe(
-    deps: DepsMut,
+    mut deps: DepsMut,
     info: MessageInfo,
     env: Env,
+    plan_id: Option<u64>,
+    coupon: Option<String>,
+    billing_cycles: Option<u64>,
+    end_at: Option<u64>,
+    beneficiary: Option<Addr>,
+    seats: Option<u64>,
 ) -> Result<Response, ContractError> {
     let config: Config = read_config(deps.storage)?;
     let mut is_undo = false; // flag on whether this is an undo cancellation request
@@ -273,19 +840,40 @@ pub fn execute_subscribe(
     let mut msgs = Vec::new();
     let mut attributes = Vec::new();
 
-    let subscriber = info.sender.clone();
+    let plan = match read_plan(deps.storage, plan_id.unwrap_or(DEFAULT_PLAN_ID))? {
+        Some(plan) => plan,
+        None => return Err(ContractError::PlanNotFound {}),
+    };
+
+    let payer = info.sender;
+    let prepaid_amount = prepaid_funds(&config, &info.funds)?;
+    let subscriber = beneficiary.unwrap_or_else(|| payer.clone());
     let new_subscription = SubscriptionInfo {
-        owner: deps.api.addr_validate(subscriber.as_str())?,
+        owner: subscriber.clone(),
         created_at: env.block.time,
-        interval_end_at: env.block.time.plus_seconds(config.unit_interval.seconds()),
+        interval_end_at: plan.interval_end_after(env.block.time, 1),
         last_charged: Timestamp::from_seconds(0u64),
         is_cancelled: false,
         discount_per_interval: None,
+        plan_id: plan.plan_id,
+        trial_end_at: None,
+        total_paid: Uint256::zero(),
+        retry_count: 0,
+        next_retry_at: None,
+        resume_at: None,
+        pause_history: vec![],
+        term_end_at: None,
+        usage: vec![],
+        payer: None,
+        lifetime_paid: Uint256::zero(),
+        seats: None,
+        seat_proration: Uint256::zero(),
+        seat_credit: Uint256::zero(),
     };
 
-    let get_subscription = SUBSCRIPTIONS.may_load(deps.storage, &subscriber)?;
+    let previous_subscription = subscriptions().may_load(deps.storage, &subscriber)?;
 
-    let mut subscription = match get_subscription {
+    let mut subscription = match previous_subscription.clone() {
         Some(mut current_subscription) => {
             // If the user have an existing subscription, check if it is active.
             let subscription_active =
@@ -299,87 +887,391 @@ pub fn execute_subscribe(
                         // subscriber had a subscription that he/she has previously cancelled, but has not reached its expiry
                         // In circumstances like this, it is treated as a undo_cancellation
                         // no change to the previous subscription period
+                        // the plan cannot be switched while undoing a cancellation
+                        if matches!(plan_id, Some(plan_id) if plan_id != current_subscription.plan_id)
+                        {
+                            return Err(ContractError::ExistingSubscriptionFound {});
+                        }
+
+                        // only the subscriber or its current payer can undo the cancellation, so that nobody else can
+                        // take over the payment of a subscription that is being cancelled
+                        let current_payer = current_subscription
+                            .payer
+                            .clone()
+                            .unwrap_or_else(|| current_subscription.owner.clone());
+                        if payer != subscriber && payer != current_payer {
+                            return Err(ContractError::Unauthorized {});
+                        }
+
                         is_undo = true;
                         attributes.push(attr("additional_info", "undo_cancellation"));
 
                         current_subscription.is_cancelled = false;
                         current_subscription
                     }
-                    false => new_subscription,
+                    // the lifetime paid by the subscriber is kept on the new subscription
+                    false => SubscriptionInfo {
+                        lifetime_paid: current_subscription.lifetime_paid,
+                        ..new_subscription
+                    },
                 }
             }
         }
         _ => new_subscription,
     };
 
-    // get fee info from factory
-    let fee = query_product_factory_config(&deps.querier, config.factory_address.clone())?;
+    // whoever subscribes pays for the subscription from now on, including when undoing a cancellation
+    subscription.payer = match payer == subscriber {
+        true => None,
+        false => Some(payer.clone()),
+    };
 
-    // protocol fee cannot be more than 100% as it is invalid
-    if fee.protocol_fee_bps > MAX_FEE_DECIMAL {
-        return Err(ContractError::InvalidFee {});
+    // archived plans do not accept new subscribers
+    if !is_undo && plan.is_archived {
+        return Err(ContractError::PlanArchived {});
     }
 
-    // handling scenario where an initial_amount is required to kickstart the subscription
-    if !config.initial_amount.is_zero() && !is_undo {
-        subscription.last_charged = env.block.time;
+    // new subscriptions start with a free trial if the plan offers one, unless the subscriber has used a trial before.
+    // No charges are made during the trial, and the first charge is due when the trial ends
+    let is_trial = !is_undo && plan.trial_period > 0 && !TRIALS.has(deps.storage, &subscriber);
+    if is_trial {
+        // calendar trials last until the period end after the trial period, so that every charge is made on a period end
+        let trial_end_at = match plan.billing_calendar {
+            Some(_) => {
+                plan.interval_end_after(env.block.time.plus_seconds(plan.trial_period - 1), 1)
+            }
+            None => env.block.time.plus_seconds(plan.trial_period),
+        };
+        subscription.interval_end_at = trial_end_at;
+        subscription.trial_end_at = Some(trial_end_at);
+        TRIALS.save(deps.storage, &subscriber, &trial_end_at)?;
 
-        let protocol_fee = calculate_protocol_fee(
-            fee.protocol_fee_bps,
-            fee.min_protocol_fee,
-            config.initial_amount,
-        );
+        attributes.push(attr("trial_end_at", trial_end_at.seconds().to_string()));
+    }
 
-        // Amount to pay merchant
-        let mut merchant_amount = config.initial_amount;
-
-        // computes the protocol fees payable if protocol_fee is non-zero
-        if let Some(protocol_fee) = protocol_fee {
-            merchant_amount = merchant_amount - protocol_fee;
-
-            // push a message for the fees payment
-            msgs.push(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: subscriber.clone().into_string(),
-                funds: vec![],
-                msg: to_binary(&SubWalletExecuteMsg::TransferAToken {
-                    recipient: fee.fee_address,
-                    amount: Uint128::from(protocol_fee),
-                })?,
-            }));
+    // fixed-term subscriptions last a number of whole billing cycles, starting after the free trial if any.
+    // Undoing a cancellation keeps the existing term
+    if billing_cycles.is_some() || end_at.is_some() {
+        if is_undo {
+            return Err(ContractError::ExistingSubscriptionFound {});
         }
 
-        msgs.push(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: subscriber.clone().into_string(),
-            funds: vec![],
-            msg: to_binary(&SubWalletExecuteMsg::TransferAToken {
-                recipient: config.receiver_address.to_string(),
-                amount: Uint128::from(merchant_amount),
-            })?,
-        }));
+        let term_start = match is_trial {
+            true => subscription.interval_end_at,
+            false => env.block.time,
+        };
+        let billing_cycles = match (billing_cycles, end_at) {
+            (Some(billing_cycles), None) => billing_cycles,
+            // the billing cycles that end by `end_at` are the ones that start by then, except for the cycle that starts at `end_at`
+            (None, Some(end_at)) => plan
+                .intervals_starting_before(term_start, Timestamp::from_seconds(end_at + 1))
+                .saturating_sub(1),
+            _ => return Err(ContractError::InvalidParam {}),
+        };
+
+        if billing_cycles == 0 {
+            return Err(ContractError::InvalidParam {});
+        }
+
+        let term_end_at = plan.interval_end_after(term_start, billing_cycles);
+        subscription.term_end_at = Some(term_end_at);
+
+        attributes.push(attr("term_end_at", term_end_at.seconds().to_string()));
+    }
+
+    // team subscriptions are billed for every seat. Undoing a cancellation keeps the existing seats and members,
+    // while new subscriptions start without members
+    if !is_undo {
+        remove_team(deps.storage, &subscriber)?;
+    }
+    if let Some(seats) = seats {
+        if is_undo {
+            return Err(ContractError::ExistingSubscriptionFound {});
+        }
+
+        if seats == 0 || seats > MAXIMUM_SEATS {
+            return Err(ContractError::InvalidParam {});
+        }
+
+        subscription.seats = Some(seats);
+        attributes.push(attr("seats", seats.to_string()));
+    }
+
+    // coupons can only be redeemed on new subscriptions. Undoing a cancellation keeps the existing discount
+    if let Some(code) = coupon {
+        if is_undo {
+            return Err(ContractError::ExistingSubscriptionFound {});
+        }
+
+        subscription.discount_per_interval =
+            Some(redeem_coupon(deps.storage, &code, env.block.time)?);
+
+        attributes.push(attr("coupon", code));
+    }
+
+    // get fee info from factory
+    let fee = query_product_factory_config(&deps.querier, config.factory_address.clone())?;
+
+    // protocol fee cannot be more than 100% as it is invalid
+    if fee.protocol_fee_bps > MAX_FEE_DECIMAL {
+        return Err(ContractError::InvalidFee {});
+    }
+
+    // handling scenario where an initial_amount is required to kickstart the subscription
+    let initial_amount = initial_amount_due(&plan, subscription.interval_end_at, env.block.time)
+        * Uint256::from(subscription.seat_count());
+    let is_initial_charge = !initial_amount.is_zero() && !is_undo && !is_trial;
+
+    // payers that prepay the first charge of the subscription do not need a subwallet. A smaller deposit does not
+    // exempt the payer, as the rest of the charge could never be collected
+    let first_charge = match is_initial_charge {
+        true => initial_amount,
+        false => {
+            plan.priced_at(subscription.interval_end_at).unit_amount
+                * Uint256::from(subscription.seat_count())
+        }
+    };
+    if prepaid_amount.is_zero() || prepaid_amount < first_charge {
+        assert_subwallet_payer(deps.as_ref(), &config, &payer)?;
+    }
+
+    // the prepaid funds are added to the balance before the initial amount is charged, so that they can pay for it
+    if !prepaid_amount.is_zero() {
+        let prepaid_balance = add_prepaid_balance(deps.storage, &subscriber, prepaid_amount)?;
+        attributes.push(attr("prepaid_balance", prepaid_balance));
+    }
+
+    if is_initial_charge {
+        // the minimum protocol fee must not be more than the initial amount, which can be prorated down to a small amount
+        if fee.min_protocol_fee > initial_amount {
+            return Err(ContractError::InvalidFee {});
+        }
+
+        // the initial amount cannot be partially charged
+        if cap_amount_chargeable(&config, &subscription, initial_amount) < initial_amount {
+            return Err(ContractError::MaxAmountChargeableReached {});
+        }
+
+        subscription.last_charged = env.block.time;
+        subscription.total_paid = subscription.total_paid + initial_amount;
+        subscription.lifetime_paid = subscription.lifetime_paid + initial_amount;
+
+        msgs.extend(build_subscription_payment_msgs(
+            deps.branch(),
+            &subscriber,
+            &payer,
+            &config,
+            &fee,
+            initial_amount,
+        )?);
+        record_payment_revenue(deps.storage, &fee, initial_amount)?;
+
+        let charge_id = record_payment(
+            deps.storage,
+            &subscriber,
+            &payer,
+            initial_amount,
+            env.block.time,
+        )?;
 
-        attributes.push(attr("initial_amount", config.initial_amount));
+        attributes.push(attr("initial_amount", initial_amount));
+        attributes.push(attr("charge_id", charge_id.to_string()));
     }
 
     // make state changes for the subscription object
-    create_subscription(
+    update_subscriber_stats(
         deps.storage,
-        deps.api.addr_validate(info.sender.as_str())?,
-        subscription,
+        previous_subscription.as_ref(),
+        Some(&subscription),
     )?;
+    create_subscription(deps.storage, subscriber.clone(), subscription)?;
+    // a removed subscriber that subscribes again is no longer reported as removed
+    REMOVED_SUBSCRIPTIONS.remove(deps.storage, &subscriber);
+
+    // new subscriptions get a membership token owned by the subscriber.
+    // An existing token is kept as it is, since it may have been transferred
+    let mint_membership_token =
+        config.membership_nft.is_some() && mint_membership(deps.storage, &subscriber)?;
 
     attributes.push(attr("method", "execute_subscribe"));
     attributes.push(attr("result", "subscribe_success"));
-    attributes.push(attr("subscriber", subscriber.into_string()));
+    attributes.push(attr("subscriber", subscriber.to_string()));
     attributes.push(attr(
         "module_contract_address",
         env.contract.address.to_string(),
     ));
 
+    if payer != subscriber {
+        attributes.push(attr("payer", payer.into_string()));
+    }
+
+    if mint_membership_token {
+        attributes.push(attr("token_id", subscriber.to_string()));
+    }
+
+    let hook_msg = if is_undo {
+        SubscriptionHookMsg::CancellationUndone {
+            subscriber: subscriber.to_string(),
+        }
+    } else {
+        SubscriptionHookMsg::Subscribed {
+            subscriber: subscriber.to_string(),
+            plan_id: plan.plan_id,
+        }
+    };
+
     Ok(Response::new()
         .add_messages(msgs)
+        .add_submessages(build_hook_msgs(&config, hook_msg)?)
         .add_attributes(attributes))
 }
 
+/// Moves the subscriber to another plan. The unused part of the current interval is credited against the price of the new plan,
+/// and a new interval of the new plan starts immediately.
+/// If the price of the new plan exceeds the credit, the difference is charged from the subscriber (and protocol fee is applied to the difference).
+/// Otherwise, the remaining credit extends the new interval proportionally. The `initial_amount` of the new plan is not charged.
+///
+/// ## Executor
+/// Only the subscriber can change its own plan
+pub fn execute_change_plan(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    plan_id: u64,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+
+    if config.is_paused {
+        return Err(ContractError::Paused {});
+    }
+
+    if config.is_frozen {
+        return Err(ContractError::Frozen {});
+    }
+
+    let subscriber = info.sender;
+
+    let mut subscription = match subscriptions().may_load(deps.storage, &subscriber)? {
+        Some(v) => v,
+        None => return Err(ContractError::SubscriptionNotFound {}),
+    };
+
+    if subscription.is_cancelled {
+        return Err(ContractError::SubscriptionCancelled {});
+    }
+
+    // outstanding payments have to be charged before the plan can be changed
+    if env.block.time >= subscription.interval_end_at {
+        return Err(ContractError::PaymentDue {});
+    }
+
+    // the paused time would otherwise be credited as unused time of the current plan
+    if subscription.is_paused(env.block.time) {
+        return Err(ContractError::SubscriptionPaused {});
+    }
+
+    // the new interval would no longer line up with the end of the term
+    if subscription.term_end_at.is_some() {
+        return Err(ContractError::FixedTermPlanChange {});
+    }
+
+    if plan_id == subscription.plan_id {
+        return Err(ContractError::InvalidParam {});
+    }
+
+    let current_plan = read_subscription_plan(deps.storage, &subscription)?;
+    let new_plan = match read_plan(deps.storage, plan_id)? {
+        Some(plan) => plan,
+        None => return Err(ContractError::PlanNotFound {}),
+    };
+
+    if new_plan.is_archived {
+        return Err(ContractError::PlanArchived {});
+    }
+
+    let plan_change = compute_plan_change(&current_plan, &new_plan, &subscription, env.block.time);
+
+    let mut msgs: Vec<CosmosMsg> = Vec::new();
+    let mut charge_id = None;
+    if !plan_change.amount.is_zero() {
+        // the difference cannot be partially charged
+        if cap_amount_chargeable(&config, &subscription, plan_change.amount) < plan_change.amount {
+            return Err(ContractError::MaxAmountChargeableReached {});
+        }
+
+        // get fee info from factory
+        let fee = query_product_factory_config(&deps.querier, config.factory_address.clone())?;
+
+        // fee cannot be more than 100% and fee must not be more than the amount charged
+        if fee.protocol_fee_bps > MAX_FEE_DECIMAL || fee.min_protocol_fee > plan_change.amount {
+            return Err(ContractError::InvalidFee {});
+        }
+
+        msgs = build_subscription_payment_msgs(
+            deps.branch(),
+            &subscriber,
+            subscription.payer_address(),
+            &config,
+            &fee,
+            plan_change.amount,
+        )?;
+        record_payment_revenue(deps.storage, &fee, plan_change.amount)?;
+        subscription.last_charged = env.block.time;
+        subscription.total_paid = subscription.total_paid + plan_change.amount;
+        subscription.lifetime_paid = subscription.lifetime_paid + plan_change.amount;
+        charge_id = Some(record_payment(
+            deps.storage,
+            &subscriber,
+            &subscription.payer_address().clone(),
+            plan_change.amount,
+            env.block.time,
+        )?);
+    }
+
+    // the first interval on the new plan uses up one discounted interval, unless the trial continues
+    if plan_change.interval_end_at != subscription.interval_end_at {
+        let discounted_intervals = discounted_intervals(
+            &subscription.discount_per_interval,
+            &new_plan,
+            env.block.time,
+            1,
+        );
+        subscription.discount_per_interval = remaining_discount(
+            subscription.discount_per_interval,
+            discounted_intervals,
+            plan_change.interval_end_at,
+        );
+    }
+
+    // the subscriber is counted on the new plan
+    let previous_subscription = subscription.clone();
+    subscription.plan_id = new_plan.plan_id;
+    subscription.interval_end_at = plan_change.interval_end_at;
+
+    update_subscriber_stats(
+        deps.storage,
+        Some(&previous_subscription),
+        Some(&subscription),
+    )?;
+    subscriptions().save(deps.storage, &subscriber, &subscription)?;
+
+    let mut response = Response::new().add_messages(msgs).add_attributes(vec![
+        attr("method", "execute_change_plan"),
+        attr("module_contract_address", env.contract.address.to_string()),
+        attr("subscriber", subscriber.into_string()),
+        attr("previous_plan_id", current_plan.plan_id.to_string()),
+        attr("plan_id", new_plan.plan_id.to_string()),
+        attr("credit", plan_change.credit.to_string()),
+        attr("amount", plan_change.amount.to_string()),
+    ]);
+
+    if let Some(charge_id) = charge_id {
+        response = response.add_attribute("charge_id", charge_id.to_string());
+    }
+
+    Ok(response)
+}
+
 /// Allows the user to cancel its own subscription. Once cancelled, workers will not be able to call charge again to initiate a new subscription.
 /// The user's existing subscription will also stay valid until the end of the period
 pub fn execute_cancel(
@@ -396,7 +1288,7 @@ pub fn execute_cancel(
     let subscriber = info.sender;
 
     // get receiver address
-    let mut subscription = match SUBSCRIPTIONS.may_load(deps.storage, &subscriber.clone())? {
+    let mut subscription = match subscriptions().may_load(deps.storage, &subscriber.clone())? {
         Some(v) => v,
         None => return Err(ContractError::SubscriptionNotFound {}),
     };
@@ -405,26 +1297,49 @@ pub fn execute_cancel(
         return Err(ContractError::SubscriptionCancelled {});
     }
 
+    let previous_subscription = subscription.clone();
     subscription.is_cancelled = true;
 
-    SUBSCRIPTIONS.save(deps.storage, &subscriber, &subscription)?;
+    update_subscriber_stats(
+        deps.storage,
+        Some(&previous_subscription),
+        Some(&subscription),
+    )?;
+    subscriptions().save(deps.storage, &subscriber, &subscription)?;
+
+    let hook_msg = SubscriptionHookMsg::Cancelled {
+        subscriber: subscriber.to_string(),
+    };
 
-    Ok(Response::new().add_attributes(vec![
-        attr("method", "execute_cancel"),
-        attr("module_contract_address", env.contract.address.to_string()),
-    ]))
+    let mut response = Response::new()
+        .add_submessages(build_hook_msgs(&config, hook_msg)?)
+        .add_attributes(vec![
+            attr("method", "execute_cancel"),
+            attr("module_contract_address", env.contract.address.to_string()),
+        ]);
+
+    // the prepaid balance is refunded right away if the current interval has already ended.
+    // Otherwise it is kept until the interval ends, so that the cancellation can be undone
+    if env.block.time >= subscription.interval_end_at {
+        if let Some((refund_msg, amount)) =
+            refund_prepaid_balance(deps.storage, &config, &subscriber, &subscription)?
+        {
+            response = response
+                .add_message(refund_msg)
+                .add_attribute("prepaid_refund", amount);
+        }
+    }
+
+    Ok(response)
 }
 
-/// Remove subscriber from the contract. Once removed, subscriber loses access to the services immediately without refunds for unused period.
-///
-/// ## Executor
-/// Only owner or admin can execute this function
-///
-pub fn execute_remove_subscriber(
+/// Adds the native billing asset sent along with the message to the prepaid balance of the subscription of `subscriber`,
+/// or of the sender if unspecified. Anyone can top up a subscription, and the balance is refunded to the payer of the subscription
+pub fn execute_top_up(
     deps: DepsMut,
     info: MessageInfo,
     env: Env,
-    subscriber: Addr,
+    subscriber: Option<Addr>,
 ) -> Result<Response, ContractError> {
     let config: Config = read_config(deps.storage)?;
 
@@ -432,152 +1347,181 @@ pub fn execute_remove_subscriber(
         return Err(ContractError::Frozen {});
     }
 
-    // Only owner or admin can call this function
-    let cfg = ADMIN_CONFIG.load(deps.storage)?;
-    if !cfg.is_admin(info.sender.as_ref()) && !cfg.is_owner(info.sender.as_ref()) {
-        return Err(ContractError::Unauthorized {});
+    let amount = prepaid_funds(&config, &info.funds)?;
+    if amount.is_zero() {
+        return Err(ContractError::InvalidPrepaidFunds {});
     }
 
-    match SUBSCRIPTIONS.may_load(deps.storage, &subscriber.clone())? {
-        Some(_) => {
-            // remove_subscriber removes the susbcriber from the Map, revoking its access to the platform immediately without refunds
-            SUBSCRIPTIONS.remove(deps.storage, &subscriber);
-        }
-        None => return Err(ContractError::SubscriptionNotFound {}),
-    };
+    // only existing subscriptions can be topped up, so that the balance can be refunded
+    let subscriber = subscriber.unwrap_or(info.sender);
+    if subscriptions()
+        .may_load(deps.storage, &subscriber)?
+        .is_none()
+    {
+        return Err(ContractError::SubscriptionNotFound {});
+    }
+
+    let prepaid_balance = add_prepaid_balance(deps.storage, &subscriber, amount)?;
 
     Ok(Response::new().add_attributes(vec![
-        attr("method", "execute_remove_subscriber"),
-        attr("subscriber", subscriber.to_string()),
+        attr("method", "execute_top_up"),
         attr("module_contract_address", env.contract.address.to_string()),
+        attr("subscriber", subscriber.to_string()),
+        attr("amount", amount),
+        attr("prepaid_balance", prepaid_balance),
     ]))
 }
 
-/// Set or unset the discount for subscribers.
-/// Only this method can modify the Discount status. ModifySubscriber will not be able to modify the Discount section.
-pub fn execute_set_discount(
+/// Refunds the prepaid balance of the subscriber to the payer of the subscription, once the subscription has been cancelled
+/// and its current interval has ended, or once its term has been completed.
+///
+/// ## Executor
+/// Anyone can execute this function
+pub fn execute_refund_prepaid_balance(
     deps: DepsMut,
-    info: MessageInfo,
     env: Env,
-    discount: Option<Discount>,
     subscriber: Addr,
 ) -> Result<Response, ContractError> {
     let config: Config = read_config(deps.storage)?;
-    let cfg = ADMIN_CONFIG.load(deps.storage)?;
 
     if config.is_frozen {
         return Err(ContractError::Frozen {});
     }
 
-    let attributes = Vec::from([
-        attr("method", "set_discount"),
-        attr("module_contract_address", env.contract.address.to_string()),
-    ]);
-
-    // Only owner or admin can call this function
-    if !cfg.is_admin(info.sender.as_ref()) && !cfg.is_owner(info.sender.as_ref()) {
-        return Err(ContractError::Unauthorized {});
-    }
-
-    // get subscriber
-    let mut subscription = match SUBSCRIPTIONS.may_load(deps.storage, &subscriber.clone())? {
+    let subscription = match subscriptions().may_load(deps.storage, &subscriber)? {
         Some(v) => v,
         None => return Err(ContractError::SubscriptionNotFound {}),
     };
 
-    // validate discount
-    if !is_valid_discount(discount.clone(), config.unit_amount) {
-        return Err(ContractError::InvalidDiscount {});
+    let has_ended = (subscription.is_cancelled && env.block.time >= subscription.interval_end_at)
+        || subscription.is_completed(env.block.time);
+    if !has_ended {
+        return Err(ContractError::PrepaidBalanceLocked {});
     }
 
-    // the admin or owner should be able to set a subscriber's discount to None.
-    subscription.discount_per_interval = discount;
+    let (refund_msg, amount) =
+        match refund_prepaid_balance(deps.storage, &config, &subscriber, &subscription)? {
+            Some(refund) => refund,
+            None => return Err(ContractError::NoPrepaidBalance {}),
+        };
 
-    SUBSCRIPTIONS.save(deps.storage, &subscriber, &subscription)?;
-    Ok(Response::new().add_attributes(attributes))
+    Ok(Response::new().add_message(refund_msg).add_attributes(vec![
+        attr("method", "execute_refund_prepaid_balance"),
+        attr("module_contract_address", env.contract.address.to_string()),
+        attr("subscriber", subscriber.to_string()),
+        attr("payer", subscription.payer_address().to_string()),
+        attr("amount", amount),
+    ]))
 }
 
-/// Modify settings for a given subscriber. Owner should be able to change the `last_charged, `created_at` and `interval_end_at` timestamp.
-/// To modify the Discount status for a user, use the `ExecuteMsg::SetDiscount` message instead
+/// Changes the number of seats of the team subscription of the sender. The seats added or removed during a paid interval
+/// are prorated over the unused part of the interval: added seats are billed on the next charge, and removed seats are credited against it.
 ///
 /// ## Executor
-/// Only owner or admin can execute this function
-///
-#[allow(clippy::too_many_arguments)]
-pub fn execute_modify_subscriber(
+/// Only the owner of the team subscription can change its seats
+pub fn execute_update_seats(
     deps: DepsMut,
     info: MessageInfo,
     env: Env,
-    new_created_at: Option<u64>,
-    new_last_charged: Option<u64>,
-    new_interval_end_at: Option<u64>,
-    subscriber: Addr,
+    seats: u64,
 ) -> Result<Response, ContractError> {
     let config: Config = read_config(deps.storage)?;
-    let cfg = ADMIN_CONFIG.load(deps.storage)?;
+
+    if config.is_paused {
+        return Err(ContractError::Paused {});
+    }
 
     if config.is_frozen {
         return Err(ContractError::Frozen {});
     }
 
-    let mut attributes = Vec::from([
-        attr("method", "modify_subscriber"),
-        attr("module_contract_address", env.contract.address.to_string()),
-    ]);
-
-    // Only owner or admin can call this function
-    if !cfg.is_admin(info.sender.as_ref()) && !cfg.is_owner(info.sender.as_ref()) {
-        return Err(ContractError::Unauthorized {});
-    }
+    let owner = info.sender;
 
-    // get subscriber
-    let mut subscription = match SUBSCRIPTIONS.may_load(deps.storage, &subscriber.clone())? {
+    let mut subscription = match subscriptions().may_load(deps.storage, &owner)? {
         Some(v) => v,
         None => return Err(ContractError::SubscriptionNotFound {}),
     };
 
-    // updates the subscription value if applicable
-    if let Some(created_at) = new_created_at {
-        // created_at must be in the past
-        if created_at > env.block.time.seconds() {
-            return Err(ContractError::InvalidParam {});
-        }
+    let previous_seats = match subscription.seats {
+        Some(seats) => seats,
+        None => return Err(ContractError::NotTeamSubscription {}),
+    };
 
-        subscription.created_at = Timestamp::from_seconds(created_at);
-        attributes.push(attr("new_created_at", created_at.to_string()));
+    if subscription.is_cancelled {
+        return Err(ContractError::SubscriptionCancelled {});
     }
 
-    if let Some(interval_end_at) = new_interval_end_at {
-        // interval_end_at must be some time in the future
-        if interval_end_at <= env.block.time.seconds() {
-            return Err(ContractError::InvalidParam {});
-        }
+    // the paused time would otherwise be prorated as unused time of the interval
+    if subscription.is_paused(env.block.time) {
+        return Err(ContractError::SubscriptionPaused {});
+    }
 
-        subscription.interval_end_at = Timestamp::from_seconds(interval_end_at);
-        attributes.push(attr("new_interval_end_at", interval_end_at.to_string()));
+    if seats == 0 || seats > MAXIMUM_SEATS || seats == previous_seats {
+        return Err(ContractError::InvalidParam {});
     }
 
-    if let Some(last_charged) = new_last_charged {
-        // last_charged be some time in the past or present, but not in the future.
-        if last_charged > env.block.time.seconds() {
-            return Err(ContractError::InvalidParam {});
-        }
+    let members = TEAMS.may_load(deps.storage, &owner)?.unwrap_or_default();
+    if (members.len() as u64) > seats {
+        return Err(ContractError::SeatsBelowMembers {});
+    }
 
-        subscription.last_charged = Timestamp::from_seconds(last_charged);
-        attributes.push(attr("new_last_charged", last_charged.to_string()));
+    let plan = read_subscription_plan(deps.storage, &subscription)?;
+    let previous_subscription = subscription.clone();
+    if seats > previous_seats {
+        let amount =
+            compute_seat_proration(&plan, &subscription, seats - previous_seats, env.block.time);
+        // the prorated amount is first taken from the credit of the seats removed earlier in the interval
+        match subscription.seat_credit > amount {
+            true => subscription.seat_credit = subscription.seat_credit - amount,
+            false => {
+                subscription.seat_proration =
+                    subscription.seat_proration + (amount - subscription.seat_credit);
+                subscription.seat_credit = Uint256::zero();
+            }
+        }
+    } else {
+        let amount =
+            compute_seat_proration(&plan, &subscription, previous_seats - seats, env.block.time);
+        // the credit first cancels out the amount owed for the seats added earlier in the interval
+        match subscription.seat_proration > amount {
+            true => subscription.seat_proration = subscription.seat_proration - amount,
+            false => {
+                subscription.seat_credit =
+                    subscription.seat_credit + (amount - subscription.seat_proration);
+                subscription.seat_proration = Uint256::zero();
+            }
+        }
     }
+    subscription.seats = Some(seats);
 
-    SUBSCRIPTIONS.save(deps.storage, &subscriber, &subscription)?;
+    update_subscriber_stats(
+        deps.storage,
+        Some(&previous_subscription),
+        Some(&subscription),
+    )?;
+    subscriptions().save(deps.storage, &owner, &subscription)?;
 
-    Ok(Response::new().add_attributes(attributes))
+    Ok(Response::new().add_attributes(vec![
+        attr("method", "execute_update_seats"),
+        attr("module_contract_address", env.contract.address.to_string()),
+        attr("subscriber", owner.to_string()),
+        attr("previous_seats", previous_seats.to_string()),
+        attr("seats", seats.to_string()),
+        attr("seat_proration", subscription.seat_proration),
+        attr("seat_credit", subscription.seat_credit),
+    ]))
 }
 
-/// `execute_work` is called by a Worker who is performing upkeep for the contract
-pub fn execute_work(
+/// Assigns a seat of the team subscription of the sender to `member`. A member can only hold one seat at a time,
+/// and is active for as long as the team subscription is active
+///
+/// ## Executor
+/// Only the owner of the team subscription can assign its seats
+pub fn execute_assign_seat(
     deps: DepsMut,
     info: MessageInfo,
     env: Env,
-    payer_address: Addr,
+    member: Addr,
 ) -> Result<Response, ContractError> {
     let config: Config = read_config(deps.storage)?;
 
@@ -585,140 +1529,1142 @@ pub fn execute_work(
         return Err(ContractError::Frozen {});
     }
 
-    let worker = info.sender.to_string();
+    let owner = info.sender;
 
-    // get the job registry contract address
-    let job_registry_address = get_job_registry(deps.as_ref())?;
+    let subscription = match subscriptions().may_load(deps.storage, &owner)? {
+        Some(v) => v,
+        None => return Err(ContractError::SubscriptionNotFound {}),
+    };
+
+    let seats = match subscription.seats {
+        Some(seats) => seats,
+        None => return Err(ContractError::NotTeamSubscription {}),
+    };
+
+    if subscription.is_cancelled {
+        return Err(ContractError::SubscriptionCancelled {});
+    }
+
+    if TEAM_MEMBERS.may_load(deps.storage, &member)?.is_some() {
+        return Err(ContractError::SeatAlreadyAssigned {});
+    }
+
+    let mut members = TEAMS.may_load(deps.storage, &owner)?.unwrap_or_default();
+    if members.len() as u64 >= seats {
+        return Err(ContractError::NoSeatAvailable {});
+    }
+
+    members.push(member.clone());
+    TEAMS.save(deps.storage, &owner, &members)?;
+    TEAM_MEMBERS.save(deps.storage, &member, &owner)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("method", "execute_assign_seat"),
+        attr("module_contract_address", env.contract.address.to_string()),
+        attr("subscriber", owner.to_string()),
+        attr("member", member.to_string()),
+        attr("assigned_seats", members.len().to_string()),
+    ]))
+}
+
+/// Frees the seat of `member` in the team subscription of the sender, revoking the access of the member immediately
+///
+/// ## Executor
+/// Only the owner of the team subscription can unassign its seats
+pub fn execute_unassign_seat(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    member: Addr,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+
+    if config.is_frozen {
+        return Err(ContractError::Frozen {});
+    }
+
+    let owner = info.sender;
+
+    if TEAM_MEMBERS.may_load(deps.storage, &member)? != Some(owner.clone()) {
+        return Err(ContractError::SeatNotAssigned {});
+    }
+
+    let mut members = TEAMS.may_load(deps.storage, &owner)?.unwrap_or_default();
+    members.retain(|assigned| assigned != &member);
+    TEAMS.save(deps.storage, &owner, &members)?;
+    TEAM_MEMBERS.remove(deps.storage, &member);
+
+    Ok(Response::new().add_attributes(vec![
+        attr("method", "execute_unassign_seat"),
+        attr("module_contract_address", env.contract.address.to_string()),
+        attr("subscriber", owner.to_string()),
+        attr("member", member.to_string()),
+        attr("assigned_seats", members.len().to_string()),
+    ]))
+}
+
+/// Makes the subscriber pay for its own subscription from now on, instead of the subwallet that subscribed it.
+/// Future charges, including retries of a failed charge, are made from the subwallet of the subscriber
+///
+/// ## Executor
+/// Only the subscriber can take over the payment of its own subscription
+pub fn execute_take_over_payment(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+
+    if config.is_frozen {
+        return Err(ContractError::Frozen {});
+    }
+
+    let subscriber = info.sender;
+    assert_subwallet_payer(deps.as_ref(), &config, &subscriber)?;
+
+    let mut subscription = match subscriptions().may_load(deps.storage, &subscriber)? {
+        Some(v) => v,
+        None => return Err(ContractError::SubscriptionNotFound {}),
+    };
+
+    if subscription.is_cancelled {
+        return Err(ContractError::SubscriptionCancelled {});
+    }
+
+    let previous_payer = match subscription.payer.take() {
+        Some(payer) => payer,
+        // the subscriber already pays for its own subscription
+        None => return Err(ContractError::InvalidParam {}),
+    };
+
+    subscriptions().save(deps.storage, &subscriber, &subscription)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("method", "execute_take_over_payment"),
+        attr("module_contract_address", env.contract.address.to_string()),
+        attr("subscriber", subscriber.into_string()),
+        attr("previous_payer", previous_payer.into_string()),
+    ]))
+}
+
+/// Allows the subscriber to pause its own subscription until `resume_at`. No charges are made while the subscription is paused,
+/// and the end of the current interval (and of the free trial, if any) is moved by the paused time so that the paid time is kept.
+/// The pause cannot exceed the `max_pause_duration` of the [`Config`], and a subscriber can pause at most `max_pauses_per_year` times in any 365 days.
+///
+/// ## Executor
+/// Only the subscriber can pause its own subscription
+pub fn execute_pause_subscription(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    resume_at: u64,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+
+    if config.is_frozen {
+        return Err(ContractError::Frozen {});
+    }
+
+    let subscriber = info.sender;
+
+    let mut subscription = match subscriptions().may_load(deps.storage, &subscriber)? {
+        Some(v) => v,
+        None => return Err(ContractError::SubscriptionNotFound {}),
+    };
+
+    if subscription.is_cancelled {
+        return Err(ContractError::SubscriptionCancelled {});
+    }
+
+    if subscription.is_paused(env.block.time) {
+        return Err(ContractError::SubscriptionPaused {});
+    }
+
+    // outstanding payments have to be charged before the subscription can be paused
+    if env.block.time >= subscription.interval_end_at || subscription.is_past_due() {
+        return Err(ContractError::PaymentDue {});
+    }
+
+    // the pause must end in the future and cannot exceed the maximum pause duration. Pausing is disabled if the maximum is zero
+    let resume_at = Timestamp::from_seconds(resume_at);
+    if resume_at <= env.block.time
+        || resume_at.seconds() - env.block.time.seconds() > config.max_pause_duration
+    {
+        return Err(ContractError::InvalidParam {});
+    }
+
+    // only the pauses of the last 365 days count against the limit
+    subscription
+        .pause_history
+        .retain(|paused_at| paused_at.plus_seconds(ONE_YEAR_IN_SECONDS) > env.block.time);
+    if subscription.pause_history.len() >= config.max_pauses_per_year as usize {
+        return Err(ContractError::PauseLimitReached {});
+    }
+
+    let paused_time = resume_at.seconds() - env.block.time.seconds();
+    subscription.interval_end_at = subscription.interval_end_at.plus_seconds(paused_time);
+    if let Some(trial_end_at) = subscription.trial_end_at {
+        if env.block.time < trial_end_at {
+            subscription.trial_end_at = Some(trial_end_at.plus_seconds(paused_time));
+        }
+    }
+    subscription.term_end_at = subscription
+        .term_end_at
+        .map(|term_end_at| term_end_at.plus_seconds(paused_time));
+    subscription.resume_at = Some(resume_at);
+    subscription.pause_history.push(env.block.time);
+
+    subscriptions().save(deps.storage, &subscriber, &subscription)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("method", "execute_pause_subscription"),
+        attr("module_contract_address", env.contract.address.to_string()),
+        attr("subscriber", subscriber.into_string()),
+        attr("resume_at", resume_at.seconds().to_string()),
+        attr(
+            "new_interval_end_at",
+            subscription.interval_end_at.seconds().to_string(),
+        ),
+    ]))
+}
+
+/// Remove subscriber from the contract. Once removed, subscriber loses access to the services immediately without refunds for unused period.
+///
+/// ## Executor
+/// Only owner or admin can execute this function
+///
+pub fn execute_remove_subscriber(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    subscriber: Addr,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+
+    if config.is_frozen {
+        return Err(ContractError::Frozen {});
+    }
+
+    // Only owner or admin can call this function
+    let cfg = ADMIN_CONFIG.load(deps.storage)?;
+    if !cfg.is_admin(info.sender.as_ref()) && !cfg.is_owner(info.sender.as_ref()) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // the unused prepaid balance is given back to the payer
+    let prepaid_refund = match subscriptions().may_load(deps.storage, &subscriber.clone())? {
+        Some(subscription) => {
+            // remove_subscriber removes the susbcriber from the Map, revoking its access to the platform immediately without refunds
+            subscriptions().remove(deps.storage, &subscriber)?;
+            REMOVED_SUBSCRIPTIONS.save(deps.storage, &subscriber, &subscription)?;
+            update_subscriber_stats(deps.storage, Some(&subscription), None)?;
+            // the members of a team lose their seats along with the team subscription
+            remove_team(deps.storage, &subscriber)?;
+            refund_prepaid_balance(deps.storage, &config, &subscriber, &subscription)?
+        }
+        None => return Err(ContractError::SubscriptionNotFound {}),
+    };
+
+    let hook_msg = SubscriptionHookMsg::Removed {
+        subscriber: subscriber.to_string(),
+    };
+
+    let mut response = Response::new()
+        .add_submessages(build_hook_msgs(&config, hook_msg)?)
+        .add_attributes(vec![
+            attr("method", "execute_remove_subscriber"),
+            attr("subscriber", subscriber.to_string()),
+            attr("module_contract_address", env.contract.address.to_string()),
+        ]);
+
+    // the membership token is burned along with the subscription
+    if burn_membership(deps.storage, &subscriber)? {
+        response = response.add_attribute("burned_token_id", subscriber.to_string());
+    }
+
+    if let Some((refund_msg, amount)) = prepaid_refund {
+        response = response
+            .add_message(refund_msg)
+            .add_attribute("prepaid_refund", amount);
+    }
+
+    Ok(response)
+}
+
+/// Set or unset the discount for subscribers.
+/// Only this method can modify the Discount status. ModifySubscriber will not be able to modify the Discount section.
+pub fn execute_set_discount(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    discount: Option<Discount>,
+    subscriber: Addr,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let cfg = ADMIN_CONFIG.load(deps.storage)?;
+
+    if config.is_frozen {
+        return Err(ContractError::Frozen {});
+    }
+
+    let attributes = Vec::from([
+        attr("method", "set_discount"),
+        attr("module_contract_address", env.contract.address.to_string()),
+    ]);
+
+    // Only owner or admin can call this function
+    if !cfg.is_admin(info.sender.as_ref()) && !cfg.is_owner(info.sender.as_ref()) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // get subscriber
+    let mut subscription = match subscriptions().may_load(deps.storage, &subscriber.clone())? {
+        Some(v) => v,
+        None => return Err(ContractError::SubscriptionNotFound {}),
+    };
+
+    // validate discount against the plan that the subscriber is on
+    let plan = read_subscription_plan(deps.storage, &subscription)?;
+    if !is_valid_discount(discount.as_ref(), plan.unit_amount, env.block.time) {
+        return Err(ContractError::InvalidDiscount {});
+    }
+
+    // the admin or owner should be able to set a subscriber's discount to None.
+    subscription.discount_per_interval = discount;
+
+    subscriptions().save(deps.storage, &subscriber, &subscription)?;
+    Ok(Response::new().add_attributes(attributes))
+}
+
+/// Modify settings for a given subscriber. Owner should be able to change the `last_charged, `created_at` and `interval_end_at` timestamp.
+/// To modify the Discount status for a user, use the `ExecuteMsg::SetDiscount` message instead
+///
+/// ## Executor
+/// Only owner or admin can execute this function
+///
+#[allow(clippy::too_many_arguments)]
+pub fn execute_modify_subscriber(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    new_created_at: Option<u64>,
+    new_last_charged: Option<u64>,
+    new_interval_end_at: Option<u64>,
+    subscriber: Addr,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let cfg = ADMIN_CONFIG.load(deps.storage)?;
+
+    if config.is_frozen {
+        return Err(ContractError::Frozen {});
+    }
+
+    let mut attributes = Vec::from([
+        attr("method", "modify_subscriber"),
+        attr("module_contract_address", env.contract.address.to_string()),
+    ]);
+
+    // Only owner or admin can call this function
+    if !cfg.is_admin(info.sender.as_ref()) && !cfg.is_owner(info.sender.as_ref()) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // get subscriber
+    let mut subscription = match subscriptions().may_load(deps.storage, &subscriber.clone())? {
+        Some(v) => v,
+        None => return Err(ContractError::SubscriptionNotFound {}),
+    };
+
+    // updates the subscription value if applicable
+    if let Some(created_at) = new_created_at {
+        // created_at must be in the past
+        if created_at > env.block.time.seconds() {
+            return Err(ContractError::InvalidParam {});
+        }
+
+        subscription.created_at = Timestamp::from_seconds(created_at);
+        attributes.push(attr("new_created_at", created_at.to_string()));
+    }
+
+    if let Some(interval_end_at) = new_interval_end_at {
+        // interval_end_at must be some time in the future
+        if interval_end_at <= env.block.time.seconds() {
+            return Err(ContractError::InvalidParam {});
+        }
+
+        subscription.interval_end_at = Timestamp::from_seconds(interval_end_at);
+        attributes.push(attr("new_interval_end_at", interval_end_at.to_string()));
+    }
+
+    if let Some(last_charged) = new_last_charged {
+        // last_charged be some time in the past or present, but not in the future.
+        if last_charged > env.block.time.seconds() {
+            return Err(ContractError::InvalidParam {});
+        }
+
+        subscription.last_charged = Timestamp::from_seconds(last_charged);
+        attributes.push(attr("new_last_charged", last_charged.to_string()));
+    }
+
+    subscriptions().save(deps.storage, &subscriber, &subscription)?;
+
+    Ok(Response::new().add_attributes(attributes))
+}
+
+/// `execute_work` is called by a Worker who is performing upkeep for the contract
+pub fn execute_work(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    payer_address: Addr,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+
+    if config.is_frozen {
+        return Err(ContractError::Frozen {});
+    }
+
+    let worker = info.sender.to_string();
+
+    // execute_work differs from execute_charge due to the work receipt that must be sent back to the `job_registry` contract.
+    // The receipt is sent after the payment, so that the worker is not rewarded for a charge that fails
+    let receipt_msg = send_work_receipt_msg(&env, worker, 1)?;
+    execute_charge(deps, env, payer_address, Some(receipt_msg))
+}
+
+// builds the message that the contract sends to itself to send the work receipt once the payments of the charges have been collected
+fn send_work_receipt_msg(env: &Env, worker_address: String, units: u64) -> StdResult<CosmosMsg> {
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: env.contract.address.to_string(),
+        funds: vec![],
+        msg: to_binary(&ExecuteMsg::SendWorkReceipt {
+            worker_address,
+            units,
+        })?,
+    }))
+}
+
+/// Sends a work receipt for `units` charges to the job registry, less the charges of the transaction whose payment failed.
+/// No receipt is sent if every charge has failed
+///
+/// ## Executor
+/// Can only be performed by the contract itself
+pub fn execute_send_work_receipt(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    worker_address: String,
+    units: u64,
+) -> Result<Response, ContractError> {
+    if info.sender != env.contract.address {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let failed = PENDING_CHARGES
+        .load(deps.storage)?
+        .iter()
+        .filter(|pending_charge| pending_charge.is_failed)
+        .count() as u64;
+    let units = units.saturating_sub(failed);
+
+    let mut response = Response::new().add_attributes(vec![
+        attr("method", "send_work_receipt"),
+        attr("worker_address", worker_address.clone()),
+        attr("units", units.to_string()),
+    ]);
+
+    if units > 0 {
+        // get the job registry contract address
+        let job_registry_address = get_job_registry(deps.as_ref())?;
+
+        response = response.add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: job_registry_address.to_string(),
+            funds: vec![],
+            msg: to_binary(&JobsRegistryExecuteMsg::WorkReceipt {
+                worker_address,
+                units: Some(units),
+            })?,
+        }));
+    }
+
+    Ok(response)
+}
+
+/// Charges up to [`MAX_LIMIT`] subscribers in a single call. Subscribers that cannot be charged
+/// (eg. not found, cancelled, lapsed or not yet due) and charges that fail are skipped instead of failing the whole batch.
+/// The attributes of every charge are kept in a `charge` event, and the error of every failed charge in a `charge_skipped` event.
+/// A single work receipt scaled to the number of charges that have been paid is sent to the job registry.
+/// ## Params
+/// * **deps** is the object of type [`DepsMut`].
+///
+/// * **info** is the object of type [`MessageInfo`].
+///
+/// * **env** is the object of type [`Env`].
+///
+/// * **payer_addresses** is the list of subscribers to charge of type [`Vec<String>`]
+///
+pub fn execute_batch_work(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    payer_addresses: Vec<String>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+
+    if config.is_frozen {
+        return Err(ContractError::Frozen {});
+    }
+
+    if payer_addresses.len() > MAX_LIMIT as usize {
+        return Err(ContractError::BatchTooLarge {});
+    }
+
+    PENDING_CHARGES.save(deps.storage, &vec![])?;
+
+    let mut response = Response::new();
+    let mut charged: Vec<String> = vec![];
+    let mut skipped: Vec<String> = vec![];
+
+    for payer_address in payer_addresses {
+        let subscriber = match deps.api.addr_validate(&payer_address) {
+            Ok(subscriber) => subscriber,
+            Err(_) => {
+                skipped.push(payer_address);
+                continue;
+            }
+        };
+
+        let chargeable = match subscriptions().may_load(deps.storage, &subscriber)? {
+            Some(subscription) => is_chargeable(deps.as_ref(), &env, &subscription),
+            None => false,
+        };
+
+        if !chargeable {
+            skipped.push(payer_address);
+            continue;
+        }
+
+        match charge_subscription(deps.branch(), &env, subscriber) {
+            Ok(charge_response) => {
+                response = response
+                    .add_submessages(charge_response.messages)
+                    .add_event(Event::new("charge").add_attributes(charge_response.attributes))
+                    .add_events(charge_response.events);
+                charged.push(payer_address);
+            }
+            Err(error) => {
+                response = response.add_event(Event::new("charge_skipped").add_attributes(vec![
+                    attr("subscriber", payer_address.clone()),
+                    attr("error", error.to_string()),
+                ]));
+                skipped.push(payer_address);
+            }
+        }
+    }
+
+    if charged.is_empty() {
+        return Err(ContractError::NoCharge {});
+    }
+
+    // the work receipt is sent after the payments, so that only the charges that have been paid are counted
+    Ok(response
+        .add_message(send_work_receipt_msg(
+            &env,
+            info.sender.to_string(),
+            charged.len() as u64,
+        )?)
+        .add_attributes(vec![
+            attr("method", "execute_batch_work"),
+            attr("module_contract_address", env.contract.address.to_string()),
+            attr("charged", charged.join(",")),
+            attr("skipped", skipped.join(",")),
+        ]))
+}
+
+/// Charges the payer (subscriber), where the contract will attempt to transfer tokens to the receiving address.
+/// If successful, the [`SubscriptionInfo`] object will be changed accordingly.
+/// ## Params
+/// * **deps** is the object of type [`DepsMut`].
+///
+/// * **_env** is the object of type [`Env`].
+///
+/// * **subscriber** is the address of the payer of type [`Addr`]
+///
+/// * **additional_message** is any [`Option`] type additional message that the caller could attach to the message. Required for job registry to work properly.
+///
+pub fn execute_charge(
+    deps: DepsMut,
+    env: Env,
+    subscriber: Addr,
+    additional_message: Option<CosmosMsg>,
+) -> Result<Response, ContractError> {
+    PENDING_CHARGES.save(deps.storage, &vec![])?;
+
+    let mut response = charge_subscription(deps, &env, subscriber)?;
+
+    // pushes the additional messages if there is any.
+    // Required if the worker wants to push some messages relating to the claim fees
+    if let Some(msg) = additional_message {
+        response = response.add_message(msg);
+    }
+
+    Ok(response)
+}
+
+/// Updates the [`SubscriptionInfo`] of the subscriber for the charge, and sends the payment as a [`SubMsg`] that replies on error.
+/// If the subwallet cannot pay, the subscription is restored in [`reply`] and becomes past due.
+/// Every check is done before the subscription is updated, so that a charge that fails leaves no changes behind.
+/// [`PENDING_CHARGES`] must be cleared before the first charge of the transaction.
+fn charge_subscription(
+    deps: DepsMut,
+    env: &Env,
+    subscriber: Addr,
+) -> Result<Response, ContractError> {
+    // checks if the contract is paused. If paused, do not proceed to charge existing subscribers
+    let config = read_config(deps.storage)?;
+
+    if config.is_frozen {
+        return Err(ContractError::Frozen {});
+    }
+
+    // get receiver address
+    let subscription = match subscriptions().may_load(deps.storage, &subscriber.clone())? {
+        Some(v) => v,
+        None => return Err(ContractError::SubscriptionNotFound {}),
+    };
+
+    if subscription.is_cancelled {
+        return Err(ContractError::SubscriptionCancelled {});
+    }
+
+    if subscription.is_completed(env.block.time) {
+        return Err(ContractError::SubscriptionCompleted {});
+    }
+
+    if subscription.is_paused(env.block.time) {
+        return Err(ContractError::SubscriptionPaused {});
+    }
+
+    // past due subscriptions are not retried once the grace period has ended. They lapse instead of being charged
+    if subscription.is_past_due() && env.block.time > grace_period_end_at(&config, &subscription) {
+        let mut subscription = subscription;
+        let mut attributes = vec![
+            attr("method", "execute_charge"),
+            attr("module_contract_address", env.contract.address.to_string()),
+            attr("subscriber", subscriber.to_string()),
+        ];
+        let hook_msgs = lapse_subscription(
+            deps.storage,
+            &config,
+            &subscriber,
+            &mut subscription,
+            &mut attributes,
+        )?;
+        subscriptions().save(deps.storage, &subscriber, &subscription)?;
+
+        return Ok(Response::new()
+            .add_submessages(hook_msgs)
+            .add_attributes(attributes));
+    }
+
+    // past due subscriptions can only be charged again once the next retry is due
+    if matches!(subscription.next_retry_at, Some(next_retry_at) if env.block.time < next_retry_at) {
+        return Err(ContractError::RetryNotDue {});
+    }
+
+    // charge should not be possible if the subscription is no longer active (i.e. falls outside of grace period)
+    // To resume subscription, subscriber should resubscribe again
+    if !is_subscription_active(deps.storage, env.clone(), subscription.clone()) {
+        // throw error CannotCharge if the subscription is no longer active. Workers should not be able to charge.
+        return Err(ContractError::CannotCharge {});
+    }
+
+    // subscribers cannot be charged once the max_amount_chargeable of the product has been paid
+    if matches!(config.max_amount_chargeable, Some(max_amount) if subscription.total_paid >= max_amount)
+    {
+        return Err(ContractError::MaxAmountChargeableReached {});
+    }
+
+    // gets the chargeable amount after discount and max cap if any. The last charge before the cap is reached may be partial
+    let chargeable_amount: AmountTransferable =
+        get_chargeable_amount(deps.as_ref(), env, &subscription)?;
+
+    // intervals of metered products without usage or base fee, and intervals that are paid by a seat credit, are renewed without a payment
+    if chargeable_amount.amount.is_zero()
+        && !((config.is_metered() || !chargeable_amount.seat_credit.is_zero())
+            && chargeable_amount.number_of_intervals > 0)
+    {
+        return Err(ContractError::NoCharge {});
+    }
+
+    let plan = read_subscription_plan(deps.storage, &subscription)?;
+
+    if !chargeable_amount.amount.is_zero() {
+        // get fee info from factory
+        let fee = query_product_factory_config(&deps.querier, config.factory_address.clone())?;
+
+        // fee cannot be more than 100% and fee must not be more than chargeable_amount
+        if fee.protocol_fee_bps > MAX_FEE_DECIMAL || fee.min_protocol_fee > chargeable_amount.amount
+        {
+            return Err(ContractError::InvalidFee {});
+        }
+    }
+
+    let mut updated_subscription = subscription.clone();
+    updated_subscription.last_charged = env.block.time;
+    updated_subscription.total_paid = subscription.total_paid + chargeable_amount.amount;
+    updated_subscription.lifetime_paid = subscription.lifetime_paid + chargeable_amount.amount;
+    updated_subscription.interval_end_at = plan.interval_end_after(
+        subscription.interval_end_at,
+        chargeable_amount.number_of_intervals,
+    );
+    updated_subscription.discount_per_interval = remaining_discount(
+        subscription.discount_per_interval.clone(),
+        chargeable_amount.discounted_intervals,
+        updated_subscription.interval_end_at,
+    );
+    updated_subscription.retry_count = 0;
+    updated_subscription.next_retry_at = None;
+    // the prorated seat changes have been billed
+    updated_subscription.seat_proration = Uint256::zero();
+    updated_subscription.seat_credit = subscription.seat_credit - chargeable_amount.seat_credit;
+    // the usage of the charged intervals has been billed
+    let billed_until = updated_subscription.interval_end_at;
+    updated_subscription
+        .usage
+        .retain(|record| record.interval_end_at >= billed_until);
+
+    // update the subscription object
+    subscriptions().save(deps.storage, &subscriber, &updated_subscription)?;
+
+    let mut response = Response::new().add_attributes(vec![
+        attr("method", "execute_charge"),
+        attr("module_contract_address", env.contract.address.to_string()),
+        attr("subscriber", subscriber.to_string()),
+        attr("amount", chargeable_amount.amount.to_string()),
+        attr("periods", chargeable_amount.number_of_intervals.to_string()),
+    ]);
+
+    if config.is_metered() {
+        response = response.add_attribute("usage_amount", chargeable_amount.usage_amount);
+    }
+
+    if chargeable_amount.amount.is_zero() {
+        return Ok(response);
+    }
+
+    let charge_id = record_payment(
+        deps.storage,
+        &subscriber,
+        subscription.payer_address(),
+        chargeable_amount.amount,
+        env.block.time,
+    )?;
+
+    // the subscription before the charge is kept until the end of the transaction, so that it can be restored if the payment fails
+    let mut pending_charges = PENDING_CHARGES.load(deps.storage)?;
+    let reply_id = pending_charges.len() as u64;
+    pending_charges.push(PendingCharge {
+        subscriber: subscriber.clone(),
+        subscription,
+        charge_id,
+        is_failed: false,
+    });
+    PENDING_CHARGES.save(deps.storage, &pending_charges)?;
+
+    let payment_msg = SubMsg::reply_on_error(
+        WasmMsg::Execute {
+            contract_addr: env.contract.address.to_string(),
+            funds: vec![],
+            msg: to_binary(&ExecuteMsg::CollectPayment {
+                subscriber: subscriber.to_string(),
+                payer: updated_subscription.payer_address().to_string(),
+                amount: chargeable_amount.amount,
+            })?,
+        },
+        reply_id,
+    );
+
+    Ok(response
+        .add_attribute("charge_id", charge_id.to_string())
+        .add_submessage(payment_msg))
+}
+
+/// Transfers `amount` for the subscriber from the subwallet of the payer to the receiver, after deducting the protocol fee.
+/// Sent by the contract to itself when charging, so that a failure of either transfer fails the whole payment.
+///
+/// ## Executor
+/// Can only be performed by the contract itself
+pub fn execute_collect_payment(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    subscriber: Addr,
+    payer: Addr,
+    amount: Uint256,
+) -> Result<Response, ContractError> {
+    if info.sender != env.contract.address {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let config = read_config(deps.storage)?;
+
+    // get fee info from factory
+    let fee = query_product_factory_config(&deps.querier, config.factory_address.clone())?;
+
+    let msgs: Vec<CosmosMsg> =
+        build_subscription_payment_msgs(deps.branch(), &subscriber, &payer, &config, &fee, amount)?;
+    // the revenue is only counted once the payment has been transferred
+    record_payment_revenue(deps.storage, &fee, amount)?;
+
+    // hooks are only called once the payment has been transferred
+    let hook_msg = SubscriptionHookMsg::Charged {
+        subscriber: subscriber.to_string(),
+        amount,
+    };
+
+    Ok(Response::new()
+        .add_messages(msgs)
+        .add_submessages(build_hook_msgs(&config, hook_msg)?)
+        .add_attributes(vec![
+            attr("method", "collect_payment"),
+            attr("subscriber", subscriber.into_string()),
+            attr("payer", payer.into_string()),
+            attr("amount", amount.to_string()),
+        ]))
+}
+
+/// Sends `amount` back to the subwallet that made the payment with the given `charge_id` for the subscriber.
+/// The receiver sends the refunded native billing asset (UST by default) along with the message, and the refund is recorded against the [`Payment`].
+/// Payments can be partially refunded any number of times, up to the amount paid.
+///
+/// ## Executor
+/// Only the receiver can execute this function
+pub fn execute_refund(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    subscriber: Addr,
+    amount: Uint256,
+    charge_id: u64,
+) -> Result<Response, ContractError> {
+    let config = read_config(deps.storage)?;
+
+    if info.sender != config.receiver_address {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if amount.is_zero() {
+        return Err(ContractError::InvalidParam {});
+    }
+
+    // products billed in a cw20 token are refunded through `Cw20HookMsg::Refund`
+    let denom = match native_billing_denom(&config) {
+        Some(denom) => denom,
+        None => return Err(ContractError::InvalidRefundFunds {}),
+    };
+
+    load_refundable_payment(deps.storage, &subscriber, charge_id, amount)?;
+
+    // the refund is funded by the receiver, so exactly the refunded amount must be sent
+    if info.funds != coins(checked_uint128(amount)?.u128(), denom.clone()) {
+        return Err(ContractError::InvalidRefundFunds {});
+    }
+
+    refund_payment(
+        deps,
+        env,
+        subscriber,
+        AssetInfo::NativeToken { denom },
+        amount,
+        charge_id,
+    )
+}
+
+/// Handles the cw20 tokens received with a [`Cw20HookMsg`]
+///
+/// ## Executor
+/// Only the cw20 billing asset of the product can be received
+pub fn execute_receive(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    cw20_msg: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let config = read_config(deps.storage)?;
+
+    let refund_asset_info = match config.billing_asset {
+        Some(AssetInfo::Token { contract_addr }) if contract_addr == info.sender => {
+            AssetInfo::Token { contract_addr }
+        }
+        _ => return Err(ContractError::InvalidRefundFunds {}),
+    };
+
+    match from_binary(&cw20_msg.msg)? {
+        Cw20HookMsg::Refund {
+            subscriber,
+            charge_id,
+        } => {
+            if deps.api.addr_validate(&cw20_msg.sender)? != config.receiver_address {
+                return Err(ContractError::Unauthorized {});
+            }
 
-    // execute_work differs from execute_charge due to the optional_message that must be sent back to the `job_regisexecute contract`
-    execute_charge(
-        deps,
-        env,
-        payer_address,
-        Some(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: job_registry_address.to_string(),
+            let subscriber = deps.api.addr_validate(&subscriber)?;
+            refund_payment(
+                deps,
+                env,
+                subscriber,
+                refund_asset_info,
+                Uint256::from(cw20_msg.amount),
+                charge_id,
+            )
+        }
+    }
+}
+
+// loads the payment `charge_id` of the subscriber, of which at least `amount` must be left to refund
+fn load_refundable_payment(
+    storage: &dyn Storage,
+    subscriber: &Addr,
+    charge_id: u64,
+    amount: Uint256,
+) -> Result<Payment, ContractError> {
+    let payment: Payment =
+        match PAYMENTS.may_load(storage, (subscriber, U64Key::from(charge_id)))? {
+            Some(payment) => payment,
+            None => return Err(ContractError::PaymentNotFound {}),
+        };
+
+    if amount > payment.amount - payment.refunded_amount {
+        return Err(ContractError::RefundExceedsPayment {});
+    }
+
+    Ok(payment)
+}
+
+// converts an amount to a `Uint128`, which native and cw20 transfers are made in
+fn checked_uint128(amount: Uint256) -> Result<Uint128, ContractError> {
+    if amount > Uint256::from(u128::MAX) {
+        return Err(ContractError::InvalidParam {});
+    }
+
+    Ok(Uint128::from(amount))
+}
+
+// records the refund of `amount` against the payment and sends the refunded asset to the payer
+fn refund_payment(
+    deps: DepsMut,
+    env: Env,
+    subscriber: Addr,
+    asset_info: AssetInfo,
+    amount: Uint256,
+    charge_id: u64,
+) -> Result<Response, ContractError> {
+    let mut payment = load_refundable_payment(deps.storage, &subscriber, charge_id, amount)?;
+    let refund_amount = checked_uint128(amount)?;
+
+    payment.refunded_amount = payment.refunded_amount + amount;
+    PAYMENTS.save(
+        deps.storage,
+        (&subscriber, U64Key::from(charge_id)),
+        &payment,
+    )?;
+
+    let refund_event = Event::new("refund").add_attributes(vec![
+        attr("subscriber", subscriber.to_string()),
+        attr("payer", payment.payer.to_string()),
+        attr("charge_id", charge_id.to_string()),
+        attr("amount", amount.to_string()),
+        attr("refunded_amount", payment.refunded_amount.to_string()),
+    ]);
+
+    let refund_msg: CosmosMsg = match asset_info {
+        AssetInfo::NativeToken { denom } => BankMsg::Send {
+            to_address: payment.payer.to_string(),
+            amount: coins(refund_amount.u128(), denom),
+        }
+        .into(),
+        AssetInfo::Token { contract_addr } => WasmMsg::Execute {
+            contract_addr: contract_addr.to_string(),
             funds: vec![],
-            msg: to_binary(&JobsRegistryExecuteMsg::WorkReceipt {
-                worker_address: worker,
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: payment.payer.to_string(),
+                amount: refund_amount,
             })?,
-        })),
-    )
+        }
+        .into(),
+    };
+
+    Ok(Response::new()
+        .add_message(refund_msg)
+        .add_event(refund_event)
+        .add_attributes(vec![
+            attr("method", "execute_refund"),
+            attr("module_contract_address", env.contract.address.to_string()),
+        ]))
 }
 
-/// Charges the payer (subscriber), where the contract will attempt to transfer tokens to the receiving address.
-/// If successful, the [`SubscriptionInfo`] object will be changed accordingly.
-/// ## Params
-/// * **deps** is the object of type [`DepsMut`].
-///
-/// * **_env** is the object of type [`Env`].
-///
-/// * **subscriber** is the address of the payer of type [`Addr`]
-///
-/// * **additional_message** is any [`Option`] type additional message that the caller could attach to the message. Required for job registry to work properly.
+/// Transfers the membership token of `subscriber` (i.e. the token ID) to `recipient`. The subscription itself is not transferred,
+/// and the subscriber keeps paying for it. Approvals are not supported
 ///
-pub fn execute_charge(
+/// ## Executor
+/// Only the owner of the token can transfer it, if the membership tokens of the product are transferable
+pub fn execute_transfer_nft(
     deps: DepsMut,
+    info: MessageInfo,
     env: Env,
+    recipient: Addr,
     subscriber: Addr,
-    additional_message: Option<CosmosMsg>,
 ) -> Result<Response, ContractError> {
-    // checks if the contract is paused. If paused, do not proceed to charge existing subscribers
     let config = read_config(deps.storage)?;
 
     if config.is_frozen {
         return Err(ContractError::Frozen {});
     }
 
-    // get receiver address
-    let subscription = match SUBSCRIPTIONS.may_load(deps.storage, &subscriber.clone())? {
-        Some(v) => v,
-        None => return Err(ContractError::SubscriptionNotFound {}),
+    let mut token: MembershipToken = match memberships().may_load(deps.storage, &subscriber)? {
+        Some(token) => token,
+        None => return Err(ContractError::MembershipNotFound {}),
     };
 
-    if subscription.is_cancelled {
-        return Err(ContractError::SubscriptionCancelled {});
+    if !matches!(config.membership_nft, Some(membership_nft) if membership_nft.transferable) {
+        return Err(ContractError::MembershipNotTransferable {});
     }
 
-    // charge should not be possible if the subscription is no longer active (i.e. past due and falls outside of grace period)
-    // To resume subscription, subscriber should resubscribe again
-    if !is_subscription_active(deps.storage, env.clone(), subscription.clone()) {
-        // throw error CannotCharge if the subscription is no longer active. Workers should not be able to charge.
-        return Err(ContractError::CannotCharge {});
+    if info.sender != token.owner {
+        return Err(ContractError::Unauthorized {});
     }
 
-    // gets the chargeable amount after discount if any
-    let chargeable_amount: AmountTransferable =
-        get_chargeable_amount(deps.as_ref(), &env, &subscription)?;
+    token.owner = recipient.clone();
+    memberships().save(deps.storage, &subscriber, &token)?;
 
-    if chargeable_amount.amount.is_zero() {
-        return Err(ContractError::NoCharge {});
+    Ok(Response::new().add_attributes(vec![
+        attr("method", "execute_transfer_nft"),
+        attr("module_contract_address", env.contract.address.to_string()),
+        attr("sender", info.sender.to_string()),
+        attr("recipient", recipient.to_string()),
+        attr("token_id", subscriber.to_string()),
+    ]))
+}
+
+/// # Description
+/// The entry point to the contract for processing the reply from a payment that failed.
+/// The charge is reverted and the subscription becomes past due. The next retry is scheduled with the `retry_schedule` of the [`Config`],
+/// and the subscription is cancelled once the final retry has failed, or if the next retry would fall after the grace period.
+/// # Params
+/// * **deps** is the object of type [`DepsMut`].
+///
+/// * **env** is the object of type [`Env`].
+///
+/// * **msg** is the object of type [`Reply`].
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    let error = match msg.result {
+        ContractResult::Err(error) => error,
+        // payments and hooks only reply on error
+        ContractResult::Ok(_) => return Err(ContractError::InvalidReplyID {}),
+    };
+
+    // a failing hook is ignored, so that it cannot block billing
+    if msg.id == HOOK_REPLY_ID {
+        return Ok(Response::new().add_attributes(vec![
+            attr("method", "hook_failed"),
+            attr("module_contract_address", env.contract.address.to_string()),
+            attr("error", error),
+        ]));
     }
 
-    let mut updated_subscription = subscription.clone();
-    updated_subscription.last_charged = env.block.time;
-    updated_subscription.interval_end_at = subscription
-        .interval_end_at
-        .plus_seconds(config.unit_interval.seconds() * chargeable_amount.number_of_intervals);
+    let mut pending_charges = PENDING_CHARGES.load(deps.storage)?;
+    let pending_charge = match pending_charges.get_mut(msg.id as usize) {
+        Some(pending_charge) => {
+            // the failed charge is not counted in the work receipt
+            pending_charge.is_failed = true;
+            pending_charge.clone()
+        }
+        None => return Err(ContractError::InvalidReplyID {}),
+    };
+    PENDING_CHARGES.save(deps.storage, &pending_charges)?;
 
-    // update the subscription object
-    SUBSCRIPTIONS.save(deps.storage, &subscriber, &updated_subscription)?;
+    let config = read_config(deps.storage)?;
+    let mut hook_msgs = vec![];
 
-    // get fee info from factory
-    let fee = query_product_factory_config(&deps.querier, config.factory_address.clone())?;
+    // restores the subscription to its state before the charge
+    let mut subscription = pending_charge.subscription;
+    subscription.retry_count += 1;
 
-    // fee cannot be more than 100% and fee must not be more than chargeable_amount
-    if fee.protocol_fee_bps > MAX_FEE_DECIMAL || fee.min_protocol_fee > chargeable_amount.amount {
-        return Err(ContractError::InvalidFee {});
+    let mut attributes = vec![
+        attr("method", "charge_failed"),
+        attr("module_contract_address", env.contract.address.to_string()),
+        attr("subscriber", pending_charge.subscriber.to_string()),
+        attr("retry_count", subscription.retry_count.to_string()),
+    ];
+
+    let next_retry_at = config
+        .retry_schedule
+        .get(subscription.retry_count as usize - 1)
+        .map(|retry_delay| env.block.time.plus_seconds(*retry_delay));
+
+    match next_retry_at {
+        Some(next_retry_at) if next_retry_at <= grace_period_end_at(&config, &subscription) => {
+            subscription.next_retry_at = Some(next_retry_at);
+            attributes.push(attr("next_retry_at", next_retry_at.seconds().to_string()));
+        }
+        // every retry has failed, or the subscription lapses before the next retry
+        _ => {
+            hook_msgs = lapse_subscription(
+                deps.storage,
+                &config,
+                &pending_charge.subscriber,
+                &mut subscription,
+                &mut attributes,
+            )?;
+        }
     }
 
-    let mut msgs: Vec<CosmosMsg> = Vec::new();
+    subscriptions().save(deps.storage, &pending_charge.subscriber, &subscription)?;
 
-    let protocol_fee = calculate_protocol_fee(
-        fee.protocol_fee_bps,
-        fee.min_protocol_fee,
-        chargeable_amount.amount,
+    // the failed payment is removed from the payment history
+    PAYMENTS.remove(
+        deps.storage,
+        (
+            &pending_charge.subscriber,
+            U64Key::from(pending_charge.charge_id),
+        ),
     );
 
-    let mut merchant_amount = chargeable_amount.amount;
+    attributes.push(attr("error", error));
 
-    if let Some(protocol_fee) = protocol_fee {
-        // updates the amt with fees
-        merchant_amount = merchant_amount - protocol_fee;
-
-        // append message for fee collection
-        msgs.push(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: subscriber.clone().into_string(),
-            funds: vec![],
-            msg: to_binary(&SubWalletExecuteMsg::TransferAToken {
-                recipient: fee.fee_address,
-                amount: Uint128::from(protocol_fee),
-            })?,
-        }));
-    }
+    Ok(Response::new()
+        .add_submessages(hook_msgs)
+        .add_attributes(attributes))
+}
 
-    // append message to send fees to the merchant
-    msgs.push(CosmosMsg::Wasm(WasmMsg::Execute {
-        contract_addr: subscriber.clone().into_string(),
-        funds: vec![],
-        msg: to_binary(&SubWalletExecuteMsg::TransferAToken {
-            recipient: config.receiver_address.to_string(),
-            amount: Uint128::from(merchant_amount),
-        })?,
-    }));
+// cancels a past due subscription that is no longer retried, burns its membership token and builds the `Lapsed` hook messages.
+// the caller saves the subscription
+fn lapse_subscription(
+    storage: &mut dyn Storage,
+    config: &Config,
+    subscriber: &Addr,
+    subscription: &mut SubscriptionInfo,
+    attributes: &mut Vec<Attribute>,
+) -> Result<Vec<SubMsg>, ContractError> {
+    let active_subscription = subscription.clone();
+    subscription.next_retry_at = None;
+    subscription.is_cancelled = true;
+    update_subscriber_stats(storage, Some(&active_subscription), Some(subscription))?;
+    attributes.push(attr("result", "subscription_cancelled"));
 
-    // pushes the additional messages if there is any.
-    // Required if the worker wants to push some messages relating to the claim fees
-    if let Some(msg) = additional_message {
-        msgs.push(msg);
+    // the subscription is no longer active, so its membership token is burned
+    if burn_membership(storage, subscriber)? {
+        attributes.push(attr("burned_token_id", subscriber.to_string()));
     }
 
-    Ok(Response::new().add_messages(msgs).add_attributes(vec![
-        attr("method", "execute_charge"),
-        attr("module_contract_address", env.contract.address.to_string()),
-        attr("subscriber", subscriber.into_string()),
-        attr("amount", chargeable_amount.amount.to_string()),
-        attr("periods", chargeable_amount.number_of_intervals.to_string()),
-    ]))
+    Ok(build_hook_msgs(
+        config,
+        SubscriptionHookMsg::Lapsed {
+            subscriber: subscriber.to_string(),
+        },
+    )?)
 }
 
 /// Toggles the `is_paused` variable in the contract. if the `is_paused` variable was `false`, then this function should toggle it to true. Same applies vice-versa.
@@ -748,51 +2694,237 @@ pub fn execute_toggle_pause(
         return Err(ContractError::Unauthorized {});
     }
 
-    let new_flag = !config.is_paused;
-    config.is_paused = new_flag;
+    let new_flag = !config.is_paused;
+    config.is_paused = new_flag;
+
+    store_config(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("method", "execute_toggle_pause"),
+        attr("module_contract_address", env.contract.address.to_string()),
+    ]))
+}
+
+/// Toggles the `is_frozen` variable in the contract. if the `is_frozen` variable was `false`, then this function should toggle it to true. Same applies vice-versa.
+/// Once frozen, only admin features are allowed. This function may caused disruptive behavior since charges cannot proceed on a frozen contract.
+///
+/// Users still have their [`SubscriptionInfo`] stored on the contract. However, as charge is not allowed, it may result in subscription expiring.
+///
+/// Returns an [`ContractError`] on failure or returns the [`Response`] with the specified attributes
+/// if the operation was successful.
+///
+/// ## Params
+/// * **deps** is the object of type [`DepsMut`].
+///
+/// * **info** is the object of type [`MessageInfo`].
+///
+///  * **_env** is the object of type [`Env`]
+pub fn execute_toggle_freeze(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+) -> Result<Response, ContractError> {
+    let mut config: Config = read_config(deps.storage)?;
+    let cfg = ADMIN_CONFIG.load(deps.storage)?;
+
+    // Only admins and owner can call this function
+    if !cfg.is_admin(info.sender.as_ref()) && !cfg.is_owner(info.sender.as_ref()) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let new_flag = !config.is_frozen;
+    config.is_frozen = new_flag;
+
+    store_config(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("method", "execute_toggle_freeze"),
+        attr("module_contract_address", env.contract.address.to_string()),
+    ]))
+}
+
+/// Adds a new plan to the product. New subscribers can subscribe to the plan immediately.
+/// The plan is subjected to the same minimums as the product, which are queried from the factory.
+///
+/// ## Executor
+/// Only owner or admin can execute this function
+pub fn execute_add_plan(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    plan: PlanInfo,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let cfg = ADMIN_CONFIG.load(deps.storage)?;
+
+    if config.is_frozen {
+        return Err(ContractError::Frozen {});
+    }
+
+    // Only owner or admin can call this function
+    if !cfg.is_admin(info.sender.as_ref()) && !cfg.is_owner(info.sender.as_ref()) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // plan_id must not clash with the default plan or an existing plan
+    if plan.plan_id == DEFAULT_PLAN_ID || PLANS.has(deps.storage, U64Key::from(plan.plan_id)) {
+        return Err(ContractError::InvalidParam {});
+    }
+
+    let factory_config = query_product_factory_config(&deps.querier, config.factory_address)?;
+    if plan.unit_amount < factory_config.min_amount_per_interval
+        || plan.unit_interval_hour < factory_config.min_unit_interval_hour
+        || !is_valid_billing_calendar(&plan.billing_calendar)
+    {
+        return Err(ContractError::InvalidParam {});
+    }
+
+    let plan_id = plan.plan_id;
+    PLANS.save(
+        deps.storage,
+        U64Key::from(plan_id),
+        &to_plan(plan, config.trial_period),
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("method", "execute_add_plan"),
+        attr("plan_id", plan_id.to_string()),
+        attr("module_contract_address", env.contract.address.to_string()),
+    ]))
+}
+
+/// Archives a plan. Archived plans do not accept new subscribers, but existing subscribers on the plan continue to be charged.
+/// The default plan cannot be archived.
+///
+/// ## Executor
+/// Only owner or admin can execute this function
+pub fn execute_archive_plan(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    plan_id: u64,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let cfg = ADMIN_CONFIG.load(deps.storage)?;
+
+    if config.is_frozen {
+        return Err(ContractError::Frozen {});
+    }
+
+    // Only owner or admin can call this function
+    if !cfg.is_admin(info.sender.as_ref()) && !cfg.is_owner(info.sender.as_ref()) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if plan_id == DEFAULT_PLAN_ID {
+        return Err(ContractError::InvalidParam {});
+    }
+
+    let mut plan = match PLANS.may_load(deps.storage, U64Key::from(plan_id))? {
+        Some(v) => v,
+        None => return Err(ContractError::PlanNotFound {}),
+    };
+
+    if plan.is_archived {
+        return Err(ContractError::PlanArchived {});
+    }
+
+    plan.is_archived = true;
+    PLANS.save(deps.storage, U64Key::from(plan_id), &plan)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("method", "execute_archive_plan"),
+        attr("plan_id", plan_id.to_string()),
+        attr("module_contract_address", env.contract.address.to_string()),
+    ]))
+}
+
+/// Creates a coupon code that subscribers can redeem when subscribing. The coupon can be redeemed up to `max_redemptions` times until `redeem_by`.
+/// The `discount` is applied to the subscription of every subscriber that redeems the coupon.
+///
+/// ## Executor
+/// Only owner or admin can execute this function
+pub fn execute_create_coupon(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    code: String,
+    discount: Discount,
+    max_redemptions: u64,
+    redeem_by: u64,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let cfg = ADMIN_CONFIG.load(deps.storage)?;
+
+    if config.is_frozen {
+        return Err(ContractError::Frozen {});
+    }
+
+    // Only owner or admin can call this function
+    if !cfg.is_admin(info.sender.as_ref()) && !cfg.is_owner(info.sender.as_ref()) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if code.is_empty()
+        || max_redemptions == 0
+        || redeem_by <= env.block.time.seconds()
+        || COUPONS.has(deps.storage, &code)
+    {
+        return Err(ContractError::InvalidParam {});
+    }
 
-    store_config(deps.storage, &config)?;
+    // fixed discounts are capped at the unit amount of the plan when applied, so only the discount type is checked here
+    if !is_valid_discount(Some(&discount), Uint256::from(u128::MAX), env.block.time) {
+        return Err(ContractError::InvalidDiscount {});
+    }
+
+    let coupon = Coupon {
+        code: code.clone(),
+        discount,
+        max_redemptions,
+        redemptions: 0,
+        redeem_by: Timestamp::from_seconds(redeem_by),
+    };
+    COUPONS.save(deps.storage, &code, &coupon)?;
 
     Ok(Response::new().add_attributes(vec![
-        attr("method", "execute_toggle_pause"),
+        attr("method", "execute_create_coupon"),
+        attr("coupon", code),
         attr("module_contract_address", env.contract.address.to_string()),
     ]))
 }
 
-/// Toggles the `is_frozen` variable in the contract. if the `is_frozen` variable was `false`, then this function should toggle it to true. Same applies vice-versa.
-/// Once frozen, only admin features are allowed. This function may caused disruptive behavior since charges cannot proceed on a frozen contract.
-///
-/// Users still have their [`SubscriptionInfo`] stored on the contract. However, as charge is not allowed, it may result in subscription expiring.
-///
-/// Returns an [`ContractError`] on failure or returns the [`Response`] with the specified attributes
-/// if the operation was successful.
-///
-/// ## Params
-/// * **deps** is the object of type [`DepsMut`].
+/// Removes a coupon code so that it can no longer be redeemed. Subscribers that have redeemed the coupon keep their discount.
 ///
-/// * **info** is the object of type [`MessageInfo`].
-///
-///  * **_env** is the object of type [`Env`]
-pub fn execute_toggle_freeze(
+/// ## Executor
+/// Only owner or admin can execute this function
+pub fn execute_remove_coupon(
     deps: DepsMut,
     info: MessageInfo,
     env: Env,
+    code: String,
 ) -> Result<Response, ContractError> {
-    let mut config: Config = read_config(deps.storage)?;
+    let config: Config = read_config(deps.storage)?;
     let cfg = ADMIN_CONFIG.load(deps.storage)?;
 
-    // Only admins and owner can call this function
+    if config.is_frozen {
+        return Err(ContractError::Frozen {});
+    }
+
+    // Only owner or admin can call this function
     if !cfg.is_admin(info.sender.as_ref()) && !cfg.is_owner(info.sender.as_ref()) {
         return Err(ContractError::Unauthorized {});
     }
 
-    let new_flag = !config.is_frozen;
-    config.is_frozen = new_flag;
+    if !COUPONS.has(deps.storage, &code) {
+        return Err(ContractError::CouponNotFound {});
+    }
 
-    store_config(deps.storage, &config)?;
+    COUPONS.remove(deps.storage, &code);
 
     Ok(Response::new().add_attributes(vec![
-        attr("method", "execute_toggle_freeze"),
+        attr("method", "execute_remove_coupon"),
+        attr("coupon", code),
         attr("module_contract_address", env.contract.address.to_string()),
     ]))
 }
@@ -802,6 +2934,248 @@ pub fn map_validate(api: &dyn Api, admins: &[String]) -> StdResult<Vec<Addr>> {
     admins.iter().map(|addr| api.addr_validate(addr)).collect()
 }
 
+/// Builds the messages that transfer `amount` from the subwallet of the subscriber.
+/// The protocol fee is deducted from `amount` and sent to the fee address, and the remainder is split between the receivers.
+pub fn build_payment_msgs(
+    subscriber: &Addr,
+    config: &Config,
+    fee: &ProductFactoryConfigResponse,
+    amount: Uint256,
+) -> StdResult<Vec<CosmosMsg>> {
+    payment_transfers(config, fee, amount)
+        .into_iter()
+        .map(|(recipient, transfer_amount)| {
+            subwallet_transfer_msg(subscriber, config, recipient, transfer_amount)
+        })
+        .collect()
+}
+
+// builds the message that transfers `amount` of the billing asset from the subwallet to the recipient
+fn subwallet_transfer_msg(
+    subwallet: &Addr,
+    config: &Config,
+    recipient: String,
+    amount: Uint256,
+) -> StdResult<CosmosMsg> {
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: subwallet.to_string(),
+        funds: vec![],
+        msg: to_binary(&SubWalletExecuteMsg::transfer(
+            &config.billing_asset,
+            Uint128::from(amount),
+            recipient,
+        ))?,
+    }))
+}
+
+/// Builds the messages that pay `amount` for the subscriber. The payment is drawn from the prepaid balance of the subscriber first,
+/// and the rest is transferred from the subwallet of the payer. The payer must be a subwallet if the balance does not cover the whole amount
+fn build_subscription_payment_msgs(
+    deps: DepsMut,
+    subscriber: &Addr,
+    payer: &Addr,
+    config: &Config,
+    fee: &ProductFactoryConfigResponse,
+    amount: Uint256,
+) -> Result<Vec<CosmosMsg>, ContractError> {
+    let prepaid_balance = PREPAID_BALANCES
+        .may_load(deps.storage, subscriber)?
+        .unwrap_or_default();
+
+    // products billed in a cw20 token cannot be prepaid
+    let denom = native_billing_denom(config);
+    let prepaid_amount = match denom {
+        Some(_) if prepaid_balance < amount => prepaid_balance,
+        Some(_) => amount,
+        None => Uint256::zero(),
+    };
+
+    if prepaid_amount < amount {
+        assert_subwallet_payer(deps.as_ref(), config, payer)?;
+    }
+
+    if !prepaid_amount.is_zero() {
+        PREPAID_BALANCES.save(
+            deps.storage,
+            subscriber,
+            &(prepaid_balance - prepaid_amount),
+        )?;
+    }
+
+    // every transfer is paid from the prepaid balance until it is used up, and from the subwallet after that
+    let mut msgs: Vec<CosmosMsg> = vec![];
+    let mut prepaid_left = prepaid_amount;
+    for (recipient, transfer_amount) in payment_transfers(config, fee, amount) {
+        let prepaid_transfer = match prepaid_left < transfer_amount {
+            true => prepaid_left,
+            false => transfer_amount,
+        };
+        prepaid_left = prepaid_left - prepaid_transfer;
+
+        if let (Some(denom), false) = (&denom, prepaid_transfer.is_zero()) {
+            msgs.push(CosmosMsg::Bank(BankMsg::Send {
+                to_address: recipient.clone(),
+                amount: coins(Uint128::from(prepaid_transfer).u128(), denom.clone()),
+            }));
+        }
+
+        let subwallet_transfer = transfer_amount - prepaid_transfer;
+        if !subwallet_transfer.is_zero() {
+            msgs.push(subwallet_transfer_msg(
+                payer,
+                config,
+                recipient,
+                subwallet_transfer,
+            )?);
+        }
+    }
+
+    Ok(msgs)
+}
+
+/// Splits a payment of `amount` into the protocol fee sent to the fee address, and the remainder split between the receivers
+fn payment_transfers(
+    config: &Config,
+    fee: &ProductFactoryConfigResponse,
+    amount: Uint256,
+) -> Vec<(String, Uint256)> {
+    let mut transfers: Vec<(String, Uint256)> = Vec::new();
+
+    let protocol_fee = calculate_protocol_fee(fee.protocol_fee_bps, fee.min_protocol_fee, amount);
+
+    // Amount to pay merchant
+    let mut merchant_amount = amount;
+
+    // computes the protocol fees payable if protocol_fee is non-zero
+    if let Some(protocol_fee) = protocol_fee {
+        merchant_amount = merchant_amount - protocol_fee;
+        transfers.push((fee.fee_address.clone(), protocol_fee));
+    }
+
+    // append the remainder of the receivers
+    for (receiver, receiver_amount) in split_revenue(config, merchant_amount) {
+        transfers.push((receiver.to_string(), receiver_amount));
+    }
+
+    transfers
+}
+
+/// Returns the native asset that refunds and prepaid balances of the product are paid in.
+/// Products billed in a cw20 token have none
+fn native_billing_denom(config: &Config) -> Option<String> {
+    match &config.billing_asset {
+        Some(AssetInfo::NativeToken { denom }) => Some(denom.clone()),
+        Some(AssetInfo::Token { .. }) => None,
+        None => Some(UST_DENOM.to_string()),
+    }
+}
+
+/// Returns the amount of the native billing asset sent along with a message, for the prepaid balance of a subscription.
+/// Any other funds are rejected
+fn prepaid_funds(config: &Config, funds: &[Coin]) -> Result<Uint256, ContractError> {
+    match (funds, native_billing_denom(config)) {
+        ([], _) => Ok(Uint256::zero()),
+        ([coin], Some(denom)) if coin.denom == denom && !coin.amount.is_zero() => {
+            Ok(Uint256::from(coin.amount))
+        }
+        _ => Err(ContractError::InvalidPrepaidFunds {}),
+    }
+}
+
+/// Adds `amount` to the prepaid balance of the subscriber. Returns the new balance
+fn add_prepaid_balance(
+    storage: &mut dyn Storage,
+    subscriber: &Addr,
+    amount: Uint256,
+) -> StdResult<Uint256> {
+    let prepaid_balance = PREPAID_BALANCES
+        .may_load(storage, subscriber)?
+        .unwrap_or_default()
+        + amount;
+    PREPAID_BALANCES.save(storage, subscriber, &prepaid_balance)?;
+    Ok(prepaid_balance)
+}
+
+/// Clears the prepaid balance of the subscriber, and builds the message that sends it to the payer of the subscription.
+/// Returns `None` if there is nothing to refund
+fn refund_prepaid_balance(
+    storage: &mut dyn Storage,
+    config: &Config,
+    subscriber: &Addr,
+    subscription: &SubscriptionInfo,
+) -> StdResult<Option<(CosmosMsg, Uint256)>> {
+    let prepaid_balance = PREPAID_BALANCES
+        .may_load(storage, subscriber)?
+        .unwrap_or_default();
+
+    let denom = match native_billing_denom(config) {
+        Some(denom) if !prepaid_balance.is_zero() => denom,
+        _ => return Ok(None),
+    };
+
+    PREPAID_BALANCES.remove(storage, subscriber);
+
+    let refund_msg = CosmosMsg::Bank(BankMsg::Send {
+        to_address: subscription.payer_address().to_string(),
+        amount: coins(Uint128::from(prepaid_balance).u128(), denom),
+    });
+
+    Ok(Some((refund_msg, prepaid_balance)))
+}
+
+/// Adds a payment of `amount` and the protocol fee paid on it to the [`Stats`] of the product
+fn record_payment_revenue(
+    storage: &mut dyn Storage,
+    fee: &ProductFactoryConfigResponse,
+    amount: Uint256,
+) -> StdResult<()> {
+    let protocol_fee = calculate_protocol_fee(fee.protocol_fee_bps, fee.min_protocol_fee, amount);
+    record_revenue(storage, amount, protocol_fee.unwrap_or_default())
+}
+
+/// Builds the callbacks that deliver `hook_msg` to every hook contract of the product.
+/// The callbacks reply on error, so that a failing hook is ignored in [`reply`] instead of reverting the transaction
+pub fn build_hook_msgs(config: &Config, hook_msg: SubscriptionHookMsg) -> StdResult<Vec<SubMsg>> {
+    config
+        .hooks
+        .iter()
+        .map(|hook| {
+            Ok(SubMsg::reply_on_error(
+                hook_msg.clone().into_cosmos_msg(hook.to_string())?,
+                HOOK_REPLY_ID,
+            ))
+        })
+        .collect()
+}
+
+/// Splits the merchant amount of a payment between the receivers of the product. Every co-receiver gets its share rounded down,
+/// and the primary receiver gets the rest, including the rounding dust. Receivers with nothing to receive are left out
+pub fn split_revenue(config: &Config, merchant_amount: Uint256) -> Vec<(Addr, Uint256)> {
+    let mut primary_amount = merchant_amount;
+    let mut shares: Vec<(Addr, Uint256)> = vec![];
+
+    for split in config.revenue_splits.iter() {
+        let share_rate = Decimal256::from_ratio(
+            Uint256::from(split.share_bps),
+            Uint256::from(MAX_FEE_DECIMAL),
+        );
+        let share = share_rate * merchant_amount;
+        if share.is_zero() {
+            continue;
+        }
+
+        primary_amount = primary_amount - share;
+        shares.push((split.receiver.clone(), share));
+    }
+
+    let mut amounts = vec![];
+    if !primary_amount.is_zero() || shares.is_empty() {
+        amounts.push((config.receiver_address.clone(), primary_amount));
+    }
+    amounts.extend(shares);
+    amounts
+}
+
 // calculates the protocol fee that will be payable to the suberra protocol. `protocol_fee_bps` is queried from the factory.
 // returns None is there is no fee that is payable. Otherwise returns the amount payable to protocola
 pub fn calculate_protocol_fee(
@@ -824,33 +3198,109 @@ pub fn calculate_protocol_fee(
     Some(protocol_fee)
 }
 
-/// Wrapper function to call the [`compute_amount_chargeable`]
+/// Converts a retry schedule from hours to seconds. Returns `None` if the schedule is invalid
+fn to_retry_schedule(retry_schedule_hour: Vec<u64>) -> Option<Vec<u64>> {
+    // every retry must be scheduled after the previous attempt
+    if retry_schedule_hour.len() > MAXIMUM_RETRY_SCHEDULE_SIZE || retry_schedule_hour.contains(&0) {
+        return None;
+    }
+
+    Some(retry_schedule_hour.iter().map(|v| v * 60 * 60).collect())
+}
+
+// converts the plan that is specified in the messages to the [`Plan`] that is stored in the contract
+// plans without a trial period of their own inherit `default_trial_period` of the product
+fn to_plan(plan: PlanInfo, default_trial_period: u64) -> Plan {
+    Plan {
+        plan_id: plan.plan_id,
+        unit_amount: plan.unit_amount,
+        initial_amount: plan.initial_amount,
+        // unit_interval needs to be converted from hours to Timestamp
+        unit_interval: Timestamp::from_seconds(plan.unit_interval_hour * 60 * 60),
+        trial_period: match plan.trial_period_hour {
+            Some(v) => v * 60 * 60,
+            None => default_trial_period,
+        },
+        is_archived: false,
+        billing_calendar: plan.billing_calendar,
+        price_changes: vec![],
+    }
+}
+
+/// Reads the [`Plan`] that the subscriber is billed on
+pub fn read_subscription_plan(
+    storage: &dyn Storage,
+    subscription: &SubscriptionInfo,
+) -> Result<Plan, ContractError> {
+    match read_plan(storage, subscription.plan_id)? {
+        Some(plan) => Ok(plan),
+        None => Err(ContractError::PlanNotFound {}),
+    }
+}
+
+/// Wrapper function to call the [`compute_amount_chargeable`]. The amount is capped by the `max_amount_chargeable` of the product
 pub fn get_chargeable_amount(
     deps: Deps,
     env: &Env,
     subscription: &SubscriptionInfo,
 ) -> Result<AmountTransferable, ContractError> {
-    let config: Config = read_config(deps.storage)?;
-    Ok(compute_amount_chargeable(
-        &config,
-        subscription,
-        env.block.time,
-    ))
+    let config = read_config(deps.storage)?;
+    let plan = read_subscription_plan(deps.storage, subscription)?;
+    let mut chargeable_amount =
+        compute_amount_chargeable(&plan, &config.usage_tiers, subscription, env.block.time);
+    chargeable_amount.amount =
+        cap_amount_chargeable(&config, subscription, chargeable_amount.amount);
+
+    Ok(chargeable_amount)
+}
+
+/// Returns `amount`, reduced to what can still be charged to the subscription before the `max_amount_chargeable` of the product is reached
+pub fn cap_amount_chargeable(
+    config: &Config,
+    subscription: &SubscriptionInfo,
+    amount: Uint256,
+) -> Uint256 {
+    let max_amount_chargeable = match config.max_amount_chargeable {
+        Some(v) => v,
+        None => return amount,
+    };
+
+    if subscription.total_paid >= max_amount_chargeable {
+        return Uint256::zero();
+    }
+
+    let remaining = max_amount_chargeable - subscription.total_paid;
+    match amount > remaining {
+        true => remaining,
+        false => amount,
+    }
 }
 
 /// Data Structure that will be returned to the [`execute_charge`] function. Stores required information to make adjustments to [`SubscriptionInfo`]
 /// amount: Chargeable amount after factoring discounts and max cap
 /// number_of_intervals: Number of periods that the amount should be charged for
+/// discounted_intervals: Number of those periods that the discount was applied to
+/// usage_amount: Part of the amount that is billed for the usage reported in the charged periods, before the max cap
+/// seat_credit: Part of the seat credit of the subscription that was deducted from the amount
 pub struct AmountTransferable {
     pub amount: Uint256,
     pub number_of_intervals: u64,
+    pub discounted_intervals: u64,
+    pub usage_amount: Uint256,
+    pub seat_credit: Uint256,
 }
 
 /// Computes the amount chargeable for the subscription and the period adjustment to be made for the [`SubscriptionInfo`] object.
-/// Factors in the discount if [`Discount`] object is set for the particular subscriber.
+/// The subscription is billed according to the [`Plan`] that the subscriber is on.
+/// Factors in the discount if [`Discount`] object is set for the particular subscriber. When several intervals are charged at once,
+/// the discount is only applied to the intervals that start before it expires, and to no more intervals than it has remaining.
+/// For metered products, the usage reported in the charged intervals is billed with `usage_tiers` on top of the unit amount,
+/// except for the usage reported during a free trial. Discounts only apply to the unit amount.
+/// Team subscriptions are billed the unit amount for every seat, along with the seat changes prorated during the last interval.
 /// Returns an object of type [`AmountTransferable`]. If no charge can be made, the object with have zero value for the amount and number_of_intervals
 pub fn compute_amount_chargeable(
-    config: &Config,
+    plan: &Plan,
+    usage_tiers: &[PriceTier],
     subscription: &SubscriptionInfo,
     block_time: Timestamp,
 ) -> AmountTransferable {
@@ -859,47 +3309,459 @@ pub fn compute_amount_chargeable(
         return AmountTransferable {
             amount: Uint256::zero(),
             number_of_intervals: 0u64,
+            discounted_intervals: 0u64,
+            usage_amount: Uint256::zero(),
+            seat_credit: Uint256::zero(),
+        };
+    }
+
+    // calculate the number of intervals that have started since the last interval_end_at timestamp
+    let interval =
+        plan.intervals_starting_before(subscription.interval_end_at, block_time.plus_seconds(1));
+
+    // fixed-term subscriptions are not billed for intervals that start after the term ends
+    let interval = match subscription.term_end_at {
+        Some(term_end_at) => {
+            interval.min(plan.intervals_starting_before(subscription.interval_end_at, term_end_at))
+        }
+        None => interval,
+    };
+
+    // checks for amount after discount. Intervals after the discount has expired are charged the full amount
+    let discounted_intervals = discounted_intervals(
+        &subscription.discount_per_interval,
+        plan,
+        subscription.interval_end_at,
+        interval,
+    );
+
+    // usage reported in the charged intervals. Usage reported during the free trial is not billed
+    let billed_until = plan.interval_end_after(subscription.interval_end_at, interval);
+    let usage_amount = subscription
+        .usage
+        .iter()
+        .filter(|record| record.interval_end_at < billed_until)
+        .filter(|record| match subscription.trial_end_at {
+            Some(trial_end_at) => record.interval_end_at > trial_end_at,
+            None => true,
+        })
+        .fold(Uint256::zero(), |amount, record| {
+            amount + tiered_usage_amount(usage_tiers, record.quantity)
+        });
+
+    // every interval is billed the unit amount at its start, so the intervals are billed in runs between the price changes
+    let mut unit_amount = Uint256::zero();
+    let mut billed_intervals = 0u64;
+    let mut priced_from = subscription.interval_end_at;
+    let run_ends = plan
+        .price_changes
+        .iter()
+        .map(|change| Some(change.effective_at))
+        .chain(std::iter::once(None));
+    for run_end in run_ends {
+        let intervals_before = match run_end {
+            Some(effective_at) => interval
+                .min(plan.intervals_starting_before(subscription.interval_end_at, effective_at)),
+            None => interval,
+        };
+        if intervals_before > billed_intervals {
+            unit_amount = unit_amount
+                + unit_amount_of_intervals(
+                    &plan.priced_at(priced_from),
+                    &subscription.discount_per_interval,
+                    intervals_before - billed_intervals,
+                    discounted_intervals.min(intervals_before)
+                        - discounted_intervals.min(billed_intervals),
+                );
+            billed_intervals = intervals_before;
+        }
+        if let Some(effective_at) = run_end {
+            priced_from = priced_from.max(effective_at);
+        }
+    }
+    let chargeable_amount = unit_amount * Uint256::from(subscription.seat_count())
+        + subscription.seat_proration
+        + usage_amount;
+
+    // the credit for removed seats is deducted until it is used up
+    let seat_credit = match subscription.seat_credit > chargeable_amount {
+        true => chargeable_amount,
+        false => subscription.seat_credit,
+    };
+
+    AmountTransferable {
+        amount: chargeable_amount - seat_credit,
+        number_of_intervals: interval,
+        discounted_intervals,
+        usage_amount,
+        seat_credit,
+    }
+}
+
+// returns the unit amount of `intervals` intervals of the plan, of which the first `discounted_intervals` are discounted
+fn unit_amount_of_intervals(
+    plan: &Plan,
+    discount: &Option<Discount>,
+    intervals: u64,
+    discounted_intervals: u64,
+) -> Uint256 {
+    Uint256::from(discounted_intervals) * discounted_unit_amount(plan, discount)
+        + Uint256::from(intervals - discounted_intervals) * plan.unit_amount
+}
+
+// returns the amount billed for `quantity` units of usage in an interval. Every unit is priced by the tier that it falls in
+fn tiered_usage_amount(usage_tiers: &[PriceTier], quantity: u64) -> Uint256 {
+    let mut amount = Uint256::zero();
+    let mut billed_quantity = 0u64;
+
+    for tier in usage_tiers {
+        if billed_quantity >= quantity {
+            break;
+        }
+
+        let tier_quantity = quantity.min(tier.up_to.unwrap_or(u64::MAX)) - billed_quantity;
+        amount = amount + Uint256::from(tier_quantity) * tier.unit_price;
+        billed_quantity += tier_quantity;
+    }
+
+    amount
+}
+
+// checks that usage tiers have increasing upper bounds, and that only the last tier is unbounded
+fn is_valid_usage_tiers(usage_tiers: &[PriceTier]) -> bool {
+    if usage_tiers.len() > MAXIMUM_USAGE_TIERS {
+        return false;
+    }
+
+    let mut previous_up_to = 0u64;
+    for (index, tier) in usage_tiers.iter().enumerate() {
+        match tier.up_to {
+            Some(up_to) if up_to > previous_up_to => previous_up_to = up_to,
+            None if index == usage_tiers.len() - 1 => {}
+            _ => return false,
+        }
+    }
+
+    true
+}
+
+// converts the revenue splits that are specified in the messages to the [`RevenueSplit`]s that are stored in the contract
+fn to_revenue_splits(
+    api: &dyn Api,
+    revenue_splits: Vec<RevenueSplitInfo>,
+) -> StdResult<Vec<RevenueSplit>> {
+    revenue_splits
+        .into_iter()
+        .map(|split| {
+            Ok(RevenueSplit {
+                receiver: api.addr_validate(&split.receiver)?,
+                share_bps: split.share_bps,
+            })
+        })
+        .collect()
+}
+
+// checks that co-receivers are unique and not the primary receiver, and that their non-zero shares add up to at most 100%
+fn is_valid_revenue_splits(receiver_address: &Addr, revenue_splits: &[RevenueSplit]) -> bool {
+    if revenue_splits.len() > MAXIMUM_REVENUE_SPLITS {
+        return false;
+    }
+
+    let mut total_share_bps = 0u64;
+    for (index, split) in revenue_splits.iter().enumerate() {
+        if split.share_bps == 0
+            || split.share_bps > MAX_FEE_DECIMAL
+            || split.receiver == *receiver_address
+            || revenue_splits[..index]
+                .iter()
+                .any(|other| other.receiver == split.receiver)
+        {
+            return false;
+        }
+        total_share_bps += split.share_bps;
+    }
+
+    total_share_bps <= MAX_FEE_DECIMAL
+}
+
+// checks that the payer is a subwallet of the subwallet factory, unless the product accepts any payer.
+// Payers are rejected if the product factory does not know the subwallet factory
+fn assert_subwallet_payer(deps: Deps, config: &Config, payer: &Addr) -> Result<(), ContractError> {
+    if config.accept_any_payer {
+        return Ok(());
+    }
+
+    let factory_config =
+        query_product_factory_config(&deps.querier, config.factory_address.clone())?;
+    let subwallet_factory_address = factory_config
+        .subwallet_factory_address
+        .ok_or(ContractError::SubwalletFactoryNotSet {})?;
+    let subwallet_factory = deps.api.addr_validate(&subwallet_factory_address)?;
+    if query_subwallet_owner(&deps.querier, subwallet_factory, payer)?.is_none() {
+        return Err(ContractError::PayerNotSubwallet {});
+    }
+
+    Ok(())
+}
+
+/// Data Structure that will be returned by [`compute_plan_change`].
+/// amount: Amount to be charged to the subscriber after the credit is applied against the price of the new plan
+/// credit: Value of the unused part of the current interval
+/// interval_end_at: End of the first interval on the new plan
+pub struct PlanChange {
+    pub amount: Uint256,
+    pub credit: Uint256,
+    pub interval_end_at: Timestamp,
+}
+
+/// Computes the proration when a subscriber moves from `current_plan` to `new_plan` at `block_time`.
+/// The unused part of the current interval is credited against the price of the new plan. If the credit exceeds the price,
+/// the remainder extends the first interval of the new plan proportionally.
+/// During a free trial, nothing has been paid for the current interval, so the plan is switched without charges and the trial continues.
+/// Factors in the discount if [`Discount`] object is set for the particular subscriber and has not expired at `block_time`.
+pub fn compute_plan_change(
+    current_plan: &Plan,
+    new_plan: &Plan,
+    subscription: &SubscriptionInfo,
+    block_time: Timestamp,
+) -> PlanChange {
+    if matches!(subscription.trial_end_at, Some(trial_end_at) if block_time < trial_end_at) {
+        return PlanChange {
+            amount: Uint256::zero(),
+            credit: Uint256::zero(),
+            interval_end_at: subscription.interval_end_at,
+        };
+    }
+
+    // the unused time is credited at the price of the running interval, and the new plan is billed at its price from now
+    let interval_start = subscription
+        .interval_end_at
+        .seconds()
+        .saturating_sub(current_plan.interval_length(subscription.interval_end_at));
+    let current_plan = &current_plan.priced_at(Timestamp::from_seconds(interval_start));
+    let new_plan = &new_plan.priced_at(block_time);
+
+    let unused_seconds = subscription
+        .interval_end_at
+        .seconds()
+        .saturating_sub(block_time.seconds());
+
+    let discount = match discounted_intervals(
+        &subscription.discount_per_interval,
+        current_plan,
+        block_time,
+        1,
+    ) {
+        0 => None,
+        _ => subscription.discount_per_interval.clone(),
+    };
+
+    // team subscriptions move every seat to the new plan
+    let seats = Uint256::from(subscription.seat_count());
+    let credit = Decimal256::from_ratio(
+        Uint256::from(unused_seconds),
+        Uint256::from(current_plan.interval_length(subscription.interval_end_at)),
+    ) * (discounted_unit_amount(current_plan, &discount) * seats);
+
+    // the first interval on a calendar plan ends on the next period end, so the new price is prorated over the part of the period that is left
+    let interval_end_at = new_plan.interval_end_after(block_time, 1);
+    let interval_length = new_plan.interval_length(interval_end_at);
+    let full_amount = discounted_unit_amount(new_plan, &discount) * seats;
+    let new_amount = match new_plan.billing_calendar {
+        Some(_) => {
+            Uint256::one()
+                * Decimal256::from_ratio(
+                    full_amount * Uint256::from(interval_end_at.seconds() - block_time.seconds()),
+                    Uint256::from(interval_length),
+                )
+        }
+        None => full_amount,
+    };
+
+    if new_amount > credit {
+        return PlanChange {
+            amount: new_amount - credit,
+            credit,
+            interval_end_at,
         };
     }
 
-    // calculates elapsed time from the last interval_end_at timestamp
-    let elapsed_time = block_time.minus_seconds(subscription.interval_end_at.seconds());
+    // the remaining credit is converted to additional time on the new plan
+    let extension = match full_amount.is_zero() {
+        true => Uint256::zero(),
+        false => {
+            Decimal256::from_ratio(credit - new_amount, full_amount)
+                * Uint256::from(interval_length)
+        }
+    };
+
+    PlanChange {
+        amount: Uint256::zero(),
+        credit,
+        interval_end_at: interval_end_at.plus_seconds(Uint128::from(extension).u128() as u64),
+    }
+}
+
+// returns the amount of `seats` seats of the subscription over the unused part of its current interval, at the price of that interval.
+// Nothing is prorated during a free trial, or once the interval has ended
+fn compute_seat_proration(
+    plan: &Plan,
+    subscription: &SubscriptionInfo,
+    seats: u64,
+    block_time: Timestamp,
+) -> Uint256 {
+    if block_time >= subscription.interval_end_at
+        || matches!(subscription.trial_end_at, Some(trial_end_at) if block_time < trial_end_at)
+    {
+        return Uint256::zero();
+    }
+
+    let interval_length = plan.interval_length(subscription.interval_end_at);
+    let interval_start = subscription
+        .interval_end_at
+        .seconds()
+        .saturating_sub(interval_length);
+    let plan = &plan.priced_at(Timestamp::from_seconds(interval_start));
+
+    let discount = match discounted_intervals(
+        &subscription.discount_per_interval,
+        plan,
+        Timestamp::from_seconds(interval_start),
+        1,
+    ) {
+        0 => None,
+        _ => subscription.discount_per_interval.clone(),
+    };
+
+    Uint256::one()
+        * Decimal256::from_ratio(
+            discounted_unit_amount(plan, &discount)
+                * Uint256::from(seats)
+                * Uint256::from(subscription.interval_end_at.seconds() - block_time.seconds()),
+            Uint256::from(interval_length),
+        )
+}
+
+// returns the initial amount of a subscription to the plan that starts at `block_time`, with a first interval that ends at `interval_end_at`.
+// Calendar plans that prorate the first period charge the part of the initial amount that covers the rest of the period
+fn initial_amount_due(plan: &Plan, interval_end_at: Timestamp, block_time: Timestamp) -> Uint256 {
+    match &plan.billing_calendar {
+        Some(calendar) if calendar.prorate_first_period => {
+            Uint256::one()
+                * Decimal256::from_ratio(
+                    plan.initial_amount
+                        * Uint256::from(interval_end_at.seconds() - block_time.seconds()),
+                    Uint256::from(plan.interval_length(interval_end_at)),
+                )
+        }
+        _ => plan.initial_amount,
+    }
+}
+
+// checks that the calendar of a plan, if any, ends its periods on a day and month that exist
+fn is_valid_billing_calendar(billing_calendar: &Option<BillingCalendar>) -> bool {
+    match billing_calendar {
+        Some(calendar) => calendar.is_valid(),
+        None => true,
+    }
+}
+
+// returns the amount chargeable per interval of the plan after the discount, if any
+fn discounted_unit_amount(plan: &Plan, discount: &Option<Discount>) -> Uint256 {
+    let discount_amount = match discount {
+        Some(Discount {
+            amount,
+            discount_type: DiscountType::Bps,
+            ..
+        }) if *amount < Uint256::from(MAX_FEE_DECIMAL) => {
+            Decimal256::from_ratio(*amount, Uint256::from(MAX_FEE_DECIMAL)) * plan.unit_amount
+        }
+        Some(Discount {
+            discount_type: DiscountType::Bps,
+            ..
+        }) => plan.unit_amount,
+        Some(discount) => discount.amount,
+        None => Uint256::zero(),
+    };
+
+    // discount cannot be more than the unit_amount
+    match discount_amount < plan.unit_amount {
+        true => plan.unit_amount - discount_amount,
+        false => Uint256::zero(),
+    }
+}
+
+// returns how many of the `intervals` consecutive intervals of the plan, with the first one starting at `interval_start`, the discount applies to.
+// Only intervals that start before the expiry are discounted, up to the number of intervals remaining on the discount
+fn discounted_intervals(
+    discount: &Option<Discount>,
+    plan: &Plan,
+    interval_start: Timestamp,
+    intervals: u64,
+) -> u64 {
+    let discount = match discount {
+        Some(discount) => discount,
+        None => return 0,
+    };
+
+    let mut discounted_intervals = intervals;
+    if let Some(remaining) = discount.intervals {
+        discounted_intervals = discounted_intervals.min(remaining);
+    }
+
+    if let Some(expiry) = discount.expiry {
+        let intervals_before_expiry =
+            plan.intervals_starting_before(interval_start, Timestamp::from_seconds(expiry));
+        discounted_intervals = discounted_intervals.min(intervals_before_expiry);
+    }
+
+    discounted_intervals
+}
 
-    // calculate the number of interval (rounded down)
-    let interval = elapsed_time
-        .plus_seconds(config.unit_interval.seconds())
-        .seconds()
-        / config.unit_interval.seconds();
-
-    // checks for amount after discount
-    // eligible_discount is the discount that will be applied to the actual final_amount after factoring in the expiry (if any)
-    let eligible_discount = match &subscription.discount_per_interval {
-        Some(discount) => {
-            if discount.amount > config.unit_amount {
-                // discount more than unit_amount, returns unit_amount
-                config.unit_amount
-            } else {
-                discount.amount
-            }
+// returns the discount that is left after `used_intervals` discounted intervals were charged.
+// The discount is removed once it has no intervals remaining or it expires before `next_interval_start`
+fn remaining_discount(
+    discount: Option<Discount>,
+    used_intervals: u64,
+    next_interval_start: Timestamp,
+) -> Option<Discount> {
+    let mut discount = discount?;
+
+    if let Some(remaining) = discount.intervals {
+        let remaining = remaining.saturating_sub(used_intervals);
+        if remaining == 0 {
+            return None;
         }
-        None => Uint256::zero(),
-    };
-
-    let interval_amount_after_discount = config.unit_amount - eligible_discount;
-
-    let chargeable_amount = Uint256::from(interval) * interval_amount_after_discount;
+        discount.intervals = Some(remaining);
+    }
 
-    AmountTransferable {
-        amount: chargeable_amount,
-        number_of_intervals: interval,
+    if matches!(discount.expiry, Some(expiry) if expiry <= next_interval_start.seconds()) {
+        return None;
     }
+
+    Some(discount)
 }
 
 // checks if a discount is valid. If valid, returns true. Otherwise return false.
-fn is_valid_discount(discount: Option<Discount>, subscription_amount: Uint256) -> bool {
+fn is_valid_discount(
+    discount: Option<&Discount>,
+    subscription_amount: Uint256,
+    block_time: Timestamp,
+) -> bool {
     if let Some(discount) = discount {
         // checks if the amount if valid
-        if discount.amount > subscription_amount {
+        let max_amount = match discount.discount_type {
+            DiscountType::Fixed => subscription_amount,
+            DiscountType::Bps => Uint256::from(MAX_FEE_DECIMAL),
+        };
+        if discount.amount > max_amount {
+            return false;
+        }
+
+        // discounts that have already expired or apply to no intervals are invalid
+        if matches!(discount.expiry, Some(expiry) if expiry <= block_time.seconds())
+            || discount.intervals == Some(0)
+        {
             return false;
         }
     }
@@ -907,25 +3769,102 @@ fn is_valid_discount(discount: Option<Discount>, subscription_amount: Uint256) -
     true
 }
 
+// redeems a coupon code and returns the discount of the coupon
+fn redeem_coupon(
+    storage: &mut dyn Storage,
+    code: &str,
+    block_time: Timestamp,
+) -> Result<Discount, ContractError> {
+    let mut coupon = match COUPONS.may_load(storage, code)? {
+        Some(v) => v,
+        None => return Err(ContractError::CouponNotFound {}),
+    };
+
+    if block_time > coupon.redeem_by {
+        return Err(ContractError::CouponExpired {});
+    }
+
+    if coupon.redemptions >= coupon.max_redemptions {
+        return Err(ContractError::CouponFullyRedeemed {});
+    }
+
+    coupon.redemptions += 1;
+    COUPONS.save(storage, code, &coupon)?;
+
+    Ok(coupon.discount)
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::Stats {} => to_binary(&query_stats(deps, env)?),
         QueryMsg::AdminConfig {} => to_binary(&query_admin_list(deps)?),
         QueryMsg::Owner {} => to_binary(&query_owner(deps)?),
         QueryMsg::Subscription { subscriber } => {
             to_binary(&query_subscription(deps, env, subscriber)?)
         }
-        QueryMsg::Subscriptions { start_after, limit } => {
-            to_binary(&query_subscriptions(deps, start_after, limit)?)
+        QueryMsg::Subscriptions {
+            start_after,
+            limit,
+            status,
+        } => to_binary(&query_subscriptions(deps, env, start_after, limit, status)?),
+        QueryMsg::Plan { plan_id } => to_binary(&query_plan(deps, plan_id)?),
+        QueryMsg::Plans { start_after, limit } => {
+            to_binary(&query_plans(deps, start_after, limit)?)
+        }
+        QueryMsg::Coupon { code } => to_binary(&query_coupon(deps, code)?),
+        QueryMsg::Team { owner } => to_binary(&query_team(deps, owner)?),
+        QueryMsg::Usage { subscriber } => to_binary(&query_usage(deps, subscriber)?),
+        QueryMsg::Payments {
+            subscriber,
+            start_after,
+            limit,
+        } => to_binary(&query_payments(deps, subscriber, start_after, limit)?),
+        QueryMsg::Coupons { start_after, limit } => {
+            to_binary(&query_coupons(deps, start_after, limit)?)
         }
-        QueryMsg::CanWork { payload } => {
-            let work_payload: WorkPayload = from_binary(&payload).unwrap();
-            to_binary(&query_can_work(
+        QueryMsg::DueSubscriptions {
+            before,
+            start_after,
+            limit,
+        } => to_binary(&query_due_subscriptions(
+            deps,
+            env,
+            before,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::CanWork { payload } => match from_binary::<WorkPayload>(&payload) {
+            Ok(work_payload) => to_binary(&query_can_work(
                 deps,
                 env,
                 deps.api.addr_validate(&work_payload.payer_address)?,
-            )?)
+            )?),
+            Err(_) => {
+                let batch_payload: BatchWorkPayload = from_binary(&payload)?;
+                to_binary(&query_can_work_batch(
+                    deps,
+                    env,
+                    batch_payload.payer_addresses,
+                )?)
+            }
+        },
+        QueryMsg::OwnerOf { token_id, .. } => to_binary(&query_owner_of(deps, token_id)?),
+        QueryMsg::NumTokens {} => to_binary(&query_num_tokens(deps)?),
+        QueryMsg::ContractInfo {} => to_binary(&query_contract_info(deps)?),
+        QueryMsg::NftInfo { token_id } => to_binary(&query_nft_info(deps, env, token_id)?),
+        QueryMsg::AllNftInfo { token_id, .. } => to_binary(&AllNftInfoResponse {
+            access: query_owner_of(deps, token_id.clone())?,
+            info: query_nft_info(deps, env, token_id)?,
+        }),
+        QueryMsg::Tokens {
+            owner,
+            start_after,
+            limit,
+        } => to_binary(&query_tokens(deps, owner, start_after, limit)?),
+        QueryMsg::AllTokens { start_after, limit } => {
+            to_binary(&query_all_tokens(deps, start_after, limit)?)
         }
     }
 }
@@ -939,41 +3878,285 @@ fn query_can_work(deps: Deps, env: Env, subscriber: Addr) -> StdResult<bool> {
         return Ok(false);
     }
 
-    let subscription = match SUBSCRIPTIONS.may_load(deps.storage, &subscriber)? {
+    let subscription = match subscriptions().may_load(deps.storage, &subscriber)? {
         Some(v) => v,
         None => return Ok(false),
     };
 
-    let chargeable_amount = get_chargeable_amount(deps, &env, &subscription);
+    Ok(is_chargeable(deps, &env, &subscription))
+}
+
+/// returns true if at least one of the subscribers in the batch can be charged
+fn query_can_work_batch(deps: Deps, env: Env, payer_addresses: Vec<String>) -> StdResult<bool> {
+    let config: Config = read_config(deps.storage)?;
+
+    if config.is_frozen || payer_addresses.len() > MAX_LIMIT as usize {
+        return Ok(false);
+    }
+
+    for payer_address in payer_addresses {
+        let subscriber = match deps.api.addr_validate(&payer_address) {
+            Ok(subscriber) => subscriber,
+            Err(_) => continue,
+        };
+
+        if let Some(subscription) = subscriptions().may_load(deps.storage, &subscriber)? {
+            if is_chargeable(deps, &env, &subscription) {
+                return Ok(true);
+            }
+        }
+    }
+
+    Ok(false)
+}
+
+// checks if a worker can charge the subscription right now
+fn is_chargeable(deps: Deps, env: &Env, subscription: &SubscriptionInfo) -> bool {
+    if subscription.is_cancelled
+        || subscription.is_paused(env.block.time)
+        || subscription.is_completed(env.block.time)
+    {
+        return false;
+    }
+
+    let config = match read_config(deps.storage) {
+        Ok(config) => config,
+        Err(_) => return false,
+    };
+
+    // past due subscriptions can be charged once the next retry is due, and are lapsed by the charge once the grace period has ended
+    if let Some(next_retry_at) = subscription.next_retry_at {
+        if env.block.time > grace_period_end_at(&config, subscription) {
+            return true;
+        }
+        if env.block.time < next_retry_at {
+            return false;
+        }
+    }
+
+    let chargeable_amount = get_chargeable_amount(deps, env, subscription);
+    let is_metered = config.is_metered();
 
     match chargeable_amount {
         Ok(chargeable_amount) => {
-            if chargeable_amount.amount.is_zero() {
-                Ok(false)
+            // intervals of metered products, and intervals paid by a seat credit, are renewed even if nothing is owed
+            if chargeable_amount.amount.is_zero()
+                && !((is_metered || !chargeable_amount.seat_credit.is_zero())
+                    && chargeable_amount.number_of_intervals > 0)
+            {
+                false
             } else {
                 // check if the subscription has lapsed
-                let sub_active: bool = is_subscription_active(deps.storage, env, subscription);
-                Ok(sub_active) // only return true if the subscription has not lapsed
+                // only return true if the subscription has not lapsed
+                is_subscription_active(deps.storage, env.clone(), subscription.clone())
             }
         }
-        Err(_) => Ok(false),
+        Err(_) => false,
     }
 }
 
-/// `query_subscriptions` returns all the subscriptions in the contract
-/// caller can specify `start_after` and `limit` to paginate the responses
+/// `query_due_subscriptions` returns the subscribers that a worker can charge right now: past due subscriptions sorted by their
+/// next retry, then the subscriptions within their grace period sorted by the end of their interval.
+/// caller can specify `before` to only return subscriptions that were due at or before the unix timestamp,
+/// and `start_after` (the last subscriber of the previous page) and `limit` to paginate the responses.
+/// `limit` entries of the index are read, and the ones that cannot be charged (eg. paused) are left out of the page
+fn query_due_subscriptions(
+    deps: Deps,
+    env: Env,
+    before: Option<u64>,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<DueSubscriptionsResponse> {
+    let config: Config = read_config(deps.storage)?;
+
+    if config.is_frozen {
+        // no work can be done on a frozen contract
+        return Ok(DueSubscriptionsResponse {
+            subscribers: vec![],
+        });
+    }
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    // subscriptions that are not due yet cannot be charged, so the bound never exceeds the current time.
+    // as we want to keep the last item (pk) unbounded, we increment time by 1 and use exclusive (below the next tick)
+    let now = env.block.time.seconds();
+    let before = before.map_or(now, |before| before.min(now));
+    let max_key = (U64Key::from(before + 1), Vec::<u8>::new()).joined_key();
+
+    // subscriptions that are not past due lapse once their grace period has ended
+    let grace_period = DEFAULT_GRACE_PERIOD + config.additional_grace_period;
+    let min_key = (
+        U64Key::from(now.saturating_sub(grace_period)),
+        Vec::<u8>::new(),
+    )
+        .joined_key();
+
+    // resumes after the position of the `start_after` subscriber in the index.
+    // the past due subscriptions come first, so they are all on the previous pages once an active subscriber is reached
+    let mut past_due_start = None;
+    let mut active_start = Bound::inclusive(min_key.clone());
+    let mut include_past_due = true;
+    if let Some(start_after) = start_after {
+        let subscriber = deps.api.addr_validate(&start_after)?;
+        let subscription = subscriptions().load(deps.storage, &subscriber)?;
+        let key = (
+            U64Key::from(subscription_due_time(&subscription).seconds()),
+            subscriber.as_bytes().to_vec(),
+        )
+            .joined_key();
+        match subscription_status_key(&subscription) == PAST_DUE_STATUS_KEY {
+            true => past_due_start = Some(Bound::exclusive(key)),
+            false => {
+                include_past_due = false;
+                active_start = Bound::exclusive(key.max(min_key));
+            }
+        }
+    }
+
+    let past_due = subscriptions()
+        .idx
+        .due_time
+        .sub_prefix(PAST_DUE_STATUS_KEY.to_vec())
+        .range(
+            deps.storage,
+            past_due_start,
+            Some(Bound::Exclusive(max_key.clone())),
+            Order::Ascending,
+        )
+        .take(match include_past_due {
+            true => limit,
+            false => 0,
+        });
+    let active = subscriptions()
+        .idx
+        .due_time
+        .sub_prefix(ACTIVE_STATUS_KEY.to_vec())
+        .range(
+            deps.storage,
+            Some(active_start),
+            Some(Bound::Exclusive(max_key)),
+            Order::Ascending,
+        );
+
+    let subscribers = past_due
+        .chain(active)
+        .take(limit)
+        .filter(|item| match item {
+            Ok((_, subscription)) => is_chargeable(deps, &env, subscription),
+            Err(_) => true,
+        })
+        .map(|item| {
+            let (subscriber, _) = item?;
+            Ok(String::from_utf8(subscriber)?)
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(DueSubscriptionsResponse { subscribers })
+}
+
+/// `query_subscriptions` returns all the subscriptions in the contract, sorted by subscriber.
+/// caller can specify `start_after` and `limit` to paginate the responses.
+/// If `status` is given, the subscriptions of the billing statuses that can have this status are paged through in the `due_time` index,
+/// and the ones without this status are skipped, so that pages are only short once the index has been read to the end.
+/// Removed subscriptions are sorted by subscriber
 fn query_subscriptions(
     deps: Deps,
+    env: Env,
     start_after: Option<String>,
     limit: Option<u32>,
+    status: Option<SubscriptionStatus>,
 ) -> StdResult<SubscriptionsResponse> {
+    let config = read_config(deps.storage)?;
     let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
-    let start = start_after.map(Bound::exclusive);
 
-    let res: StdResult<Vec<SubscriptionInfo>> = SUBSCRIPTIONS
-        .range(deps.storage, start, None, Order::Ascending)
+    let status = match status {
+        Some(status) => status,
+        None => {
+            let start = start_after.map(Bound::exclusive);
+            let res: StdResult<Vec<SubscriptionInfoResponse>> = subscriptions()
+                .range(deps.storage, start, None, Order::Ascending)
+                .take(limit)
+                .map(|elem| {
+                    let (_, subscription) = elem?;
+                    let status = compute_status(&config, &subscription, env.block.time);
+                    to_subscription_response(deps, &env, &config, subscription, status)
+                })
+                .collect();
+            return Ok(SubscriptionsResponse {
+                subscriptions: res?,
+            });
+        }
+    };
+
+    if status == SubscriptionStatus::Removed {
+        let start = start_after.map(Bound::exclusive);
+        let res: StdResult<Vec<SubscriptionInfoResponse>> = REMOVED_SUBSCRIPTIONS
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|elem| {
+                let (_, subscription) = elem?;
+                to_subscription_response(
+                    deps,
+                    &env,
+                    &config,
+                    subscription,
+                    SubscriptionStatus::Removed,
+                )
+            })
+            .collect();
+        return Ok(SubscriptionsResponse {
+            subscriptions: res?,
+        });
+    }
+
+    // resumes after the position of the `start_after` subscriber in the index
+    let start = match start_after {
+        Some(start_after) => {
+            let subscriber = deps.api.addr_validate(&start_after)?;
+            let subscription = subscriptions().load(deps.storage, &subscriber)?;
+            let key = (
+                U64Key::from(subscription_due_time(&subscription).seconds()),
+                subscriber.as_bytes().to_vec(),
+            )
+                .joined_key();
+            Some((subscription_status_key(&subscription), key))
+        }
+        None => None,
+    };
+    let rank = |status_key: &[u8]| STATUS_KEYS.iter().position(|key| *key == status_key);
+
+    let res: StdResult<Vec<SubscriptionInfoResponse>> = status_index_keys(&status)
+        .into_iter()
+        .filter_map(|status_key| match &start {
+            Some((start_status_key, _)) if rank(status_key) < rank(start_status_key) => None,
+            Some((start_status_key, key)) if status_key == start_status_key.as_slice() => {
+                Some((status_key, Some(Bound::exclusive(key.clone()))))
+            }
+            _ => Some((status_key, None)),
+        })
+        .flat_map(|(status_key, start)| {
+            subscriptions()
+                .idx
+                .due_time
+                .sub_prefix(status_key.to_vec())
+                .range(deps.storage, start, None, Order::Ascending)
+        })
+        .map(|elem| {
+            elem.map(|(_, subscription)| {
+                let subscription_status = compute_status(&config, &subscription, env.block.time);
+                (subscription, subscription_status)
+            })
+        })
+        .filter(|elem| match elem {
+            Ok((_, subscription_status)) => *subscription_status == status,
+            Err(_) => true,
+        })
         .take(limit)
-        .map(|elem| elem.map(|(_, subscription)| subscription))
+        .map(|elem| {
+            let (subscription, subscription_status) = elem?;
+            to_subscription_response(deps, &env, &config, subscription, subscription_status)
+        })
         .collect();
 
     Ok(SubscriptionsResponse {
@@ -981,6 +4164,209 @@ fn query_subscriptions(
     })
 }
 
+/// `query_plan` returns the plan for a given `plan_id`
+fn query_plan(deps: Deps, plan_id: u64) -> StdResult<Option<PlanResponse>> {
+    Ok(read_plan(deps.storage, plan_id)?.map(to_plan_response))
+}
+
+/// `query_plans` returns all the plans offered by the product, starting with the default plan.
+/// caller can specify `start_after` and `limit` to paginate the responses
+fn query_plans(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<PlansResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    // the default plan is not stored in PLANS and is only returned on the first page
+    let mut plans: Vec<PlanResponse> = match start_after {
+        Some(_) => vec![],
+        None => vec![to_plan_response(read_config(deps.storage)?.default_plan())],
+    };
+
+    let start = start_after.map(U64Key::from).map(Bound::exclusive);
+    let res: StdResult<Vec<PlanResponse>> = PLANS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit.saturating_sub(plans.len()))
+        .map(|elem| elem.map(|(_, plan)| to_plan_response(plan)))
+        .collect();
+    plans.extend(res?);
+
+    Ok(PlansResponse { plans })
+}
+
+fn to_plan_response(plan: Plan) -> PlanResponse {
+    PlanResponse {
+        plan_id: plan.plan_id,
+        unit_amount: plan.unit_amount,
+        initial_amount: plan.initial_amount,
+        unit_interval_seconds: plan.unit_interval.seconds(),
+        trial_period_seconds: plan.trial_period,
+        is_archived: plan.is_archived,
+        billing_calendar: plan.billing_calendar,
+        price_changes: to_price_change_responses(plan.price_changes),
+    }
+}
+
+fn to_price_change_responses(price_changes: Vec<PriceChange>) -> Vec<PriceChangeResponse> {
+    price_changes
+        .into_iter()
+        .map(|change| PriceChangeResponse {
+            unit_amount: change.unit_amount,
+            effective_at: change.effective_at.seconds(),
+        })
+        .collect()
+}
+
+/// `query_stats` returns the running counters of the product. The monthly recurring revenue is the price of the plan
+/// of every subscription that is not cancelled, normalized to 30 days or to a calendar month. Discounts and usage are not included
+fn query_stats(deps: Deps, env: Env) -> StdResult<StatsResponse> {
+    let stats = STATS.may_load(deps.storage)?.unwrap_or_default();
+
+    let plans = PLANS
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|elem| elem.map(|(_, plan)| plan))
+        .collect::<StdResult<Vec<Plan>>>()?;
+
+    let mut monthly_recurring_revenue = Uint256::zero();
+    for plan in std::iter::once(read_config(deps.storage)?.default_plan()).chain(plans) {
+        let subscribers = PLAN_SUBSCRIBERS
+            .may_load(deps.storage, U64Key::from(plan.plan_id))?
+            .unwrap_or_default();
+        if subscribers == 0 {
+            continue;
+        }
+
+        // the revenue of the plan is normalized before rounding down, so that no precision is lost
+        let revenue = plan.priced_at(env.block.time).unit_amount * Uint256::from(subscribers);
+        let monthly_revenue = match &plan.billing_calendar {
+            Some(calendar) => {
+                Decimal256::from_ratio(revenue, Uint256::from(calendar.period_months()))
+            }
+            None => Decimal256::from_ratio(
+                revenue * Uint256::from(SECONDS_PER_MONTH),
+                Uint256::from(plan.unit_interval.seconds()),
+            ),
+        };
+        monthly_recurring_revenue = monthly_recurring_revenue + Uint256::one() * monthly_revenue;
+    }
+
+    Ok(StatsResponse {
+        total_subscribers: stats.total_subscribers,
+        active_subscribers: stats.active_subscribers,
+        cancelled_subscribers: stats.cancelled_subscribers,
+        gross_charged: stats.gross_charged,
+        protocol_fees: stats.protocol_fees,
+        monthly_recurring_revenue,
+    })
+}
+
+/// `query_coupon` returns the coupon for a given `code`
+fn query_coupon(deps: Deps, code: String) -> StdResult<Option<CouponResponse>> {
+    Ok(COUPONS
+        .may_load(deps.storage, &code)?
+        .map(to_coupon_response))
+}
+
+/// `query_team` returns the seats and the members of the team subscription of `owner`, if any
+fn query_team(deps: Deps, owner: String) -> StdResult<Option<TeamResponse>> {
+    let owner = deps.api.addr_validate(&owner)?;
+    let seats = match subscriptions().may_load(deps.storage, &owner)? {
+        Some(SubscriptionInfo {
+            seats: Some(seats), ..
+        }) => seats,
+        _ => return Ok(None),
+    };
+    let members = TEAMS.may_load(deps.storage, &owner)?.unwrap_or_default();
+
+    Ok(Some(TeamResponse {
+        owner: owner.to_string(),
+        seats,
+        members: members
+            .into_iter()
+            .map(|member| member.to_string())
+            .collect(),
+    }))
+}
+
+/// `query_coupons` returns all the coupons of the product
+/// caller can specify `start_after` and `limit` to paginate the responses
+fn query_coupons(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<CouponsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let res: StdResult<Vec<CouponResponse>> = COUPONS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|elem| elem.map(|(_, coupon)| to_coupon_response(coupon)))
+        .collect();
+
+    Ok(CouponsResponse { coupons: res? })
+}
+
+fn to_coupon_response(coupon: Coupon) -> CouponResponse {
+    CouponResponse {
+        code: coupon.code,
+        discount: coupon.discount,
+        max_redemptions: coupon.max_redemptions,
+        redemptions: coupon.redemptions,
+        redeem_by: coupon.redeem_by.seconds(),
+    }
+}
+
+/// `query_usage` returns the usage of the subscriber that has not been billed yet, per interval
+fn query_usage(deps: Deps, subscriber: String) -> StdResult<Option<UsageResponse>> {
+    let subscription =
+        subscriptions().may_load(deps.storage, &deps.api.addr_validate(&subscriber)?)?;
+
+    Ok(subscription.map(|subscription| UsageResponse {
+        subscriber: subscription.owner.to_string(),
+        usage: subscription
+            .usage
+            .iter()
+            .map(|record| UsageRecordResponse {
+                interval_end_at: record.interval_end_at.seconds(),
+                quantity: record.quantity,
+            })
+            .collect(),
+    }))
+}
+
+fn query_payments(
+    deps: Deps,
+    subscriber: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<PaymentsResponse> {
+    let subscriber = deps.api.addr_validate(&subscriber)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(U64Key::from).map(Bound::exclusive);
+
+    let payments: StdResult<Vec<PaymentResponse>> = PAYMENTS
+        .prefix(&subscriber)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|elem| {
+            elem.map(|(_, payment)| PaymentResponse {
+                charge_id: payment.charge_id,
+                payer: payment.payer.to_string(),
+                amount: payment.amount,
+                paid_at: payment.paid_at.seconds(),
+                refunded_amount: payment.refunded_amount,
+            })
+        })
+        .collect();
+
+    Ok(PaymentsResponse {
+        subscriber: subscriber.to_string(),
+        payments: payments?,
+    })
+}
+
 fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
     let config = read_config(deps.storage)?;
 
@@ -991,34 +4377,112 @@ fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
         unit_interval_seconds: config.unit_interval.seconds(),
         unit_amount: config.unit_amount,
         additional_grace_period: config.additional_grace_period,
+        trial_period_seconds: config.trial_period,
+        max_amount_chargeable: config.max_amount_chargeable,
+        retry_schedule_seconds: config.retry_schedule,
+        max_pause_duration_seconds: config.max_pause_duration,
+        max_pauses_per_year: config.max_pauses_per_year,
+        usage_tiers: config.usage_tiers,
+        usage_reporters: config
+            .usage_reporters
+            .iter()
+            .map(|reporter| reporter.to_string())
+            .collect(),
+        billing_asset: config.billing_asset,
+        revenue_splits: config
+            .revenue_splits
+            .into_iter()
+            .map(|split| RevenueSplitInfo {
+                receiver: split.receiver.to_string(),
+                share_bps: split.share_bps,
+            })
+            .collect(),
+        hooks: config.hooks.iter().map(|hook| hook.to_string()).collect(),
+        billing_calendar: config.billing_calendar,
+        price_changes: to_price_change_responses(config.price_changes),
+        accept_any_payer: config.accept_any_payer,
         is_paused: config.is_paused,
         is_frozen: config.is_frozen,
         uri: config.uri,
     })
 }
 
-/// is_subscription_active returns true if the subscription is active.
+/// is_subscription_active returns true if the subscription has neither lapsed nor completed. Paused subscriptions have not lapsed.
 /// It takes into account the tolerance period that is set in the config
 pub fn is_subscription_active(storage: &dyn Storage, env: Env, sub: SubscriptionInfo) -> bool {
     let config = read_config(storage).unwrap();
 
+    !matches!(
+        compute_status(&config, &sub, env.block.time),
+        SubscriptionStatus::Lapsed | SubscriptionStatus::Completed
+    )
+}
+
+/// compute_status returns the [`SubscriptionStatus`] of the subscription at `block_time`
+pub fn compute_status(
+    config: &Config,
+    subscription: &SubscriptionInfo,
+    block_time: Timestamp,
+) -> SubscriptionStatus {
+    // fixed-term subscriptions are no longer active once the term has been completed
+    if subscription.is_completed(block_time) {
+        return SubscriptionStatus::Completed;
+    }
+
+    if subscription.is_paused(block_time) {
+        return SubscriptionStatus::Paused;
+    }
+
     // check if it is within the period
-    match env.block.time <= sub.interval_end_at {
-        true => true,
-        false =>
-        // check if it is within the grace period
-        {
-            match sub.is_cancelled {
-                true => false, // users who cancelled do not fall within the grace period
-                false => {
-                    env.block.time
-                        <= sub
-                            .interval_end_at
-                            .plus_seconds(DEFAULT_GRACE_PERIOD)
-                            .plus_seconds(config.additional_grace_period)
-                }
+    if block_time <= subscription.interval_end_at {
+        return match (subscription.is_cancelled, subscription.trial_end_at) {
+            (true, _) => SubscriptionStatus::CancelledUntilPeriodEnd,
+            (false, Some(trial_end_at)) if block_time < trial_end_at => SubscriptionStatus::InTrial,
+            // the first payment is due once the trial has ended
+            (false, Some(trial_end_at)) if trial_end_at == subscription.interval_end_at => {
+                SubscriptionStatus::PendingInitialPayment
             }
+            (false, _) => SubscriptionStatus::Active,
+        };
+    }
+
+    // users who cancelled do not fall within the grace period
+    match !subscription.is_cancelled && block_time <= grace_period_end_at(config, subscription) {
+        // nothing has been charged since the trial ended
+        true if subscription.trial_end_at == Some(subscription.interval_end_at) => {
+            SubscriptionStatus::PendingInitialPayment
+        }
+        true => SubscriptionStatus::InGrace,
+        false => SubscriptionStatus::Lapsed,
+    }
+}
+
+/// Returns the end of the grace period of the subscription, after which it lapses unless it is charged
+fn grace_period_end_at(config: &Config, subscription: &SubscriptionInfo) -> Timestamp {
+    subscription
+        .interval_end_at
+        .plus_seconds(DEFAULT_GRACE_PERIOD)
+        .plus_seconds(config.additional_grace_period)
+}
+
+/// Returns the billing statuses of the `due_time` index that hold the subscriptions with the given [`SubscriptionStatus`],
+/// in the order they are paged through. Removed subscriptions are not in the index
+fn status_index_keys(status: &SubscriptionStatus) -> Vec<&'static [u8]> {
+    match status {
+        // the last billing cycle of a fixed-term subscription is active until it ends
+        SubscriptionStatus::InTrial | SubscriptionStatus::Active => {
+            vec![ACTIVE_STATUS_KEY, PAST_DUE_STATUS_KEY, COMPLETED_STATUS_KEY]
         }
+        SubscriptionStatus::PendingInitialPayment | SubscriptionStatus::InGrace => {
+            vec![ACTIVE_STATUS_KEY, PAST_DUE_STATUS_KEY]
+        }
+        // subscriptions are paused or completed before they are cancelled
+        SubscriptionStatus::Paused | SubscriptionStatus::Lapsed => {
+            vec![ACTIVE_STATUS_KEY, PAST_DUE_STATUS_KEY, CANCELLED_STATUS_KEY]
+        }
+        SubscriptionStatus::CancelledUntilPeriodEnd => vec![CANCELLED_STATUS_KEY],
+        SubscriptionStatus::Completed => vec![CANCELLED_STATUS_KEY, COMPLETED_STATUS_KEY],
+        SubscriptionStatus::Removed => vec![],
     }
 }
 
@@ -1028,32 +4492,204 @@ fn query_subscription(
     env: Env,
     subscriber: String,
 ) -> StdResult<Option<SubscriptionInfoResponse>> {
-    let response = match SUBSCRIPTIONS
-        .may_load(deps.storage, &deps.api.addr_validate(&subscriber)?)?
+    let subscriber = deps.api.addr_validate(&subscriber)?;
+
+    let config = read_config(deps.storage)?;
+
+    // members of a team without a subscription of their own are reported with the subscription of their team
+    let (subscription, team_owner) = match subscriptions().may_load(deps.storage, &subscriber)? {
+        Some(subscription) => (subscription, None),
+        None => match TEAM_MEMBERS.may_load(deps.storage, &subscriber)? {
+            Some(owner) => match subscriptions().may_load(deps.storage, &owner)? {
+                Some(subscription) => (subscription, Some(owner)),
+                None => return Ok(None),
+            },
+            None => {
+                return match REMOVED_SUBSCRIPTIONS.may_load(deps.storage, &subscriber)? {
+                    Some(subscription) => Ok(Some(to_subscription_response(
+                        deps,
+                        &env,
+                        &config,
+                        subscription,
+                        SubscriptionStatus::Removed,
+                    )?)),
+                    None => Ok(None),
+                }
+            }
+        },
+    };
+
+    let status = compute_status(&config, &subscription, env.block.time);
+    let mut response = to_subscription_response(deps, &env, &config, subscription, status)?;
+    if let Some(owner) = team_owner {
+        response.subscriber = subscriber.to_string();
+        response.team_owner = Some(owner.to_string());
+    }
+
+    Ok(Some(response))
+}
+
+fn to_subscription_response(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    subscription: SubscriptionInfo,
+    status: SubscriptionStatus,
+) -> StdResult<SubscriptionInfoResponse> {
+    let plan = match read_plan(deps.storage, subscription.plan_id)? {
+        Some(plan) => plan,
+        None => return Err(StdError::not_found("Plan")),
+    };
+
+    // paused subscriptions are not active until they resume
+    let is_paused = status == SubscriptionStatus::Paused;
+    let sub_active = status.is_active();
+    // past due subscriptions still owe the charge that failed, unless they have been removed
+    let amount_chargeable: Uint256 = match sub_active
+        || (subscription.is_past_due() && status != SubscriptionStatus::Removed)
     {
-        Some(subscription) => {
-            let config = read_config(deps.storage)?;
-
-            let sub_active: bool =
-                is_subscription_active(deps.storage, env.clone(), subscription.clone());
-            let amount_chargeable: Uint256 = match sub_active {
-                true => compute_amount_chargeable(&config, &subscription, env.block.time).amount,
-                false => Uint256::zero(),
-            };
-
-            Some(SubscriptionInfoResponse {
-                subscriber: subscription.owner.to_string(),
-                created_at: subscription.created_at.seconds(),
-                interval_end_at: subscription.interval_end_at.seconds(),
-                last_charged: subscription.last_charged.seconds(),
-                is_cancelled: subscription.is_cancelled,
-                is_active: sub_active,
-                discount_per_interval: subscription.discount_per_interval,
-                amount_chargeable: Some(amount_chargeable),
-            })
-        }
-        None => None,
+        true => cap_amount_chargeable(
+            config,
+            &subscription,
+            compute_amount_chargeable(&plan, &config.usage_tiers, &subscription, env.block.time)
+                .amount,
+        ),
+        false => Uint256::zero(),
     };
 
-    Ok(response)
+    Ok(SubscriptionInfoResponse {
+        subscriber: subscription.owner.to_string(),
+        plan_id: subscription.plan_id,
+        created_at: subscription.created_at.seconds(),
+        interval_end_at: subscription.interval_end_at.seconds(),
+        last_charged: subscription.last_charged.seconds(),
+        is_cancelled: subscription.is_cancelled,
+        is_active: sub_active,
+        discount_per_interval: subscription.discount_per_interval.clone(),
+        trial_end_at: subscription.trial_end_at.map(|t| t.seconds()),
+        amount_chargeable: Some(amount_chargeable),
+        total_paid: subscription.total_paid,
+        lifetime_paid: subscription.lifetime_paid,
+        max_amount_chargeable: config.max_amount_chargeable,
+        is_past_due: subscription.is_past_due(),
+        retry_count: subscription.retry_count,
+        next_retry_at: subscription.next_retry_at.map(|t| t.seconds()),
+        is_paused,
+        resume_at: match is_paused {
+            true => subscription.resume_at.map(|t| t.seconds()),
+            false => None,
+        },
+        term_end_at: subscription.term_end_at.map(|t| t.seconds()),
+        is_completed: status == SubscriptionStatus::Completed,
+        payer: subscription.payer_address().to_string(),
+        prepaid_balance: PREPAID_BALANCES
+            .may_load(deps.storage, &subscription.owner)?
+            .unwrap_or_default(),
+        seats: subscription.seats,
+        team_owner: None,
+        status,
+    })
+}
+
+/// Loads the membership token with the given `token_id`, which is the address of the subscriber
+fn read_membership(deps: Deps, token_id: &str) -> StdResult<MembershipToken> {
+    match memberships().may_load(deps.storage, &deps.api.addr_validate(token_id)?)? {
+        Some(token) => Ok(token),
+        None => Err(StdError::not_found("MembershipToken")),
+    }
+}
+
+/// `query_owner_of` returns the owner of a membership token. Membership tokens have no approvals
+fn query_owner_of(deps: Deps, token_id: String) -> StdResult<OwnerOfResponse> {
+    let token = read_membership(deps, &token_id)?;
+
+    Ok(OwnerOfResponse {
+        owner: token.owner.to_string(),
+        approvals: vec![],
+    })
+}
+
+fn query_num_tokens(deps: Deps) -> StdResult<NumTokensResponse> {
+    Ok(NumTokensResponse {
+        count: MEMBERSHIP_COUNT.may_load(deps.storage)?.unwrap_or_default(),
+    })
+}
+
+fn query_contract_info(deps: Deps) -> StdResult<ContractInfoResponse> {
+    match read_config(deps.storage)?.membership_nft {
+        Some(membership_nft) => Ok(ContractInfoResponse {
+            name: membership_nft.name,
+            symbol: membership_nft.symbol,
+        }),
+        None => Err(StdError::not_found("MembershipNftInfo")),
+    }
+}
+
+/// `query_nft_info` returns the metadata of a membership token, which follows the subscription it was minted for.
+/// The token is expired once the subscription is no longer active
+fn query_nft_info(deps: Deps, env: Env, token_id: String) -> StdResult<NftInfoResponse> {
+    let token = read_membership(deps, &token_id)?;
+    let subscription = subscriptions().load(deps.storage, &token.subscriber)?;
+
+    let is_active =
+        compute_status(&read_config(deps.storage)?, &subscription, env.block.time).is_active();
+
+    Ok(NftInfoResponse {
+        token_uri: None,
+        extension: MembershipMetadata {
+            subscriber: token.subscriber.to_string(),
+            plan_id: subscription.plan_id,
+            interval_end_at: subscription.interval_end_at.seconds(),
+            is_active,
+        },
+    })
+}
+
+/// `query_tokens` returns the IDs of the membership tokens held by `owner`
+/// caller can specify `start_after` and `limit` to paginate the responses
+fn query_tokens(
+    deps: Deps,
+    owner: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<TokensResponse> {
+    let owner = deps.api.addr_validate(&owner)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let tokens = memberships()
+        .idx
+        .owner
+        .prefix(owner.as_bytes().to_vec())
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (token_id, _) = item?;
+            Ok(String::from_utf8(token_id)?)
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(TokensResponse { tokens })
+}
+
+/// `query_all_tokens` returns the IDs of all membership tokens
+/// caller can specify `start_after` and `limit` to paginate the responses
+fn query_all_tokens(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<TokensResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let tokens = memberships()
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (token_id, _) = item?;
+            Ok(String::from_utf8(token_id)?)
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(TokensResponse { tokens })
 }
@@ -41,4 +41,103 @@ pub enum ContractError {
 
     #[error("Subscription cancelled")]
     SubscriptionCancelled {},
+
+    #[error("Plan not found")]
+    PlanNotFound {},
+
+    #[error("Plan is archived")]
+    PlanArchived {},
+
+    #[error("Subscription has an outstanding payment")]
+    PaymentDue {},
+
+    #[error("Maximum amount chargeable has been reached")]
+    MaxAmountChargeableReached {},
+
+    #[error("Coupon not found")]
+    CouponNotFound {},
+
+    #[error("Coupon can no longer be redeemed")]
+    CouponExpired {},
+
+    #[error("Coupon has reached its maximum redemptions")]
+    CouponFullyRedeemed {},
+
+    #[error("Batch exceeds the maximum number of entries")]
+    BatchTooLarge {},
+
+    #[error("Failed charge cannot be retried yet")]
+    RetryNotDue {},
+
+    #[error("Invalid Reply ID")]
+    InvalidReplyID {},
+
+    #[error("Subscription is paused")]
+    SubscriptionPaused {},
+
+    #[error("Maximum number of pauses has been reached")]
+    PauseLimitReached {},
+
+    #[error("Subscription term has been completed")]
+    SubscriptionCompleted {},
+
+    #[error("Fixed-term subscriptions cannot change plans")]
+    FixedTermPlanChange {},
+
+    #[error("Subscription is not active")]
+    SubscriptionInactive {},
+
+    #[error("Product is not metered")]
+    NotMetered {},
+
+    #[error("Payment not found")]
+    PaymentNotFound {},
+
+    #[error("Refund exceeds the unrefunded amount of the payment")]
+    RefundExceedsPayment {},
+
+    #[error("Funds sent do not match the refund amount")]
+    InvalidRefundFunds {},
+
+    #[error("Membership token not found")]
+    MembershipNotFound {},
+
+    #[error("Membership tokens are not transferable")]
+    MembershipNotTransferable {},
+
+    #[error("Price change does not give subscribers the minimum notice")]
+    PriceChangeNoticeTooShort {},
+
+    #[error("Payer is not a subwallet")]
+    PayerNotSubwallet {},
+
+    #[error("Subwallet factory is not set")]
+    SubwalletFactoryNotSet {},
+
+    #[error("accept_any_payer must be set when migrating from before 0.3.0")]
+    AcceptAnyPayerNotSet {},
+
+    #[error("Funds sent are not the native billing asset of the product")]
+    InvalidPrepaidFunds {},
+
+    #[error("Prepaid balance is refunded once the subscription has ended")]
+    PrepaidBalanceLocked {},
+
+    #[error("No prepaid balance to refund")]
+    NoPrepaidBalance {},
+
+    #[error("Subscription is not a team subscription")]
+    NotTeamSubscription {},
+
+    #[error("Every seat of the team subscription has been assigned")]
+    NoSeatAvailable {},
+
+    #[error("Member has already been assigned a seat")]
+    SeatAlreadyAssigned {},
+
+    #[error("Member has not been assigned a seat of the team subscription")]
+    SeatNotAssigned {},
+
+    #[error("Seats cannot be fewer than the members that have been assigned a seat")]
+    SeatsBelowMembers {},
 }
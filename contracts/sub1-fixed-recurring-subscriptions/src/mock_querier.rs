@@ -5,6 +5,7 @@ use cosmwasm_std::{
     QueryRequest, SystemError, SystemResult, WasmQuery,
 };
 use suberra_core::product_factory::{ConfigResponse, ProductsResponse, QueryMsg};
+use suberra_core::subwallet_factory::QueryMsg as SubwalletFactoryQueryMsg;
 use terra_cosmwasm::TerraQueryWrapper;
 /// mock_dependencies is a drop-in replacement for cosmwasm_std::testing::mock_dependencies
 /// this uses our CustomQuerier.
@@ -24,6 +25,7 @@ pub fn mock_dependencies(
 pub struct WasmMockQuerier {
     base: MockQuerier<TerraQueryWrapper>,
     fee_querier: FeeQuerier,
+    subwallet_querier: SubwalletQuerier,
 }
 
 impl WasmMockQuerier {
@@ -31,6 +33,7 @@ impl WasmMockQuerier {
         WasmMockQuerier {
             base,
             fee_querier: FeeQuerier::default(),
+            subwallet_querier: SubwalletQuerier::default(),
         }
     }
 
@@ -54,6 +57,34 @@ impl WasmMockQuerier {
             "job_registry".to_string(),
         );
     }
+
+    // configure the minimum notice of price changes
+    pub fn with_price_change_notice(&mut self, min_price_change_notice_hour: u64) {
+        self.fee_querier.min_price_change_notice_hour = min_price_change_notice_hour;
+    }
+
+    // configure the subwallet factory of the product factory, and the (subwallet, owner) pairs it has created
+    pub fn with_subwallets(
+        &mut self,
+        subwallet_factory_address: &str,
+        subwallets: &[(&str, &str)],
+    ) {
+        self.fee_querier.subwallet_factory_address = Some(subwallet_factory_address.to_string());
+        self.subwallet_querier = SubwalletQuerier {
+            subwallet_factory_address: subwallet_factory_address.to_string(),
+            subwallets: subwallets
+                .iter()
+                .map(|(subwallet, owner)| (subwallet.to_string(), owner.to_string()))
+                .collect(),
+        };
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct SubwalletQuerier {
+    subwallet_factory_address: String,
+    // (subwallet, owner) pairs
+    subwallets: Vec<(String, String)>,
 }
 
 #[derive(Clone, Default)]
@@ -65,8 +96,10 @@ pub struct FeeQuerier {
     min_protocol_fee: Uint256,
     min_amount_per_interval: Uint256,
     min_unit_interval_hour: u64,
+    min_price_change_notice_hour: u64,
     fee_address: String,
     job_registry_address: String,
+    subwallet_factory_address: Option<String>,
 }
 
 impl FeeQuerier {
@@ -89,8 +122,10 @@ impl FeeQuerier {
             min_protocol_fee,
             min_amount_per_interval,
             min_unit_interval_hour,
+            min_price_change_notice_hour: 0,
             fee_address,
             job_registry_address,
+            subwallet_factory_address: None,
         }
     }
 }
@@ -114,6 +149,22 @@ impl Querier for WasmMockQuerier {
 impl WasmMockQuerier {
     pub fn handle_query(&self, request: &QueryRequest<TerraQueryWrapper>) -> QuerierResult {
         match &request {
+            QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg })
+                if *contract_addr == self.subwallet_querier.subwallet_factory_address =>
+            {
+                match from_binary(msg).unwrap() {
+                    SubwalletFactoryQueryMsg::GetSubwalletOwner { subwallet_address } => {
+                        let owner = self
+                            .subwallet_querier
+                            .subwallets
+                            .iter()
+                            .find(|(subwallet, _)| *subwallet == subwallet_address)
+                            .map(|(_, owner)| owner.clone());
+                        SystemResult::Ok(to_binary(&owner).into())
+                    }
+                    _ => panic!("DO NOT ENTER HERE"),
+                }
+            }
             QueryRequest::Wasm(WasmQuery::Smart {contract_addr: _, msg})// => {
                 => match from_binary(&msg).unwrap() {
                     QueryMsg::Config {} => {
@@ -125,8 +176,10 @@ impl WasmMockQuerier {
                             min_protocol_fee: self.fee_querier.min_protocol_fee,
                             min_amount_per_interval: self.fee_querier.min_amount_per_interval,
                             min_unit_interval_hour: self.fee_querier.min_unit_interval_hour,
+                            min_price_change_notice_hour: self.fee_querier.min_price_change_notice_hour,
                             fee_address: self.fee_querier.fee_address.clone(),
                             job_registry_address: self.fee_querier.job_registry_address.clone(),
+                            subwallet_factory_address: self.fee_querier.subwallet_factory_address.clone(),
                         };
                         SystemResult::Ok(to_binary(&config).into())
                     }
@@ -1,9 +1,21 @@
-use crate::state::SubscriptionInfo;
+use crate::state::SubscriptionStatus;
 use cosmwasm_bignumber::Uint256;
 use cosmwasm_std::{Binary, Uint128};
+use cw0::Expiration;
+use cw20::Cw20ReceiveMsg;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use suberra_core::subscriptions::Discount;
+use suberra_core::asset::{Asset, AssetInfo};
+use suberra_core::subscriptions::{
+    BillingCalendar, Discount, PlanInfo, PriceTier, RevenueSplitInfo,
+};
+
+/// `accept_any_payer` is required to migrate a product from before `0.3.0`, which accepted any payer
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateMsg {
+    /// Accepts payers that are not subwallets of the subwallet factory
+    pub accept_any_payer: Option<bool>,
+}
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
@@ -11,14 +23,86 @@ pub enum ExecuteMsg {
     UpdateConfig {
         receiver_address: Option<String>,
         additional_grace_period_hour: Option<u64>,
+        retry_schedule_hour: Option<Vec<u64>>,
+        max_pause_duration_hour: Option<u64>,
+        max_pauses_per_year: Option<u32>,
         initial_amount: Option<Uint256>,
         uri: Option<String>,
+        revenue_splits: Option<Vec<RevenueSplitInfo>>,
+        accept_any_payer: Option<bool>,
     },
     UpdateAdmins {
         admins: Vec<String>,
     },
-    Subscribe {},
+    /// Sets the addresses that can report the usage of subscribers of a metered product
+    UpdateUsageReporters {
+        reporters: Vec<String>,
+    },
+    /// Sets the contracts that receive a [`SubscriptionHookMsg`] when a subscription changes. Replaces the existing hooks
+    ///
+    /// [`SubscriptionHookMsg`]: suberra_core::msg::SubscriptionHookMsg
+    UpdateHooks {
+        hooks: Vec<String>,
+    },
+    /// Schedules a change of the `unit_amount` of the plan, or of the default plan if `plan_id` is unset. Subscribers of the plan
+    /// are billed `new_unit_amount` from their first interval that starts at or after `effective_at` (unix timestamp in seconds)
+    SchedulePriceChange {
+        plan_id: Option<u64>,
+        new_unit_amount: Uint256,
+        effective_at: u64,
+    },
+    /// Adds `quantity` units of usage to the current interval of the subscriber. Only callable by usage reporters
+    ReportUsage {
+        subscriber: String,
+        quantity: u64,
+    },
+    /// Subscribes the `beneficiary` to the product, or the sender if unspecified. The sender pays for the subscription.
+    /// The subscription runs until it is cancelled, unless a fixed term of `billing_cycles` or ending by `end_at`
+    /// (unix timestamp in seconds) is given. The native billing asset sent along with the message is added to the prepaid balance
+    /// of the subscription, and the sender does not need to be a subwallet then.
+    /// Team subscriptions are created with a number of `seats`, and are billed the unit amount for every seat
+    Subscribe {
+        plan_id: Option<u64>,
+        coupon: Option<String>,
+        billing_cycles: Option<u64>,
+        end_at: Option<u64>,
+        beneficiary: Option<String>,
+        seats: Option<u64>,
+    },
+    ChangePlan {
+        plan_id: u64,
+    },
     Cancel {},
+    /// Adds the native billing asset sent along with the message to the prepaid balance of the subscription of `subscriber`,
+    /// or of the sender if unspecified. Charges are paid from the prepaid balance when it covers them
+    TopUp {
+        subscriber: Option<String>,
+    },
+    /// Refunds the prepaid balance of the subscriber to the payer of the subscription, once the subscription has been cancelled
+    /// and its current interval has ended, or once its term has been completed. Callable by anyone
+    RefundPrepaidBalance {
+        subscriber: String,
+    },
+    /// Changes the number of seats of the team subscription of the sender. Seats added or removed during an interval are prorated
+    /// on the next charge. The seats cannot be fewer than the members that have been assigned a seat
+    UpdateSeats {
+        seats: u64,
+    },
+    /// Assigns a seat of the team subscription of the sender to `member`. Members are active while the team subscription is active
+    AssignSeat {
+        member: String,
+    },
+    /// Frees the seat of `member` in the team subscription of the sender
+    UnassignSeat {
+        member: String,
+    },
+    /// Makes the sender pay for its own subscription from now on, instead of the subwallet that subscribed it
+    TakeOverPayment {},
+    /// Pauses the subscription of the sender until `resume_at` (unix timestamp in seconds).
+    /// The end of the current interval is moved by the paused time
+    PauseSubscription {
+        resume_at: u64,
+    },
     TogglePause {},
     ToggleFreeze {},
     RemoveSubscriber {
@@ -34,12 +118,65 @@ pub enum ExecuteMsg {
         discount: Option<Discount>,
         subscriber: String,
     },
+    AddPlan {
+        plan: PlanInfo,
+    },
+    ArchivePlan {
+        plan_id: u64,
+    },
+    CreateCoupon {
+        code: String,
+        discount: Discount,
+        max_redemptions: u64,
+        redeem_by: u64,
+    },
+    RemoveCoupon {
+        code: String,
+    },
     Charge {
         payer_address: String,
     },
     Work {
         payload: Binary,
     },
+    /// Transfers a charge for the subscriber from its prepaid balance, or from the subwallet of the payer if the balance does not cover it.
+    /// Only callable by the contract itself,
+    /// so that the protocol fee and the payment to the receiver succeed or fail together
+    CollectPayment {
+        subscriber: String,
+        payer: String,
+        amount: Uint256,
+    },
+    /// Sends a work receipt for `units` charges to the job registry, less the charges whose payment failed in the transaction.
+    /// Only callable by the contract itself, after the payments of the charges have been collected
+    SendWorkReceipt {
+        worker_address: String,
+        units: u64,
+    },
+    /// Sends `amount` back to the subwallet that made the payment with the given `charge_id` for the subscriber.
+    /// Only callable by the receiver, which must send exactly `amount` of the native billing asset (UST by default) along with the message.
+    /// Products billed in a cw20 token are refunded with [`Cw20HookMsg::Refund`] instead
+    Refund {
+        subscriber: String,
+        amount: Uint256,
+        charge_id: u64,
+    },
+    /// Transfers the cw721 membership token with the given `token_id` to `recipient`.
+    /// Only callable by the owner of the token, if the membership tokens of the product are transferable
+    TransferNft {
+        recipient: String,
+        token_id: String,
+    },
+    /// Receives cw20 tokens with a [`Cw20HookMsg`]
+    Receive(Cw20ReceiveMsg),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw20HookMsg {
+    /// Sends the received tokens back to the subwallet that made the payment with the given `charge_id` for the subscriber.
+    /// Only the receiver can send the tokens, which must be the cw20 billing asset of the product
+    Refund { subscriber: String, charge_id: u64 },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -47,12 +184,16 @@ pub enum ExecuteMsg {
 pub enum SubWalletExecuteMsg {
     TransferUST { amount: Uint128, recipient: String },
     TransferAToken { amount: Uint128, recipient: String },
+    TransferAsset { asset: Asset, recipient: String },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum JobsRegistryExecuteMsg {
-    WorkReceipt { worker_address: String },
+    WorkReceipt {
+        worker_address: String,
+        units: Option<u64>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -61,16 +202,82 @@ pub enum QueryMsg {
     AdminConfig {},
     Owner {},
     Config {},
+    /// Returns the subscriber counts, revenue and monthly recurring revenue of the product
+    Stats {},
+    /// Returns the subscription of `subscriber`, or the team subscription that `subscriber` has been assigned a seat of
     Subscription {
         subscriber: String,
     },
+    /// Lists the subscriptions sorted by subscriber, or only the ones with the given `status` sorted by billing status and due time.
+    /// `start_after` is the last subscriber of the previous page
     Subscriptions {
         start_after: Option<String>,
         limit: Option<u32>,
+        status: Option<SubscriptionStatus>,
+    },
+    Plan {
+        plan_id: u64,
+    },
+    Plans {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    Coupon {
+        code: String,
+    },
+    /// Returns the seats and the members of the team subscription of `owner`
+    Team {
+        owner: String,
+    },
+    Usage {
+        subscriber: String,
+    },
+    Payments {
+        subscriber: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    Coupons {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    DueSubscriptions {
+        before: Option<u64>,
+        start_after: Option<String>,
+        limit: Option<u32>,
     },
     CanWork {
         payload: Binary,
     },
+    /// cw721: Returns the owner of the membership token with the given `token_id`
+    OwnerOf {
+        token_id: String,
+        include_expired: Option<bool>,
+    },
+    /// cw721: Returns the number of membership tokens
+    NumTokens {},
+    /// cw721: Returns the name and symbol of the membership tokens
+    ContractInfo {},
+    /// cw721: Returns the metadata of the membership token with the given `token_id`
+    NftInfo {
+        token_id: String,
+    },
+    /// cw721: Returns the owner and the metadata of the membership token with the given `token_id`
+    AllNftInfo {
+        token_id: String,
+        include_expired: Option<bool>,
+    },
+    /// cw721: Returns the IDs of the membership tokens held by `owner`
+    Tokens {
+        owner: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// cw721: Returns the IDs of all membership tokens
+    AllTokens {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -78,6 +285,12 @@ pub struct WorkPayload {
     pub payer_address: String,
 }
 
+/// Payload to charge multiple subscribers in a single `Work` call
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BatchWorkPayload {
+    pub payer_addresses: Vec<String>,
+}
+
 // We define a custom struct for each query response
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct ConfigResponse {
@@ -86,26 +299,195 @@ pub struct ConfigResponse {
     pub unit_interval_seconds: u64,
     pub unit_amount: Uint256,
     pub additional_grace_period: u64,
+    pub trial_period_seconds: u64,
+    pub max_amount_chargeable: Option<Uint256>,
+    pub retry_schedule_seconds: Vec<u64>,
+    pub max_pause_duration_seconds: u64,
+    pub max_pauses_per_year: u32,
+    pub usage_tiers: Vec<PriceTier>,
+    pub usage_reporters: Vec<String>,
+    pub billing_asset: Option<AssetInfo>,
+    pub revenue_splits: Vec<RevenueSplitInfo>,
+    pub hooks: Vec<String>,
+    pub billing_calendar: Option<BillingCalendar>,
+    pub price_changes: Vec<PriceChangeResponse>,
+    pub accept_any_payer: bool,
     pub initial_amount: Uint256,
     pub is_paused: bool,
     pub is_frozen: bool,
     pub uri: String,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PriceChangeResponse {
+    pub unit_amount: Uint256,
+    pub effective_at: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StatsResponse {
+    pub total_subscribers: u64,
+    /// subscriptions that are not cancelled, whatever their [`SubscriptionStatus`]
+    pub active_subscribers: u64,
+    pub cancelled_subscribers: u64,
+    pub gross_charged: Uint256,
+    pub protocol_fees: Uint256,
+    pub monthly_recurring_revenue: Uint256,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct SubscriptionInfoResponse {
     pub subscriber: String,
+    pub plan_id: u64,
     pub created_at: u64,
     pub interval_end_at: u64,
     pub last_charged: u64,
     pub is_cancelled: bool,
     pub is_active: bool,
+    pub status: SubscriptionStatus,
     pub discount_per_interval: Option<Discount>,
+    pub trial_end_at: Option<u64>,
     pub amount_chargeable: Option<Uint256>,
+    pub total_paid: Uint256,
+    pub lifetime_paid: Uint256,
+    pub max_amount_chargeable: Option<Uint256>,
+    pub is_past_due: bool,
+    pub retry_count: u32,
+    pub next_retry_at: Option<u64>,
+    pub is_paused: bool,
+    pub resume_at: Option<u64>,
+    pub term_end_at: Option<u64>,
+    pub is_completed: bool,
+    pub payer: String,
+    pub prepaid_balance: Uint256,
+    pub seats: Option<u64>,
+    /// Owner of the team subscription, if the subscription is returned for a member of the team
+    pub team_owner: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub struct SubscriptionsResponse {
-    pub subscriptions: Vec<SubscriptionInfo>,
+    pub subscriptions: Vec<SubscriptionInfoResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DueSubscriptionsResponse {
+    pub subscribers: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PlanResponse {
+    pub plan_id: u64,
+    pub unit_amount: Uint256,
+    pub initial_amount: Uint256,
+    pub unit_interval_seconds: u64,
+    pub trial_period_seconds: u64,
+    pub is_archived: bool,
+    pub billing_calendar: Option<BillingCalendar>,
+    pub price_changes: Vec<PriceChangeResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PlansResponse {
+    pub plans: Vec<PlanResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CouponResponse {
+    pub code: String,
+    pub discount: Discount,
+    pub max_redemptions: u64,
+    pub redemptions: u64,
+    pub redeem_by: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CouponsResponse {
+    pub coupons: Vec<CouponResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TeamResponse {
+    pub owner: String,
+    pub seats: u64,
+    pub members: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UsageRecordResponse {
+    pub interval_end_at: u64,
+    pub quantity: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UsageResponse {
+    pub subscriber: String,
+    pub usage: Vec<UsageRecordResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PaymentResponse {
+    pub charge_id: u64,
+    pub payer: String,
+    pub amount: Uint256,
+    pub paid_at: u64,
+    pub refunded_amount: Uint256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PaymentsResponse {
+    pub subscriber: String,
+    pub payments: Vec<PaymentResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Approval {
+    pub spender: String,
+    pub expires: Expiration,
+}
+
+/// Membership tokens have no approvals, as only the owner can transfer them
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OwnerOfResponse {
+    pub owner: String,
+    pub approvals: Vec<Approval>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct NumTokensResponse {
+    pub count: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ContractInfoResponse {
+    pub name: String,
+    pub symbol: String,
+}
+
+/// Metadata of a membership token, computed from the subscription when queried.
+/// The token is expired once `is_active` is false
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MembershipMetadata {
+    pub subscriber: String,
+    pub plan_id: u64,
+    pub interval_end_at: u64,
+    pub is_active: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct NftInfoResponse {
+    pub token_uri: Option<String>,
+    pub extension: MembershipMetadata,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AllNftInfoResponse {
+    pub access: OwnerOfResponse,
+    pub info: NftInfoResponse,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TokensResponse {
+    pub tokens: Vec<String>,
 }
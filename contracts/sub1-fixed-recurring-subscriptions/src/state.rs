@@ -1,14 +1,20 @@
 use crate::error::ContractError;
 use cosmwasm_bignumber::Uint256;
-use cosmwasm_std::{Addr, StdResult, Storage, Timestamp};
-use cosmwasm_storage::{bucket_read, ReadonlySingleton, Singleton};
-use cw_storage_plus::Map;
+use cosmwasm_std::{Addr, Order, StdError, StdResult, Storage, Timestamp};
+use cosmwasm_storage::{singleton, singleton_read};
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex, U64Key};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use suberra_core::subscriptions::Discount;
+use suberra_core::asset::AssetInfo;
+use suberra_core::subscriptions::{
+    BillingCalendar, Discount, MembershipNftInfo, PriceTier, DEFAULT_PLAN_ID,
+};
 
-const CONFIG_KEY: &[u8] = b"config";
-const PREFIX_SUBSCRIPTIONS: &[u8] = b"subscriptions";
+// key of the `cosmwasm_storage` singleton that stored the config of contracts instantiated before sub1 stored its own contract version
+const LEGACY_CONFIG_KEY: &[u8] = b"config";
+
+// retry schedule of contracts instantiated before dunning was introduced: retries 1, 3 and 7 days after a failed charge
+pub const DEFAULT_RETRY_SCHEDULE: [u64; 3] = [86_400, 259_200, 604_800];
 
 /// Takes in a [`SubscriptionInfo`] object and save it in the Subscriptions application.
 /// Increments the counter by one.
@@ -17,7 +23,7 @@ pub fn create_subscription(
     subscriber: Addr,
     subscription: SubscriptionInfo,
 ) -> Result<(), ContractError> {
-    SUBSCRIPTIONS.save(storage, &subscriber, &subscription)?;
+    subscriptions().save(storage, &subscriber, &subscription)?;
     Ok(())
 }
 
@@ -33,6 +39,210 @@ pub struct Config {
     pub is_frozen: bool,
     pub uri: String,
     pub factory_address: Addr,
+    // duration of the free trial in seconds. Contracts instantiated before trials were introduced have no trial
+    #[serde(default)]
+    pub trial_period: u64,
+    // maximum amount that a subscription can be charged over its lifetime. No cap if unset
+    #[serde(default)]
+    pub max_amount_chargeable: Option<Uint256>,
+    // delays in seconds before each retry of a failed charge. The subscription is cancelled once every retry has failed
+    #[serde(default = "default_retry_schedule")]
+    pub retry_schedule: Vec<u64>,
+    // maximum duration in seconds of a pause requested by a subscriber. Subscribers cannot pause if zero
+    #[serde(default)]
+    pub max_pause_duration: u64,
+    // maximum number of pauses that a subscriber can request in any 365 days. Subscribers cannot pause if zero
+    #[serde(default)]
+    pub max_pauses_per_year: u32,
+    // graduated unit prices of the usage reported in every interval. The product is flat-rate if empty
+    #[serde(default)]
+    pub usage_tiers: Vec<PriceTier>,
+    // addresses that can report the usage of subscribers
+    #[serde(default)]
+    pub usage_reporters: Vec<Addr>,
+    // cw721 membership tokens minted for the subscriptions. No tokens are minted if unset
+    #[serde(default)]
+    pub membership_nft: Option<MembershipNftInfo>,
+    // asset that subscriptions are paid in. Subscriptions are paid in aUST if unset
+    #[serde(default)]
+    pub billing_asset: Option<AssetInfo>,
+    // co-receivers of the revenue. The receiver address is the primary receiver, and gets the rest of the revenue
+    #[serde(default)]
+    pub revenue_splits: Vec<RevenueSplit>,
+    // contracts that receive a callback when a subscription changes
+    #[serde(default)]
+    pub hooks: Vec<Addr>,
+    // anchors the intervals of the default plan to the UTC calendar. The default plan uses the unit interval if unset
+    #[serde(default)]
+    pub billing_calendar: Option<BillingCalendar>,
+    // changes of the unit amount of the default plan, sorted by `effective_at`. Changes are kept once they take effect
+    #[serde(default)]
+    pub price_changes: Vec<PriceChange>,
+    // accepts payers that are not subwallets of the subwallet factory. Contracts instantiated before the check was introduced set it when migrating
+    #[serde(default)]
+    pub accept_any_payer: bool,
+}
+
+/// PriceChange describes a scheduled change of the unit amount of a plan. Intervals that start at or after `effective_at` are
+/// billed the new `unit_amount`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PriceChange {
+    pub unit_amount: Uint256,
+    pub effective_at: Timestamp,
+}
+
+/// RevenueSplit describes the share of the merchant amount of every payment that is sent to a co-receiver
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RevenueSplit {
+    pub receiver: Addr,
+    pub share_bps: u64,
+}
+
+fn default_retry_schedule() -> Vec<u64> {
+    DEFAULT_RETRY_SCHEDULE.to_vec()
+}
+
+impl Config {
+    /// Returns true if subscribers are billed for the usage reported in every interval
+    pub fn is_metered(&self) -> bool {
+        !self.usage_tiers.is_empty()
+    }
+
+    /// Returns the default plan of the product, which is described by the pricing fields of the [`Config`]
+    pub fn default_plan(&self) -> Plan {
+        Plan {
+            plan_id: DEFAULT_PLAN_ID,
+            unit_amount: self.unit_amount,
+            initial_amount: self.initial_amount,
+            unit_interval: self.unit_interval,
+            trial_period: self.trial_period,
+            is_archived: false,
+            billing_calendar: self.billing_calendar.clone(),
+            price_changes: self.price_changes.clone(),
+        }
+    }
+}
+
+/// # Description
+/// Stores the pricing of a plan that is offered by the product
+/// - plan_id: identifier of the plan
+/// - unit_amount: amount to be charged in every billing cycle
+/// - initial_amount: amount that must be transferred to the receiver for the subscription to be created
+/// - unit_interval: duration of the billing cycle
+/// - trial_period: duration of the free trial in seconds
+/// - is_archived: archived plans continue to bill existing subscribers but do not accept new subscriptions
+/// - billing_calendar (optional): calendar that the intervals end on. Replaces the `unit_interval` if set
+/// - price_changes: changes of the `unit_amount`, sorted by `effective_at`. Changes are kept once they take effect, so that
+///   an interval is billed the unit amount at its start however late it is charged
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Plan {
+    pub plan_id: u64,
+    pub unit_amount: Uint256,
+    pub initial_amount: Uint256,
+    pub unit_interval: Timestamp,
+    pub trial_period: u64,
+    pub is_archived: bool,
+    #[serde(default)]
+    pub billing_calendar: Option<BillingCalendar>,
+    #[serde(default)]
+    pub price_changes: Vec<PriceChange>,
+}
+
+impl Plan {
+    /// Returns the plan with the unit amount that is billed for an interval that starts at `interval_start`
+    pub fn priced_at(&self, interval_start: Timestamp) -> Plan {
+        let unit_amount = self
+            .price_changes
+            .iter()
+            .rev()
+            .find(|change| change.effective_at <= interval_start)
+            .map_or(self.unit_amount, |change| change.unit_amount);
+        Plan {
+            unit_amount,
+            price_changes: vec![],
+            ..self.clone()
+        }
+    }
+
+    /// Replaces the changes of the unit amount that have not taken effect at `block_time` with `change`.
+    /// Changes that have taken effect are kept, as intervals that started after them may not be billed yet
+    pub fn schedule_price_change(&mut self, change: PriceChange, block_time: Timestamp) {
+        self.price_changes
+            .retain(|scheduled| scheduled.effective_at <= block_time);
+        self.price_changes.push(change);
+    }
+
+    /// Returns the end of the `intervals`-th interval that starts at `start`.
+    /// Calendar intervals end on the period ends of the calendar, so the first interval is partial if `start` is not a period end
+    pub fn interval_end_after(&self, start: Timestamp, intervals: u64) -> Timestamp {
+        match &self.billing_calendar {
+            Some(calendar) => {
+                Timestamp::from_seconds(calendar.period_end_after(start.seconds(), intervals))
+            }
+            None => start.plus_seconds(self.unit_interval.seconds() * intervals),
+        }
+    }
+
+    /// Returns the number of consecutive intervals, the first one starting at `start`, that start before `until`
+    pub fn intervals_starting_before(&self, start: Timestamp, until: Timestamp) -> u64 {
+        if until <= start {
+            return 0;
+        }
+
+        match &self.billing_calendar {
+            Some(calendar) => 1 + calendar.period_ends_between(start.seconds(), until.seconds()),
+            None => (until.seconds() - start.seconds()).div_ceil(self.unit_interval.seconds()),
+        }
+    }
+
+    /// Returns the duration in seconds of a full interval that ends at `end`
+    pub fn interval_length(&self, end: Timestamp) -> u64 {
+        match &self.billing_calendar {
+            Some(calendar) => end.seconds() - calendar.previous_period_end(end.seconds()),
+            None => self.unit_interval.seconds(),
+        }
+    }
+}
+
+/// SubscriptionStatus describes the state of a subscription at a point in time. It is computed from the [`SubscriptionInfo`]
+/// by `compute_status` and is never stored
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SubscriptionStatus {
+    /// In its free trial. Nothing is charged until the trial ends
+    InTrial,
+    /// The free trial has ended and the first payment has not been collected yet, but the grace period has not ended
+    PendingInitialPayment,
+    /// Paid for the current interval
+    Active,
+    /// The interval has ended and has not been charged yet, but the grace period has not ended
+    InGrace,
+    /// Paused by the subscriber until `resume_at`. The subscriber has no access and is not charged while paused,
+    /// and the end of the current interval is moved by the paused time
+    Paused,
+    /// Cancelled, and paid until the end of the current interval
+    CancelledUntilPeriodEnd,
+    /// The interval and the grace period have ended without a payment, or the subscription was cancelled and its interval has ended
+    Lapsed,
+    /// Every billing cycle of a fixed-term subscription has ended. The subscriber has no access and is not charged anymore,
+    /// and can subscribe again
+    Completed,
+    /// Removed by the owner or an admin. The subscriber has no access, and the subscription is kept until the subscriber subscribes again
+    Removed,
+}
+
+impl SubscriptionStatus {
+    /// Returns true if the subscriber has access to the product
+    pub fn is_active(&self) -> bool {
+        matches!(
+            self,
+            SubscriptionStatus::InTrial
+                | SubscriptionStatus::PendingInitialPayment
+                | SubscriptionStatus::Active
+                | SubscriptionStatus::InGrace
+                | SubscriptionStatus::CancelledUntilPeriodEnd
+        )
+    }
 }
 
 /// # Description
@@ -43,6 +253,20 @@ pub struct Config {
 /// - discount (optional): Discount applicable for the subscriber per interval
 /// - is_cancelled: Returns a value on type [`bool`] on whether the subscription is cancelled
 /// - owner: Value of type [`Addr`] of the owner of the object (i.e. the Subscriber)
+/// - plan_id: identifier of the [`Plan`] that the subscriber is billed on
+/// - trial_end_at (optional): timestamp when the free trial of the subscription ends, if the subscription started with a trial
+/// - total_paid: cumulative amount paid by the subscriber for the subscription, which is capped by the `max_amount_chargeable` of the [`Config`]
+/// - lifetime_paid: cumulative amount paid by the subscriber for all its subscriptions to the product, before refunds. Kept when the subscriber subscribes again
+/// - retry_count: number of consecutive failed charges. Reset once a charge succeeds
+/// - next_retry_at (optional): timestamp after which a failed charge can be retried. Set while the subscription is past due
+/// - resume_at (optional): timestamp when the latest pause requested by the subscriber ends
+/// - pause_history: timestamps when the subscriber paused the subscription in the last 365 days
+/// - term_end_at (optional): timestamp when the term of a fixed-term subscription ends. No charges are made past the term
+/// - usage: [`UsageRecord`] of every interval of a metered product that has not been billed yet
+/// - payer (optional): address of the subwallet that pays for the subscription, if it is not the owner (i.e. a gift or sponsored subscription)
+/// - seats (optional): number of seats of a team subscription, each billed the unit amount. Individual subscriptions have no seats
+/// - seat_proration: prorated amount owed for the seats added during the current interval, billed on the next charge
+/// - seat_credit: prorated amount credited for the seats removed during the current interval, deducted from the next charges
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct SubscriptionInfo {
     pub created_at: Timestamp,
@@ -51,29 +275,470 @@ pub struct SubscriptionInfo {
     pub discount_per_interval: Option<Discount>,
     pub is_cancelled: bool,
     pub owner: Addr,
+    // subscriptions created before plans were introduced are on the default plan
+    #[serde(default)]
+    pub plan_id: u64,
+    #[serde(default)]
+    pub trial_end_at: Option<Timestamp>,
+    #[serde(default)]
+    pub total_paid: Uint256,
+    #[serde(default)]
+    pub retry_count: u32,
+    #[serde(default)]
+    pub next_retry_at: Option<Timestamp>,
+    #[serde(default)]
+    pub resume_at: Option<Timestamp>,
+    #[serde(default)]
+    pub pause_history: Vec<Timestamp>,
+    #[serde(default)]
+    pub term_end_at: Option<Timestamp>,
+    #[serde(default)]
+    pub usage: Vec<UsageRecord>,
+    #[serde(default)]
+    pub payer: Option<Addr>,
+    #[serde(default)]
+    pub lifetime_paid: Uint256,
+    #[serde(default)]
+    pub seats: Option<u64>,
+    #[serde(default)]
+    pub seat_proration: Uint256,
+    #[serde(default)]
+    pub seat_credit: Uint256,
+}
+
+impl SubscriptionInfo {
+    /// Returns the address of the subwallet that is charged for the subscription
+    pub fn payer_address(&self) -> &Addr {
+        self.payer.as_ref().unwrap_or(&self.owner)
+    }
+
+    /// Returns the number of seats that the subscription is billed for. Individual subscriptions are billed for one seat
+    pub fn seat_count(&self) -> u64 {
+        self.seats.unwrap_or(1)
+    }
+
+    /// Returns true if the last charge of the subscription has failed and is waiting to be retried
+    pub fn is_past_due(&self) -> bool {
+        self.next_retry_at.is_some()
+    }
+
+    /// Returns true if the subscriber has paused the subscription and it has not resumed yet at `block_time`
+    pub fn is_paused(&self, block_time: Timestamp) -> bool {
+        matches!(self.resume_at, Some(resume_at) if block_time < resume_at)
+    }
+
+    /// Returns true if the subscription is fixed-term and every billing cycle of the term has ended at `block_time`
+    pub fn is_completed(&self, block_time: Timestamp) -> bool {
+        match self.term_end_at {
+            Some(term_end_at) => {
+                block_time >= self.interval_end_at && self.interval_end_at >= term_end_at
+            }
+            None => false,
+        }
+    }
+}
+
+/// # Description
+/// Stores the usage reported for a subscriber of a metered product during an interval
+/// - interval_end_at: end of the interval that the usage was reported in
+/// - quantity: units of usage reported in the interval
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UsageRecord {
+    pub interval_end_at: Timestamp,
+    pub quantity: u64,
 }
 
-// Saves the Subscriptions metadata for all subscribers
-pub const SUBSCRIPTIONS: Map<&Addr, SubscriptionInfo> = Map::new("subscriptions");
+/// # Description
+/// Stores a charge that has been sent to the subwallet of the subscriber in the current transaction
+/// - subscriber: address of the subscriber that is charged
+/// - subscription: the [`SubscriptionInfo`] before the charge, which is restored if the payment fails
+/// - charge_id: identifier of the [`Payment`] recorded for the charge, which is removed if the payment fails
+/// - is_failed: whether the payment has failed, in which case the charge is not counted in the work receipt
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingCharge {
+    pub subscriber: Addr,
+    pub subscription: SubscriptionInfo,
+    pub charge_id: u64,
+    pub is_failed: bool,
+}
+
+/// # Description
+/// Stores a payment made by a subscriber to the receiver
+/// - charge_id: identifier of the payment, unique across all subscribers of the product
+/// - payer: address of the subwallet that made the payment, which receives the refunds
+/// - amount: amount paid by the subscriber, including the protocol fee
+/// - paid_at: timestamp when the payment was made
+/// - refunded_amount: cumulative amount refunded to the subscriber by the receiver. Cannot exceed `amount`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Payment {
+    pub charge_id: u64,
+    pub payer: Addr,
+    pub amount: Uint256,
+    pub paid_at: Timestamp,
+    pub refunded_amount: Uint256,
+}
+
+/// # Description
+/// Stores the cw721 membership token of a subscription. The token ID is the address of the subscriber
+/// - owner: address that holds the token. Differs from the subscriber once a transferable token has been transferred
+/// - subscriber: address of the subscriber that the token was minted for
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MembershipToken {
+    pub owner: Addr,
+    pub subscriber: Addr,
+}
+
+/// # Description
+/// Stores a coupon code defined by the merchant that subscribers can redeem when subscribing
+/// - code: the coupon code
+/// - discount: [`Discount`] given to the subscriber that redeems the coupon
+/// - max_redemptions: maximum number of times the coupon can be redeemed
+/// - redemptions: number of times the coupon has been redeemed
+/// - redeem_by: deadline after which the coupon can no longer be redeemed
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Coupon {
+    pub code: String,
+    pub discount: Discount,
+    pub max_redemptions: u64,
+    pub redemptions: u64,
+    pub redeem_by: Timestamp,
+}
+
+// Indexed by billing status & due time
+pub struct SubscriptionIndexes<'a> {
+    pub due_time: MultiIndex<'a, (Vec<u8>, U64Key, Vec<u8>), SubscriptionInfo>, // Allows iteration over the subscriptions of a billing status, sorted by due time
+}
+
+impl<'a> IndexList<SubscriptionInfo> for SubscriptionIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<SubscriptionInfo>> + '_> {
+        let v: Vec<&dyn Index<SubscriptionInfo>> = vec![&self.due_time];
+        Box::new(v.into_iter())
+    }
+}
+
+// Saves the Subscriptions metadata for all subscribers, keyed by the subscriber address
+pub fn subscriptions<'a>() -> IndexedMap<'a, &'a Addr, SubscriptionInfo, SubscriptionIndexes<'a>> {
+    let indexes = SubscriptionIndexes {
+        due_time: MultiIndex::new(
+            |d: &SubscriptionInfo, k: Vec<u8>| {
+                (
+                    subscription_status_key(d),
+                    subscription_due_time(d).seconds().into(),
+                    k,
+                )
+            },
+            "subscriptions",
+            "subscriptions__duetime",
+        ),
+    };
+    IndexedMap::new("subscriptions", indexes)
+}
+
+// Indexed by owner
+pub struct MembershipIndexes<'a> {
+    pub owner: MultiIndex<'a, (Vec<u8>, Vec<u8>), MembershipToken>, // Allows iteration over the tokens of an owner
+}
+
+impl<'a> IndexList<MembershipToken> for MembershipIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<MembershipToken>> + '_> {
+        let v: Vec<&dyn Index<MembershipToken>> = vec![&self.owner];
+        Box::new(v.into_iter())
+    }
+}
+
+// Saves the membership tokens of the subscriptions, keyed by the subscriber address (i.e. the token ID)
+pub fn memberships<'a>() -> IndexedMap<'a, &'a Addr, MembershipToken, MembershipIndexes<'a>> {
+    let indexes = MembershipIndexes {
+        owner: MultiIndex::new(
+            |d: &MembershipToken, k: Vec<u8>| (d.owner.as_bytes().to_vec(), k),
+            "memberships",
+            "memberships__owner",
+        ),
+    };
+    IndexedMap::new("memberships", indexes)
+}
+
+// Saves the number of membership tokens that have been minted and not burned
+pub const MEMBERSHIP_COUNT: Item<u64> = Item::new("membership_count");
+
+/// Mints the membership token of the subscriber. Returns false if the subscriber already has a token, which is left untouched
+/// since it may have been transferred
+pub fn mint_membership(storage: &mut dyn Storage, subscriber: &Addr) -> StdResult<bool> {
+    if memberships().may_load(storage, subscriber)?.is_some() {
+        return Ok(false);
+    }
+
+    let count = MEMBERSHIP_COUNT.may_load(storage)?.unwrap_or_default();
+    MEMBERSHIP_COUNT.save(storage, &(count + 1))?;
+    memberships().save(
+        storage,
+        subscriber,
+        &MembershipToken {
+            owner: subscriber.clone(),
+            subscriber: subscriber.clone(),
+        },
+    )?;
+
+    Ok(true)
+}
+
+/// Burns the membership token of the subscriber. Returns false if the subscriber has no token
+pub fn burn_membership(storage: &mut dyn Storage, subscriber: &Addr) -> StdResult<bool> {
+    if memberships().may_load(storage, subscriber)?.is_none() {
+        return Ok(false);
+    }
+
+    memberships().remove(storage, subscriber)?;
+    let count = MEMBERSHIP_COUNT.load(storage)?;
+    MEMBERSHIP_COUNT.save(storage, &(count - 1))?;
+
+    Ok(true)
+}
+
+// billing statuses of the `due_time` index
+pub const ACTIVE_STATUS_KEY: &[u8] = b"active";
+pub const PAST_DUE_STATUS_KEY: &[u8] = b"past_due";
+pub const CANCELLED_STATUS_KEY: &[u8] = b"cancelled";
+pub const COMPLETED_STATUS_KEY: &[u8] = b"completed";
+// the order that the subscriptions filtered by status are paged through
+pub const STATUS_KEYS: [&[u8]; 4] = [
+    ACTIVE_STATUS_KEY,
+    PAST_DUE_STATUS_KEY,
+    CANCELLED_STATUS_KEY,
+    COMPLETED_STATUS_KEY,
+];
+
+/// Returns the billing status of the subscription in the `due_time` index. Cancelled (including lapsed) and completed subscriptions
+/// are never charged again, so only `active` and `past_due` subscriptions are looked up when listing the due subscriptions
+pub fn subscription_status_key(subscription: &SubscriptionInfo) -> Vec<u8> {
+    if subscription.is_cancelled {
+        return CANCELLED_STATUS_KEY.to_vec();
+    }
+
+    // the last billing cycle of a fixed-term subscription is never charged
+    if matches!(subscription.term_end_at, Some(term_end_at) if subscription.interval_end_at >= term_end_at)
+    {
+        return COMPLETED_STATUS_KEY.to_vec();
+    }
+
+    match subscription.is_past_due() {
+        true => PAST_DUE_STATUS_KEY.to_vec(),
+        false => ACTIVE_STATUS_KEY.to_vec(),
+    }
+}
+
+/// Returns the time the subscription is due in the `due_time` index: the next retry of past due subscriptions,
+/// or the end of the current interval
+pub fn subscription_due_time(subscription: &SubscriptionInfo) -> Timestamp {
+    subscription
+        .next_retry_at
+        .unwrap_or(subscription.interval_end_at)
+}
+
+// Saves the timestamp when the free trial ends for every subscriber that has used a trial.
+// Kept after the subscription is removed, so that a subscriber only gets one trial
+pub const TRIALS: Map<&Addr, Timestamp> = Map::new("trials");
+
+// Saves the last subscription of every subscriber that has been removed, so that it is reported with the `Removed` status.
+// Deleted when the subscriber subscribes again
+pub const REMOVED_SUBSCRIPTIONS: Map<&Addr, SubscriptionInfo> = Map::new("removed_subscriptions");
+
+// Saves the additional plans offered by the product. The default plan lives in the [`Config`]
+pub const PLANS: Map<U64Key, Plan> = Map::new("plans");
+
+// Saves the coupon codes defined by the merchant
+pub const COUPONS: Map<&str, Coupon> = Map::new("coupons");
+
+// Saves the charges sent in the current transaction. The index of a charge is the ID of the reply sent if its payment fails.
+// Cleared before every charge, as replies are processed in the same transaction
+pub const PENDING_CHARGES: Item<Vec<PendingCharge>> = Item::new("pending_charges");
+
+// Saves the payment history of every subscriber, keyed by the subscriber address and the charge ID
+pub const PAYMENTS: Map<(&Addr, U64Key), Payment> = Map::new("payments");
+
+// Saves the number of payments recorded by the product. The charge ID of a payment is the count after it is recorded
+pub const PAYMENT_COUNT: Item<u64> = Item::new("payment_count");
+
+/// Records a payment of `amount` by `payer` in the payment history of the subscriber. Returns the charge ID of the payment
+pub fn record_payment(
+    storage: &mut dyn Storage,
+    subscriber: &Addr,
+    payer: &Addr,
+    amount: Uint256,
+    paid_at: Timestamp,
+) -> StdResult<u64> {
+    let charge_id = PAYMENT_COUNT.may_load(storage)?.unwrap_or_default() + 1;
+    PAYMENT_COUNT.save(storage, &charge_id)?;
+
+    PAYMENTS.save(
+        storage,
+        (subscriber, U64Key::from(charge_id)),
+        &Payment {
+            charge_id,
+            payer: payer.clone(),
+            amount,
+            paid_at,
+            refunded_amount: Uint256::zero(),
+        },
+    )?;
+
+    Ok(charge_id)
+}
+
+// Saves the members of every team subscription, keyed by the address of the team owner
+pub const TEAMS: Map<&Addr, Vec<Addr>> = Map::new("teams");
+
+// Saves the team owner of every member that has been assigned a seat
+pub const TEAM_MEMBERS: Map<&Addr, Addr> = Map::new("team_members");
+
+/// Removes every member from the team of `owner`
+pub fn remove_team(storage: &mut dyn Storage, owner: &Addr) -> StdResult<()> {
+    for member in TEAMS.may_load(storage, owner)?.unwrap_or_default() {
+        TEAM_MEMBERS.remove(storage, &member);
+    }
+    TEAMS.remove(storage, owner);
+    Ok(())
+}
+
+// Saves the prepaid balance of every subscriber, held by the product in its native billing asset until it is charged or refunded
+pub const PREPAID_BALANCES: Map<&Addr, Uint256> = Map::new("prepaid_balances");
+
+/// # Description
+/// Stores the running counters of the product
+/// - total_subscribers: number of stored subscriptions, active or cancelled
+/// - active_subscribers: number of subscriptions that are not cancelled. The counters are not updated as time passes, so
+///   subscriptions in a trial, paused, in grace or lapsed are counted until they are cancelled or removed
+/// - cancelled_subscribers: number of cancelled subscriptions that have not been removed
+/// - gross_charged: cumulative amount charged to subscribers, including the protocol fee and before refunds
+/// - protocol_fees: cumulative protocol fees paid on the charges
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct Stats {
+    pub total_subscribers: u64,
+    pub active_subscribers: u64,
+    pub cancelled_subscribers: u64,
+    pub gross_charged: Uint256,
+    pub protocol_fees: Uint256,
+}
+
+// Saves the running counters of the product
+pub const STATS: Item<Stats> = Item::new("stats");
+
+// Saves the number of seats of the subscriptions that are not cancelled on every plan, from which the monthly recurring revenue is computed.
+// Individual subscriptions count as one seat
+pub const PLAN_SUBSCRIBERS: Map<U64Key, u64> = Map::new("plan_subscribers");
+
+/// Updates the subscriber counts of the [`Stats`] when the stored subscription of a subscriber changes from `previous` to `current`.
+/// `None` if the subscriber has no stored subscription
+pub fn update_subscriber_stats(
+    storage: &mut dyn Storage,
+    previous: Option<&SubscriptionInfo>,
+    current: Option<&SubscriptionInfo>,
+) -> StdResult<()> {
+    let mut stats = STATS.may_load(storage)?.unwrap_or_default();
+
+    if let Some(previous) = previous {
+        stats.total_subscribers = stats.total_subscribers.saturating_sub(1);
+        match previous.is_cancelled {
+            true => stats.cancelled_subscribers = stats.cancelled_subscribers.saturating_sub(1),
+            false => {
+                stats.active_subscribers = stats.active_subscribers.saturating_sub(1);
+                PLAN_SUBSCRIBERS.update(
+                    storage,
+                    U64Key::from(previous.plan_id),
+                    |count| -> StdResult<u64> {
+                        Ok(count
+                            .unwrap_or_default()
+                            .saturating_sub(previous.seat_count()))
+                    },
+                )?;
+            }
+        }
+    }
+
+    if let Some(current) = current {
+        stats.total_subscribers += 1;
+        match current.is_cancelled {
+            true => stats.cancelled_subscribers += 1,
+            false => {
+                stats.active_subscribers += 1;
+                PLAN_SUBSCRIBERS.update(
+                    storage,
+                    U64Key::from(current.plan_id),
+                    |count| -> StdResult<u64> {
+                        Ok(count.unwrap_or_default() + current.seat_count())
+                    },
+                )?;
+            }
+        }
+    }
+
+    STATS.save(storage, &stats)
+}
+
+/// Adds a payment of `amount`, of which `protocol_fee` is paid to the protocol, to the [`Stats`]
+pub fn record_revenue(
+    storage: &mut dyn Storage,
+    amount: Uint256,
+    protocol_fee: Uint256,
+) -> StdResult<()> {
+    let mut stats = STATS.may_load(storage)?.unwrap_or_default();
+    stats.gross_charged = stats.gross_charged + amount;
+    stats.protocol_fees = stats.protocol_fees + protocol_fee;
+    STATS.save(storage, &stats)
+}
+
+/// Rebuilds the [`Stats`] of a product that was instantiated before the counters were introduced.
+/// The subscriber counts are rebuilt from the stored subscriptions and `gross_charged` from the payment history.
+/// Protocol fees paid before are not known, and the lifetime paid of every subscriber starts at its `total_paid`
+pub fn rebuild_stats(storage: &mut dyn Storage) -> StdResult<()> {
+    STATS.save(storage, &Stats::default())?;
+
+    let existing_subscriptions = subscriptions()
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (subscriber, mut subscription) in existing_subscriptions {
+        update_subscriber_stats(storage, None, Some(&subscription))?;
+
+        subscription.lifetime_paid = subscription.total_paid;
+        let subscriber = Addr::unchecked(String::from_utf8(subscriber).map_err(StdError::from)?);
+        subscriptions().save(storage, &subscriber, &subscription)?;
+    }
+
+    let gross_charged = PAYMENTS
+        .range(storage, None, None, Order::Ascending)
+        .try_fold(Uint256::zero(), |total, payment| -> StdResult<Uint256> {
+            Ok(total + payment?.1.amount)
+        })?;
+    record_revenue(storage, gross_charged, Uint256::zero())
+}
+
+// Saves the config of the product
+pub const CONFIG: Item<Config> = Item::new("config");
 
 /// Saves the config of type [`Config`]
 pub fn store_config(storage: &mut dyn Storage, data: &Config) -> StdResult<()> {
-    Singleton::new(storage, CONFIG_KEY).save(data)
+    CONFIG.save(storage, data)
 }
 
 /// Reads the [`Config`] file of the user
 pub fn read_config(storage: &dyn Storage) -> StdResult<Config> {
-    ReadonlySingleton::new(storage, CONFIG_KEY).load()
+    CONFIG.load(storage)
 }
 
-/// Reads the information of a user and returns an object of [`Option<SubscriptionInfo>`] given a `Subscriber` of type [`Addr`].
-/// If the user cannot be found, returns `None`
-pub fn read_subscription_info(
-    storage: &dyn Storage,
-    subscriber: &Addr,
-) -> Option<SubscriptionInfo> {
-    match bucket_read(storage, PREFIX_SUBSCRIPTIONS).load(subscriber.as_bytes()) {
-        Ok(v) => v,
-        _ => None,
+/// Moves the [`Config`] from the legacy `cosmwasm_storage` singleton to [`CONFIG`].
+/// Subscriptions were stored in a `cosmwasm_storage` bucket under the same keys as [`subscriptions`], so they are not moved
+pub fn migrate_legacy_config(storage: &mut dyn Storage) -> StdResult<()> {
+    let config: Config = singleton_read(storage, LEGACY_CONFIG_KEY).load()?;
+    singleton::<Config>(storage, LEGACY_CONFIG_KEY).remove();
+
+    CONFIG.save(storage, &config)
+}
+
+/// Reads the [`Plan`] with the given `plan_id`. Returns `None` if the plan does not exist
+pub fn read_plan(storage: &dyn Storage, plan_id: u64) -> StdResult<Option<Plan>> {
+    if plan_id == DEFAULT_PLAN_ID {
+        return Ok(Some(read_config(storage)?.default_plan()));
     }
+
+    PLANS.may_load(storage, U64Key::from(plan_id))
 }
@@ -1,3 +1,152 @@
+mod test_billing_assets;
+mod test_calendar;
+mod test_caps;
+mod test_discounts;
+mod test_dunning;
 mod test_fees;
+mod test_gifts;
+mod test_hooks;
+mod test_memberships;
+mod test_metering;
+mod test_migrations;
+mod test_pauses;
+mod test_plans;
+mod test_prepaid;
+mod test_price_changes;
+mod test_refunds;
+mod test_revenue_splits;
+mod test_seats;
+mod test_stats;
+mod test_status;
+mod test_subwallets;
+mod test_terms;
+mod test_trials;
 mod test_worker;
 mod tests;
+
+use crate::contract;
+use crate::error::ContractError;
+use crate::msg::{ExecuteMsg, QueryMsg, SubscriptionInfoResponse, WorkPayload};
+
+use cosmwasm_bignumber::Uint256;
+use cosmwasm_std::testing::{mock_env, mock_info};
+use cosmwasm_std::{
+    from_binary, to_binary, ContractResult, Deps, DepsMut, Env, Reply, Response, Timestamp,
+};
+use suberra_core::msg::ProductInstantiateMsg;
+
+const START_TIMESTAMP: u64 = 1609459200; // 1 January 2021 00:00:00 GMT
+const ONE_DAY_IN_SECONDS: u64 = 60 * 60 * 24;
+const SEVEN_DAYS_IN_SECONDS: u64 = 60 * 60 * 168;
+const THIRTY_DAYS_IN_SECONDS: u64 = 60 * 60 * 720;
+const DEFAULT_GRACE_PERIOD: u64 = 86400; // 24 hours in seconds
+
+// product used by the tests, which override the fields they need with the struct update syntax.
+// It accepts any payer, so that the tests that are not about subwallets do not need to set them up
+fn product_msg() -> ProductInstantiateMsg {
+    ProductInstantiateMsg {
+        receiver_address: "merchant".to_string(),
+        unit_amount: Uint256::from(1000u128),
+        initial_amount: Uint256::from(1000u128),
+        unit_interval_hour: 720u64,
+        additional_grace_period_hour: None,
+        retry_schedule_hour: None,
+        max_pause_duration_hour: None,
+        max_pauses_per_year: None,
+        admins: Vec::new(),
+        mutable: false,
+        uri: "{\"image_url\": \"www.google.com\" }".to_string(),
+        factory_address: "factory".to_string(),
+        trial_period_hour: None,
+        plans: None,
+        max_amount_chargeable: None,
+        usage_tiers: None,
+        membership_nft: None,
+        billing_asset: None,
+        revenue_splits: None,
+        billing_calendar: None,
+        accept_any_payer: Some(true),
+        owner: "creator".to_string(),
+    }
+}
+
+fn env_at(timestamp: u64) -> Env {
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(timestamp);
+    env
+}
+
+fn subscribe(deps: DepsMut, env: Env, subscriber: &str) -> Result<Response, ContractError> {
+    contract::execute(
+        deps,
+        env,
+        mock_info(subscriber, &[]),
+        ExecuteMsg::Subscribe {
+            plan_id: None,
+            coupon: None,
+            billing_cycles: None,
+            end_at: None,
+            beneficiary: None,
+            seats: None,
+        },
+    )
+}
+
+fn charge(deps: DepsMut, env: Env, payer_address: &str) -> Result<Response, ContractError> {
+    contract::execute(
+        deps,
+        env,
+        mock_info("charger", &[]),
+        ExecuteMsg::Charge {
+            payer_address: payer_address.to_string(),
+        },
+    )
+}
+
+fn query_subscription(deps: Deps, env: Env, subscriber: &str) -> Option<SubscriptionInfoResponse> {
+    let res = contract::query(
+        deps,
+        env,
+        QueryMsg::Subscription {
+            subscriber: subscriber.to_string(),
+        },
+    )
+    .unwrap();
+    from_binary(&res).unwrap()
+}
+
+fn query_can_work(deps: Deps, env: Env, payer_address: &str) -> bool {
+    let res = contract::query(
+        deps,
+        env,
+        QueryMsg::CanWork {
+            payload: to_binary(&WorkPayload {
+                payer_address: payer_address.to_string(),
+            })
+            .unwrap(),
+        },
+    )
+    .unwrap();
+    from_binary(&res).unwrap()
+}
+
+fn execute(
+    deps: DepsMut,
+    env: Env,
+    sender: &str,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    contract::execute(deps, env, mock_info(sender, &[]), msg)
+}
+
+// simulates the subwallet failing to pay the charge sent with the reply id
+fn fail_payment(deps: DepsMut, env: Env, id: u64) -> Result<Response, ContractError> {
+    contract::reply(
+        deps,
+        env,
+        Reply {
+            id,
+            result: ContractResult::Err("insufficient funds".to_string()),
+        },
+    )
+}
@@ -0,0 +1,165 @@
+use super::{env_at, product_msg, subscribe, START_TIMESTAMP};
+use crate::contract;
+use crate::error::ContractError;
+use crate::mock_querier::mock_dependencies;
+use crate::msg::{Cw20HookMsg, ExecuteMsg, SubWalletExecuteMsg};
+
+use cosmwasm_bignumber::Uint256;
+use cosmwasm_std::testing::mock_info;
+use cosmwasm_std::{coins, to_binary, Addr, BankMsg, CosmosMsg, DepsMut, Env, Response, WasmMsg};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use suberra_core::asset::{Asset, AssetInfo};
+use suberra_core::msg::ProductInstantiateMsg;
+
+fn token() -> AssetInfo {
+    AssetInfo::Token {
+        contract_addr: Addr::unchecked("mirror_token"),
+    }
+}
+
+fn instantiate(deps: DepsMut, env: Env, billing_asset: Option<AssetInfo>) {
+    contract::instantiate(
+        deps,
+        env,
+        mock_info("creator", &[]),
+        ProductInstantiateMsg {
+            initial_amount: Uint256::from(500u128),
+            billing_asset,
+            ..product_msg()
+        },
+    )
+    .unwrap();
+}
+
+fn cw20_refund(
+    deps: DepsMut,
+    env: Env,
+    token: &str,
+    sender: &str,
+    amount: u128,
+) -> Result<Response, ContractError> {
+    contract::execute(
+        deps,
+        env,
+        mock_info(token, &[]),
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: sender.to_string(),
+            amount: amount.into(),
+            msg: to_binary(&Cw20HookMsg::Refund {
+                subscriber: "subscriber".to_string(),
+                charge_id: 1,
+            })
+            .unwrap(),
+        }),
+    )
+}
+
+#[test]
+fn charges_in_billing_asset() {
+    let mut deps = mock_dependencies(&[]);
+    let env = env_at(START_TIMESTAMP);
+    instantiate(deps.as_mut(), env.clone(), Some(token()));
+
+    let res = subscribe(deps.as_mut(), env, "subscriber").unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "subscriber".to_string(),
+            msg: to_binary(&SubWalletExecuteMsg::TransferAsset {
+                asset: Asset {
+                    info: token(),
+                    amount: 500u128.into(),
+                },
+                recipient: "merchant".to_string(),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+}
+
+#[test]
+fn native_refunds_in_billing_asset() {
+    let mut deps = mock_dependencies(&[]);
+    let env = env_at(START_TIMESTAMP);
+    let billing_asset = AssetInfo::NativeToken {
+        denom: "uluna".to_string(),
+    };
+    instantiate(deps.as_mut(), env.clone(), Some(billing_asset));
+    subscribe(deps.as_mut(), env.clone(), "subscriber").unwrap();
+
+    let refund = || ExecuteMsg::Refund {
+        subscriber: "subscriber".to_string(),
+        amount: Uint256::from(100u128),
+        charge_id: 1,
+    };
+
+    // refunds must be sent in the billing asset
+    let err = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("merchant", &coins(100, "uusd")),
+        refund(),
+    )
+    .unwrap_err();
+    assert!(matches!(err, ContractError::InvalidRefundFunds {}));
+
+    let res = contract::execute(
+        deps.as_mut(),
+        env,
+        mock_info("merchant", &coins(100, "uluna")),
+        refund(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: "subscriber".to_string(),
+            amount: coins(100, "uluna"),
+        })
+    );
+}
+
+#[test]
+fn cw20_refunds_through_receive_hook() {
+    let mut deps = mock_dependencies(&[]);
+    let env = env_at(START_TIMESTAMP);
+    instantiate(deps.as_mut(), env.clone(), Some(token()));
+    subscribe(deps.as_mut(), env.clone(), "subscriber").unwrap();
+
+    // native refunds are rejected for products billed in a cw20 token
+    let err = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("merchant", &coins(100, "uusd")),
+        ExecuteMsg::Refund {
+            subscriber: "subscriber".to_string(),
+            amount: Uint256::from(100u128),
+            charge_id: 1,
+        },
+    )
+    .unwrap_err();
+    assert!(matches!(err, ContractError::InvalidRefundFunds {}));
+
+    // only the billing asset can be received, and only from the receiver
+    let err = cw20_refund(deps.as_mut(), env.clone(), "other_token", "merchant", 100).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidRefundFunds {}));
+    let err = cw20_refund(deps.as_mut(), env.clone(), "mirror_token", "anyone", 100).unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized {}));
+    let err = cw20_refund(deps.as_mut(), env.clone(), "mirror_token", "merchant", 501).unwrap_err();
+    assert!(matches!(err, ContractError::RefundExceedsPayment {}));
+
+    let res = cw20_refund(deps.as_mut(), env, "mirror_token", "merchant", 100).unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "mirror_token".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "subscriber".to_string(),
+                amount: 100u128.into(),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+}
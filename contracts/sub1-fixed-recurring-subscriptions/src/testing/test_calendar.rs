@@ -0,0 +1,224 @@
+use super::{charge, env_at, product_msg, query_subscription, ONE_DAY_IN_SECONDS};
+use crate::contract;
+use crate::error::ContractError;
+use crate::mock_querier::mock_dependencies;
+use crate::msg::{ExecuteMsg, QueryMsg, StatsResponse};
+
+use admin_core::error::ContractError as AdminCoreContractError;
+use cosmwasm_bignumber::Uint256;
+use cosmwasm_std::testing::mock_info;
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::{attr, from_binary, DepsMut, Env, Response};
+use suberra_core::msg::ProductInstantiateMsg;
+use suberra_core::subscriptions::{BillingCalendar, CalendarPeriod, PlanInfo};
+
+const JAN_16_2021: u64 = 1610755200;
+const JAN_31_2021: u64 = 1612051200;
+const FEB_28_2021: u64 = JAN_31_2021 + 28 * ONE_DAY_IN_SECONDS;
+const MAR_31_2021: u64 = FEB_28_2021 + 31 * ONE_DAY_IN_SECONDS;
+const JAN_1_2022: u64 = 1640995200;
+const JAN_1_2023: u64 = 1672531200;
+
+fn calendar_product_msg(billing_calendar: Option<BillingCalendar>) -> ProductInstantiateMsg {
+    ProductInstantiateMsg {
+        unit_amount: Uint256::from(3100u128),
+        initial_amount: Uint256::from(3100u128),
+        plans: Some(vec![PlanInfo {
+            plan_id: 1,
+            unit_amount: Uint256::from(12000u128),
+            initial_amount: Uint256::from(12000u128),
+            unit_interval_hour: 8760u64,
+            trial_period_hour: None,
+            billing_calendar: Some(BillingCalendar {
+                period: CalendarPeriod::Yearly,
+                day_of_month: 1,
+                month: Some(1),
+                prorate_first_period: false,
+            }),
+        }]),
+        billing_calendar,
+        ..product_msg()
+    }
+}
+
+fn monthly_calendar() -> BillingCalendar {
+    BillingCalendar {
+        period: CalendarPeriod::Monthly,
+        day_of_month: 31,
+        month: None,
+        prorate_first_period: true,
+    }
+}
+
+fn subscribe(
+    deps: DepsMut,
+    env: Env,
+    plan_id: Option<u64>,
+    billing_cycles: Option<u64>,
+) -> Result<Response, ContractError> {
+    contract::execute(
+        deps,
+        env,
+        mock_info("subscriber", &[]),
+        ExecuteMsg::Subscribe {
+            plan_id,
+            coupon: None,
+            billing_cycles,
+            end_at: None,
+            beneficiary: None,
+            seats: None,
+        },
+    )
+}
+
+#[test]
+fn instantiate_with_invalid_calendar() {
+    let mut deps = mock_dependencies(&[]);
+
+    // there is no 32nd day of the month
+    let res = contract::instantiate(
+        deps.as_mut(),
+        env_at(JAN_16_2021),
+        mock_info("creator", &[]),
+        calendar_product_msg(Some(BillingCalendar {
+            day_of_month: 32,
+            ..monthly_calendar()
+        })),
+    );
+    match res {
+        Err(AdminCoreContractError::InvalidParams {}) => {}
+        _ => panic!("Must return invalid params error"),
+    }
+
+    // there is no 13th month
+    let res = contract::instantiate(
+        deps.as_mut(),
+        env_at(JAN_16_2021),
+        mock_info("creator", &[]),
+        calendar_product_msg(Some(BillingCalendar {
+            period: CalendarPeriod::Quarterly,
+            month: Some(13),
+            ..monthly_calendar()
+        })),
+    );
+    match res {
+        Err(AdminCoreContractError::InvalidParams {}) => {}
+        _ => panic!("Must return invalid params error"),
+    }
+}
+
+#[test]
+fn monthly_calendar_is_prorated_and_clamped_to_short_months() {
+    let mut deps = mock_dependencies(&[]);
+
+    let _res = contract::instantiate(
+        deps.as_mut(),
+        env_at(JAN_16_2021),
+        mock_info("creator", &[]),
+        calendar_product_msg(Some(monthly_calendar())),
+    )
+    .unwrap();
+
+    // the first interval covers 15 of the 31 days of January, so only part of the initial amount is charged
+    let res = subscribe(deps.as_mut(), env_at(JAN_16_2021), None, None).unwrap();
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(res.attributes[0], attr("initial_amount", "1500"));
+
+    let subscription =
+        query_subscription(deps.as_ref(), env_at(JAN_16_2021), "subscriber").unwrap();
+    assert_eq!(subscription.interval_end_at, JAN_31_2021);
+
+    // no charge can be made before the end of the period
+    match charge(deps.as_mut(), env_at(JAN_31_2021 - 1), "subscriber") {
+        Err(ContractError::NoCharge {}) => {}
+        _ => panic!("Must return no charge error"),
+    }
+
+    // the period after January ends on the last day of February
+    let res = charge(deps.as_mut(), env_at(JAN_31_2021), "subscriber").unwrap();
+    assert_eq!(res.attributes[3], attr("amount", "3100"));
+    assert_eq!(res.attributes[4], attr("periods", "1"));
+
+    let subscription =
+        query_subscription(deps.as_ref(), env_at(JAN_31_2021), "subscriber").unwrap();
+    assert_eq!(subscription.interval_end_at, FEB_28_2021);
+
+    // and is followed by a period that ends on the 31st again
+    let res = charge(deps.as_mut(), env_at(FEB_28_2021), "subscriber").unwrap();
+    assert_eq!(res.attributes[3], attr("amount", "3100"));
+
+    let subscription =
+        query_subscription(deps.as_ref(), env_at(FEB_28_2021), "subscriber").unwrap();
+    assert_eq!(subscription.interval_end_at, MAR_31_2021);
+}
+
+#[test]
+fn prorated_initial_amount_must_cover_min_protocol_fee() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_fee(100, Uint256::from(500u128), Uint256::zero(), 1);
+
+    let _res = contract::instantiate(
+        deps.as_mut(),
+        env_at(JAN_16_2021),
+        mock_info("creator", &[]),
+        calendar_product_msg(Some(monthly_calendar())),
+    )
+    .unwrap();
+
+    // the first interval covers 1 of the 31 days of January, so the prorated initial amount is below the minimum protocol fee
+    match subscribe(
+        deps.as_mut(),
+        env_at(JAN_31_2021 - ONE_DAY_IN_SECONDS),
+        None,
+        None,
+    ) {
+        Err(ContractError::InvalidFee {}) => {}
+        _ => panic!("Must return invalid fee error"),
+    }
+
+    let res = subscribe(deps.as_mut(), env_at(JAN_16_2021), None, None).unwrap();
+    assert_eq!(res.attributes[0], attr("initial_amount", "1500"));
+}
+
+#[test]
+fn yearly_calendar_counts_billing_cycles_in_periods() {
+    let mut deps = mock_dependencies(&[]);
+
+    let _res = contract::instantiate(
+        deps.as_mut(),
+        env_at(JAN_16_2021),
+        mock_info("creator", &[]),
+        calendar_product_msg(None),
+    )
+    .unwrap();
+
+    // the first period is partial and is not prorated, so the full initial amount is charged
+    let res = subscribe(deps.as_mut(), env_at(JAN_16_2021), Some(1), Some(2)).unwrap();
+    assert!(res.attributes.contains(&attr("initial_amount", "12000")));
+    assert!(res
+        .attributes
+        .contains(&attr("term_end_at", JAN_1_2023.to_string())));
+
+    let subscription =
+        query_subscription(deps.as_ref(), env_at(JAN_16_2021), "subscriber").unwrap();
+    assert_eq!(subscription.interval_end_at, JAN_1_2022);
+
+    // yearly plans contribute a twelfth of their price to the monthly recurring revenue
+    let res = contract::query(deps.as_ref(), env_at(JAN_16_2021), QueryMsg::Stats {}).unwrap();
+    let stats: StatsResponse = from_binary(&res).unwrap();
+    assert_eq!(stats.monthly_recurring_revenue, Uint256::from(1000u128));
+
+    // the second billing cycle is the last one of the term
+    let res = charge(deps.as_mut(), env_at(JAN_1_2022), "subscriber").unwrap();
+    assert_eq!(res.attributes[3], attr("amount", "12000"));
+
+    let subscription = query_subscription(deps.as_ref(), env_at(JAN_1_2022), "subscriber").unwrap();
+    assert_eq!(subscription.interval_end_at, JAN_1_2023);
+    assert_eq!(subscription.term_end_at, Some(JAN_1_2023));
+
+    match charge(deps.as_mut(), env_at(JAN_1_2023), "subscriber") {
+        Err(ContractError::SubscriptionCompleted {}) => {}
+        _ => panic!("Must return subscription completed error"),
+    }
+}
@@ -0,0 +1,165 @@
+use super::{product_msg, query_subscription, THIRTY_DAYS_IN_SECONDS};
+use crate::contract;
+use crate::error::ContractError;
+use crate::mock_querier::mock_dependencies;
+use crate::msg::{ExecuteMsg, QueryMsg, WorkPayload};
+
+use cosmwasm_bignumber::Uint256;
+use cosmwasm_std::testing::{mock_env, mock_info};
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::{attr, from_binary, to_binary, Timestamp};
+use suberra_core::msg::ProductInstantiateMsg;
+
+#[test]
+fn instantiate_with_zero_cap() {
+    let mut deps = mock_dependencies(&[]);
+
+    let res = contract::instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("creator", &[]),
+        ProductInstantiateMsg {
+            max_amount_chargeable: Some(Uint256::zero()),
+            ..product_msg()
+        },
+    );
+    assert!(res.is_err());
+}
+
+#[test]
+fn charge_up_to_max_amount_chargeable() {
+    let mut deps = mock_dependencies(&[]);
+
+    let mut env = mock_env();
+    let start_timestamp = 1609459200;
+    env.block.time = Timestamp::from_seconds(start_timestamp); // set to 1 January 2021 00:00:00 GMT
+
+    let _res = contract::instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        ProductInstantiateMsg {
+            max_amount_chargeable: Some(Uint256::from(2500u128)),
+            ..product_msg()
+        },
+    )
+    .unwrap();
+
+    // initial amount counts towards the cap
+    let _res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("subscriber", &[]),
+        ExecuteMsg::Subscribe {
+            plan_id: None,
+            coupon: None,
+            billing_cycles: None,
+            end_at: None,
+            beneficiary: None,
+            seats: None,
+        },
+    )
+    .unwrap();
+
+    let subscription = query_subscription(deps.as_ref(), env.clone(), "subscriber").unwrap();
+    assert_eq!(subscription.total_paid, Uint256::from(1000u128));
+    assert_eq!(
+        subscription.max_amount_chargeable,
+        Some(Uint256::from(2500u128))
+    );
+
+    let charge = ExecuteMsg::Charge {
+        payer_address: "subscriber".to_string(),
+    };
+
+    env.block.time = Timestamp::from_seconds(start_timestamp + THIRTY_DAYS_IN_SECONDS);
+    let res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("charger", &[]),
+        charge.clone(),
+    )
+    .unwrap();
+    assert_eq!(res.attributes[3], attr("amount", "1000"));
+
+    // only the remainder of the cap is charged for the next interval
+    env.block.time = Timestamp::from_seconds(start_timestamp + 2 * THIRTY_DAYS_IN_SECONDS);
+    let subscription = query_subscription(deps.as_ref(), env.clone(), "subscriber").unwrap();
+    assert_eq!(subscription.amount_chargeable, Some(Uint256::from(500u128)));
+
+    let res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("charger", &[]),
+        charge.clone(),
+    )
+    .unwrap();
+    assert_eq!(res.attributes[3], attr("amount", "500"));
+    assert_eq!(res.attributes[4], attr("periods", "1"));
+
+    let subscription = query_subscription(deps.as_ref(), env.clone(), "subscriber").unwrap();
+    assert_eq!(subscription.total_paid, Uint256::from(2500u128));
+    assert_eq!(
+        subscription.interval_end_at,
+        start_timestamp + 3 * THIRTY_DAYS_IN_SECONDS
+    );
+
+    // no further charges can be made once the cap is reached
+    env.block.time = Timestamp::from_seconds(start_timestamp + 3 * THIRTY_DAYS_IN_SECONDS);
+    let subscription = query_subscription(deps.as_ref(), env.clone(), "subscriber").unwrap();
+    assert_eq!(subscription.amount_chargeable, Some(Uint256::zero()));
+
+    let res = contract::query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::CanWork {
+            payload: to_binary(&WorkPayload {
+                payer_address: "subscriber".to_string(),
+            })
+            .unwrap(),
+        },
+    )
+    .unwrap();
+    let can_work: bool = from_binary(&res).unwrap();
+    assert!(!can_work);
+
+    let res = contract::execute(deps.as_mut(), env, mock_info("charger", &[]), charge);
+    match res {
+        Err(ContractError::MaxAmountChargeableReached {}) => {}
+        _ => panic!("Must return max amount chargeable reached error"),
+    }
+}
+
+#[test]
+fn initial_amount_above_cap() {
+    let mut deps = mock_dependencies(&[]);
+
+    let _res = contract::instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("creator", &[]),
+        ProductInstantiateMsg {
+            max_amount_chargeable: Some(Uint256::from(999u128)),
+            ..product_msg()
+        },
+    )
+    .unwrap();
+
+    let res = contract::execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("subscriber", &[]),
+        ExecuteMsg::Subscribe {
+            plan_id: None,
+            coupon: None,
+            billing_cycles: None,
+            end_at: None,
+            beneficiary: None,
+            seats: None,
+        },
+    );
+    match res {
+        Err(ContractError::MaxAmountChargeableReached {}) => {}
+        _ => panic!("Must return max amount chargeable reached error"),
+    }
+}
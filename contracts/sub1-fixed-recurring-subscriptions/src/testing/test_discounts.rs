@@ -0,0 +1,405 @@
+use super::{product_msg, query_subscription, THIRTY_DAYS_IN_SECONDS};
+use crate::contract;
+use crate::error::ContractError;
+use crate::mock_querier::mock_dependencies;
+use crate::msg::{CouponResponse, ExecuteMsg, QueryMsg};
+use crate::state::{Plan, SubscriptionInfo};
+
+use cosmwasm_bignumber::Uint256;
+use cosmwasm_std::testing::{mock_env, mock_info};
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::{attr, from_binary, Addr, Timestamp};
+use suberra_core::subscriptions::{Discount, DiscountType};
+
+#[test]
+fn compute_amount_chargeable_with_discounts() {
+    // charge 1000 every 100 seconds
+    let plan = Plan {
+        plan_id: 0,
+        unit_amount: Uint256::from(1000u128),
+        initial_amount: Uint256::zero(),
+        unit_interval: Timestamp::from_seconds(100u64),
+        trial_period: 0,
+        is_archived: false,
+        billing_calendar: None,
+        price_changes: vec![],
+    };
+
+    let mut subscription_info = SubscriptionInfo {
+        owner: Addr::unchecked("subscriber"),
+        created_at: Timestamp::from_seconds(100000),
+        last_charged: Timestamp::from_seconds(100000),
+        interval_end_at: Timestamp::from_seconds(100100),
+        is_cancelled: false,
+        discount_per_interval: Some(Discount {
+            amount: Uint256::from(2000u128),
+            discount_type: DiscountType::Bps,
+            expiry: None,
+            intervals: Some(2),
+        }),
+        plan_id: 0,
+        trial_end_at: None,
+        total_paid: Uint256::zero(),
+        retry_count: 0,
+        next_retry_at: None,
+        resume_at: None,
+        pause_history: vec![],
+        term_end_at: None,
+        usage: vec![],
+        payer: None,
+        lifetime_paid: Uint256::zero(),
+        seats: None,
+        seat_proration: Uint256::zero(),
+        seat_credit: Uint256::zero(),
+    };
+
+    // 20% discount is only applied to the first 2 of the 3 intervals charged
+    let amount_chargeable = contract::compute_amount_chargeable(
+        &plan,
+        &[],
+        &subscription_info,
+        Timestamp::from_seconds(100300),
+    );
+    assert_eq!(amount_chargeable.number_of_intervals, 3);
+    assert_eq!(amount_chargeable.discounted_intervals, 2);
+    assert_eq!(amount_chargeable.amount, Uint256::from(2600u128));
+
+    // discount expires after the second interval starts, so the third interval is charged the full amount
+    subscription_info.discount_per_interval = Some(Discount {
+        amount: Uint256::from(100u128),
+        discount_type: DiscountType::Fixed,
+        expiry: Some(100201),
+        intervals: None,
+    });
+    let amount_chargeable = contract::compute_amount_chargeable(
+        &plan,
+        &[],
+        &subscription_info,
+        Timestamp::from_seconds(100300),
+    );
+    assert_eq!(amount_chargeable.discounted_intervals, 2);
+    assert_eq!(amount_chargeable.amount, Uint256::from(2800u128));
+
+    // discount that expires exactly when an interval starts is not applied to it
+    subscription_info.discount_per_interval = Some(Discount {
+        amount: Uint256::from(100u128),
+        discount_type: DiscountType::Fixed,
+        expiry: Some(100100),
+        intervals: None,
+    });
+    let amount_chargeable = contract::compute_amount_chargeable(
+        &plan,
+        &[],
+        &subscription_info,
+        Timestamp::from_seconds(100300),
+    );
+    assert_eq!(amount_chargeable.discounted_intervals, 0);
+    assert_eq!(amount_chargeable.amount, Uint256::from(3000u128));
+}
+
+#[test]
+fn set_invalid_discounts() {
+    let mut deps = mock_dependencies(&[]);
+
+    let mut env = mock_env();
+    let start_timestamp = 1609459200;
+    env.block.time = Timestamp::from_seconds(start_timestamp); // set to 1 January 2021 00:00:00 GMT
+
+    let info = mock_info("creator", &[]);
+    let _res =
+        contract::instantiate(deps.as_mut(), env.clone(), info.clone(), product_msg()).unwrap();
+    let _res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("subscriber", &[]),
+        ExecuteMsg::Subscribe {
+            plan_id: None,
+            coupon: None,
+            billing_cycles: None,
+            end_at: None,
+            beneficiary: None,
+            seats: None,
+        },
+    )
+    .unwrap();
+
+    let invalid_discounts = vec![
+        // more than 100%
+        Discount {
+            amount: Uint256::from(10001u128),
+            discount_type: DiscountType::Bps,
+            expiry: None,
+            intervals: None,
+        },
+        // already expired
+        Discount {
+            amount: Uint256::from(100u128),
+            discount_type: DiscountType::Fixed,
+            expiry: Some(start_timestamp),
+            intervals: None,
+        },
+        // applies to no intervals
+        Discount {
+            amount: Uint256::from(100u128),
+            discount_type: DiscountType::Fixed,
+            expiry: None,
+            intervals: Some(0),
+        },
+    ];
+
+    for discount in invalid_discounts {
+        let res = contract::execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            ExecuteMsg::SetDiscount {
+                discount: Some(discount),
+                subscriber: "subscriber".to_string(),
+            },
+        );
+        match res {
+            Err(ContractError::InvalidDiscount {}) => {}
+            _ => panic!("Must return invalid discount error"),
+        }
+    }
+}
+
+#[test]
+fn redeem_coupon() {
+    let mut deps = mock_dependencies(&[]);
+
+    let mut env = mock_env();
+    let start_timestamp = 1609459200;
+    env.block.time = Timestamp::from_seconds(start_timestamp); // set to 1 January 2021 00:00:00 GMT
+
+    let info = mock_info("creator", &[]);
+    let _res =
+        contract::instantiate(deps.as_mut(), env.clone(), info.clone(), product_msg()).unwrap();
+
+    // 50% off the first interval, redeemable once until 2 days after creation
+    let discount = Discount {
+        amount: Uint256::from(5000u128),
+        discount_type: DiscountType::Bps,
+        expiry: None,
+        intervals: Some(1),
+    };
+    let msg = ExecuteMsg::CreateCoupon {
+        code: "HALFOFF".to_string(),
+        discount: discount.clone(),
+        max_redemptions: 1,
+        redeem_by: start_timestamp + 172800,
+    };
+
+    // only admin can create coupons
+    let res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("subscriber", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let res = contract::execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("method", "execute_create_coupon"),
+            attr("coupon", "HALFOFF"),
+            attr("module_contract_address", "cosmos2contract"),
+        ]
+    );
+
+    // coupon codes must be unique
+    let res = contract::execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::InvalidParam {}) => {}
+        _ => panic!("Must return invalid param error"),
+    }
+
+    let res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("subscriber", &[]),
+        ExecuteMsg::Subscribe {
+            plan_id: None,
+            coupon: Some("UNKNOWN".to_string()),
+            billing_cycles: None,
+            end_at: None,
+            beneficiary: None,
+            seats: None,
+        },
+    );
+    match res {
+        Err(ContractError::CouponNotFound {}) => {}
+        _ => panic!("Must return coupon not found error"),
+    }
+
+    // the discount does not apply to the initial amount
+    let res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("subscriber", &[]),
+        ExecuteMsg::Subscribe {
+            plan_id: None,
+            coupon: Some("HALFOFF".to_string()),
+            billing_cycles: None,
+            end_at: None,
+            beneficiary: None,
+            seats: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(res.attributes[0], attr("coupon", "HALFOFF"));
+    assert_eq!(res.attributes[1], attr("initial_amount", "1000"));
+
+    let subscription = query_subscription(deps.as_ref(), env.clone(), "subscriber").unwrap();
+    assert_eq!(subscription.discount_per_interval, Some(discount.clone()));
+
+    let res = contract::query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::Coupon {
+            code: "HALFOFF".to_string(),
+        },
+    )
+    .unwrap();
+    let coupon: Option<CouponResponse> = from_binary(&res).unwrap();
+    assert_eq!(
+        coupon,
+        Some(CouponResponse {
+            code: "HALFOFF".to_string(),
+            discount,
+            max_redemptions: 1,
+            redemptions: 1,
+            redeem_by: start_timestamp + 172800,
+        })
+    );
+
+    // coupon has been redeemed the maximum number of times
+    let res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("subscriber2", &[]),
+        ExecuteMsg::Subscribe {
+            plan_id: None,
+            coupon: Some("HALFOFF".to_string()),
+            billing_cycles: None,
+            end_at: None,
+            beneficiary: None,
+            seats: None,
+        },
+    );
+    match res {
+        Err(ContractError::CouponFullyRedeemed {}) => {}
+        _ => panic!("Must return coupon fully redeemed error"),
+    }
+
+    // first interval is discounted, and the discount is removed afterwards
+    env.block.time = Timestamp::from_seconds(start_timestamp + THIRTY_DAYS_IN_SECONDS);
+    let charge = ExecuteMsg::Charge {
+        payer_address: "subscriber".to_string(),
+    };
+    let res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("charger", &[]),
+        charge.clone(),
+    )
+    .unwrap();
+    assert_eq!(res.attributes[3], attr("amount", "500"));
+
+    let subscription = query_subscription(deps.as_ref(), env.clone(), "subscriber").unwrap();
+    assert_eq!(subscription.discount_per_interval, None);
+
+    env.block.time = Timestamp::from_seconds(start_timestamp + 2 * THIRTY_DAYS_IN_SECONDS);
+    let res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("charger", &[]),
+        charge,
+    )
+    .unwrap();
+    assert_eq!(res.attributes[3], attr("amount", "1000"));
+}
+
+#[test]
+fn redeem_expired_coupon() {
+    let mut deps = mock_dependencies(&[]);
+
+    let mut env = mock_env();
+    let start_timestamp = 1609459200;
+    env.block.time = Timestamp::from_seconds(start_timestamp); // set to 1 January 2021 00:00:00 GMT
+
+    let info = mock_info("creator", &[]);
+    let _res =
+        contract::instantiate(deps.as_mut(), env.clone(), info.clone(), product_msg()).unwrap();
+
+    let _res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::CreateCoupon {
+            code: "LAUNCH".to_string(),
+            discount: Discount {
+                amount: Uint256::from(100u128),
+                discount_type: DiscountType::Fixed,
+                expiry: None,
+                intervals: None,
+            },
+            max_redemptions: 100,
+            redeem_by: start_timestamp + 3600,
+        },
+    )
+    .unwrap();
+
+    env.block.time = Timestamp::from_seconds(start_timestamp + 3601);
+    let res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("subscriber", &[]),
+        ExecuteMsg::Subscribe {
+            plan_id: None,
+            coupon: Some("LAUNCH".to_string()),
+            billing_cycles: None,
+            end_at: None,
+            beneficiary: None,
+            seats: None,
+        },
+    );
+    match res {
+        Err(ContractError::CouponExpired {}) => {}
+        _ => panic!("Must return coupon expired error"),
+    }
+
+    // removed coupons can no longer be redeemed
+    let _res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        ExecuteMsg::RemoveCoupon {
+            code: "LAUNCH".to_string(),
+        },
+    )
+    .unwrap();
+
+    let res = contract::execute(
+        deps.as_mut(),
+        env,
+        mock_info("subscriber", &[]),
+        ExecuteMsg::Subscribe {
+            plan_id: None,
+            coupon: Some("LAUNCH".to_string()),
+            billing_cycles: None,
+            end_at: None,
+            beneficiary: None,
+            seats: None,
+        },
+    );
+    match res {
+        Err(ContractError::CouponNotFound {}) => {}
+        _ => panic!("Must return coupon not found error"),
+    }
+}
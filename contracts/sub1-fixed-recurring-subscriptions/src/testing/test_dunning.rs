@@ -0,0 +1,371 @@
+use super::{
+    charge, env_at, fail_payment, product_msg, query_can_work, query_subscription, subscribe,
+    ONE_DAY_IN_SECONDS, START_TIMESTAMP, THIRTY_DAYS_IN_SECONDS,
+};
+use crate::contract;
+use crate::error::ContractError;
+use crate::mock_querier::mock_dependencies;
+use crate::msg::{ConfigResponse, ExecuteMsg, QueryMsg};
+use crate::state::SubscriptionStatus;
+
+use cosmwasm_bignumber::Uint256;
+use cosmwasm_std::testing::{mock_env, mock_info};
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::{attr, from_binary, to_binary, DepsMut, SubMsg, Timestamp, WasmMsg};
+use suberra_core::msg::{ProductInstantiateMsg, SubscriptionHookExecuteMsg, SubscriptionHookMsg};
+
+#[test]
+fn failed_charges_are_retried_then_cancelled() {
+    let mut deps = mock_dependencies(&[]);
+
+    let mut env = mock_env();
+    let start_timestamp = 1609459200;
+    env.block.time = Timestamp::from_seconds(start_timestamp); // set to 1 January 2021 00:00:00 GMT
+
+    // retries 1 day, then 3 days after a failed charge, within a grace period of 5 days
+    let _res = contract::instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        ProductInstantiateMsg {
+            retry_schedule_hour: Some(vec![24, 72]),
+            additional_grace_period_hour: Some(96),
+            ..product_msg()
+        },
+    )
+    .unwrap();
+
+    let res = contract::query(deps.as_ref(), env.clone(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        config.retry_schedule_seconds,
+        vec![ONE_DAY_IN_SECONDS, 3 * ONE_DAY_IN_SECONDS]
+    );
+
+    let _res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("subscriber", &[]),
+        ExecuteMsg::Subscribe {
+            plan_id: None,
+            coupon: None,
+            billing_cycles: None,
+            end_at: None,
+            beneficiary: None,
+            seats: None,
+        },
+    )
+    .unwrap();
+
+    // the charge fails, and the subscription is restored and becomes past due
+    let charged_at = start_timestamp + THIRTY_DAYS_IN_SECONDS;
+    env.block.time = Timestamp::from_seconds(charged_at);
+    charge(deps.as_mut(), env.clone(), "subscriber").unwrap();
+
+    let res = fail_payment(deps.as_mut(), env.clone(), 0).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("method", "charge_failed"),
+            attr("module_contract_address", "cosmos2contract"),
+            attr("subscriber", "subscriber"),
+            attr("retry_count", "1"),
+            attr(
+                "next_retry_at",
+                (charged_at + ONE_DAY_IN_SECONDS).to_string()
+            ),
+            attr("error", "insufficient funds"),
+        ]
+    );
+
+    let subscription = query_subscription(deps.as_ref(), env.clone(), "subscriber").unwrap();
+    assert!(subscription.is_past_due);
+    assert_eq!(subscription.retry_count, 1);
+    assert_eq!(
+        subscription.next_retry_at,
+        Some(charged_at + ONE_DAY_IN_SECONDS)
+    );
+    assert_eq!(
+        subscription.interval_end_at,
+        start_timestamp + THIRTY_DAYS_IN_SECONDS
+    );
+    assert_eq!(subscription.total_paid, Uint256::from(1000u128));
+    assert_eq!(
+        subscription.amount_chargeable,
+        Some(Uint256::from(1000u128))
+    );
+
+    // the charge cannot be retried before the next retry
+    assert!(!query_can_work(deps.as_ref(), env.clone(), "subscriber"));
+    match charge(deps.as_mut(), env.clone(), "subscriber") {
+        Err(ContractError::RetryNotDue {}) => {}
+        _ => panic!("Must return retry not due error"),
+    }
+
+    // the first retry fails within the grace period
+    env.block.time = Timestamp::from_seconds(charged_at + ONE_DAY_IN_SECONDS + 1);
+    assert!(query_can_work(deps.as_ref(), env.clone(), "subscriber"));
+    charge(deps.as_mut(), env.clone(), "subscriber").unwrap();
+    fail_payment(deps.as_mut(), env.clone(), 0).unwrap();
+
+    let subscription = query_subscription(deps.as_ref(), env.clone(), "subscriber").unwrap();
+    assert!(subscription.is_active);
+    assert!(subscription.is_past_due);
+    assert_eq!(subscription.retry_count, 2);
+    assert_eq!(
+        subscription.next_retry_at,
+        Some(charged_at + 4 * ONE_DAY_IN_SECONDS + 1)
+    );
+
+    // the final retry fails and the subscription is cancelled
+    env.block.time = Timestamp::from_seconds(charged_at + 4 * ONE_DAY_IN_SECONDS + 1);
+    assert!(query_can_work(deps.as_ref(), env.clone(), "subscriber"));
+    charge(deps.as_mut(), env.clone(), "subscriber").unwrap();
+    let res = fail_payment(deps.as_mut(), env.clone(), 0).unwrap();
+    assert_eq!(res.attributes[4], attr("result", "subscription_cancelled"));
+
+    let subscription = query_subscription(deps.as_ref(), env.clone(), "subscriber").unwrap();
+    assert!(subscription.is_cancelled);
+    assert!(!subscription.is_past_due);
+    assert_eq!(subscription.retry_count, 3);
+    assert!(!query_can_work(deps.as_ref(), env.clone(), "subscriber"));
+    match charge(deps.as_mut(), env, "subscriber") {
+        Err(ContractError::SubscriptionCancelled {}) => {}
+        _ => panic!("Must return subscription cancelled error"),
+    }
+}
+
+#[test]
+fn successful_retry_clears_past_due() {
+    let mut deps = mock_dependencies(&[]);
+
+    let mut env = mock_env();
+    let start_timestamp = 1609459200;
+    env.block.time = Timestamp::from_seconds(start_timestamp); // set to 1 January 2021 00:00:00 GMT
+
+    // uses the default retry schedule
+    let _res = contract::instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        product_msg(),
+    )
+    .unwrap();
+
+    let _res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("subscriber", &[]),
+        ExecuteMsg::Subscribe {
+            plan_id: None,
+            coupon: None,
+            billing_cycles: None,
+            end_at: None,
+            beneficiary: None,
+            seats: None,
+        },
+    )
+    .unwrap();
+
+    let charged_at = start_timestamp + THIRTY_DAYS_IN_SECONDS;
+    env.block.time = Timestamp::from_seconds(charged_at);
+    charge(deps.as_mut(), env.clone(), "subscriber").unwrap();
+    fail_payment(deps.as_mut(), env.clone(), 0).unwrap();
+
+    let subscription = query_subscription(deps.as_ref(), env.clone(), "subscriber").unwrap();
+    assert_eq!(
+        subscription.next_retry_at,
+        Some(charged_at + ONE_DAY_IN_SECONDS)
+    );
+
+    // the retry succeeds and charges the interval that was due
+    env.block.time = Timestamp::from_seconds(charged_at + ONE_DAY_IN_SECONDS);
+    let res = charge(deps.as_mut(), env.clone(), "subscriber").unwrap();
+    assert_eq!(res.attributes[3], attr("amount", "1000"));
+
+    let subscription = query_subscription(deps.as_ref(), env.clone(), "subscriber").unwrap();
+    assert!(!subscription.is_past_due);
+    assert_eq!(subscription.retry_count, 0);
+    assert_eq!(subscription.next_retry_at, None);
+    assert_eq!(
+        subscription.interval_end_at,
+        start_timestamp + 2 * THIRTY_DAYS_IN_SECONDS
+    );
+    assert_eq!(subscription.total_paid, Uint256::from(2000u128));
+
+    // replies are only expected for charges sent in the same transaction
+    match fail_payment(deps.as_mut(), env, 1) {
+        Err(ContractError::InvalidReplyID {}) => {}
+        _ => panic!("Must return invalid reply id error"),
+    }
+}
+
+#[test]
+fn invalid_retry_schedule() {
+    let mut deps = mock_dependencies(&[]);
+
+    let res = contract::instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("creator", &[]),
+        ProductInstantiateMsg {
+            retry_schedule_hour: Some(vec![24, 0]),
+            ..product_msg()
+        },
+    );
+    assert!(res.is_err());
+
+    let _res = contract::instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("creator", &[]),
+        product_msg(),
+    )
+    .unwrap();
+
+    let res = contract::execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("creator", &[]),
+        ExecuteMsg::UpdateConfig {
+            receiver_address: None,
+            additional_grace_period_hour: None,
+            retry_schedule_hour: Some(vec![24; 11]),
+            max_pause_duration_hour: None,
+            max_pauses_per_year: None,
+            initial_amount: None,
+            uri: None,
+            revenue_splits: None,
+            accept_any_payer: None,
+        },
+    );
+    match res {
+        Err(ContractError::InvalidParam {}) => {}
+        _ => panic!("Must return invalid param error"),
+    }
+
+    // no retries cancels the subscription as soon as a charge fails
+    let _res = contract::execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("creator", &[]),
+        ExecuteMsg::UpdateConfig {
+            receiver_address: None,
+            additional_grace_period_hour: None,
+            retry_schedule_hour: Some(vec![]),
+            max_pause_duration_hour: None,
+            max_pauses_per_year: None,
+            initial_amount: None,
+            uri: None,
+            revenue_splits: None,
+            accept_any_payer: None,
+        },
+    )
+    .unwrap();
+
+    let res = contract::query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(config.retry_schedule_seconds, Vec::<u64>::new());
+}
+
+// the `Lapsed` callback sent to the hook contract
+fn lapsed_hook_msg() -> SubMsg {
+    SubMsg::reply_on_error(
+        WasmMsg::Execute {
+            contract_addr: "hook".to_string(),
+            msg: to_binary(&SubscriptionHookExecuteMsg::SubscriptionHook(
+                SubscriptionHookMsg::Lapsed {
+                    subscriber: "subscriber".to_string(),
+                },
+            ))
+            .unwrap(),
+            funds: vec![],
+        },
+        u64::MAX,
+    )
+}
+
+// instantiates a product with the default grace period and retry schedule, and a subscription whose renewal has failed
+fn past_due_subscription(mut deps: DepsMut, due_at: u64) {
+    contract::instantiate(
+        deps.branch(),
+        env_at(START_TIMESTAMP),
+        mock_info("creator", &[]),
+        product_msg(),
+    )
+    .unwrap();
+    contract::execute(
+        deps.branch(),
+        env_at(START_TIMESTAMP),
+        mock_info("creator", &[]),
+        ExecuteMsg::UpdateHooks {
+            hooks: vec!["hook".to_string()],
+        },
+    )
+    .unwrap();
+    subscribe(deps.branch(), env_at(START_TIMESTAMP), "subscriber").unwrap();
+
+    charge(deps.branch(), env_at(due_at), "subscriber").unwrap();
+    fail_payment(deps, env_at(due_at), 0).unwrap();
+}
+
+#[test]
+fn retries_stop_once_the_grace_period_ends() {
+    let mut deps = mock_dependencies(&[]);
+
+    // the first retry is due when the grace period of 1 day ends
+    let due_at = START_TIMESTAMP + THIRTY_DAYS_IN_SECONDS;
+    past_due_subscription(deps.as_mut(), due_at);
+    let subscription = query_subscription(deps.as_ref(), env_at(due_at), "subscriber").unwrap();
+    assert_eq!(
+        subscription.next_retry_at,
+        Some(due_at + ONE_DAY_IN_SECONDS)
+    );
+
+    // the next retry would be after the grace period, so the subscription lapses when the first retry fails
+    let retried_at = due_at + ONE_DAY_IN_SECONDS;
+    charge(deps.as_mut(), env_at(retried_at), "subscriber").unwrap();
+    let res = fail_payment(deps.as_mut(), env_at(retried_at), 0).unwrap();
+    assert!(res
+        .attributes
+        .contains(&attr("result", "subscription_cancelled")));
+    assert_eq!(res.messages, vec![lapsed_hook_msg()]);
+
+    let subscription =
+        query_subscription(deps.as_ref(), env_at(retried_at + 1), "subscriber").unwrap();
+    assert!(subscription.is_cancelled);
+    assert!(!subscription.is_past_due);
+    assert_eq!(subscription.status, SubscriptionStatus::Lapsed);
+}
+
+#[test]
+fn retry_after_grace_period_lapses_the_subscription() {
+    let mut deps = mock_dependencies(&[]);
+
+    let due_at = START_TIMESTAMP + THIRTY_DAYS_IN_SECONDS;
+    past_due_subscription(deps.as_mut(), due_at);
+
+    // the retry is only attempted once the grace period has ended
+    let late_at = due_at + ONE_DAY_IN_SECONDS + 1;
+    let subscription = query_subscription(deps.as_ref(), env_at(late_at), "subscriber").unwrap();
+    assert_eq!(subscription.status, SubscriptionStatus::Lapsed);
+    assert!(query_can_work(deps.as_ref(), env_at(late_at), "subscriber"));
+
+    // the subscription lapses instead of being billed for the lapsed intervals
+    let res = charge(deps.as_mut(), env_at(late_at), "subscriber").unwrap();
+    assert!(res
+        .attributes
+        .contains(&attr("result", "subscription_cancelled")));
+    assert_eq!(res.messages, vec![lapsed_hook_msg()]);
+
+    let subscription = query_subscription(deps.as_ref(), env_at(late_at), "subscriber").unwrap();
+    assert!(subscription.is_cancelled);
+    assert!(!subscription.is_past_due);
+    assert_eq!(subscription.interval_end_at, due_at);
+    assert_eq!(subscription.total_paid, Uint256::from(1000u128));
+    assert!(!query_can_work(
+        deps.as_ref(),
+        env_at(late_at),
+        "subscriber"
+    ));
+}
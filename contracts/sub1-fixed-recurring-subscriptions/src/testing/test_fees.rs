@@ -1,4 +1,5 @@
 use crate::contract;
+use crate::error::ContractError;
 use crate::mock_querier::mock_dependencies;
 use crate::msg::ExecuteMsg;
 
@@ -27,10 +28,22 @@ fn subscribe_with_fees() {
         initial_amount: Uint256::from(1000u128),
         unit_interval_hour: 720u64,
         additional_grace_period_hour: None,
+        retry_schedule_hour: None,
+        max_pause_duration_hour: None,
+        max_pauses_per_year: None,
         admins: Vec::new(),
         mutable: false,
         uri: "{\"image_url\": \"www.google.com\" }".to_string(),
         factory_address: "factory".to_string(),
+        trial_period_hour: None,
+        plans: None,
+        max_amount_chargeable: None,
+        usage_tiers: None,
+        membership_nft: None,
+        billing_asset: None,
+        revenue_splits: None,
+        billing_calendar: None,
+        accept_any_payer: Some(true),
         owner: "creator".to_string(),
     };
 
@@ -48,23 +61,25 @@ fn subscribe_with_fees() {
 
     let _res = contract::instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
-    let subscriber1 = mock_info(
-        "subscriber",
-        &[Coin {
-            denom: "uusd".to_string(),
-            amount: Uint128::from(10000u128),
-        }],
-    );
+    let subscriber1 = mock_info("subscriber", &[]);
 
     // user tries to subscribe
 
-    let msg = ExecuteMsg::Subscribe {};
+    let msg = ExecuteMsg::Subscribe {
+        plan_id: None,
+        coupon: None,
+        billing_cycles: None,
+        end_at: None,
+        beneficiary: None,
+        seats: None,
+    };
     let res =
         contract::execute(deps.as_mut(), env.clone(), subscriber1.clone(), msg.clone()).unwrap();
     assert_eq!(
         res.attributes,
         vec![
             attr("initial_amount", "1000"),
+            attr("charge_id", "1"),
             attr("method", "execute_subscribe"),
             attr("result", "subscribe_success"),
             attr("subscriber", "subscriber"),
@@ -132,10 +147,22 @@ fn test_charge() {
         initial_amount: Uint256::from(1000u128),
         unit_interval_hour: 720u64,
         additional_grace_period_hour: None,
+        retry_schedule_hour: None,
+        max_pause_duration_hour: None,
+        max_pauses_per_year: None,
         admins: Vec::new(),
         mutable: false,
         uri: "{\"image_url\": \"www.google.com\" }".to_string(),
         factory_address: "factory".to_string(),
+        trial_period_hour: None,
+        plans: None,
+        max_amount_chargeable: None,
+        usage_tiers: None,
+        membership_nft: None,
+        billing_asset: None,
+        revenue_splits: None,
+        billing_calendar: None,
+        accept_any_payer: Some(true),
         owner: "creator".to_string(),
     };
 
@@ -153,17 +180,18 @@ fn test_charge() {
 
     let _res = contract::instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
-    let subscriber1 = mock_info(
-        "subscriber",
-        &[Coin {
-            denom: "uusd".to_string(),
-            amount: Uint128::from(10000u128),
-        }],
-    );
+    let subscriber1 = mock_info("subscriber", &[]);
 
     // user tries to subscribe
 
-    let msg = ExecuteMsg::Subscribe {};
+    let msg = ExecuteMsg::Subscribe {
+        plan_id: None,
+        coupon: None,
+        billing_cycles: None,
+        end_at: None,
+        beneficiary: None,
+        seats: None,
+    };
     let _res =
         contract::execute(deps.as_mut(), env.clone(), subscriber1.clone(), msg.clone()).unwrap();
 
@@ -185,10 +213,48 @@ fn test_charge() {
             attr("module_contract_address", "cosmos2contract"),
             attr("subscriber", "subscriber"),
             attr("amount", "1000"),
-            attr("periods", "1")
+            attr("periods", "1"),
+            attr("charge_id", "2"),
         ]
     );
 
+    // the payment is collected by the contract in a submessage that replies on error
+    let collect_payment = ExecuteMsg::CollectPayment {
+        subscriber: "subscriber".to_string(),
+        payer: "subscriber".to_string(),
+        amount: Uint256::from(1000u128),
+    };
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::reply_on_error(
+            WasmMsg::Execute {
+                contract_addr: String::from("cosmos2contract"),
+                msg: to_binary(&collect_payment).unwrap(),
+                funds: vec![],
+            },
+            0
+        )]
+    );
+
+    // only the contract can collect payments
+    let res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        collect_payment.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("cosmos2contract", &[]),
+        collect_payment,
+    )
+    .unwrap();
     assert_eq!(res.messages.len(), 2);
 
     let msg_protocol_transfer = res.messages.get(0).expect("no message");
@@ -253,11 +319,23 @@ fn test_charge_with_min_fees() {
         initial_amount: Uint256::from(1000u128),
         unit_interval_hour: 720u64,
         additional_grace_period_hour: None,
+        retry_schedule_hour: None,
+        max_pause_duration_hour: None,
+        max_pauses_per_year: None,
         owner: "creator".to_string(),
         admins: Vec::new(),
         mutable: false,
         uri: "{\"image_url\": \"www.google.com\" }".to_string(),
         factory_address: "factory".to_string(),
+        trial_period_hour: None,
+        plans: None,
+        max_amount_chargeable: None,
+        usage_tiers: None,
+        membership_nft: None,
+        billing_asset: None,
+        revenue_splits: None,
+        billing_calendar: None,
+        accept_any_payer: Some(true),
     };
 
     let mut env = mock_env();
@@ -274,17 +352,18 @@ fn test_charge_with_min_fees() {
 
     let _res = contract::instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
-    let subscriber1 = mock_info(
-        "subscriber",
-        &[Coin {
-            denom: "uusd".to_string(),
-            amount: Uint128::from(10000u128),
-        }],
-    );
+    let subscriber1 = mock_info("subscriber", &[]);
 
     // user tries to subscribe
 
-    let msg = ExecuteMsg::Subscribe {};
+    let msg = ExecuteMsg::Subscribe {
+        plan_id: None,
+        coupon: None,
+        billing_cycles: None,
+        end_at: None,
+        beneficiary: None,
+        seats: None,
+    };
     let _res =
         contract::execute(deps.as_mut(), env.clone(), subscriber1.clone(), msg.clone()).unwrap();
 
@@ -306,10 +385,36 @@ fn test_charge_with_min_fees() {
             attr("module_contract_address", "cosmos2contract"),
             attr("subscriber", "subscriber"),
             attr("amount", "1000"),
-            attr("periods", "1")
+            attr("periods", "1"),
+            attr("charge_id", "2"),
         ]
     );
 
+    // the payment is collected by the contract in a submessage that replies on error
+    let collect_payment = ExecuteMsg::CollectPayment {
+        subscriber: "subscriber".to_string(),
+        payer: "subscriber".to_string(),
+        amount: Uint256::from(1000u128),
+    };
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::reply_on_error(
+            WasmMsg::Execute {
+                contract_addr: String::from("cosmos2contract"),
+                msg: to_binary(&collect_payment).unwrap(),
+                funds: vec![],
+            },
+            0
+        )]
+    );
+
+    let res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("cosmos2contract", &[]),
+        collect_payment,
+    )
+    .unwrap();
     assert_eq!(res.messages.len(), 2);
 
     let msg_protocol_transfer = res.messages.get(0).expect("no message");
@@ -0,0 +1,254 @@
+use super::{charge, product_msg, query_subscription, THIRTY_DAYS_IN_SECONDS};
+use crate::contract;
+use crate::error::ContractError;
+use crate::mock_querier::mock_dependencies;
+use crate::msg::{ExecuteMsg, PaymentsResponse, QueryMsg, SubWalletExecuteMsg};
+
+use cosmwasm_bignumber::Uint256;
+use cosmwasm_std::testing::{mock_env, mock_info};
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::{
+    attr, coins, from_binary, to_binary, BankMsg, CosmosMsg, DepsMut, Env, Response, Timestamp,
+    WasmMsg,
+};
+
+fn gift(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    contract::execute(
+        deps,
+        env,
+        mock_info("sponsor", &[]),
+        ExecuteMsg::Subscribe {
+            plan_id: None,
+            coupon: None,
+            billing_cycles: None,
+            end_at: None,
+            beneficiary: Some("friend".to_string()),
+            seats: None,
+        },
+    )
+}
+
+// returns the subwallet that the payment sent in the response is collected from
+fn collected_from(res: &Response) -> String {
+    match &res.messages[0].msg {
+        CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) => match from_binary(msg).unwrap() {
+            ExecuteMsg::CollectPayment { payer, .. } => payer,
+            _ => panic!("Must collect the payment"),
+        },
+        _ => panic!("Must collect the payment"),
+    }
+}
+
+#[test]
+fn gift_subscription_is_paid_by_sponsor() {
+    let mut deps = mock_dependencies(&[]);
+
+    let mut env = mock_env();
+    let start_timestamp = 1609459200;
+    env.block.time = Timestamp::from_seconds(start_timestamp); // set to 1 January 2021 00:00:00 GMT
+
+    let _res = contract::instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        product_msg(),
+    )
+    .unwrap();
+
+    let res = gift(deps.as_mut(), env.clone()).unwrap();
+    assert!(res.attributes.contains(&attr("subscriber", "friend")));
+    assert!(res.attributes.contains(&attr("payer", "sponsor")));
+
+    // the initial amount is paid by the sponsor
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "sponsor".to_string(),
+            msg: to_binary(&SubWalletExecuteMsg::TransferAToken {
+                amount: 1000u128.into(),
+                recipient: "merchant".to_string(),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+
+    // access is granted to the beneficiary, not to the sponsor
+    let subscription = query_subscription(deps.as_ref(), env.clone(), "friend").unwrap();
+    assert!(subscription.is_active);
+    assert_eq!(subscription.payer, "sponsor");
+    assert_eq!(
+        query_subscription(deps.as_ref(), env.clone(), "sponsor"),
+        None
+    );
+
+    // renewals are charged from the sponsor
+    env.block.time = Timestamp::from_seconds(start_timestamp + THIRTY_DAYS_IN_SECONDS);
+    let res = charge(deps.as_mut(), env.clone(), "friend").unwrap();
+    assert_eq!(collected_from(&res), "sponsor");
+
+    // refunds go back to the sponsor
+    let res = contract::query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::Payments {
+            subscriber: "friend".to_string(),
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let history: PaymentsResponse = from_binary(&res).unwrap();
+    assert_eq!(history.payments.len(), 2);
+    assert!(history.payments.iter().all(|p| p.payer == "sponsor"));
+
+    let res = contract::execute(
+        deps.as_mut(),
+        env,
+        mock_info("merchant", &coins(1000, "uusd")),
+        ExecuteMsg::Refund {
+            subscriber: "friend".to_string(),
+            amount: Uint256::from(1000u128),
+            charge_id: 2,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: "sponsor".to_string(),
+            amount: coins(1000, "uusd"),
+        })
+    );
+}
+
+#[test]
+fn beneficiary_takes_over_payment() {
+    let mut deps = mock_dependencies(&[]);
+
+    let mut env = mock_env();
+    let start_timestamp = 1609459200;
+    env.block.time = Timestamp::from_seconds(start_timestamp); // set to 1 January 2021 00:00:00 GMT
+
+    let _res = contract::instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        product_msg(),
+    )
+    .unwrap();
+
+    gift(deps.as_mut(), env.clone()).unwrap();
+
+    // only the beneficiary can take over the payment
+    match contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("sponsor", &[]),
+        ExecuteMsg::TakeOverPayment {},
+    ) {
+        Err(ContractError::SubscriptionNotFound {}) => {}
+        _ => panic!("Must return subscription not found error"),
+    }
+
+    let res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("friend", &[]),
+        ExecuteMsg::TakeOverPayment {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("method", "execute_take_over_payment"),
+            attr("module_contract_address", "cosmos2contract"),
+            attr("subscriber", "friend"),
+            attr("previous_payer", "sponsor"),
+        ]
+    );
+
+    let subscription = query_subscription(deps.as_ref(), env.clone(), "friend").unwrap();
+    assert_eq!(subscription.payer, "friend");
+
+    // the beneficiary already pays for its subscription
+    match contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("friend", &[]),
+        ExecuteMsg::TakeOverPayment {},
+    ) {
+        Err(ContractError::InvalidParam {}) => {}
+        _ => panic!("Must return invalid param error"),
+    }
+
+    env.block.time = Timestamp::from_seconds(start_timestamp + THIRTY_DAYS_IN_SECONDS);
+    let res = charge(deps.as_mut(), env, "friend").unwrap();
+    assert_eq!(collected_from(&res), "friend");
+}
+
+#[test]
+fn only_subscriber_or_payer_undoes_cancellation() {
+    let mut deps = mock_dependencies(&[]);
+    let env = mock_env();
+
+    let _res = contract::instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        product_msg(),
+    )
+    .unwrap();
+
+    gift(deps.as_mut(), env.clone()).unwrap();
+    let cancel = |deps: DepsMut, env: Env| {
+        contract::execute(deps, env, mock_info("friend", &[]), ExecuteMsg::Cancel {}).unwrap()
+    };
+    cancel(deps.as_mut(), env.clone());
+
+    // other senders cannot undo the cancellation and become the payer
+    match contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("stranger", &[]),
+        ExecuteMsg::Subscribe {
+            plan_id: None,
+            coupon: None,
+            billing_cycles: None,
+            end_at: None,
+            beneficiary: Some("friend".to_string()),
+            seats: None,
+        },
+    ) {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    // the sponsor keeps paying when it undoes the cancellation
+    let res = gift(deps.as_mut(), env.clone()).unwrap();
+    assert!(res
+        .attributes
+        .contains(&attr("additional_info", "undo_cancellation")));
+    let subscription = query_subscription(deps.as_ref(), env.clone(), "friend").unwrap();
+    assert_eq!(subscription.payer, "sponsor");
+
+    // the subscriber pays for itself when it undoes the cancellation
+    cancel(deps.as_mut(), env.clone());
+    contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("friend", &[]),
+        ExecuteMsg::Subscribe {
+            plan_id: None,
+            coupon: None,
+            billing_cycles: None,
+            end_at: None,
+            beneficiary: None,
+            seats: None,
+        },
+    )
+    .unwrap();
+    let subscription = query_subscription(deps.as_ref(), env, "friend").unwrap();
+    assert!(!subscription.is_cancelled);
+    assert_eq!(subscription.payer, "friend");
+}
@@ -0,0 +1,228 @@
+use super::{env_at, product_msg, START_TIMESTAMP};
+use crate::contract;
+use crate::error::ContractError;
+use crate::mock_querier::mock_dependencies;
+use crate::msg::{ConfigResponse, ExecuteMsg, QueryMsg};
+
+use cosmwasm_bignumber::Uint256;
+use cosmwasm_std::testing::mock_info;
+use cosmwasm_std::{
+    attr, from_binary, to_binary, ContractResult, CosmosMsg, Reply, ReplyOn, SubMsg, WasmMsg,
+};
+use suberra_core::msg::{SubscriptionHookExecuteMsg, SubscriptionHookMsg};
+
+fn subscribe_msg() -> ExecuteMsg {
+    ExecuteMsg::Subscribe {
+        plan_id: None,
+        coupon: None,
+        billing_cycles: None,
+        end_at: None,
+        beneficiary: None,
+        seats: None,
+    }
+}
+
+fn update_hooks(hooks: &[&str]) -> ExecuteMsg {
+    ExecuteMsg::UpdateHooks {
+        hooks: hooks.iter().map(|hook| hook.to_string()).collect(),
+    }
+}
+
+// the callbacks expected for every hook contract, in order
+fn hook_msgs(hook_msg: SubscriptionHookMsg) -> Vec<SubMsg> {
+    ["hook_a", "hook_b"]
+        .iter()
+        .map(|hook| SubMsg {
+            id: u64::MAX,
+            msg: CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: hook.to_string(),
+                msg: to_binary(&SubscriptionHookExecuteMsg::SubscriptionHook(
+                    hook_msg.clone(),
+                ))
+                .unwrap(),
+                funds: vec![],
+            }),
+            gas_limit: None,
+            reply_on: ReplyOn::Error,
+        })
+        .collect()
+}
+
+#[test]
+fn hooks_are_called_on_subscription_changes() {
+    let mut deps = mock_dependencies(&[]);
+    let env = env_at(START_TIMESTAMP);
+
+    contract::instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        product_msg(),
+    )
+    .unwrap();
+    contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        update_hooks(&["hook_a", "hook_b"]),
+    )
+    .unwrap();
+
+    let res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("subscriber", &[]),
+        subscribe_msg(),
+    )
+    .unwrap();
+    // the initial payment is sent first, followed by the callbacks
+    assert_eq!(
+        res.messages[1..].to_vec(),
+        hook_msgs(SubscriptionHookMsg::Subscribed {
+            subscriber: "subscriber".to_string(),
+            plan_id: 0,
+        })
+    );
+
+    let res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("subscriber", &[]),
+        ExecuteMsg::Cancel {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        hook_msgs(SubscriptionHookMsg::Cancelled {
+            subscriber: "subscriber".to_string(),
+        })
+    );
+
+    let res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("subscriber", &[]),
+        subscribe_msg(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        hook_msgs(SubscriptionHookMsg::CancellationUndone {
+            subscriber: "subscriber".to_string(),
+        })
+    );
+
+    // renewals notify the hooks once the payment is collected
+    let res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(env.contract.address.as_str(), &[]),
+        ExecuteMsg::CollectPayment {
+            subscriber: "subscriber".to_string(),
+            payer: "subscriber".to_string(),
+            amount: Uint256::from(1000u128),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[1..].to_vec(),
+        hook_msgs(SubscriptionHookMsg::Charged {
+            subscriber: "subscriber".to_string(),
+            amount: Uint256::from(1000u128),
+        })
+    );
+
+    let res = contract::execute(
+        deps.as_mut(),
+        env,
+        mock_info("creator", &[]),
+        ExecuteMsg::RemoveSubscriber {
+            subscriber: "subscriber".to_string(),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        hook_msgs(SubscriptionHookMsg::Removed {
+            subscriber: "subscriber".to_string(),
+        })
+    );
+}
+
+#[test]
+fn failed_hooks_are_ignored() {
+    let mut deps = mock_dependencies(&[]);
+    let env = env_at(START_TIMESTAMP);
+
+    contract::instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        product_msg(),
+    )
+    .unwrap();
+
+    let res = contract::reply(
+        deps.as_mut(),
+        env,
+        Reply {
+            id: u64::MAX,
+            result: ContractResult::Err("hook failed".to_string()),
+        },
+    )
+    .unwrap();
+    assert!(res.messages.is_empty());
+    assert!(res.attributes.contains(&attr("method", "hook_failed")));
+    assert!(res.attributes.contains(&attr("error", "hook failed")));
+}
+
+#[test]
+fn update_hooks_by_admins() {
+    let mut deps = mock_dependencies(&[]);
+    let env = env_at(START_TIMESTAMP);
+
+    contract::instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        product_msg(),
+    )
+    .unwrap();
+
+    let res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("anyone", &[]),
+        update_hooks(&["hook_a"]),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        update_hooks(&["hook"; 11]),
+    );
+    match res {
+        Err(ContractError::InvalidParam {}) => {}
+        _ => panic!("Must return invalid param error"),
+    }
+
+    contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        update_hooks(&["hook_a", "hook_b"]),
+    )
+    .unwrap();
+
+    let res = contract::query(deps.as_ref(), env, QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        config.hooks,
+        vec!["hook_a".to_string(), "hook_b".to_string()]
+    );
+}
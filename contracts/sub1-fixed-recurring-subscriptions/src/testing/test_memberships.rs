@@ -0,0 +1,359 @@
+use super::{product_msg, subscribe, THIRTY_DAYS_IN_SECONDS};
+use crate::contract;
+use crate::error::ContractError;
+use crate::mock_querier::mock_dependencies;
+use crate::msg::{
+    AllNftInfoResponse, ContractInfoResponse, ExecuteMsg, MembershipMetadata, NftInfoResponse,
+    NumTokensResponse, OwnerOfResponse, QueryMsg, TokensResponse,
+};
+
+use cosmwasm_std::testing::{mock_env, mock_info};
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::{
+    attr, from_binary, ContractResult, Deps, DepsMut, Env, Reply, Response, StdError, Timestamp,
+};
+use suberra_core::msg::ProductInstantiateMsg;
+use suberra_core::subscriptions::MembershipNftInfo;
+
+fn membership_nft(transferable: bool) -> Option<MembershipNftInfo> {
+    Some(MembershipNftInfo {
+        name: "Suberra Membership".to_string(),
+        symbol: "MEMBER".to_string(),
+        transferable,
+    })
+}
+
+fn transfer(
+    deps: DepsMut,
+    env: Env,
+    sender: &str,
+    recipient: &str,
+) -> Result<Response, ContractError> {
+    contract::execute(
+        deps,
+        env,
+        mock_info(sender, &[]),
+        ExecuteMsg::TransferNft {
+            recipient: recipient.to_string(),
+            token_id: "subscriber".to_string(),
+        },
+    )
+}
+
+fn query_nft_info(deps: Deps, env: Env) -> NftInfoResponse {
+    let res = contract::query(
+        deps,
+        env,
+        QueryMsg::NftInfo {
+            token_id: "subscriber".to_string(),
+        },
+    )
+    .unwrap();
+    from_binary(&res).unwrap()
+}
+
+fn query_tokens(deps: Deps, env: Env, owner: &str) -> Vec<String> {
+    let res = contract::query(
+        deps,
+        env,
+        QueryMsg::Tokens {
+            owner: owner.to_string(),
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let value: TokensResponse = from_binary(&res).unwrap();
+    value.tokens
+}
+
+fn query_num_tokens(deps: Deps, env: Env) -> u64 {
+    let res = contract::query(deps, env, QueryMsg::NumTokens {}).unwrap();
+    let value: NumTokensResponse = from_binary(&res).unwrap();
+    value.count
+}
+
+#[test]
+fn subscribe_mints_membership() {
+    let mut deps = mock_dependencies(&[]);
+
+    let mut env = mock_env();
+    let start_timestamp = 1609459200;
+    env.block.time = Timestamp::from_seconds(start_timestamp); // set to 1 January 2021 00:00:00 GMT
+
+    let _res = contract::instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        ProductInstantiateMsg {
+            retry_schedule_hour: Some(vec![]),
+            membership_nft: membership_nft(false),
+            ..product_msg()
+        },
+    )
+    .unwrap();
+
+    let res = subscribe(deps.as_mut(), env.clone(), "subscriber").unwrap();
+    assert!(res.attributes.contains(&attr("token_id", "subscriber")));
+    subscribe(deps.as_mut(), env.clone(), "another_subscriber").unwrap();
+
+    let res = contract::query(deps.as_ref(), env.clone(), QueryMsg::ContractInfo {}).unwrap();
+    let value: ContractInfoResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        value,
+        ContractInfoResponse {
+            name: "Suberra Membership".to_string(),
+            symbol: "MEMBER".to_string(),
+        }
+    );
+    assert_eq!(query_num_tokens(deps.as_ref(), env.clone()), 2);
+
+    let res = contract::query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::AllNftInfo {
+            token_id: "subscriber".to_string(),
+            include_expired: None,
+        },
+    )
+    .unwrap();
+    let value: AllNftInfoResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        value,
+        AllNftInfoResponse {
+            access: OwnerOfResponse {
+                owner: "subscriber".to_string(),
+                approvals: vec![],
+            },
+            info: NftInfoResponse {
+                token_uri: None,
+                extension: MembershipMetadata {
+                    subscriber: "subscriber".to_string(),
+                    plan_id: 0,
+                    interval_end_at: start_timestamp + THIRTY_DAYS_IN_SECONDS,
+                    is_active: true,
+                },
+            },
+        }
+    );
+
+    assert_eq!(
+        query_tokens(deps.as_ref(), env.clone(), "subscriber"),
+        vec!["subscriber".to_string()]
+    );
+    let res = contract::query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::AllTokens {
+            start_after: Some("another_subscriber".to_string()),
+            limit: None,
+        },
+    )
+    .unwrap();
+    let value: TokensResponse = from_binary(&res).unwrap();
+    assert_eq!(value.tokens, vec!["subscriber".to_string()]);
+
+    // the token is expired once the subscription is no longer active
+    env.block.time = Timestamp::from_seconds(start_timestamp + 2 * THIRTY_DAYS_IN_SECONDS);
+    let info = query_nft_info(deps.as_ref(), env.clone());
+    assert!(!info.extension.is_active);
+
+    // membership tokens are not transferable unless the product allows it
+    match transfer(deps.as_mut(), env, "subscriber", "friend") {
+        Err(ContractError::MembershipNotTransferable {}) => {}
+        _ => panic!("Must return membership not transferable error"),
+    }
+}
+
+#[test]
+fn transfer_membership() {
+    let mut deps = mock_dependencies(&[]);
+    let mut env = mock_env();
+
+    let _res = contract::instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        ProductInstantiateMsg {
+            retry_schedule_hour: Some(vec![]),
+            membership_nft: membership_nft(true),
+            ..product_msg()
+        },
+    )
+    .unwrap();
+
+    subscribe(deps.as_mut(), env.clone(), "subscriber").unwrap();
+
+    // only the owner of the token can transfer it
+    match transfer(deps.as_mut(), env.clone(), "friend", "friend") {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let res = transfer(deps.as_mut(), env.clone(), "subscriber", "friend").unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("method", "execute_transfer_nft"),
+            attr("module_contract_address", "cosmos2contract"),
+            attr("sender", "subscriber"),
+            attr("recipient", "friend"),
+            attr("token_id", "subscriber"),
+        ]
+    );
+
+    assert_eq!(
+        query_tokens(deps.as_ref(), env.clone(), "subscriber"),
+        Vec::<String>::new()
+    );
+    assert_eq!(
+        query_tokens(deps.as_ref(), env.clone(), "friend"),
+        vec!["subscriber".to_string()]
+    );
+
+    // the token still follows the subscription it was minted for
+    let info = query_nft_info(deps.as_ref(), env.clone());
+    assert_eq!(info.extension.subscriber, "subscriber");
+    assert!(info.extension.is_active);
+
+    // undoing a cancellation keeps the transferred token
+    contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("subscriber", &[]),
+        ExecuteMsg::Cancel {},
+    )
+    .unwrap();
+    let res = subscribe(deps.as_mut(), env.clone(), "subscriber").unwrap();
+    assert!(!res.attributes.contains(&attr("token_id", "subscriber")));
+    assert_eq!(
+        query_tokens(deps.as_ref(), env.clone(), "friend"),
+        vec!["subscriber".to_string()]
+    );
+
+    // subscribing again after the subscription has lapsed keeps the transferred token too
+    contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("subscriber", &[]),
+        ExecuteMsg::Cancel {},
+    )
+    .unwrap();
+    env.block.time = env.block.time.plus_seconds(THIRTY_DAYS_IN_SECONDS + 1);
+    let res = subscribe(deps.as_mut(), env.clone(), "subscriber").unwrap();
+    assert!(!res.attributes.contains(&attr("token_id", "subscriber")));
+    assert_eq!(
+        query_tokens(deps.as_ref(), env.clone(), "friend"),
+        vec!["subscriber".to_string()]
+    );
+    assert_eq!(query_num_tokens(deps.as_ref(), env), 1);
+}
+
+#[test]
+fn memberships_are_burned() {
+    let mut deps = mock_dependencies(&[]);
+
+    let mut env = mock_env();
+    let start_timestamp = 1609459200;
+    env.block.time = Timestamp::from_seconds(start_timestamp); // set to 1 January 2021 00:00:00 GMT
+
+    let _res = contract::instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        ProductInstantiateMsg {
+            retry_schedule_hour: Some(vec![]),
+            membership_nft: membership_nft(false),
+            ..product_msg()
+        },
+    )
+    .unwrap();
+
+    subscribe(deps.as_mut(), env.clone(), "subscriber").unwrap();
+    subscribe(deps.as_mut(), env.clone(), "another_subscriber").unwrap();
+
+    // removed subscribers lose their token
+    let res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        ExecuteMsg::RemoveSubscriber {
+            subscriber: "another_subscriber".to_string(),
+        },
+    )
+    .unwrap();
+    assert!(res
+        .attributes
+        .contains(&attr("burned_token_id", "another_subscriber")));
+    assert_eq!(query_num_tokens(deps.as_ref(), env.clone()), 1);
+
+    // the token is burned once the subscription is cancelled after a failed payment without retries
+    env.block.time = Timestamp::from_seconds(start_timestamp + THIRTY_DAYS_IN_SECONDS);
+    contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("charger", &[]),
+        ExecuteMsg::Charge {
+            payer_address: "subscriber".to_string(),
+        },
+    )
+    .unwrap();
+    let res = contract::reply(
+        deps.as_mut(),
+        env.clone(),
+        Reply {
+            id: 0,
+            result: ContractResult::Err("insufficient funds".to_string()),
+        },
+    )
+    .unwrap();
+    assert!(res
+        .attributes
+        .contains(&attr("burned_token_id", "subscriber")));
+    assert_eq!(query_num_tokens(deps.as_ref(), env.clone()), 0);
+
+    let err = contract::query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::OwnerOf {
+            token_id: "subscriber".to_string(),
+            include_expired: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, StdError::not_found("MembershipToken"));
+
+    // resubscribing mints a new token
+    let res = subscribe(deps.as_mut(), env.clone(), "subscriber").unwrap();
+    assert!(res.attributes.contains(&attr("token_id", "subscriber")));
+    assert_eq!(query_num_tokens(deps.as_ref(), env), 1);
+}
+
+#[test]
+fn memberships_are_optional() {
+    let mut deps = mock_dependencies(&[]);
+    let env = mock_env();
+
+    let _res = contract::instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        ProductInstantiateMsg {
+            retry_schedule_hour: Some(vec![]),
+            ..product_msg()
+        },
+    )
+    .unwrap();
+
+    let res = subscribe(deps.as_mut(), env.clone(), "subscriber").unwrap();
+    assert!(!res.attributes.contains(&attr("token_id", "subscriber")));
+    assert_eq!(query_num_tokens(deps.as_ref(), env.clone()), 0);
+
+    let err = contract::query(deps.as_ref(), env.clone(), QueryMsg::ContractInfo {}).unwrap_err();
+    assert_eq!(err, StdError::not_found("MembershipNftInfo"));
+
+    match transfer(deps.as_mut(), env, "subscriber", "friend") {
+        Err(ContractError::MembershipNotFound {}) => {}
+        _ => panic!("Must return membership not found error"),
+    }
+}
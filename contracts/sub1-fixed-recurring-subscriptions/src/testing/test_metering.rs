@@ -0,0 +1,275 @@
+use super::{
+    charge, product_msg, query_subscription, subscribe, ONE_DAY_IN_SECONDS, THIRTY_DAYS_IN_SECONDS,
+};
+use crate::contract;
+use crate::error::ContractError;
+use crate::mock_querier::mock_dependencies;
+use crate::msg::{ConfigResponse, ExecuteMsg, QueryMsg, UsageRecordResponse, UsageResponse};
+
+use cosmwasm_bignumber::Uint256;
+use cosmwasm_std::testing::{mock_env, mock_info};
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::{attr, from_binary, Deps, DepsMut, Env, Response, Timestamp};
+use suberra_core::msg::ProductInstantiateMsg;
+use suberra_core::subscriptions::PriceTier;
+
+// the first 100 units of every interval cost 10, and every unit after costs 5
+fn usage_tiers() -> Vec<PriceTier> {
+    vec![
+        PriceTier {
+            up_to: Some(100),
+            unit_price: Uint256::from(10u128),
+        },
+        PriceTier {
+            up_to: None,
+            unit_price: Uint256::from(5u128),
+        },
+    ]
+}
+
+fn setup(deps: DepsMut, env: Env, msg: ProductInstantiateMsg) {
+    let _res = contract::instantiate(deps, env, mock_info("creator", &[]), msg).unwrap();
+}
+
+fn update_usage_reporters(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    contract::execute(
+        deps,
+        env,
+        mock_info("creator", &[]),
+        ExecuteMsg::UpdateUsageReporters {
+            reporters: vec!["reporter".to_string()],
+        },
+    )
+}
+
+fn report_usage(
+    deps: DepsMut,
+    env: Env,
+    reporter: &str,
+    quantity: u64,
+) -> Result<Response, ContractError> {
+    contract::execute(
+        deps,
+        env,
+        mock_info(reporter, &[]),
+        ExecuteMsg::ReportUsage {
+            subscriber: "subscriber".to_string(),
+            quantity,
+        },
+    )
+}
+
+fn query_usage(deps: Deps, env: Env) -> Vec<UsageRecordResponse> {
+    let res = contract::query(
+        deps,
+        env,
+        QueryMsg::Usage {
+            subscriber: "subscriber".to_string(),
+        },
+    )
+    .unwrap();
+    let value: Option<UsageResponse> = from_binary(&res).unwrap();
+    value.unwrap().usage
+}
+
+#[test]
+fn usage_is_billed_with_tiers_and_base_fee() {
+    let mut deps = mock_dependencies(&[]);
+
+    let mut env = mock_env();
+    let start_timestamp = 1609459200;
+    env.block.time = Timestamp::from_seconds(start_timestamp); // set to 1 January 2021 00:00:00 GMT
+
+    setup(
+        deps.as_mut(),
+        env.clone(),
+        ProductInstantiateMsg {
+            initial_amount: Uint256::zero(),
+            unit_amount: Uint256::from(100u128),
+            usage_tiers: Some(usage_tiers()),
+            ..product_msg()
+        },
+    );
+
+    let res = contract::query(deps.as_ref(), env.clone(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(config.usage_tiers, usage_tiers());
+
+    update_usage_reporters(deps.as_mut(), env.clone()).unwrap();
+    subscribe(deps.as_mut(), env.clone(), "subscriber").unwrap();
+
+    // only usage reporters can report usage
+    match report_usage(deps.as_mut(), env.clone(), "subscriber", 10) {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+    match report_usage(deps.as_mut(), env.clone(), "reporter", 0) {
+        Err(ContractError::InvalidParam {}) => {}
+        _ => panic!("Must return invalid param error"),
+    }
+
+    let first_interval_end_at = start_timestamp + THIRTY_DAYS_IN_SECONDS;
+    let res = report_usage(deps.as_mut(), env.clone(), "reporter", 60).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("method", "report_usage"),
+            attr("module_contract_address", "cosmos2contract"),
+            attr("subscriber", "subscriber"),
+            attr("quantity", "60"),
+            attr("interval_end_at", first_interval_end_at.to_string()),
+        ]
+    );
+
+    env.block.time = Timestamp::from_seconds(start_timestamp + 10 * ONE_DAY_IN_SECONDS);
+    report_usage(deps.as_mut(), env.clone(), "reporter", 90).unwrap();
+    assert_eq!(
+        query_usage(deps.as_ref(), env.clone()),
+        vec![UsageRecordResponse {
+            interval_end_at: first_interval_end_at,
+            quantity: 150,
+        }]
+    );
+
+    // usage reported after the interval ended belongs to the next interval
+    env.block.time = Timestamp::from_seconds(first_interval_end_at);
+    report_usage(deps.as_mut(), env.clone(), "reporter", 20).unwrap();
+
+    // base fee of 100, 100 units at 10 and 50 units at 5
+    let subscription = query_subscription(deps.as_ref(), env.clone(), "subscriber").unwrap();
+    assert_eq!(
+        subscription.amount_chargeable,
+        Some(Uint256::from(1350u128))
+    );
+
+    let res = charge(deps.as_mut(), env.clone(), "subscriber").unwrap();
+    assert_eq!(res.attributes[3], attr("amount", "1350"));
+    assert_eq!(res.attributes[5], attr("usage_amount", "1250"));
+    assert_eq!(res.messages.len(), 1);
+
+    assert_eq!(
+        query_usage(deps.as_ref(), env.clone()),
+        vec![UsageRecordResponse {
+            interval_end_at: first_interval_end_at + THIRTY_DAYS_IN_SECONDS,
+            quantity: 20,
+        }]
+    );
+
+    // base fee of 100 and 20 units at 10
+    env.block.time = Timestamp::from_seconds(first_interval_end_at + THIRTY_DAYS_IN_SECONDS);
+    let res = charge(deps.as_mut(), env.clone(), "subscriber").unwrap();
+    assert_eq!(res.attributes[3], attr("amount", "300"));
+    assert!(query_usage(deps.as_ref(), env).is_empty());
+}
+
+#[test]
+fn intervals_without_charges_are_renewed() {
+    let mut deps = mock_dependencies(&[]);
+
+    let mut env = mock_env();
+    let start_timestamp = 1609459200;
+    env.block.time = Timestamp::from_seconds(start_timestamp); // set to 1 January 2021 00:00:00 GMT
+
+    // no base fee, and a 7 days free trial
+    setup(
+        deps.as_mut(),
+        env.clone(),
+        ProductInstantiateMsg {
+            initial_amount: Uint256::zero(),
+            unit_amount: Uint256::zero(),
+            trial_period_hour: Some(168),
+            usage_tiers: Some(usage_tiers()),
+            ..product_msg()
+        },
+    );
+    update_usage_reporters(deps.as_mut(), env.clone()).unwrap();
+    subscribe(deps.as_mut(), env.clone(), "subscriber").unwrap();
+
+    // usage during the trial is not billed
+    report_usage(deps.as_mut(), env.clone(), "reporter", 500).unwrap();
+
+    let trial_end_at = start_timestamp + 7 * ONE_DAY_IN_SECONDS;
+    env.block.time = Timestamp::from_seconds(trial_end_at);
+    let res = charge(deps.as_mut(), env.clone(), "subscriber").unwrap();
+    assert_eq!(res.attributes[3], attr("amount", "0"));
+    assert_eq!(res.messages.len(), 0);
+
+    let subscription = query_subscription(deps.as_ref(), env.clone(), "subscriber").unwrap();
+    assert_eq!(
+        subscription.interval_end_at,
+        trial_end_at + THIRTY_DAYS_IN_SECONDS
+    );
+    assert!(query_usage(deps.as_ref(), env.clone()).is_empty());
+
+    // an interval without usage is renewed without a payment
+    env.block.time = Timestamp::from_seconds(trial_end_at + THIRTY_DAYS_IN_SECONDS);
+    let res = charge(deps.as_mut(), env.clone(), "subscriber").unwrap();
+    assert_eq!(res.messages.len(), 0);
+
+    let subscription = query_subscription(deps.as_ref(), env, "subscriber").unwrap();
+    assert!(subscription.is_active);
+    assert_eq!(subscription.total_paid, Uint256::zero());
+}
+
+#[test]
+fn invalid_metering() {
+    let mut deps = mock_dependencies(&[]);
+    let env = mock_env();
+
+    // tiers must have increasing upper bounds, and only the last tier can be unbounded
+    let invalid_tiers = vec![
+        vec![
+            PriceTier {
+                up_to: Some(100),
+                unit_price: Uint256::from(10u128),
+            },
+            PriceTier {
+                up_to: Some(100),
+                unit_price: Uint256::from(5u128),
+            },
+        ],
+        vec![
+            PriceTier {
+                up_to: None,
+                unit_price: Uint256::from(10u128),
+            },
+            PriceTier {
+                up_to: Some(100),
+                unit_price: Uint256::from(5u128),
+            },
+        ],
+    ];
+    for usage_tiers in invalid_tiers {
+        let res = contract::instantiate(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("creator", &[]),
+            ProductInstantiateMsg {
+                initial_amount: Uint256::zero(),
+                unit_amount: Uint256::from(100u128),
+                usage_tiers: Some(usage_tiers),
+                ..product_msg()
+            },
+        );
+        assert!(res.is_err());
+    }
+
+    // flat-rate products do not accept usage
+    setup(
+        deps.as_mut(),
+        env.clone(),
+        ProductInstantiateMsg {
+            initial_amount: Uint256::zero(),
+            unit_amount: Uint256::from(100u128),
+            ..product_msg()
+        },
+    );
+    match update_usage_reporters(deps.as_mut(), env.clone()) {
+        Err(ContractError::NotMetered {}) => {}
+        _ => panic!("Must return not metered error"),
+    }
+    match report_usage(deps.as_mut(), env, "reporter", 10) {
+        Err(ContractError::NotMetered {}) => {}
+        _ => panic!("Must return not metered error"),
+    }
+}
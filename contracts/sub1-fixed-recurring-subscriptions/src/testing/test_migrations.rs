@@ -0,0 +1,294 @@
+use super::product_msg;
+use crate::contract;
+use crate::error::ContractError;
+use crate::mock_querier::mock_dependencies;
+use crate::msg::{
+    ConfigResponse, ExecuteMsg, MigrateMsg, QueryMsg, StatsResponse, SubscriptionsResponse,
+};
+use crate::state::{subscriptions, Config, CONFIG, PLAN_SUBSCRIBERS, STATS};
+
+use cosmwasm_bignumber::Uint256;
+use cosmwasm_std::testing::{mock_env, mock_info};
+use cosmwasm_std::Addr;
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::{attr, from_binary, StdError};
+use cosmwasm_storage::singleton;
+use cw2::{get_contract_version, set_contract_version};
+
+#[test]
+fn instantiate_stores_contract_version() {
+    let mut deps = mock_dependencies(&[]);
+
+    let _res = contract::instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("creator", &[]),
+        product_msg(),
+    )
+    .unwrap();
+
+    let version = get_contract_version(&deps.storage).unwrap();
+    assert_eq!(
+        version.contract,
+        "crates.io:sub1-fixed-recurring-subscriptions"
+    );
+    assert_eq!(version.version, env!("CARGO_PKG_VERSION"));
+}
+
+#[test]
+fn migrate_legacy_storage() {
+    let mut deps = mock_dependencies(&[]);
+    let env = mock_env();
+
+    let _res = contract::instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        product_msg(),
+    )
+    .unwrap();
+    let _res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("subscriber", &[]),
+        ExecuteMsg::Subscribe {
+            plan_id: None,
+            coupon: None,
+            billing_cycles: None,
+            end_at: None,
+            beneficiary: None,
+            seats: None,
+        },
+    )
+    .unwrap();
+
+    // rewind the storage to the layout of a contract instantiated by a previous release
+    let config: Config = CONFIG.load(&deps.storage).unwrap();
+    CONFIG.remove(&mut deps.storage);
+    singleton(&mut deps.storage, b"config")
+        .save(&config)
+        .unwrap();
+    set_contract_version(&mut deps.storage, "crates.io:admin-core", "0.2.1").unwrap();
+
+    let res = contract::migrate(
+        deps.as_mut(),
+        env.clone(),
+        MigrateMsg {
+            accept_any_payer: Some(true),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("method", "migrate"),
+            attr("previous_version", "0.0.0"),
+            attr("version", env!("CARGO_PKG_VERSION")),
+            attr("accept_any_payer", "true"),
+        ]
+    );
+
+    let res = contract::query(deps.as_ref(), env.clone(), QueryMsg::Config {}).unwrap();
+    let value: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(value.receiver_address, "merchant");
+    assert!(value.accept_any_payer);
+
+    let res = contract::query(
+        deps.as_ref(),
+        env,
+        QueryMsg::Subscriptions {
+            start_after: None,
+            limit: None,
+            status: None,
+        },
+    )
+    .unwrap();
+    let value: SubscriptionsResponse = from_binary(&res).unwrap();
+    assert_eq!(value.subscriptions.len(), 1);
+
+    let version = get_contract_version(&deps.storage).unwrap();
+    assert_eq!(
+        version.contract,
+        "crates.io:sub1-fixed-recurring-subscriptions"
+    );
+    assert_eq!(version.version, env!("CARGO_PKG_VERSION"));
+}
+
+#[test]
+fn migrate_refuses_downgrade() {
+    let mut deps = mock_dependencies(&[]);
+    let env = mock_env();
+
+    let _res = contract::instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        product_msg(),
+    )
+    .unwrap();
+
+    // migrating to the same version is allowed
+    contract::migrate(
+        deps.as_mut(),
+        env.clone(),
+        MigrateMsg {
+            accept_any_payer: None,
+        },
+    )
+    .unwrap();
+
+    set_contract_version(
+        &mut deps.storage,
+        "crates.io:sub1-fixed-recurring-subscriptions",
+        "99.0.0",
+    )
+    .unwrap();
+    let err = contract::migrate(
+        deps.as_mut(),
+        env.clone(),
+        MigrateMsg {
+            accept_any_payer: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        StdError::generic_err(format!(
+            "Cannot migrate from version 99.0.0 to an older version {}",
+            env!("CARGO_PKG_VERSION")
+        ))
+        .to_string()
+    );
+
+    set_contract_version(&mut deps.storage, "crates.io:other-contract", "0.1.0").unwrap();
+    let err = contract::migrate(
+        deps.as_mut(),
+        env,
+        MigrateMsg {
+            accept_any_payer: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        StdError::generic_err("Cannot migrate from a different contract: crates.io:other-contract")
+            .to_string()
+    );
+}
+
+#[test]
+fn migrate_rebuilds_stats() {
+    let mut deps = mock_dependencies(&[]);
+    let env = mock_env();
+
+    let _res = contract::instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        product_msg(),
+    )
+    .unwrap();
+    let _res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("subscriber", &[]),
+        ExecuteMsg::Subscribe {
+            plan_id: None,
+            coupon: None,
+            billing_cycles: None,
+            end_at: None,
+            beneficiary: None,
+            seats: None,
+        },
+    )
+    .unwrap();
+
+    // rewind the storage to a contract instantiated before the running counters were introduced
+    STATS.remove(&mut deps.storage);
+    PLAN_SUBSCRIBERS.remove(&mut deps.storage, 0u64.into());
+    let subscriber = Addr::unchecked("subscriber");
+    let mut subscription = subscriptions().load(&deps.storage, &subscriber).unwrap();
+    subscription.lifetime_paid = Uint256::zero();
+    subscriptions()
+        .save(&mut deps.storage, &subscriber, &subscription)
+        .unwrap();
+    set_contract_version(
+        &mut deps.storage,
+        "crates.io:sub1-fixed-recurring-subscriptions",
+        "0.2.1",
+    )
+    .unwrap();
+
+    contract::migrate(
+        deps.as_mut(),
+        env.clone(),
+        MigrateMsg {
+            accept_any_payer: Some(false),
+        },
+    )
+    .unwrap();
+
+    let res = contract::query(deps.as_ref(), env.clone(), QueryMsg::Stats {}).unwrap();
+    let stats: StatsResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        stats,
+        StatsResponse {
+            total_subscribers: 1,
+            active_subscribers: 1,
+            cancelled_subscribers: 0,
+            gross_charged: Uint256::from(1000u128),
+            protocol_fees: Uint256::zero(),
+            monthly_recurring_revenue: Uint256::from(1000u128),
+        }
+    );
+
+    let subscription = subscriptions().load(&deps.storage, &subscriber).unwrap();
+    assert_eq!(subscription.lifetime_paid, Uint256::from(1000u128));
+
+    // later migrations keep the counters, which include the protocol fees that cannot be rebuilt
+    let mut stats = STATS.load(&deps.storage).unwrap();
+    stats.protocol_fees = Uint256::from(10u128);
+    STATS.save(&mut deps.storage, &stats).unwrap();
+    contract::migrate(
+        deps.as_mut(),
+        env,
+        MigrateMsg {
+            accept_any_payer: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(STATS.load(&deps.storage).unwrap(), stats);
+}
+
+#[test]
+fn migrate_requires_accept_any_payer() {
+    let mut deps = mock_dependencies(&[]);
+    let env = mock_env();
+
+    let _res = contract::instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        product_msg(),
+    )
+    .unwrap();
+    set_contract_version(
+        &mut deps.storage,
+        "crates.io:sub1-fixed-recurring-subscriptions",
+        "0.2.1",
+    )
+    .unwrap();
+
+    // products from before 0.3.0 accepted any payer, so the migration does not pick a default
+    let res = contract::migrate(
+        deps.as_mut(),
+        env,
+        MigrateMsg {
+            accept_any_payer: None,
+        },
+    );
+    match res {
+        Err(ContractError::AcceptAnyPayerNotSet {}) => {}
+        _ => panic!("Must return accept any payer not set error"),
+    }
+}
@@ -0,0 +1,239 @@
+use super::{
+    charge, product_msg, query_can_work, query_subscription, subscribe, ONE_DAY_IN_SECONDS,
+    THIRTY_DAYS_IN_SECONDS,
+};
+use crate::contract;
+use crate::error::ContractError;
+use crate::mock_querier::mock_dependencies;
+use crate::msg::ExecuteMsg;
+
+use cosmwasm_std::testing::{mock_env, mock_info};
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::{attr, DepsMut, Env, Response, Timestamp};
+use suberra_core::msg::ProductInstantiateMsg;
+
+fn pause(deps: DepsMut, env: Env, resume_at: u64) -> Result<Response, ContractError> {
+    contract::execute(
+        deps,
+        env,
+        mock_info("subscriber", &[]),
+        ExecuteMsg::PauseSubscription { resume_at },
+    )
+}
+
+#[test]
+fn pause_moves_interval_end() {
+    let mut deps = mock_dependencies(&[]);
+
+    let mut env = mock_env();
+    let start_timestamp = 1609459200;
+    env.block.time = Timestamp::from_seconds(start_timestamp); // set to 1 January 2021 00:00:00 GMT
+
+    // pauses of up to 30 days, twice a year
+    let _res = contract::instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        ProductInstantiateMsg {
+            max_pause_duration_hour: Some(720),
+            max_pauses_per_year: Some(2),
+            ..product_msg()
+        },
+    )
+    .unwrap();
+
+    subscribe(deps.as_mut(), env.clone(), "subscriber").unwrap();
+
+    // pauses for 10 days, 10 days into the interval
+    let paused_at = start_timestamp + 10 * ONE_DAY_IN_SECONDS;
+    let resume_at = paused_at + 10 * ONE_DAY_IN_SECONDS;
+    env.block.time = Timestamp::from_seconds(paused_at);
+    let res = pause(deps.as_mut(), env.clone(), resume_at).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("method", "execute_pause_subscription"),
+            attr("module_contract_address", "cosmos2contract"),
+            attr("subscriber", "subscriber"),
+            attr("resume_at", resume_at.to_string()),
+            attr(
+                "new_interval_end_at",
+                (start_timestamp + THIRTY_DAYS_IN_SECONDS + 10 * ONE_DAY_IN_SECONDS).to_string()
+            ),
+        ]
+    );
+
+    let subscription = query_subscription(deps.as_ref(), env.clone(), "subscriber").unwrap();
+    assert!(subscription.is_paused);
+    assert!(!subscription.is_active);
+    assert_eq!(subscription.resume_at, Some(resume_at));
+    assert_eq!(
+        subscription.interval_end_at,
+        start_timestamp + THIRTY_DAYS_IN_SECONDS + 10 * ONE_DAY_IN_SECONDS
+    );
+
+    // the subscription cannot be paused again or charged while paused
+    match pause(deps.as_mut(), env.clone(), resume_at + ONE_DAY_IN_SECONDS) {
+        Err(ContractError::SubscriptionPaused {}) => {}
+        _ => panic!("Must return subscription paused error"),
+    }
+    assert!(!query_can_work(deps.as_ref(), env.clone(), "subscriber"));
+    match charge(deps.as_mut(), env.clone(), "subscriber") {
+        Err(ContractError::SubscriptionPaused {}) => {}
+        _ => panic!("Must return subscription paused error"),
+    }
+
+    // the subscription is active again once it resumes, and is not due at the original interval end
+    env.block.time = Timestamp::from_seconds(resume_at);
+    let subscription = query_subscription(deps.as_ref(), env.clone(), "subscriber").unwrap();
+    assert!(!subscription.is_paused);
+    assert!(subscription.is_active);
+    assert_eq!(subscription.resume_at, None);
+
+    env.block.time = Timestamp::from_seconds(start_timestamp + THIRTY_DAYS_IN_SECONDS);
+    assert!(!query_can_work(deps.as_ref(), env.clone(), "subscriber"));
+
+    // the next charge is due at the moved interval end
+    env.block.time =
+        Timestamp::from_seconds(start_timestamp + THIRTY_DAYS_IN_SECONDS + 10 * ONE_DAY_IN_SECONDS);
+    assert!(query_can_work(deps.as_ref(), env.clone(), "subscriber"));
+    let res = charge(deps.as_mut(), env.clone(), "subscriber").unwrap();
+    assert_eq!(res.attributes[3], attr("amount", "1000"));
+
+    let subscription = query_subscription(deps.as_ref(), env, "subscriber").unwrap();
+    assert_eq!(
+        subscription.interval_end_at,
+        start_timestamp + 2 * THIRTY_DAYS_IN_SECONDS + 10 * ONE_DAY_IN_SECONDS
+    );
+}
+
+#[test]
+fn pause_limits() {
+    let mut deps = mock_dependencies(&[]);
+
+    let mut env = mock_env();
+    let start_timestamp = 1609459200;
+    env.block.time = Timestamp::from_seconds(start_timestamp); // set to 1 January 2021 00:00:00 GMT
+
+    // pausing is disabled by default
+    let _res = contract::instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        product_msg(),
+    )
+    .unwrap();
+
+    subscribe(deps.as_mut(), env.clone(), "subscriber").unwrap();
+
+    match pause(
+        deps.as_mut(),
+        env.clone(),
+        start_timestamp + ONE_DAY_IN_SECONDS,
+    ) {
+        Err(ContractError::InvalidParam {}) => {}
+        _ => panic!("Must return invalid param error"),
+    }
+
+    // the merchant allows pauses of up to 7 days, once a year
+    let _res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        ExecuteMsg::UpdateConfig {
+            receiver_address: None,
+            additional_grace_period_hour: None,
+            retry_schedule_hour: None,
+            max_pause_duration_hour: Some(168),
+            max_pauses_per_year: Some(1),
+            initial_amount: None,
+            uri: None,
+            revenue_splits: None,
+            accept_any_payer: None,
+        },
+    )
+    .unwrap();
+
+    // the pause must end in the future, within the maximum pause duration
+    match pause(deps.as_mut(), env.clone(), start_timestamp) {
+        Err(ContractError::InvalidParam {}) => {}
+        _ => panic!("Must return invalid param error"),
+    }
+    match pause(
+        deps.as_mut(),
+        env.clone(),
+        start_timestamp + 7 * ONE_DAY_IN_SECONDS + 1,
+    ) {
+        Err(ContractError::InvalidParam {}) => {}
+        _ => panic!("Must return invalid param error"),
+    }
+
+    // only the subscriber can pause its subscription
+    match contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        ExecuteMsg::PauseSubscription {
+            resume_at: start_timestamp + ONE_DAY_IN_SECONDS,
+        },
+    ) {
+        Err(ContractError::SubscriptionNotFound {}) => {}
+        _ => panic!("Must return subscription not found error"),
+    }
+
+    pause(
+        deps.as_mut(),
+        env.clone(),
+        start_timestamp + 7 * ONE_DAY_IN_SECONDS,
+    )
+    .unwrap();
+
+    // the second pause within a year is rejected
+    env.block.time = Timestamp::from_seconds(start_timestamp + 10 * ONE_DAY_IN_SECONDS);
+    match pause(
+        deps.as_mut(),
+        env.clone(),
+        start_timestamp + 11 * ONE_DAY_IN_SECONDS,
+    ) {
+        Err(ContractError::PauseLimitReached {}) => {}
+        _ => panic!("Must return pause limit reached error"),
+    }
+
+    // pauses cannot start while a payment is due
+    env.block.time = Timestamp::from_seconds(start_timestamp + 365 * ONE_DAY_IN_SECONDS);
+    match pause(
+        deps.as_mut(),
+        env.clone(),
+        start_timestamp + 366 * ONE_DAY_IN_SECONDS,
+    ) {
+        Err(ContractError::PaymentDue {}) => {}
+        _ => panic!("Must return payment due error"),
+    }
+
+    // a year after the first pause, the subscriber can pause again
+    let _res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        ExecuteMsg::ModifySubscriber {
+            new_created_at: None,
+            new_last_charged: None,
+            new_interval_end_at: Some(start_timestamp + 370 * ONE_DAY_IN_SECONDS),
+            subscriber: "subscriber".to_string(),
+        },
+    )
+    .unwrap();
+
+    pause(
+        deps.as_mut(),
+        env.clone(),
+        start_timestamp + 366 * ONE_DAY_IN_SECONDS,
+    )
+    .unwrap();
+
+    let subscription = query_subscription(deps.as_ref(), env, "subscriber").unwrap();
+    assert_eq!(
+        subscription.interval_end_at,
+        start_timestamp + 371 * ONE_DAY_IN_SECONDS
+    );
+}
@@ -0,0 +1,599 @@
+use super::{product_msg, ONE_DAY_IN_SECONDS, THIRTY_DAYS_IN_SECONDS};
+use crate::contract;
+use crate::error::ContractError;
+use crate::mock_querier::mock_dependencies;
+use crate::msg::{ExecuteMsg, PlanResponse, PlansResponse, QueryMsg, SubscriptionInfoResponse};
+
+use admin_core::error::ContractError as AdminCoreContractError;
+use cosmwasm_bignumber::Uint256;
+use cosmwasm_std::testing::{mock_env, mock_info};
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::{
+    attr, from_binary, to_binary, Coin, ReplyOn, SubMsg, Timestamp, Uint128, WasmMsg,
+};
+use suberra_core::msg::{ProductInstantiateMsg, SubWalletExecuteMsg};
+use suberra_core::subscriptions::PlanInfo;
+
+fn daily_plan(plan_id: u64) -> PlanInfo {
+    PlanInfo {
+        plan_id,
+        unit_amount: Uint256::from(2000u128),
+        initial_amount: Uint256::zero(),
+        unit_interval_hour: 24u64,
+        trial_period_hour: None,
+        billing_calendar: None,
+    }
+}
+
+#[test]
+fn instantiate_with_invalid_plans() {
+    let mut deps = mock_dependencies(&[]);
+    let info = mock_info("creator", &[]);
+
+    // plan_id 0 is reserved for the default plan
+    let res = contract::instantiate(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        ProductInstantiateMsg {
+            plans: Some(vec![daily_plan(0)]),
+            ..product_msg()
+        },
+    );
+    match res {
+        Err(AdminCoreContractError::InvalidParams {}) => {}
+        _ => panic!("Must return invalid params error"),
+    }
+
+    // plan_ids must be unique
+    let res = contract::instantiate(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ProductInstantiateMsg {
+            plans: Some(vec![daily_plan(1), daily_plan(1)]),
+            ..product_msg()
+        },
+    );
+    match res {
+        Err(AdminCoreContractError::InvalidParams {}) => {}
+        _ => panic!("Must return invalid params error"),
+    }
+}
+
+#[test]
+fn subscribe_and_charge_on_plan() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(100u128),
+    }]);
+
+    let mut env = mock_env();
+    let start_timestamp = 1609459200;
+    env.block.time = Timestamp::from_seconds(start_timestamp); // set to 1 January 2021 00:00:00 GMT
+
+    let info = mock_info("creator", &[]);
+    let _res = contract::instantiate(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        ProductInstantiateMsg {
+            plans: Some(vec![daily_plan(1)]),
+            ..product_msg()
+        },
+    )
+    .unwrap();
+
+    // subscribing to a plan that does not exist should fail
+    let subscriber = mock_info("subscriber", &[]);
+    let res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        subscriber.clone(),
+        ExecuteMsg::Subscribe {
+            plan_id: Some(2),
+            coupon: None,
+            billing_cycles: None,
+            end_at: None,
+            beneficiary: None,
+            seats: None,
+        },
+    );
+    match res {
+        Err(ContractError::PlanNotFound {}) => {}
+        _ => panic!("Must return plan not found error"),
+    }
+
+    // plan 1 has no initial amount, so no transfer should happen
+    let res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        subscriber.clone(),
+        ExecuteMsg::Subscribe {
+            plan_id: Some(1),
+            coupon: None,
+            billing_cycles: None,
+            end_at: None,
+            beneficiary: None,
+            seats: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 0);
+
+    let res = contract::query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::Subscription {
+            subscriber: "subscriber".to_string(),
+        },
+    )
+    .unwrap();
+    let value: Option<SubscriptionInfoResponse> = from_binary(&res).unwrap();
+    let value = value.unwrap();
+    assert_eq!(value.plan_id, 1);
+    assert_eq!(value.interval_end_at, start_timestamp + ONE_DAY_IN_SECONDS);
+
+    // subscriber is billed with the unit_amount and unit_interval of the plan
+    env.block.time = Timestamp::from_seconds(start_timestamp + ONE_DAY_IN_SECONDS);
+    let res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("charger", &[]),
+        ExecuteMsg::Charge {
+            payer_address: "subscriber".to_string(),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("method", "execute_charge"),
+            attr("module_contract_address", "cosmos2contract"),
+            attr("subscriber", "subscriber"),
+            attr("amount", "2000"),
+            attr("periods", "1"),
+            attr("charge_id", "1"),
+        ]
+    );
+
+    // the subscriber cancels and undo the cancellation, but cannot switch plans while doing so
+    contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        subscriber.clone(),
+        ExecuteMsg::Cancel {},
+    )
+    .unwrap();
+    let res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        subscriber.clone(),
+        ExecuteMsg::Subscribe {
+            plan_id: Some(0),
+            coupon: None,
+            billing_cycles: None,
+            end_at: None,
+            beneficiary: None,
+            seats: None,
+        },
+    );
+    match res {
+        Err(ContractError::ExistingSubscriptionFound {}) => {}
+        _ => panic!("Must return existing subscription found error"),
+    }
+    contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        subscriber,
+        ExecuteMsg::Subscribe {
+            plan_id: None,
+            coupon: None,
+            billing_cycles: None,
+            end_at: None,
+            beneficiary: None,
+            seats: None,
+        },
+    )
+    .unwrap();
+
+    // subscriber on the default plan is billed with the config
+    contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("subscriber2", &[]),
+        ExecuteMsg::Subscribe {
+            plan_id: None,
+            coupon: None,
+            billing_cycles: None,
+            end_at: None,
+            beneficiary: None,
+            seats: None,
+        },
+    )
+    .unwrap();
+
+    env.block.time =
+        Timestamp::from_seconds(start_timestamp + ONE_DAY_IN_SECONDS + THIRTY_DAYS_IN_SECONDS);
+    let res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("charger", &[]),
+        ExecuteMsg::Charge {
+            payer_address: "subscriber2".to_string(),
+        },
+    )
+    .unwrap();
+    assert_eq!(res.attributes[3], attr("amount", "1000"));
+}
+
+#[test]
+fn add_and_archive_plan() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_fee(0, Uint256::zero(), Uint256::from(100u64), 24u64);
+
+    let mut env = mock_env();
+    let start_timestamp = 1609459200;
+    env.block.time = Timestamp::from_seconds(start_timestamp); // set to 1 January 2021 00:00:00 GMT
+
+    let info = mock_info("creator", &[]);
+    let _res =
+        contract::instantiate(deps.as_mut(), env.clone(), info.clone(), product_msg()).unwrap();
+
+    // only owner or admins can add plans
+    let res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("anyone", &[]),
+        ExecuteMsg::AddPlan {
+            plan: daily_plan(1),
+        },
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    // plans must meet the minimums of the factory
+    let mut cheap_plan = daily_plan(1);
+    cheap_plan.unit_amount = Uint256::from(99u64);
+    let res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::AddPlan { plan: cheap_plan },
+    );
+    match res {
+        Err(ContractError::InvalidParam {}) => {}
+        _ => panic!("Must return invalid param error"),
+    }
+
+    let res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::AddPlan {
+            plan: daily_plan(1),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("method", "execute_add_plan"),
+            attr("plan_id", "1"),
+            attr("module_contract_address", "cosmos2contract"),
+        ]
+    );
+
+    // plan_id cannot be reused
+    let res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::AddPlan {
+            plan: daily_plan(1),
+        },
+    );
+    match res {
+        Err(ContractError::InvalidParam {}) => {}
+        _ => panic!("Must return invalid param error"),
+    }
+
+    let res = contract::query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::Plans {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let value: PlansResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        value.plans,
+        vec![
+            PlanResponse {
+                plan_id: 0,
+                unit_amount: Uint256::from(1000u128),
+                initial_amount: Uint256::from(1000u128),
+                unit_interval_seconds: THIRTY_DAYS_IN_SECONDS,
+                trial_period_seconds: 0,
+                is_archived: false,
+                billing_calendar: None,
+                price_changes: vec![],
+            },
+            PlanResponse {
+                plan_id: 1,
+                unit_amount: Uint256::from(2000u128),
+                initial_amount: Uint256::zero(),
+                unit_interval_seconds: ONE_DAY_IN_SECONDS,
+                trial_period_seconds: 0,
+                is_archived: false,
+                billing_calendar: None,
+                price_changes: vec![],
+            }
+        ]
+    );
+
+    // subscriber joins plan 1 before it is archived
+    contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("subscriber", &[]),
+        ExecuteMsg::Subscribe {
+            plan_id: Some(1),
+            coupon: None,
+            billing_cycles: None,
+            end_at: None,
+            beneficiary: None,
+            seats: None,
+        },
+    )
+    .unwrap();
+
+    // the default plan cannot be archived
+    let res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::ArchivePlan { plan_id: 0 },
+    );
+    match res {
+        Err(ContractError::InvalidParam {}) => {}
+        _ => panic!("Must return invalid param error"),
+    }
+
+    contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::ArchivePlan { plan_id: 1 },
+    )
+    .unwrap();
+
+    let res = contract::query(deps.as_ref(), env.clone(), QueryMsg::Plan { plan_id: 1 }).unwrap();
+    let value: Option<PlanResponse> = from_binary(&res).unwrap();
+    assert_eq!(value.unwrap().is_archived, true);
+
+    // archived plans do not accept new subscribers
+    let res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("subscriber2", &[]),
+        ExecuteMsg::Subscribe {
+            plan_id: Some(1),
+            coupon: None,
+            billing_cycles: None,
+            end_at: None,
+            beneficiary: None,
+            seats: None,
+        },
+    );
+    match res {
+        Err(ContractError::PlanArchived {}) => {}
+        _ => panic!("Must return plan archived error"),
+    }
+
+    // existing subscribers on the archived plan can still be charged
+    env.block.time = Timestamp::from_seconds(start_timestamp + ONE_DAY_IN_SECONDS);
+    let res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("charger", &[]),
+        ExecuteMsg::Charge {
+            payer_address: "subscriber".to_string(),
+        },
+    )
+    .unwrap();
+    assert_eq!(res.attributes[3], attr("amount", "2000"));
+}
+
+#[test]
+fn change_plan_with_proration() {
+    let mut deps = mock_dependencies(&[]);
+
+    // test with 1% fee
+    deps.querier
+        .with_fee(100, Uint256::zero(), Uint256::from(100u64), 24u64);
+
+    let mut env = mock_env();
+    let start_timestamp = 1609459200;
+    env.block.time = Timestamp::from_seconds(start_timestamp); // set to 1 January 2021 00:00:00 GMT
+
+    let premium_plan = PlanInfo {
+        plan_id: 1,
+        unit_amount: Uint256::from(3000u128),
+        initial_amount: Uint256::from(3000u128),
+        unit_interval_hour: 720u64,
+        trial_period_hour: None,
+        billing_calendar: None,
+    };
+    let basic_plan = PlanInfo {
+        plan_id: 2,
+        unit_amount: Uint256::from(500u128),
+        initial_amount: Uint256::zero(),
+        unit_interval_hour: 720u64,
+        trial_period_hour: None,
+        billing_calendar: None,
+    };
+    let _res = contract::instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        ProductInstantiateMsg {
+            plans: Some(vec![premium_plan, basic_plan]),
+            ..product_msg()
+        },
+    )
+    .unwrap();
+
+    let subscriber = mock_info("subscriber", &[]);
+    contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        subscriber.clone(),
+        ExecuteMsg::Subscribe {
+            plan_id: None,
+            coupon: None,
+            billing_cycles: None,
+            end_at: None,
+            beneficiary: None,
+            seats: None,
+        },
+    )
+    .unwrap();
+
+    // subscriber is already on the default plan
+    let res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        subscriber.clone(),
+        ExecuteMsg::ChangePlan { plan_id: 0 },
+    );
+    match res {
+        Err(ContractError::InvalidParam {}) => {}
+        _ => panic!("Must return invalid param error"),
+    }
+
+    // upgrade halfway through the interval. 500 of credit is applied against the price of 3000
+    let upgrade_timestamp = start_timestamp + THIRTY_DAYS_IN_SECONDS / 2;
+    env.block.time = Timestamp::from_seconds(upgrade_timestamp);
+    let res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        subscriber.clone(),
+        ExecuteMsg::ChangePlan { plan_id: 1 },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("method", "execute_change_plan"),
+            attr("module_contract_address", "cosmos2contract"),
+            attr("subscriber", "subscriber"),
+            attr("previous_plan_id", "0"),
+            attr("plan_id", "1"),
+            attr("credit", "500"),
+            attr("amount", "2500"),
+            attr("charge_id", "2"),
+        ]
+    );
+
+    // protocol fee is applied on the net amount
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg {
+                msg: WasmMsg::Execute {
+                    contract_addr: String::from("subscriber"),
+                    msg: to_binary(&SubWalletExecuteMsg::TransferAToken {
+                        recipient: String::from("fee_address"),
+                        amount: Uint128::from(25u128),
+                    })
+                    .unwrap(),
+                    funds: vec![],
+                }
+                .into(),
+                id: 0,
+                gas_limit: None,
+                reply_on: ReplyOn::Never
+            },
+            SubMsg {
+                msg: WasmMsg::Execute {
+                    contract_addr: String::from("subscriber"),
+                    msg: to_binary(&SubWalletExecuteMsg::TransferAToken {
+                        recipient: String::from("merchant"),
+                        amount: Uint128::from(2475u128),
+                    })
+                    .unwrap(),
+                    funds: vec![],
+                }
+                .into(),
+                id: 0,
+                gas_limit: None,
+                reply_on: ReplyOn::Never
+            }
+        ]
+    );
+
+    let res = contract::query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::Subscription {
+            subscriber: "subscriber".to_string(),
+        },
+    )
+    .unwrap();
+    let value: Option<SubscriptionInfoResponse> = from_binary(&res).unwrap();
+    let value = value.unwrap();
+    assert_eq!(value.plan_id, 1);
+    assert_eq!(value.last_charged, upgrade_timestamp);
+    assert_eq!(
+        value.interval_end_at,
+        upgrade_timestamp + THIRTY_DAYS_IN_SECONDS
+    );
+
+    // immediate downgrade. The credit of 3000 exceeds the price of 500, so nothing is charged
+    // and the remaining credit of 2500 extends the interval by 5 intervals of the basic plan
+    let res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        subscriber.clone(),
+        ExecuteMsg::ChangePlan { plan_id: 2 },
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 0);
+    assert_eq!(res.attributes[5], attr("credit", "3000"));
+    assert_eq!(res.attributes[6], attr("amount", "0"));
+
+    let res = contract::query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::Subscription {
+            subscriber: "subscriber".to_string(),
+        },
+    )
+    .unwrap();
+    let value: Option<SubscriptionInfoResponse> = from_binary(&res).unwrap();
+    let value = value.unwrap();
+    assert_eq!(value.plan_id, 2);
+    assert_eq!(
+        value.interval_end_at,
+        upgrade_timestamp + 6 * THIRTY_DAYS_IN_SECONDS
+    );
+
+    // plan cannot be changed while a payment is due
+    env.block.time = Timestamp::from_seconds(upgrade_timestamp + 6 * THIRTY_DAYS_IN_SECONDS);
+    let res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        subscriber,
+        ExecuteMsg::ChangePlan { plan_id: 0 },
+    );
+    match res {
+        Err(ContractError::PaymentDue {}) => {}
+        _ => panic!("Must return payment due error"),
+    }
+}
@@ -0,0 +1,289 @@
+use super::{env_at, product_msg, START_TIMESTAMP, THIRTY_DAYS_IN_SECONDS};
+use crate::contract;
+use crate::error::ContractError;
+use crate::mock_querier::mock_dependencies;
+use crate::msg::{ExecuteMsg, QueryMsg, SubscriptionInfoResponse};
+
+use cosmwasm_bignumber::Uint256;
+use cosmwasm_std::testing::{mock_info, MOCK_CONTRACT_ADDR};
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::{
+    attr, coins, from_binary, to_binary, BankMsg, Coin, CosmosMsg, Deps, DepsMut, Env, Response,
+    Uint128, WasmMsg,
+};
+use suberra_core::msg::{ProductInstantiateMsg, SubWalletExecuteMsg};
+
+fn subscribe(deps: DepsMut, env: Env, funds: &[Coin]) -> Result<Response, ContractError> {
+    contract::execute(
+        deps,
+        env,
+        mock_info("subscriber", funds),
+        ExecuteMsg::Subscribe {
+            plan_id: None,
+            coupon: None,
+            billing_cycles: None,
+            end_at: None,
+            beneficiary: None,
+            seats: None,
+        },
+    )
+}
+
+// charges the subscriber, and collects the payment as the contract would
+fn charge(mut deps: DepsMut, env: Env) -> Response {
+    let res = contract::execute(
+        deps.branch(),
+        env.clone(),
+        mock_info("charger", &[]),
+        ExecuteMsg::Charge {
+            payer_address: "subscriber".to_string(),
+        },
+    )
+    .unwrap();
+    let collect_payment_msg = match &res.messages[0].msg {
+        CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) => from_binary(msg).unwrap(),
+        _ => panic!("Must send a collect payment message"),
+    };
+
+    contract::execute(
+        deps,
+        env,
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        collect_payment_msg,
+    )
+    .unwrap()
+}
+
+fn refund_prepaid_balance(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    contract::execute(
+        deps,
+        env,
+        mock_info("anyone", &[]),
+        ExecuteMsg::RefundPrepaidBalance {
+            subscriber: "subscriber".to_string(),
+        },
+    )
+}
+
+fn query_prepaid_balance(deps: Deps, env: Env) -> Uint256 {
+    let res = contract::query(
+        deps,
+        env,
+        QueryMsg::Subscription {
+            subscriber: "subscriber".to_string(),
+        },
+    )
+    .unwrap();
+    let value: Option<SubscriptionInfoResponse> = from_binary(&res).unwrap();
+    value.unwrap().prepaid_balance
+}
+
+fn bank_send(to_address: &str, amount: u128) -> CosmosMsg {
+    CosmosMsg::Bank(BankMsg::Send {
+        to_address: to_address.to_string(),
+        amount: coins(amount, "uusd"),
+    })
+}
+
+#[test]
+fn prepaid_balance_pays_for_charges() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_subwallets("subwallet_factory", &[("subwallet", "user")]);
+
+    contract::instantiate(
+        deps.as_mut(),
+        env_at(START_TIMESTAMP),
+        mock_info("creator", &[]),
+        product_msg(),
+    )
+    .unwrap();
+
+    // only the billing denom can be prepaid
+    match subscribe(
+        deps.as_mut(),
+        env_at(START_TIMESTAMP),
+        &coins(1500, "uluna"),
+    ) {
+        Err(ContractError::InvalidPrepaidFunds {}) => {}
+        _ => panic!("Must return invalid prepaid funds error"),
+    }
+
+    // prepaid subscribers do not need a subwallet, and the initial amount is paid from the balance
+    let res = subscribe(deps.as_mut(), env_at(START_TIMESTAMP), &coins(1500, "uusd")).unwrap();
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(res.messages[0].msg, bank_send("merchant", 1000));
+    assert!(res.attributes.contains(&attr("prepaid_balance", "1500")));
+    assert_eq!(
+        query_prepaid_balance(deps.as_ref(), env_at(START_TIMESTAMP)),
+        Uint256::from(500u128)
+    );
+
+    let res = contract::execute(
+        deps.as_mut(),
+        env_at(START_TIMESTAMP),
+        mock_info("sponsor", &coins(600, "uusd")),
+        ExecuteMsg::TopUp {
+            subscriber: Some("subscriber".to_string()),
+        },
+    )
+    .unwrap();
+    assert!(res.attributes.contains(&attr("prepaid_balance", "1100")));
+
+    // the renewal is drawn from the balance
+    let renewed_at = START_TIMESTAMP + THIRTY_DAYS_IN_SECONDS;
+    let res = charge(deps.as_mut(), env_at(renewed_at));
+    assert_eq!(res.messages[0].msg, bank_send("merchant", 1000));
+    assert_eq!(
+        query_prepaid_balance(deps.as_ref(), env_at(renewed_at)),
+        Uint256::from(100u128)
+    );
+
+    // once the balance does not cover a charge, it pays for part of it and the rest is pulled from the subwallet of the payer
+    let res = charge(deps.as_mut(), env_at(renewed_at + THIRTY_DAYS_IN_SECONDS));
+    assert_eq!(res.messages.len(), 2);
+    assert_eq!(res.messages[0].msg, bank_send("merchant", 100));
+    assert_eq!(
+        res.messages[1].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "subscriber".to_string(),
+            funds: vec![],
+            msg: to_binary(&SubWalletExecuteMsg::transfer(
+                &None,
+                Uint128::from(900u128),
+                "merchant".to_string(),
+            ))
+            .unwrap(),
+        })
+    );
+    assert_eq!(
+        query_prepaid_balance(deps.as_ref(), env_at(renewed_at)),
+        Uint256::zero()
+    );
+}
+
+#[test]
+fn prepaid_payers_must_cover_the_first_charge() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_subwallets("subwallet_factory", &[("subwallet", "user")]);
+
+    contract::instantiate(
+        deps.as_mut(),
+        env_at(START_TIMESTAMP),
+        mock_info("creator", &[]),
+        ProductInstantiateMsg {
+            accept_any_payer: Some(false),
+            ..product_msg()
+        },
+    )
+    .unwrap();
+
+    // a deposit that does not cover the initial amount does not exempt the payer from having a subwallet
+    match subscribe(deps.as_mut(), env_at(START_TIMESTAMP), &coins(1, "uusd")) {
+        Err(ContractError::PayerNotSubwallet {}) => {}
+        _ => panic!("Must return payer not subwallet error"),
+    }
+
+    let res = subscribe(deps.as_mut(), env_at(START_TIMESTAMP), &coins(1000, "uusd")).unwrap();
+    assert_eq!(res.messages[0].msg, bank_send("merchant", 1000));
+
+    // the rest of a charge that the balance does not cover cannot be pulled from a payer that is not a subwallet,
+    // so the payment fails and the balance is kept
+    contract::execute(
+        deps.as_mut(),
+        env_at(START_TIMESTAMP),
+        mock_info("subscriber", &coins(999, "uusd")),
+        ExecuteMsg::TopUp { subscriber: None },
+    )
+    .unwrap();
+    match contract::execute(
+        deps.as_mut(),
+        env_at(START_TIMESTAMP + THIRTY_DAYS_IN_SECONDS),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        ExecuteMsg::CollectPayment {
+            subscriber: "subscriber".to_string(),
+            payer: "subscriber".to_string(),
+            amount: Uint256::from(1000u128),
+        },
+    ) {
+        Err(ContractError::PayerNotSubwallet {}) => {}
+        _ => panic!("Must return payer not subwallet error"),
+    }
+    assert_eq!(
+        query_prepaid_balance(deps.as_ref(), env_at(START_TIMESTAMP)),
+        Uint256::from(999u128)
+    );
+}
+
+#[test]
+fn cancel_refunds_prepaid_balance_once_interval_ends() {
+    let mut deps = mock_dependencies(&[]);
+
+    contract::instantiate(
+        deps.as_mut(),
+        env_at(START_TIMESTAMP),
+        mock_info("creator", &[]),
+        product_msg(),
+    )
+    .unwrap();
+
+    subscribe(deps.as_mut(), env_at(START_TIMESTAMP), &coins(3000, "uusd")).unwrap();
+
+    // the balance is kept until the paid interval ends, so that the cancellation can be undone
+    let res = contract::execute(
+        deps.as_mut(),
+        env_at(START_TIMESTAMP),
+        mock_info("subscriber", &[]),
+        ExecuteMsg::Cancel {},
+    )
+    .unwrap();
+    assert!(res.messages.is_empty());
+
+    let interval_end_at = START_TIMESTAMP + THIRTY_DAYS_IN_SECONDS;
+    match refund_prepaid_balance(deps.as_mut(), env_at(interval_end_at - 1)) {
+        Err(ContractError::PrepaidBalanceLocked {}) => {}
+        _ => panic!("Must return prepaid balance locked error"),
+    }
+
+    let res = refund_prepaid_balance(deps.as_mut(), env_at(interval_end_at)).unwrap();
+    assert_eq!(res.messages[0].msg, bank_send("subscriber", 2000));
+    assert_eq!(
+        query_prepaid_balance(deps.as_ref(), env_at(interval_end_at)),
+        Uint256::zero()
+    );
+
+    match refund_prepaid_balance(deps.as_mut(), env_at(interval_end_at)) {
+        Err(ContractError::NoPrepaidBalance {}) => {}
+        _ => panic!("Must return no prepaid balance error"),
+    }
+}
+
+#[test]
+fn cancel_after_interval_end_refunds_immediately() {
+    let mut deps = mock_dependencies(&[]);
+
+    contract::instantiate(
+        deps.as_mut(),
+        env_at(START_TIMESTAMP),
+        mock_info("creator", &[]),
+        product_msg(),
+    )
+    .unwrap();
+
+    subscribe(deps.as_mut(), env_at(START_TIMESTAMP), &coins(1800, "uusd")).unwrap();
+
+    // the subscription is in its grace period, so nothing of the balance is needed anymore
+    let res = contract::execute(
+        deps.as_mut(),
+        env_at(START_TIMESTAMP + THIRTY_DAYS_IN_SECONDS + 1),
+        mock_info("subscriber", &[]),
+        ExecuteMsg::Cancel {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages.last().unwrap().msg,
+        bank_send("subscriber", 800)
+    );
+    assert!(res.attributes.contains(&attr("prepaid_refund", "800")));
+}
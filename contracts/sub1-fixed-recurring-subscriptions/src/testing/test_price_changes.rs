@@ -0,0 +1,444 @@
+use super::{
+    charge, env_at, execute, fail_payment, product_msg, subscribe, ONE_DAY_IN_SECONDS,
+    START_TIMESTAMP, THIRTY_DAYS_IN_SECONDS,
+};
+use crate::contract;
+use crate::error::ContractError;
+use crate::mock_querier::mock_dependencies;
+use crate::msg::{ConfigResponse, ExecuteMsg, PlansResponse, PriceChangeResponse, QueryMsg};
+use crate::state::{Plan, PriceChange, SubscriptionInfo};
+
+use cosmwasm_bignumber::Uint256;
+use cosmwasm_std::testing::{mock_env, mock_info};
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::{attr, from_binary, Addr, Deps, DepsMut, Env, Response, Timestamp};
+use suberra_core::msg::ProductInstantiateMsg;
+use suberra_core::subscriptions::PlanInfo;
+
+fn query_config(deps: Deps) -> ConfigResponse {
+    let res = contract::query(deps, mock_env(), QueryMsg::Config {}).unwrap();
+    from_binary(&res).unwrap()
+}
+
+fn schedule_price_change(
+    deps: DepsMut,
+    env: Env,
+    sender: &str,
+    plan_id: Option<u64>,
+    new_unit_amount: u128,
+    effective_at: u64,
+) -> Result<Response, ContractError> {
+    contract::execute(
+        deps,
+        env,
+        mock_info(sender, &[]),
+        ExecuteMsg::SchedulePriceChange {
+            plan_id,
+            new_unit_amount: Uint256::from(new_unit_amount),
+            effective_at,
+        },
+    )
+}
+
+#[test]
+fn schedule_price_change_requires_notice() {
+    let mut deps = mock_dependencies(&[]);
+    let env = env_at(START_TIMESTAMP);
+
+    // subscribers must be given 30 days of notice
+    deps.querier
+        .with_fee(0, Uint256::zero(), Uint256::from(500u128), 24u64);
+    deps.querier.with_price_change_notice(720);
+
+    contract::instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        product_msg(),
+    )
+    .unwrap();
+
+    // only the owner or admins can change the price
+    let effective_at = START_TIMESTAMP + THIRTY_DAYS_IN_SECONDS;
+    match schedule_price_change(
+        deps.as_mut(),
+        env.clone(),
+        "anyone",
+        None,
+        1500,
+        effective_at,
+    ) {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    match schedule_price_change(
+        deps.as_mut(),
+        env.clone(),
+        "creator",
+        None,
+        1500,
+        effective_at - 1,
+    ) {
+        Err(ContractError::PriceChangeNoticeTooShort {}) => {}
+        _ => panic!("Must return price change notice too short error"),
+    }
+
+    // the new price must meet the minimum amount of the product factory
+    match schedule_price_change(
+        deps.as_mut(),
+        env.clone(),
+        "creator",
+        None,
+        499,
+        effective_at,
+    ) {
+        Err(ContractError::InvalidParam {}) => {}
+        _ => panic!("Must return invalid param error"),
+    }
+
+    let res = schedule_price_change(
+        deps.as_mut(),
+        env.clone(),
+        "creator",
+        None,
+        1500,
+        effective_at,
+    )
+    .unwrap();
+    assert!(res
+        .attributes
+        .contains(&attr("effective_at", effective_at.to_string())));
+
+    let config = query_config(deps.as_ref());
+    assert_eq!(config.unit_amount, Uint256::from(1000u128));
+    assert_eq!(
+        config.price_changes,
+        vec![PriceChangeResponse {
+            unit_amount: Uint256::from(1500u128),
+            effective_at,
+        }]
+    );
+
+    // a change that has not taken effect yet is replaced
+    let res = schedule_price_change(
+        deps.as_mut(),
+        env.clone(),
+        "creator",
+        None,
+        1200,
+        effective_at + THIRTY_DAYS_IN_SECONDS,
+    );
+    assert!(res.is_ok());
+
+    let config = query_config(deps.as_ref());
+    assert_eq!(config.unit_amount, Uint256::from(1000u128));
+    assert_eq!(
+        config.price_changes,
+        vec![PriceChangeResponse {
+            unit_amount: Uint256::from(1200u128),
+            effective_at: effective_at + THIRTY_DAYS_IN_SECONDS,
+        }]
+    );
+
+    // a change that has taken effect is kept when the next change is scheduled
+    let env = env_at(effective_at + THIRTY_DAYS_IN_SECONDS);
+    let res = schedule_price_change(
+        deps.as_mut(),
+        env,
+        "creator",
+        None,
+        2000,
+        effective_at + 2 * THIRTY_DAYS_IN_SECONDS,
+    );
+    assert!(res.is_ok());
+
+    let config = query_config(deps.as_ref());
+    assert_eq!(config.unit_amount, Uint256::from(1000u128));
+    assert_eq!(
+        config.price_changes,
+        vec![
+            PriceChangeResponse {
+                unit_amount: Uint256::from(1200u128),
+                effective_at: effective_at + THIRTY_DAYS_IN_SECONDS,
+            },
+            PriceChangeResponse {
+                unit_amount: Uint256::from(2000u128),
+                effective_at: effective_at + 2 * THIRTY_DAYS_IN_SECONDS,
+            }
+        ]
+    );
+}
+
+#[test]
+fn new_price_applies_from_first_interval_after_change() {
+    let mut deps = mock_dependencies(&[]);
+
+    contract::instantiate(
+        deps.as_mut(),
+        env_at(START_TIMESTAMP),
+        mock_info("creator", &[]),
+        product_msg(),
+    )
+    .unwrap();
+
+    // the price changes on day 45
+    subscribe(deps.as_mut(), env_at(START_TIMESTAMP), "early").unwrap();
+    let effective_at = START_TIMESTAMP + 45 * 24 * 60 * 60;
+    schedule_price_change(
+        deps.as_mut(),
+        env_at(START_TIMESTAMP),
+        "creator",
+        None,
+        1500,
+        effective_at,
+    )
+    .unwrap();
+
+    // "early" renews on days 30 and 60, and "late", which subscribes before the change, renews on day 70
+    let late_start = START_TIMESTAMP + 40 * 24 * 60 * 60;
+    subscribe(deps.as_mut(), env_at(late_start), "late").unwrap();
+
+    // the interval that starts on day 30 is billed the old price
+    let res = charge(
+        deps.as_mut(),
+        env_at(START_TIMESTAMP + THIRTY_DAYS_IN_SECONDS),
+        "early",
+    )
+    .unwrap();
+    assert_eq!(res.attributes[3], attr("amount", "1000"));
+
+    // every interval that starts after day 45 is billed the new price
+    let res = charge(
+        deps.as_mut(),
+        env_at(START_TIMESTAMP + 2 * THIRTY_DAYS_IN_SECONDS),
+        "early",
+    )
+    .unwrap();
+    assert_eq!(res.attributes[3], attr("amount", "1500"));
+
+    let res = charge(
+        deps.as_mut(),
+        env_at(late_start + THIRTY_DAYS_IN_SECONDS),
+        "late",
+    )
+    .unwrap();
+    assert_eq!(res.attributes[3], attr("amount", "1500"));
+}
+
+#[test]
+fn effective_price_change_is_kept_for_unbilled_intervals() {
+    let mut deps = mock_dependencies(&[]);
+
+    // a failed charge is retried after 25 days, within the grace period
+    contract::instantiate(
+        deps.as_mut(),
+        env_at(START_TIMESTAMP),
+        mock_info("creator", &[]),
+        ProductInstantiateMsg {
+            retry_schedule_hour: Some(vec![600]),
+            additional_grace_period_hour: Some(600),
+            ..product_msg()
+        },
+    )
+    .unwrap();
+
+    subscribe(deps.as_mut(), env_at(START_TIMESTAMP), "subscriber").unwrap();
+    schedule_price_change(
+        deps.as_mut(),
+        env_at(START_TIMESTAMP),
+        "creator",
+        None,
+        1500,
+        START_TIMESTAMP + 45 * ONE_DAY_IN_SECONDS,
+    )
+    .unwrap();
+
+    // the interval that starts on day 30 is not paid until the retry on day 55
+    let due_at = START_TIMESTAMP + THIRTY_DAYS_IN_SECONDS;
+    charge(deps.as_mut(), env_at(due_at), "subscriber").unwrap();
+    fail_payment(deps.as_mut(), env_at(due_at), 0).unwrap();
+
+    // the next change is scheduled once the change on day 45 has taken effect
+    schedule_price_change(
+        deps.as_mut(),
+        env_at(START_TIMESTAMP + 50 * ONE_DAY_IN_SECONDS),
+        "creator",
+        None,
+        2000,
+        START_TIMESTAMP + 80 * ONE_DAY_IN_SECONDS,
+    )
+    .unwrap();
+
+    // the interval that started on day 30 is still billed the price before the change on day 45
+    let res = charge(
+        deps.as_mut(),
+        env_at(START_TIMESTAMP + 55 * ONE_DAY_IN_SECONDS),
+        "subscriber",
+    )
+    .unwrap();
+    assert!(res.attributes.contains(&attr("amount", "1000")));
+
+    let res = charge(
+        deps.as_mut(),
+        env_at(START_TIMESTAMP + 2 * THIRTY_DAYS_IN_SECONDS),
+        "subscriber",
+    )
+    .unwrap();
+    assert!(res.attributes.contains(&attr("amount", "1500")));
+
+    let res = charge(
+        deps.as_mut(),
+        env_at(START_TIMESTAMP + 3 * THIRTY_DAYS_IN_SECONDS),
+        "subscriber",
+    )
+    .unwrap();
+    assert!(res.attributes.contains(&attr("amount", "2000")));
+}
+
+#[test]
+fn schedule_price_change_of_plan() {
+    let mut deps = mock_dependencies(&[]);
+
+    contract::instantiate(
+        deps.as_mut(),
+        env_at(START_TIMESTAMP),
+        mock_info("creator", &[]),
+        ProductInstantiateMsg {
+            plans: Some(vec![PlanInfo {
+                plan_id: 1,
+                unit_amount: Uint256::from(2000u128),
+                initial_amount: Uint256::zero(),
+                unit_interval_hour: 720u64,
+                trial_period_hour: None,
+                billing_calendar: None,
+            }]),
+            ..product_msg()
+        },
+    )
+    .unwrap();
+
+    subscribe(deps.as_mut(), env_at(START_TIMESTAMP), "default").unwrap();
+    execute(
+        deps.as_mut(),
+        env_at(START_TIMESTAMP),
+        "premium",
+        ExecuteMsg::Subscribe {
+            plan_id: Some(1),
+            coupon: None,
+            billing_cycles: None,
+            end_at: None,
+            beneficiary: None,
+            seats: None,
+        },
+    )
+    .unwrap();
+
+    let effective_at = START_TIMESTAMP + 15 * ONE_DAY_IN_SECONDS;
+    match schedule_price_change(
+        deps.as_mut(),
+        env_at(START_TIMESTAMP),
+        "creator",
+        Some(2),
+        2500,
+        effective_at,
+    ) {
+        Err(ContractError::PlanNotFound {}) => {}
+        _ => panic!("Must return plan not found error"),
+    }
+
+    let res = schedule_price_change(
+        deps.as_mut(),
+        env_at(START_TIMESTAMP),
+        "creator",
+        Some(1),
+        2500,
+        effective_at,
+    )
+    .unwrap();
+    assert!(res.attributes.contains(&attr("plan_id", "1")));
+
+    let res = contract::query(
+        deps.as_ref(),
+        env_at(START_TIMESTAMP),
+        QueryMsg::Plans {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let plans: PlansResponse = from_binary(&res).unwrap();
+    assert_eq!(plans.plans[0].price_changes, vec![]);
+    assert_eq!(
+        plans.plans[1].price_changes,
+        vec![PriceChangeResponse {
+            unit_amount: Uint256::from(2500u128),
+            effective_at,
+        }]
+    );
+
+    // only the subscribers of the plan are billed the new price
+    let renewed_at = START_TIMESTAMP + THIRTY_DAYS_IN_SECONDS;
+    let res = charge(deps.as_mut(), env_at(renewed_at), "premium").unwrap();
+    assert!(res.attributes.contains(&attr("amount", "2500")));
+
+    let res = charge(deps.as_mut(), env_at(renewed_at), "default").unwrap();
+    assert!(res.attributes.contains(&attr("amount", "1000")));
+}
+
+#[test]
+fn compute_amount_chargeable_across_price_changes() {
+    // charge 1000 every 100 seconds, 2000 for intervals that start from 100150, and 3000 for intervals that start from 100250
+    let plan = Plan {
+        plan_id: 0,
+        unit_amount: Uint256::from(1000u128),
+        initial_amount: Uint256::zero(),
+        unit_interval: Timestamp::from_seconds(100u64),
+        trial_period: 0,
+        is_archived: false,
+        billing_calendar: None,
+        price_changes: vec![
+            PriceChange {
+                unit_amount: Uint256::from(2000u128),
+                effective_at: Timestamp::from_seconds(100150),
+            },
+            PriceChange {
+                unit_amount: Uint256::from(3000u128),
+                effective_at: Timestamp::from_seconds(100250),
+            },
+        ],
+    };
+
+    let subscription_info = SubscriptionInfo {
+        owner: Addr::unchecked("subscriber"),
+        created_at: Timestamp::from_seconds(100000),
+        last_charged: Timestamp::from_seconds(100000),
+        interval_end_at: Timestamp::from_seconds(100100),
+        is_cancelled: false,
+        discount_per_interval: None,
+        plan_id: 0,
+        trial_end_at: None,
+        total_paid: Uint256::zero(),
+        retry_count: 0,
+        next_retry_at: None,
+        resume_at: None,
+        pause_history: vec![],
+        term_end_at: None,
+        usage: vec![],
+        payer: None,
+        lifetime_paid: Uint256::zero(),
+        seats: None,
+        seat_proration: Uint256::zero(),
+        seat_credit: Uint256::zero(),
+    };
+
+    // the intervals that start at 100100, 100200 and 100300 are each billed the price at their start
+    let amount_chargeable = contract::compute_amount_chargeable(
+        &plan,
+        &[],
+        &subscription_info,
+        Timestamp::from_seconds(100300),
+    );
+    assert_eq!(amount_chargeable.number_of_intervals, 3);
+    assert_eq!(amount_chargeable.amount, Uint256::from(6000u128));
+}
@@ -0,0 +1,261 @@
+use super::{charge, product_msg, subscribe, THIRTY_DAYS_IN_SECONDS};
+use crate::contract;
+use crate::error::ContractError;
+use crate::mock_querier::mock_dependencies;
+use crate::msg::{ExecuteMsg, PaymentResponse, PaymentsResponse, QueryMsg};
+
+use cosmwasm_bignumber::Uint256;
+use cosmwasm_std::testing::{mock_env, mock_info};
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::{
+    attr, coins, from_binary, BankMsg, Coin, ContractResult, CosmosMsg, Deps, DepsMut, Env, Event,
+    Reply, Response, Timestamp,
+};
+use suberra_core::msg::ProductInstantiateMsg;
+
+fn refund(
+    deps: DepsMut,
+    env: Env,
+    sender: &str,
+    funds: &[Coin],
+    amount: u128,
+    charge_id: u64,
+) -> Result<Response, ContractError> {
+    contract::execute(
+        deps,
+        env,
+        mock_info(sender, funds),
+        ExecuteMsg::Refund {
+            subscriber: "subscriber".to_string(),
+            amount: Uint256::from(amount),
+            charge_id,
+        },
+    )
+}
+
+fn query_payments(deps: Deps, env: Env) -> Vec<PaymentResponse> {
+    let res = contract::query(
+        deps,
+        env,
+        QueryMsg::Payments {
+            subscriber: "subscriber".to_string(),
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let value: PaymentsResponse = from_binary(&res).unwrap();
+    value.payments
+}
+
+#[test]
+fn payments_are_recorded_and_refunded() {
+    let mut deps = mock_dependencies(&[]);
+
+    let mut env = mock_env();
+    let start_timestamp = 1609459200;
+    env.block.time = Timestamp::from_seconds(start_timestamp); // set to 1 January 2021 00:00:00 GMT
+
+    let _res = contract::instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        ProductInstantiateMsg {
+            initial_amount: Uint256::from(500u128),
+            ..product_msg()
+        },
+    )
+    .unwrap();
+
+    subscribe(deps.as_mut(), env.clone(), "subscriber").unwrap();
+
+    let charged_at = start_timestamp + THIRTY_DAYS_IN_SECONDS;
+    env.block.time = Timestamp::from_seconds(charged_at);
+    let res = charge(deps.as_mut(), env.clone(), "subscriber").unwrap();
+    assert_eq!(res.attributes[5], attr("charge_id", "2"));
+
+    assert_eq!(
+        query_payments(deps.as_ref(), env.clone()),
+        vec![
+            PaymentResponse {
+                charge_id: 1,
+                payer: "subscriber".to_string(),
+                amount: Uint256::from(500u128),
+                paid_at: start_timestamp,
+                refunded_amount: Uint256::zero(),
+            },
+            PaymentResponse {
+                charge_id: 2,
+                payer: "subscriber".to_string(),
+                amount: Uint256::from(1000u128),
+                paid_at: charged_at,
+                refunded_amount: Uint256::zero(),
+            },
+        ]
+    );
+
+    // the receiver refunds part of the second charge to the subwallet of the subscriber
+    let res = refund(
+        deps.as_mut(),
+        env.clone(),
+        "merchant",
+        &coins(400, "uusd"),
+        400,
+        2,
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: "subscriber".to_string(),
+            amount: coins(400, "uusd"),
+        })
+    );
+    assert_eq!(
+        res.events,
+        vec![Event::new("refund").add_attributes(vec![
+            attr("subscriber", "subscriber"),
+            attr("payer", "subscriber"),
+            attr("charge_id", "2"),
+            attr("amount", "400"),
+            attr("refunded_amount", "400"),
+        ])]
+    );
+
+    // the rest of the charge can be refunded, but not more
+    match refund(
+        deps.as_mut(),
+        env.clone(),
+        "merchant",
+        &coins(601, "uusd"),
+        601,
+        2,
+    ) {
+        Err(ContractError::RefundExceedsPayment {}) => {}
+        _ => panic!("Must return refund exceeds payment error"),
+    }
+    refund(
+        deps.as_mut(),
+        env.clone(),
+        "merchant",
+        &coins(600, "uusd"),
+        600,
+        2,
+    )
+    .unwrap();
+
+    let payments = query_payments(deps.as_ref(), env);
+    assert_eq!(payments[0].refunded_amount, Uint256::zero());
+    assert_eq!(payments[1].refunded_amount, Uint256::from(1000u128));
+}
+
+#[test]
+fn failed_payments_are_not_recorded() {
+    let mut deps = mock_dependencies(&[]);
+
+    let mut env = mock_env();
+    let start_timestamp = 1609459200;
+    env.block.time = Timestamp::from_seconds(start_timestamp); // set to 1 January 2021 00:00:00 GMT
+
+    let _res = contract::instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        ProductInstantiateMsg {
+            initial_amount: Uint256::from(500u128),
+            ..product_msg()
+        },
+    )
+    .unwrap();
+
+    subscribe(deps.as_mut(), env.clone(), "subscriber").unwrap();
+
+    env.block.time = Timestamp::from_seconds(start_timestamp + THIRTY_DAYS_IN_SECONDS);
+    charge(deps.as_mut(), env.clone(), "subscriber").unwrap();
+    let _res = contract::reply(
+        deps.as_mut(),
+        env.clone(),
+        Reply {
+            id: 0,
+            result: ContractResult::Err("insufficient funds".to_string()),
+        },
+    )
+    .unwrap();
+
+    let payments = query_payments(deps.as_ref(), env.clone());
+    assert_eq!(payments.len(), 1);
+    assert_eq!(payments[0].charge_id, 1);
+
+    match refund(
+        deps.as_mut(),
+        env,
+        "merchant",
+        &coins(1000, "uusd"),
+        1000,
+        2,
+    ) {
+        Err(ContractError::PaymentNotFound {}) => {}
+        _ => panic!("Must return payment not found error"),
+    }
+}
+
+#[test]
+fn invalid_refund() {
+    let mut deps = mock_dependencies(&[]);
+    let env = mock_env();
+
+    let _res = contract::instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        ProductInstantiateMsg {
+            initial_amount: Uint256::from(500u128),
+            ..product_msg()
+        },
+    )
+    .unwrap();
+
+    subscribe(deps.as_mut(), env.clone(), "subscriber").unwrap();
+
+    // only the receiver can refund
+    match refund(
+        deps.as_mut(),
+        env.clone(),
+        "creator",
+        &coins(100, "uusd"),
+        100,
+        1,
+    ) {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    // the refunded amount must be sent in UST with the message
+    for funds in [coins(99, "uusd"), coins(100, "uluna"), vec![]] {
+        match refund(deps.as_mut(), env.clone(), "merchant", &funds, 100, 1) {
+            Err(ContractError::InvalidRefundFunds {}) => {}
+            _ => panic!("Must return invalid refund funds error"),
+        }
+    }
+
+    match refund(deps.as_mut(), env.clone(), "merchant", &[], 0, 1) {
+        Err(ContractError::InvalidParam {}) => {}
+        _ => panic!("Must return invalid param error"),
+    }
+
+    // amounts that do not fit a native transfer are checked against the payment instead of overflowing
+    let res = contract::execute(
+        deps.as_mut(),
+        env,
+        mock_info("merchant", &[]),
+        ExecuteMsg::Refund {
+            subscriber: "subscriber".to_string(),
+            amount: Uint256::from(u128::MAX) + Uint256::one(),
+            charge_id: 1,
+        },
+    );
+    match res {
+        Err(ContractError::RefundExceedsPayment {}) => {}
+        _ => panic!("Must return refund exceeds payment error"),
+    }
+}
@@ -0,0 +1,191 @@
+use super::{env_at, product_msg, START_TIMESTAMP};
+use crate::contract;
+use crate::error::ContractError;
+use crate::mock_querier::mock_dependencies;
+use crate::msg::{ConfigResponse, ExecuteMsg, QueryMsg};
+
+use cosmwasm_bignumber::Uint256;
+use cosmwasm_std::testing::mock_info;
+use cosmwasm_std::{from_binary, to_binary, CosmosMsg, WasmMsg};
+use suberra_core::msg::{ProductInstantiateMsg, SubWalletExecuteMsg};
+use suberra_core::subscriptions::RevenueSplitInfo;
+
+fn split(receiver: &str, share_bps: u64) -> RevenueSplitInfo {
+    RevenueSplitInfo {
+        receiver: receiver.to_string(),
+        share_bps,
+    }
+}
+
+fn transfer(recipient: &str, amount: u128) -> CosmosMsg {
+    CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: "subscriber".to_string(),
+        msg: to_binary(&SubWalletExecuteMsg::TransferAToken {
+            amount: amount.into(),
+            recipient: recipient.to_string(),
+        })
+        .unwrap(),
+        funds: vec![],
+    })
+}
+
+fn update_revenue_splits(revenue_splits: Vec<RevenueSplitInfo>) -> ExecuteMsg {
+    ExecuteMsg::UpdateConfig {
+        receiver_address: None,
+        additional_grace_period_hour: None,
+        retry_schedule_hour: None,
+        max_pause_duration_hour: None,
+        max_pauses_per_year: None,
+        initial_amount: None,
+        uri: None,
+        revenue_splits: Some(revenue_splits),
+        accept_any_payer: None,
+    }
+}
+
+#[test]
+fn payments_are_split_between_receivers() {
+    let mut deps = mock_dependencies(&[]);
+    let env = env_at(START_TIMESTAMP);
+
+    // 1% protocol fee
+    deps.querier
+        .with_fee(100, Uint256::zero(), Uint256::from(10_000_000u64), 24u64);
+
+    contract::instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        ProductInstantiateMsg {
+            initial_amount: Uint256::from(1001u128),
+            revenue_splits: Some(vec![split("creator_b", 3000), split("platform", 1500)]),
+            ..product_msg()
+        },
+    )
+    .unwrap();
+
+    let res = contract::execute(
+        deps.as_mut(),
+        env,
+        mock_info("subscriber", &[]),
+        ExecuteMsg::Subscribe {
+            plan_id: None,
+            coupon: None,
+            billing_cycles: None,
+            end_at: None,
+            beneficiary: None,
+            seats: None,
+        },
+    )
+    .unwrap();
+
+    // the merchant amount of 991 is split 30% and 15% to the co-receivers, rounded down.
+    // The primary receiver gets the remaining 55% and the rounding dust
+    let msgs: Vec<CosmosMsg> = res.messages.into_iter().map(|msg| msg.msg).collect();
+    assert_eq!(
+        msgs,
+        vec![
+            transfer("fee_address", 10),
+            transfer("merchant", 546),
+            transfer("creator_b", 297),
+            transfer("platform", 148),
+        ]
+    );
+}
+
+#[test]
+fn invalid_revenue_splits() {
+    let invalid_splits = vec![
+        vec![split("creator_b", 6000), split("platform", 4001)],
+        vec![split("creator_b", 0)],
+        vec![split("merchant", 1000)],
+        vec![split("creator_b", 1000), split("creator_b", 1000)],
+    ];
+
+    for revenue_splits in invalid_splits {
+        let mut deps = mock_dependencies(&[]);
+        contract::instantiate(
+            deps.as_mut(),
+            env_at(START_TIMESTAMP),
+            mock_info("creator", &[]),
+            ProductInstantiateMsg {
+                initial_amount: Uint256::from(1001u128),
+                revenue_splits: Some(revenue_splits),
+                ..product_msg()
+            },
+        )
+        .unwrap_err();
+    }
+}
+
+#[test]
+fn update_revenue_splits_by_admins() {
+    let mut deps = mock_dependencies(&[]);
+    let env = env_at(START_TIMESTAMP);
+    contract::instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        ProductInstantiateMsg {
+            initial_amount: Uint256::from(1001u128),
+            ..product_msg()
+        },
+    )
+    .unwrap();
+
+    let res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("anyone", &[]),
+        update_revenue_splits(vec![split("creator_b", 5000)]),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        update_revenue_splits(vec![split("creator_b", 10_001)]),
+    );
+    match res {
+        Err(ContractError::InvalidParam {}) => {}
+        _ => panic!("Must return invalid param error"),
+    }
+
+    contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        update_revenue_splits(vec![split("creator_b", 5000)]),
+    )
+    .unwrap();
+
+    let res = contract::query(deps.as_ref(), env.clone(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(config.revenue_splits, vec![split("creator_b", 5000)]);
+
+    // the co-receiver cannot become the primary receiver while it has a share
+    let res = contract::execute(
+        deps.as_mut(),
+        env,
+        mock_info("creator", &[]),
+        ExecuteMsg::UpdateConfig {
+            receiver_address: Some("creator_b".to_string()),
+            additional_grace_period_hour: None,
+            retry_schedule_hour: None,
+            max_pause_duration_hour: None,
+            max_pauses_per_year: None,
+            initial_amount: None,
+            uri: None,
+            revenue_splits: None,
+            accept_any_payer: None,
+        },
+    );
+    match res {
+        Err(ContractError::InvalidParam {}) => {}
+        _ => panic!("Must return invalid param error"),
+    }
+}
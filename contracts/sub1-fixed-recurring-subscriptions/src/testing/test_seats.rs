@@ -0,0 +1,334 @@
+use super::{
+    charge, env_at, execute, product_msg, query_subscription, ONE_DAY_IN_SECONDS, START_TIMESTAMP,
+    THIRTY_DAYS_IN_SECONDS,
+};
+use crate::contract;
+use crate::error::ContractError;
+use crate::mock_querier::mock_dependencies;
+use crate::msg::{ExecuteMsg, QueryMsg, StatsResponse, TeamResponse};
+
+use cosmwasm_bignumber::Uint256;
+use cosmwasm_std::testing::mock_info;
+use cosmwasm_std::{attr, from_binary, Deps, Env};
+
+fn subscribe_msg(seats: Option<u64>) -> ExecuteMsg {
+    ExecuteMsg::Subscribe {
+        plan_id: None,
+        coupon: None,
+        billing_cycles: None,
+        end_at: None,
+        beneficiary: None,
+        seats,
+    }
+}
+
+fn query_team(deps: Deps, env: Env, owner: &str) -> Option<TeamResponse> {
+    let res = contract::query(
+        deps,
+        env,
+        QueryMsg::Team {
+            owner: owner.to_string(),
+        },
+    )
+    .unwrap();
+    from_binary(&res).unwrap()
+}
+
+#[test]
+fn team_subscription_bills_every_seat() {
+    let mut deps = mock_dependencies(&[]);
+
+    contract::instantiate(
+        deps.as_mut(),
+        env_at(START_TIMESTAMP),
+        mock_info("creator", &[]),
+        product_msg(),
+    )
+    .unwrap();
+
+    for seats in [0u64, 101u64] {
+        match execute(
+            deps.as_mut(),
+            env_at(START_TIMESTAMP),
+            "owner",
+            subscribe_msg(Some(seats)),
+        ) {
+            Err(ContractError::InvalidParam {}) => {}
+            _ => panic!("Must return invalid param error"),
+        }
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        env_at(START_TIMESTAMP),
+        "owner",
+        subscribe_msg(Some(3)),
+    )
+    .unwrap();
+    assert!(res.attributes.contains(&attr("initial_amount", "3000")));
+
+    let subscription = query_subscription(deps.as_ref(), env_at(START_TIMESTAMP), "owner").unwrap();
+    assert_eq!(subscription.seats, Some(3));
+    assert_eq!(subscription.team_owner, None);
+
+    // every seat is counted in the monthly recurring revenue
+    let res = contract::query(deps.as_ref(), env_at(START_TIMESTAMP), QueryMsg::Stats {}).unwrap();
+    let stats: StatsResponse = from_binary(&res).unwrap();
+    assert_eq!(stats.active_subscribers, 1);
+    assert_eq!(stats.monthly_recurring_revenue, Uint256::from(3000u128));
+
+    let res = charge(
+        deps.as_mut(),
+        env_at(START_TIMESTAMP + THIRTY_DAYS_IN_SECONDS),
+        "owner",
+    )
+    .unwrap();
+    assert!(res.attributes.contains(&attr("amount", "3000")));
+
+    // individual subscriptions have no team
+    execute(
+        deps.as_mut(),
+        env_at(START_TIMESTAMP),
+        "individual",
+        subscribe_msg(None),
+    )
+    .unwrap();
+    match execute(
+        deps.as_mut(),
+        env_at(START_TIMESTAMP),
+        "individual",
+        ExecuteMsg::AssignSeat {
+            member: "member".to_string(),
+        },
+    ) {
+        Err(ContractError::NotTeamSubscription {}) => {}
+        _ => panic!("Must return not team subscription error"),
+    }
+    assert_eq!(
+        query_team(deps.as_ref(), env_at(START_TIMESTAMP), "individual"),
+        None
+    );
+}
+
+#[test]
+fn members_are_active_with_their_team() {
+    let mut deps = mock_dependencies(&[]);
+
+    contract::instantiate(
+        deps.as_mut(),
+        env_at(START_TIMESTAMP),
+        mock_info("creator", &[]),
+        product_msg(),
+    )
+    .unwrap();
+
+    execute(
+        deps.as_mut(),
+        env_at(START_TIMESTAMP),
+        "owner",
+        subscribe_msg(Some(2)),
+    )
+    .unwrap();
+
+    for member in ["alice", "bob"] {
+        execute(
+            deps.as_mut(),
+            env_at(START_TIMESTAMP),
+            "owner",
+            ExecuteMsg::AssignSeat {
+                member: member.to_string(),
+            },
+        )
+        .unwrap();
+    }
+
+    match execute(
+        deps.as_mut(),
+        env_at(START_TIMESTAMP),
+        "owner",
+        ExecuteMsg::AssignSeat {
+            member: "carol".to_string(),
+        },
+    ) {
+        Err(ContractError::NoSeatAvailable {}) => {}
+        _ => panic!("Must return no seat available error"),
+    }
+
+    // a member can only hold a seat of one team
+    execute(
+        deps.as_mut(),
+        env_at(START_TIMESTAMP),
+        "other_owner",
+        subscribe_msg(Some(1)),
+    )
+    .unwrap();
+    match execute(
+        deps.as_mut(),
+        env_at(START_TIMESTAMP),
+        "other_owner",
+        ExecuteMsg::AssignSeat {
+            member: "alice".to_string(),
+        },
+    ) {
+        Err(ContractError::SeatAlreadyAssigned {}) => {}
+        _ => panic!("Must return seat already assigned error"),
+    }
+
+    match execute(
+        deps.as_mut(),
+        env_at(START_TIMESTAMP),
+        "owner",
+        ExecuteMsg::UpdateSeats { seats: 1 },
+    ) {
+        Err(ContractError::SeatsBelowMembers {}) => {}
+        _ => panic!("Must return seats below members error"),
+    }
+
+    assert_eq!(
+        query_team(deps.as_ref(), env_at(START_TIMESTAMP), "owner"),
+        Some(TeamResponse {
+            owner: "owner".to_string(),
+            seats: 2,
+            members: vec!["alice".to_string(), "bob".to_string()],
+        })
+    );
+
+    let subscription = query_subscription(deps.as_ref(), env_at(START_TIMESTAMP), "alice").unwrap();
+    assert_eq!(subscription.subscriber, "alice".to_string());
+    assert_eq!(subscription.team_owner, Some("owner".to_string()));
+    assert_eq!(subscription.seats, Some(2));
+    assert!(subscription.is_active);
+
+    // unassigned members lose their access immediately
+    execute(
+        deps.as_mut(),
+        env_at(START_TIMESTAMP),
+        "owner",
+        ExecuteMsg::UnassignSeat {
+            member: "bob".to_string(),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        query_subscription(deps.as_ref(), env_at(START_TIMESTAMP), "bob"),
+        None
+    );
+    match execute(
+        deps.as_mut(),
+        env_at(START_TIMESTAMP),
+        "owner",
+        ExecuteMsg::UnassignSeat {
+            member: "bob".to_string(),
+        },
+    ) {
+        Err(ContractError::SeatNotAssigned {}) => {}
+        _ => panic!("Must return seat not assigned error"),
+    }
+
+    // members are no longer active once the cancelled team subscription ends
+    execute(
+        deps.as_mut(),
+        env_at(START_TIMESTAMP),
+        "owner",
+        ExecuteMsg::Cancel {},
+    )
+    .unwrap();
+    let expired_at = START_TIMESTAMP + THIRTY_DAYS_IN_SECONDS + ONE_DAY_IN_SECONDS;
+    let subscription = query_subscription(deps.as_ref(), env_at(expired_at), "alice").unwrap();
+    assert!(!subscription.is_active);
+}
+
+#[test]
+fn seat_changes_are_prorated_on_next_charge() {
+    let mut deps = mock_dependencies(&[]);
+
+    contract::instantiate(
+        deps.as_mut(),
+        env_at(START_TIMESTAMP),
+        mock_info("creator", &[]),
+        product_msg(),
+    )
+    .unwrap();
+
+    execute(
+        deps.as_mut(),
+        env_at(START_TIMESTAMP),
+        "owner",
+        subscribe_msg(Some(2)),
+    )
+    .unwrap();
+
+    // the added seat is billed for the 20 days left in the interval
+    let res = execute(
+        deps.as_mut(),
+        env_at(START_TIMESTAMP + 10 * ONE_DAY_IN_SECONDS),
+        "owner",
+        ExecuteMsg::UpdateSeats { seats: 3 },
+    )
+    .unwrap();
+    assert!(res.attributes.contains(&attr("seat_proration", "666")));
+
+    // the 2 removed seats are credited for the 15 days left, after cancelling out the amount owed for the added seat
+    let res = execute(
+        deps.as_mut(),
+        env_at(START_TIMESTAMP + 15 * ONE_DAY_IN_SECONDS),
+        "owner",
+        ExecuteMsg::UpdateSeats { seats: 1 },
+    )
+    .unwrap();
+    assert!(res.attributes.contains(&attr("seat_proration", "0")));
+    assert!(res.attributes.contains(&attr("seat_credit", "334")));
+
+    let res = charge(
+        deps.as_mut(),
+        env_at(START_TIMESTAMP + THIRTY_DAYS_IN_SECONDS),
+        "owner",
+    )
+    .unwrap();
+    assert!(res.attributes.contains(&attr("amount", "666")));
+
+    // a credit larger than the next charge renews the subscription without a payment, and carries over
+    execute(
+        deps.as_mut(),
+        env_at(START_TIMESTAMP),
+        "other_owner",
+        subscribe_msg(Some(3)),
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        env_at(START_TIMESTAMP),
+        "other_owner",
+        ExecuteMsg::UpdateSeats { seats: 1 },
+    )
+    .unwrap();
+
+    let renewed_at = START_TIMESTAMP + THIRTY_DAYS_IN_SECONDS;
+    let res = charge(deps.as_mut(), env_at(renewed_at), "other_owner").unwrap();
+    assert!(res.attributes.contains(&attr("amount", "0")));
+    assert!(res.messages.is_empty());
+
+    let subscription =
+        query_subscription(deps.as_ref(), env_at(renewed_at), "other_owner").unwrap();
+    assert!(subscription.is_active);
+    assert_eq!(
+        subscription.interval_end_at,
+        renewed_at + THIRTY_DAYS_IN_SECONDS
+    );
+
+    let res = charge(
+        deps.as_mut(),
+        env_at(renewed_at + THIRTY_DAYS_IN_SECONDS),
+        "other_owner",
+    )
+    .unwrap();
+    assert!(res.attributes.contains(&attr("amount", "0")));
+
+    let res = charge(
+        deps.as_mut(),
+        env_at(renewed_at + 2 * THIRTY_DAYS_IN_SECONDS),
+        "other_owner",
+    )
+    .unwrap();
+    assert!(res.attributes.contains(&attr("amount", "1000")));
+}
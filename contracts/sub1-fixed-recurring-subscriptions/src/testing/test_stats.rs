@@ -0,0 +1,211 @@
+use super::{env_at, product_msg, START_TIMESTAMP};
+use crate::contract;
+use crate::mock_querier::mock_dependencies;
+use crate::msg::{ExecuteMsg, QueryMsg, StatsResponse, SubscriptionInfoResponse};
+
+use cosmwasm_bignumber::Uint256;
+use cosmwasm_std::testing::mock_info;
+use cosmwasm_std::{from_binary, Deps, Env};
+use suberra_core::msg::ProductInstantiateMsg;
+use suberra_core::subscriptions::PlanInfo;
+
+fn stats_product_msg() -> ProductInstantiateMsg {
+    ProductInstantiateMsg {
+        plans: Some(vec![PlanInfo {
+            plan_id: 1,
+            unit_amount: Uint256::from(700u128),
+            initial_amount: Uint256::zero(),
+            unit_interval_hour: 168u64,
+            trial_period_hour: None,
+            billing_calendar: None,
+        }]),
+        ..product_msg()
+    }
+}
+
+fn subscribe_msg(plan_id: Option<u64>) -> ExecuteMsg {
+    ExecuteMsg::Subscribe {
+        plan_id,
+        coupon: None,
+        billing_cycles: None,
+        end_at: None,
+        beneficiary: None,
+        seats: None,
+    }
+}
+
+fn query_stats(deps: Deps, env: Env) -> StatsResponse {
+    let res = contract::query(deps, env, QueryMsg::Stats {}).unwrap();
+    from_binary(&res).unwrap()
+}
+
+#[test]
+fn stats_follow_subscriptions_and_payments() {
+    let mut deps = mock_dependencies(&[]);
+    let env = env_at(START_TIMESTAMP);
+
+    // 1% protocol fee
+    deps.querier
+        .with_fee(100, Uint256::zero(), Uint256::from(10_000_000u64), 24u64);
+
+    contract::instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        stats_product_msg(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        query_stats(deps.as_ref(), env.clone()),
+        StatsResponse {
+            total_subscribers: 0,
+            active_subscribers: 0,
+            cancelled_subscribers: 0,
+            gross_charged: Uint256::zero(),
+            protocol_fees: Uint256::zero(),
+            monthly_recurring_revenue: Uint256::zero(),
+        }
+    );
+
+    contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("subscriber_a", &[]),
+        subscribe_msg(None),
+    )
+    .unwrap();
+    contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("subscriber_b", &[]),
+        subscribe_msg(Some(1)),
+    )
+    .unwrap();
+
+    // the weekly plan of 700 is worth 3000 over 30 days
+    assert_eq!(
+        query_stats(deps.as_ref(), env.clone()),
+        StatsResponse {
+            total_subscribers: 2,
+            active_subscribers: 2,
+            cancelled_subscribers: 0,
+            gross_charged: Uint256::from(1000u128),
+            protocol_fees: Uint256::from(10u128),
+            monthly_recurring_revenue: Uint256::from(4000u128),
+        }
+    );
+
+    contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("subscriber_b", &[]),
+        ExecuteMsg::Cancel {},
+    )
+    .unwrap();
+
+    // renewals are counted once the payment is collected
+    contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(env.contract.address.as_str(), &[]),
+        ExecuteMsg::CollectPayment {
+            subscriber: "subscriber_a".to_string(),
+            payer: "subscriber_a".to_string(),
+            amount: Uint256::from(1000u128),
+        },
+    )
+    .unwrap();
+
+    assert_eq!(
+        query_stats(deps.as_ref(), env.clone()),
+        StatsResponse {
+            total_subscribers: 2,
+            active_subscribers: 1,
+            cancelled_subscribers: 1,
+            gross_charged: Uint256::from(2000u128),
+            protocol_fees: Uint256::from(20u128),
+            monthly_recurring_revenue: Uint256::from(1000u128),
+        }
+    );
+
+    contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        ExecuteMsg::RemoveSubscriber {
+            subscriber: "subscriber_a".to_string(),
+        },
+    )
+    .unwrap();
+
+    assert_eq!(
+        query_stats(deps.as_ref(), env),
+        StatsResponse {
+            total_subscribers: 1,
+            active_subscribers: 0,
+            cancelled_subscribers: 1,
+            gross_charged: Uint256::from(2000u128),
+            protocol_fees: Uint256::from(20u128),
+            monthly_recurring_revenue: Uint256::zero(),
+        }
+    );
+}
+
+#[test]
+fn lifetime_paid_is_kept_when_subscribing_again() {
+    let mut deps = mock_dependencies(&[]);
+    let mut env = env_at(START_TIMESTAMP);
+
+    contract::instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        stats_product_msg(),
+    )
+    .unwrap();
+
+    contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("subscriber", &[]),
+        subscribe_msg(None),
+    )
+    .unwrap();
+    contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("subscriber", &[]),
+        ExecuteMsg::Cancel {},
+    )
+    .unwrap();
+
+    // the cancelled subscription has expired, so subscribing again creates a new subscription
+    env.block.time = env.block.time.plus_seconds(31 * 24 * 60 * 60);
+    contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("subscriber", &[]),
+        subscribe_msg(None),
+    )
+    .unwrap();
+
+    let res = contract::query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::Subscription {
+            subscriber: "subscriber".to_string(),
+        },
+    )
+    .unwrap();
+    let subscription: Option<SubscriptionInfoResponse> = from_binary(&res).unwrap();
+    let subscription = subscription.unwrap();
+    assert_eq!(subscription.total_paid, Uint256::from(1000u128));
+    assert_eq!(subscription.lifetime_paid, Uint256::from(2000u128));
+
+    let stats = query_stats(deps.as_ref(), env);
+    assert_eq!(stats.total_subscribers, 1);
+    assert_eq!(stats.active_subscribers, 1);
+    assert_eq!(stats.cancelled_subscribers, 0);
+    assert_eq!(stats.gross_charged, Uint256::from(2000u128));
+}
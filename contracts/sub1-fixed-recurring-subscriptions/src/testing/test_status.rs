@@ -0,0 +1,398 @@
+use super::{
+    env_at, execute, product_msg, query_subscription, DEFAULT_GRACE_PERIOD, ONE_DAY_IN_SECONDS,
+    SEVEN_DAYS_IN_SECONDS, START_TIMESTAMP, THIRTY_DAYS_IN_SECONDS,
+};
+use crate::contract;
+use crate::mock_querier::mock_dependencies;
+use crate::msg::{ExecuteMsg, QueryMsg, SubscriptionsResponse};
+use crate::state::SubscriptionStatus;
+
+use cosmwasm_std::testing::mock_info;
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::{from_binary, Deps, DepsMut, Env};
+use suberra_core::msg::ProductInstantiateMsg;
+
+fn query_subscriptions(
+    deps: Deps,
+    env: Env,
+    start_after: Option<&str>,
+    limit: Option<u32>,
+    status: Option<SubscriptionStatus>,
+) -> Vec<String> {
+    let res = contract::query(
+        deps,
+        env,
+        QueryMsg::Subscriptions {
+            start_after: start_after.map(|subscriber| subscriber.to_string()),
+            limit,
+            status,
+        },
+    )
+    .unwrap();
+    let value: SubscriptionsResponse = from_binary(&res).unwrap();
+    value
+        .subscriptions
+        .into_iter()
+        .map(|subscription| subscription.subscriber)
+        .collect()
+}
+
+fn subscribe(deps: DepsMut, env: Env, subscriber: &str, billing_cycles: Option<u64>) {
+    let _res = contract::execute(
+        deps,
+        env,
+        mock_info(subscriber, &[]),
+        ExecuteMsg::Subscribe {
+            plan_id: None,
+            coupon: None,
+            billing_cycles,
+            end_at: None,
+            beneficiary: None,
+            seats: None,
+        },
+    )
+    .unwrap();
+}
+
+#[test]
+fn status_moves_from_trial_to_pending_payment_to_lapsed() {
+    let mut deps = mock_dependencies(&[]);
+
+    let _res = contract::instantiate(
+        deps.as_mut(),
+        env_at(START_TIMESTAMP),
+        mock_info("creator", &[]),
+        ProductInstantiateMsg {
+            max_pause_duration_hour: Some(720),
+            max_pauses_per_year: Some(2),
+            trial_period_hour: Some(168),
+            ..product_msg()
+        },
+    )
+    .unwrap();
+
+    subscribe(deps.as_mut(), env_at(START_TIMESTAMP), "subscriber", None);
+
+    // nothing has been paid during the trial
+    let subscription =
+        query_subscription(deps.as_ref(), env_at(START_TIMESTAMP), "subscriber").unwrap();
+    assert_eq!(subscription.status, SubscriptionStatus::InTrial);
+    assert_eq!(subscription.is_active, true);
+
+    // the first payment is due once the trial has ended, and has not been collected yet
+    let trial_end_at = START_TIMESTAMP + SEVEN_DAYS_IN_SECONDS;
+    let subscription =
+        query_subscription(deps.as_ref(), env_at(trial_end_at), "subscriber").unwrap();
+    assert_eq!(
+        subscription.status,
+        SubscriptionStatus::PendingInitialPayment
+    );
+
+    let subscription =
+        query_subscription(deps.as_ref(), env_at(trial_end_at + 1), "subscriber").unwrap();
+    assert_eq!(
+        subscription.status,
+        SubscriptionStatus::PendingInitialPayment
+    );
+    assert_eq!(subscription.is_active, true);
+
+    let lapsed_at = trial_end_at + DEFAULT_GRACE_PERIOD + 1;
+    let subscription = query_subscription(deps.as_ref(), env_at(lapsed_at), "subscriber").unwrap();
+    assert_eq!(subscription.status, SubscriptionStatus::Lapsed);
+    assert_eq!(subscription.is_active, false);
+}
+
+#[test]
+fn status_of_cancelled_paused_and_completed_subscriptions() {
+    let mut deps = mock_dependencies(&[]);
+
+    let _res = contract::instantiate(
+        deps.as_mut(),
+        env_at(START_TIMESTAMP),
+        mock_info("creator", &[]),
+        ProductInstantiateMsg {
+            max_pause_duration_hour: Some(720),
+            max_pauses_per_year: Some(2),
+            ..product_msg()
+        },
+    )
+    .unwrap();
+
+    subscribe(deps.as_mut(), env_at(START_TIMESTAMP), "cancelled", None);
+    subscribe(deps.as_mut(), env_at(START_TIMESTAMP), "paused", None);
+    subscribe(
+        deps.as_mut(),
+        env_at(START_TIMESTAMP),
+        "fixed_term",
+        Some(1),
+    );
+
+    // cancelled subscriptions stay active until the end of the period, and have no grace period
+    execute(
+        deps.as_mut(),
+        env_at(START_TIMESTAMP),
+        "cancelled",
+        ExecuteMsg::Cancel {},
+    )
+    .unwrap();
+    let subscription =
+        query_subscription(deps.as_ref(), env_at(START_TIMESTAMP), "cancelled").unwrap();
+    assert_eq!(
+        subscription.status,
+        SubscriptionStatus::CancelledUntilPeriodEnd
+    );
+    assert_eq!(subscription.is_active, true);
+
+    let interval_end_at = START_TIMESTAMP + THIRTY_DAYS_IN_SECONDS;
+    let subscription =
+        query_subscription(deps.as_ref(), env_at(interval_end_at + 1), "cancelled").unwrap();
+    assert_eq!(subscription.status, SubscriptionStatus::Lapsed);
+
+    // paused subscriptions are not active until they resume
+    let resume_at = START_TIMESTAMP + 10 * ONE_DAY_IN_SECONDS;
+    execute(
+        deps.as_mut(),
+        env_at(START_TIMESTAMP),
+        "paused",
+        ExecuteMsg::PauseSubscription { resume_at },
+    )
+    .unwrap();
+    let subscription =
+        query_subscription(deps.as_ref(), env_at(START_TIMESTAMP), "paused").unwrap();
+    assert_eq!(subscription.status, SubscriptionStatus::Paused);
+    assert_eq!(subscription.is_active, false);
+
+    let subscription = query_subscription(deps.as_ref(), env_at(resume_at), "paused").unwrap();
+    assert_eq!(subscription.status, SubscriptionStatus::Active);
+
+    // fixed-term subscriptions are completed once the last billing cycle has ended
+    let subscription =
+        query_subscription(deps.as_ref(), env_at(interval_end_at), "fixed_term").unwrap();
+    assert_eq!(subscription.status, SubscriptionStatus::Completed);
+    assert_eq!(subscription.is_active, false);
+    assert_eq!(subscription.is_completed, true);
+}
+
+#[test]
+fn subscriptions_filtered_by_status() {
+    let mut deps = mock_dependencies(&[]);
+    let env = env_at(START_TIMESTAMP);
+
+    let _res = contract::instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        ProductInstantiateMsg {
+            max_pause_duration_hour: Some(720),
+            max_pauses_per_year: Some(2),
+            ..product_msg()
+        },
+    )
+    .unwrap();
+
+    for subscriber in ["subscriber1", "subscriber2", "subscriber3", "subscriber4"].iter() {
+        subscribe(deps.as_mut(), env.clone(), subscriber, None);
+    }
+    for subscriber in ["subscriber1", "subscriber3"].iter() {
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            subscriber,
+            ExecuteMsg::Cancel {},
+        )
+        .unwrap();
+    }
+
+    assert_eq!(
+        query_subscriptions(deps.as_ref(), env.clone(), None, None, None).len(),
+        4
+    );
+    assert_eq!(
+        query_subscriptions(
+            deps.as_ref(),
+            env.clone(),
+            None,
+            None,
+            Some(SubscriptionStatus::Active)
+        ),
+        vec!["subscriber2".to_string(), "subscriber4".to_string()]
+    );
+
+    // only the cancelled subscriptions are read
+    assert_eq!(
+        query_subscriptions(
+            deps.as_ref(),
+            env.clone(),
+            None,
+            Some(1),
+            Some(SubscriptionStatus::CancelledUntilPeriodEnd)
+        ),
+        vec!["subscriber1".to_string()]
+    );
+    assert_eq!(
+        query_subscriptions(
+            deps.as_ref(),
+            env,
+            None,
+            Some(1),
+            Some(SubscriptionStatus::Lapsed)
+        ),
+        Vec::<String>::new()
+    );
+}
+
+#[test]
+fn subscriptions_filtered_by_status_are_paged() {
+    let mut deps = mock_dependencies(&[]);
+    let env = env_at(START_TIMESTAMP);
+
+    let _res = contract::instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        ProductInstantiateMsg {
+            max_pause_duration_hour: Some(720),
+            max_pauses_per_year: Some(2),
+            ..product_msg()
+        },
+    )
+    .unwrap();
+
+    for subscriber in ["subscriber1", "subscriber2", "subscriber3", "subscriber4"].iter() {
+        subscribe(deps.as_mut(), env.clone(), subscriber, None);
+    }
+
+    // paused and active subscriptions share the same billing status in the index
+    for subscriber in ["subscriber1", "subscriber2"].iter() {
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            subscriber,
+            ExecuteMsg::PauseSubscription {
+                resume_at: START_TIMESTAMP + SEVEN_DAYS_IN_SECONDS,
+            },
+        )
+        .unwrap();
+    }
+
+    // every page is filled with subscriptions of the status until the end of the index
+    for status in [SubscriptionStatus::Active, SubscriptionStatus::Paused].iter() {
+        let first_page = query_subscriptions(
+            deps.as_ref(),
+            env.clone(),
+            None,
+            Some(1),
+            Some(status.clone()),
+        );
+        assert_eq!(first_page.len(), 1);
+
+        let second_page = query_subscriptions(
+            deps.as_ref(),
+            env.clone(),
+            Some(&first_page[0]),
+            Some(1),
+            Some(status.clone()),
+        );
+        assert_eq!(second_page.len(), 1);
+        assert_ne!(first_page, second_page);
+
+        let last_page = query_subscriptions(
+            deps.as_ref(),
+            env.clone(),
+            Some(&second_page[0]),
+            Some(1),
+            Some(status.clone()),
+        );
+        assert!(last_page.is_empty());
+    }
+}
+
+#[test]
+fn removed_subscriptions_are_reported_until_subscribing_again() {
+    let mut deps = mock_dependencies(&[]);
+    let env = env_at(START_TIMESTAMP);
+
+    let _res = contract::instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        product_msg(),
+    )
+    .unwrap();
+
+    for subscriber in ["subscriber1", "subscriber2", "subscriber3"].iter() {
+        subscribe(deps.as_mut(), env.clone(), subscriber, None);
+    }
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        "creator",
+        ExecuteMsg::RemoveSubscriber {
+            subscriber: "subscriber2".to_string(),
+        },
+    )
+    .unwrap();
+
+    let subscription = query_subscription(deps.as_ref(), env.clone(), "subscriber2").unwrap();
+    assert_eq!(subscription.status, SubscriptionStatus::Removed);
+    assert!(!subscription.is_active);
+    assert_eq!(
+        query_subscriptions(
+            deps.as_ref(),
+            env.clone(),
+            None,
+            None,
+            Some(SubscriptionStatus::Removed)
+        ),
+        vec!["subscriber2".to_string()]
+    );
+
+    // paginated through the index
+    assert_eq!(
+        query_subscriptions(
+            deps.as_ref(),
+            env.clone(),
+            None,
+            Some(1),
+            Some(SubscriptionStatus::Active)
+        ),
+        vec!["subscriber1".to_string()]
+    );
+    assert_eq!(
+        query_subscriptions(
+            deps.as_ref(),
+            env.clone(),
+            Some("subscriber1"),
+            Some(1),
+            Some(SubscriptionStatus::Active)
+        ),
+        vec!["subscriber3".to_string()]
+    );
+
+    // the limit applies to the subscriptions read, so a page can hold fewer subscriptions with the status
+    assert_eq!(
+        query_subscriptions(
+            deps.as_ref(),
+            env.clone(),
+            None,
+            Some(2),
+            Some(SubscriptionStatus::Lapsed)
+        ),
+        Vec::<String>::new()
+    );
+
+    // subscribing again replaces the removed subscription
+    subscribe(deps.as_mut(), env.clone(), "subscriber2", None);
+    let subscription = query_subscription(deps.as_ref(), env.clone(), "subscriber2").unwrap();
+    assert_eq!(subscription.status, SubscriptionStatus::Active);
+    assert_eq!(
+        query_subscriptions(
+            deps.as_ref(),
+            env,
+            None,
+            None,
+            Some(SubscriptionStatus::Removed)
+        ),
+        Vec::<String>::new()
+    );
+}
@@ -0,0 +1,155 @@
+use super::product_msg;
+use crate::contract;
+use crate::error::ContractError;
+use crate::mock_querier::mock_dependencies;
+use crate::msg::{ConfigResponse, ExecuteMsg, QueryMsg};
+
+use cosmwasm_std::testing::{mock_env, mock_info};
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::{attr, from_binary, DepsMut, Env, Response};
+use suberra_core::msg::ProductInstantiateMsg;
+
+fn subscribe(
+    deps: DepsMut,
+    env: Env,
+    sender: &str,
+    beneficiary: Option<String>,
+) -> Result<Response, ContractError> {
+    contract::execute(
+        deps,
+        env,
+        mock_info(sender, &[]),
+        ExecuteMsg::Subscribe {
+            plan_id: None,
+            coupon: None,
+            billing_cycles: None,
+            end_at: None,
+            beneficiary,
+            seats: None,
+        },
+    )
+}
+
+#[test]
+fn only_subwallets_can_pay() {
+    let mut deps = mock_dependencies(&[]);
+    let env = mock_env();
+    deps.querier.with_subwallets(
+        "subwallet_factory",
+        &[("subwallet", "user"), ("sponsor_subwallet", "sponsor")],
+    );
+
+    contract::instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        ProductInstantiateMsg {
+            accept_any_payer: None,
+            ..product_msg()
+        },
+    )
+    .unwrap();
+
+    // plain accounts cannot be charged, so they cannot subscribe
+    match subscribe(deps.as_mut(), env.clone(), "user", None) {
+        Err(ContractError::PayerNotSubwallet {}) => {}
+        _ => panic!("Must return payer not subwallet error"),
+    }
+
+    subscribe(deps.as_mut(), env.clone(), "subwallet", None).unwrap();
+
+    // the sponsor pays for the gift, and the beneficiary does not need to be a subwallet
+    subscribe(
+        deps.as_mut(),
+        env.clone(),
+        "sponsor_subwallet",
+        Some("friend".to_string()),
+    )
+    .unwrap();
+
+    // until the beneficiary takes over the payment
+    match contract::execute(
+        deps.as_mut(),
+        env,
+        mock_info("friend", &[]),
+        ExecuteMsg::TakeOverPayment {},
+    ) {
+        Err(ContractError::PayerNotSubwallet {}) => {}
+        _ => panic!("Must return payer not subwallet error"),
+    }
+}
+
+#[test]
+fn products_can_accept_any_payer() {
+    let mut deps = mock_dependencies(&[]);
+    let env = mock_env();
+    deps.querier
+        .with_subwallets("subwallet_factory", &[("subwallet", "user")]);
+
+    contract::instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        ProductInstantiateMsg {
+            accept_any_payer: Some(true),
+            ..product_msg()
+        },
+    )
+    .unwrap();
+
+    subscribe(deps.as_mut(), env.clone(), "user", None).unwrap();
+
+    // the owner can turn the check back on
+    let res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        ExecuteMsg::UpdateConfig {
+            receiver_address: None,
+            additional_grace_period_hour: None,
+            retry_schedule_hour: None,
+            max_pause_duration_hour: None,
+            max_pauses_per_year: None,
+            initial_amount: None,
+            uri: None,
+            revenue_splits: None,
+            accept_any_payer: Some(false),
+        },
+    )
+    .unwrap();
+    assert!(res
+        .attributes
+        .contains(&attr("new_accept_any_payer", "false")));
+
+    let res = contract::query(deps.as_ref(), env.clone(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(config.accept_any_payer, false);
+
+    match subscribe(deps.as_mut(), env, "other_user", None) {
+        Err(ContractError::PayerNotSubwallet {}) => {}
+        _ => panic!("Must return payer not subwallet error"),
+    }
+}
+
+#[test]
+fn payers_are_rejected_without_subwallet_factory() {
+    let mut deps = mock_dependencies(&[]);
+    let env = mock_env();
+
+    contract::instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        ProductInstantiateMsg {
+            accept_any_payer: None,
+            ..product_msg()
+        },
+    )
+    .unwrap();
+
+    // the payer cannot be checked if the product factory does not know the subwallet factory
+    match subscribe(deps.as_mut(), env, "subwallet", None) {
+        Err(ContractError::SubwalletFactoryNotSet {}) => {}
+        _ => panic!("Must return subwallet factory not set error"),
+    }
+}
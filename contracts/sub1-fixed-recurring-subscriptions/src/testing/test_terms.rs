@@ -0,0 +1,238 @@
+use super::{
+    charge, product_msg, query_can_work, query_subscription, ONE_DAY_IN_SECONDS,
+    THIRTY_DAYS_IN_SECONDS,
+};
+use crate::contract;
+use crate::error::ContractError;
+use crate::mock_querier::mock_dependencies;
+use crate::msg::ExecuteMsg;
+
+use cosmwasm_bignumber::Uint256;
+use cosmwasm_std::testing::{mock_env, mock_info};
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::{attr, DepsMut, Env, Response, Timestamp};
+use suberra_core::msg::ProductInstantiateMsg;
+use suberra_core::subscriptions::PlanInfo;
+
+fn term_product_msg(trial_period_hour: Option<u64>) -> ProductInstantiateMsg {
+    ProductInstantiateMsg {
+        trial_period_hour,
+        plans: Some(vec![PlanInfo {
+            plan_id: 1,
+            unit_amount: Uint256::from(2000u128),
+            initial_amount: Uint256::zero(),
+            unit_interval_hour: 720u64,
+            trial_period_hour: None,
+            billing_calendar: None,
+        }]),
+        ..product_msg()
+    }
+}
+
+fn subscribe(
+    deps: DepsMut,
+    env: Env,
+    billing_cycles: Option<u64>,
+    end_at: Option<u64>,
+) -> Result<Response, ContractError> {
+    contract::execute(
+        deps,
+        env,
+        mock_info("subscriber", &[]),
+        ExecuteMsg::Subscribe {
+            plan_id: None,
+            coupon: None,
+            billing_cycles,
+            end_at,
+            beneficiary: None,
+            seats: None,
+        },
+    )
+}
+
+#[test]
+fn subscription_completes_after_billing_cycles() {
+    let mut deps = mock_dependencies(&[]);
+
+    let mut env = mock_env();
+    let start_timestamp = 1609459200;
+    env.block.time = Timestamp::from_seconds(start_timestamp); // set to 1 January 2021 00:00:00 GMT
+
+    let _res = contract::instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        term_product_msg(None),
+    )
+    .unwrap();
+
+    // the first of the 3 billing cycles is paid by the initial amount
+    let term_end_at = start_timestamp + 3 * THIRTY_DAYS_IN_SECONDS;
+    let res = subscribe(deps.as_mut(), env.clone(), Some(3), None).unwrap();
+    assert!(res
+        .attributes
+        .contains(&attr("term_end_at", term_end_at.to_string())));
+
+    let subscription = query_subscription(deps.as_ref(), env.clone(), "subscriber").unwrap();
+    assert_eq!(subscription.term_end_at, Some(term_end_at));
+    assert!(!subscription.is_completed);
+
+    // fixed-term subscriptions cannot change plans
+    match contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("subscriber", &[]),
+        ExecuteMsg::ChangePlan { plan_id: 1 },
+    ) {
+        Err(ContractError::FixedTermPlanChange {}) => {}
+        _ => panic!("Must return fixed term plan change error"),
+    }
+
+    env.block.time = Timestamp::from_seconds(start_timestamp + THIRTY_DAYS_IN_SECONDS);
+    charge(deps.as_mut(), env.clone(), "subscriber").unwrap();
+
+    env.block.time = Timestamp::from_seconds(start_timestamp + 2 * THIRTY_DAYS_IN_SECONDS);
+    let res = charge(deps.as_mut(), env.clone(), "subscriber").unwrap();
+    assert_eq!(res.attributes[3], attr("amount", "1000"));
+    assert_eq!(res.attributes[4], attr("periods", "1"));
+
+    // the term ends with the third billing cycle
+    env.block.time = Timestamp::from_seconds(term_end_at);
+    let subscription = query_subscription(deps.as_ref(), env.clone(), "subscriber").unwrap();
+    assert!(subscription.is_completed);
+    assert!(!subscription.is_active);
+    assert_eq!(subscription.interval_end_at, term_end_at);
+    assert_eq!(subscription.amount_chargeable, Some(Uint256::zero()));
+    assert_eq!(subscription.total_paid, Uint256::from(3000u128));
+
+    assert!(!query_can_work(deps.as_ref(), env.clone(), "subscriber"));
+    match charge(deps.as_mut(), env.clone(), "subscriber") {
+        Err(ContractError::SubscriptionCompleted {}) => {}
+        _ => panic!("Must return subscription completed error"),
+    }
+
+    // the subscriber can start a new subscription once the term is completed
+    let res = subscribe(deps.as_mut(), env.clone(), None, None).unwrap();
+    assert!(res.attributes.contains(&attr("initial_amount", "1000")));
+
+    let subscription = query_subscription(deps.as_ref(), env, "subscriber").unwrap();
+    assert_eq!(subscription.term_end_at, None);
+    assert!(subscription.is_active);
+}
+
+#[test]
+fn term_is_never_billed_past_end_at() {
+    let mut deps = mock_dependencies(&[]);
+
+    let mut env = mock_env();
+    let start_timestamp = 1609459200;
+    env.block.time = Timestamp::from_seconds(start_timestamp); // set to 1 January 2021 00:00:00 GMT
+
+    // 7 days free trial
+    let _res = contract::instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        term_product_msg(Some(168)),
+    )
+    .unwrap();
+
+    // the term starts after the trial, and only the 2 billing cycles that end by end_at are part of it
+    let trial_end_at = start_timestamp + 7 * ONE_DAY_IN_SECONDS;
+    let term_end_at = trial_end_at + 2 * THIRTY_DAYS_IN_SECONDS;
+    subscribe(
+        deps.as_mut(),
+        env.clone(),
+        None,
+        Some(term_end_at + 15 * ONE_DAY_IN_SECONDS),
+    )
+    .unwrap();
+
+    let subscription = query_subscription(deps.as_ref(), env.clone(), "subscriber").unwrap();
+    assert_eq!(subscription.trial_end_at, Some(trial_end_at));
+    assert_eq!(subscription.term_end_at, Some(term_end_at));
+
+    env.block.time = Timestamp::from_seconds(trial_end_at);
+    charge(deps.as_mut(), env.clone(), "subscriber").unwrap();
+
+    env.block.time = Timestamp::from_seconds(trial_end_at + THIRTY_DAYS_IN_SECONDS);
+    charge(deps.as_mut(), env.clone(), "subscriber").unwrap();
+
+    let subscription = query_subscription(deps.as_ref(), env.clone(), "subscriber").unwrap();
+    assert_eq!(subscription.interval_end_at, term_end_at);
+    assert_eq!(subscription.total_paid, Uint256::from(2000u128));
+
+    // nothing past the term is billed
+    env.block.time = Timestamp::from_seconds(term_end_at + ONE_DAY_IN_SECONDS);
+    let subscription = query_subscription(deps.as_ref(), env.clone(), "subscriber").unwrap();
+    assert!(subscription.is_completed);
+    assert_eq!(subscription.amount_chargeable, Some(Uint256::zero()));
+    match charge(deps.as_mut(), env, "subscriber") {
+        Err(ContractError::SubscriptionCompleted {}) => {}
+        _ => panic!("Must return subscription completed error"),
+    }
+}
+
+#[test]
+fn invalid_term() {
+    let mut deps = mock_dependencies(&[]);
+
+    let mut env = mock_env();
+    let start_timestamp = 1609459200;
+    env.block.time = Timestamp::from_seconds(start_timestamp); // set to 1 January 2021 00:00:00 GMT
+
+    let _res = contract::instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        term_product_msg(None),
+    )
+    .unwrap();
+
+    // the term must have at least one billing cycle
+    match subscribe(deps.as_mut(), env.clone(), Some(0), None) {
+        Err(ContractError::InvalidParam {}) => {}
+        _ => panic!("Must return invalid param error"),
+    }
+    match subscribe(
+        deps.as_mut(),
+        env.clone(),
+        None,
+        Some(start_timestamp + THIRTY_DAYS_IN_SECONDS - 1),
+    ) {
+        Err(ContractError::InvalidParam {}) => {}
+        _ => panic!("Must return invalid param error"),
+    }
+
+    // the term is either a number of billing cycles or an end timestamp
+    match subscribe(
+        deps.as_mut(),
+        env.clone(),
+        Some(12),
+        Some(start_timestamp + 12 * THIRTY_DAYS_IN_SECONDS),
+    ) {
+        Err(ContractError::InvalidParam {}) => {}
+        _ => panic!("Must return invalid param error"),
+    }
+
+    // undoing a cancellation keeps the existing term
+    subscribe(deps.as_mut(), env.clone(), Some(12), None).unwrap();
+    let _res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("subscriber", &[]),
+        ExecuteMsg::Cancel {},
+    )
+    .unwrap();
+    match subscribe(deps.as_mut(), env.clone(), Some(6), None) {
+        Err(ContractError::ExistingSubscriptionFound {}) => {}
+        _ => panic!("Must return existing subscription found error"),
+    }
+
+    subscribe(deps.as_mut(), env.clone(), None, None).unwrap();
+    let subscription = query_subscription(deps.as_ref(), env, "subscriber").unwrap();
+    assert_eq!(
+        subscription.term_end_at,
+        Some(start_timestamp + 12 * THIRTY_DAYS_IN_SECONDS)
+    );
+}
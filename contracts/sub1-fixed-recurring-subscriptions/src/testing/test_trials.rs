@@ -0,0 +1,200 @@
+use super::{
+    product_msg, query_subscription, DEFAULT_GRACE_PERIOD, SEVEN_DAYS_IN_SECONDS,
+    THIRTY_DAYS_IN_SECONDS,
+};
+use crate::contract;
+use crate::error::ContractError;
+use crate::mock_querier::mock_dependencies;
+use crate::msg::{ExecuteMsg, PlanResponse, QueryMsg};
+
+use cosmwasm_bignumber::Uint256;
+use cosmwasm_std::testing::{mock_env, mock_info};
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::{attr, from_binary, Timestamp};
+use suberra_core::msg::ProductInstantiateMsg;
+use suberra_core::subscriptions::PlanInfo;
+
+#[test]
+fn subscribe_with_trial() {
+    let mut deps = mock_dependencies(&[]);
+
+    let mut env = mock_env();
+    let start_timestamp = 1609459200;
+    env.block.time = Timestamp::from_seconds(start_timestamp); // set to 1 January 2021 00:00:00 GMT
+
+    let _res = contract::instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        ProductInstantiateMsg {
+            trial_period_hour: Some(168u64),
+            ..product_msg()
+        },
+    )
+    .unwrap();
+
+    // no initial amount is charged during the trial
+    let subscriber = mock_info("subscriber", &[]);
+    let res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        subscriber.clone(),
+        ExecuteMsg::Subscribe {
+            plan_id: None,
+            coupon: None,
+            billing_cycles: None,
+            end_at: None,
+            beneficiary: None,
+            seats: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 0);
+    assert_eq!(
+        res.attributes[0],
+        attr(
+            "trial_end_at",
+            (start_timestamp + SEVEN_DAYS_IN_SECONDS).to_string()
+        )
+    );
+
+    let subscription = query_subscription(deps.as_ref(), env.clone(), "subscriber").unwrap();
+    assert_eq!(subscription.is_active, true);
+    assert_eq!(
+        subscription.trial_end_at,
+        Some(start_timestamp + SEVEN_DAYS_IN_SECONDS)
+    );
+    assert_eq!(
+        subscription.interval_end_at,
+        start_timestamp + SEVEN_DAYS_IN_SECONDS
+    );
+
+    // no charge can be made during the trial
+    env.block.time = Timestamp::from_seconds(start_timestamp + SEVEN_DAYS_IN_SECONDS - 1);
+    let res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("charger", &[]),
+        ExecuteMsg::Charge {
+            payer_address: "subscriber".to_string(),
+        },
+    );
+    match res {
+        Err(ContractError::NoCharge {}) => {}
+        _ => panic!("Must return no charge error"),
+    }
+
+    // first charge happens when the trial ends
+    let trial_end_at = start_timestamp + SEVEN_DAYS_IN_SECONDS;
+    env.block.time = Timestamp::from_seconds(trial_end_at);
+    let res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("charger", &[]),
+        ExecuteMsg::Charge {
+            payer_address: "subscriber".to_string(),
+        },
+    )
+    .unwrap();
+    assert_eq!(res.attributes[3], attr("amount", "1000"));
+    assert_eq!(res.attributes[4], attr("periods", "1"));
+
+    let subscription = query_subscription(deps.as_ref(), env.clone(), "subscriber").unwrap();
+    assert_eq!(
+        subscription.interval_end_at,
+        trial_end_at + THIRTY_DAYS_IN_SECONDS
+    );
+
+    // subscriber cancels and lets the subscription lapse
+    contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        subscriber.clone(),
+        ExecuteMsg::Cancel {},
+    )
+    .unwrap();
+    env.block.time =
+        Timestamp::from_seconds(trial_end_at + THIRTY_DAYS_IN_SECONDS + DEFAULT_GRACE_PERIOD + 1);
+
+    // subscribing again does not give a second trial, and the initial amount is charged
+    let res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        subscriber,
+        ExecuteMsg::Subscribe {
+            plan_id: None,
+            coupon: None,
+            billing_cycles: None,
+            end_at: None,
+            beneficiary: None,
+            seats: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(res.attributes[0], attr("initial_amount", "1000"));
+
+    let subscription = query_subscription(deps.as_ref(), env.clone(), "subscriber").unwrap();
+    assert_eq!(subscription.trial_end_at, None);
+}
+
+#[test]
+fn plan_overrides_trial() {
+    let mut deps = mock_dependencies(&[]);
+
+    let mut env = mock_env();
+    let start_timestamp = 1609459200;
+    env.block.time = Timestamp::from_seconds(start_timestamp); // set to 1 January 2021 00:00:00 GMT
+
+    let plan = PlanInfo {
+        plan_id: 1,
+        unit_amount: Uint256::from(2000u128),
+        initial_amount: Uint256::from(2000u128),
+        unit_interval_hour: 720u64,
+        trial_period_hour: Some(0u64),
+        billing_calendar: None,
+    };
+    let _res = contract::instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        ProductInstantiateMsg {
+            trial_period_hour: Some(168u64),
+            plans: Some(vec![plan]),
+            ..product_msg()
+        },
+    )
+    .unwrap();
+
+    let res = contract::query(deps.as_ref(), env.clone(), QueryMsg::Plan { plan_id: 0 }).unwrap();
+    let value: Option<PlanResponse> = from_binary(&res).unwrap();
+    assert_eq!(value.unwrap().trial_period_seconds, SEVEN_DAYS_IN_SECONDS);
+
+    let res = contract::query(deps.as_ref(), env.clone(), QueryMsg::Plan { plan_id: 1 }).unwrap();
+    let value: Option<PlanResponse> = from_binary(&res).unwrap();
+    assert_eq!(value.unwrap().trial_period_seconds, 0);
+
+    // plan 1 has no trial, so the initial amount is charged immediately
+    let res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("subscriber", &[]),
+        ExecuteMsg::Subscribe {
+            plan_id: Some(1),
+            coupon: None,
+            billing_cycles: None,
+            end_at: None,
+            beneficiary: None,
+            seats: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 1);
+
+    let subscription = query_subscription(deps.as_ref(), env.clone(), "subscriber").unwrap();
+    assert_eq!(subscription.trial_end_at, None);
+    assert_eq!(
+        subscription.interval_end_at,
+        start_timestamp + THIRTY_DAYS_IN_SECONDS
+    );
+}
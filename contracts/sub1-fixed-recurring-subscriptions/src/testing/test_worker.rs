@@ -1,14 +1,19 @@
+use super::{
+    charge, env_at, execute, fail_payment, product_msg, ONE_DAY_IN_SECONDS, START_TIMESTAMP,
+    THIRTY_DAYS_IN_SECONDS,
+};
 use crate::contract;
 
-use crate::msg::ExecuteMsg;
+use crate::error::ContractError;
+use crate::msg::{BatchWorkPayload, ExecuteMsg, JobsRegistryExecuteMsg};
 use cosmwasm_bignumber::Uint256;
 #[cfg(not(feature = "library"))]
-use cosmwasm_std::{from_binary, to_binary, Coin, Timestamp, Uint128};
+use cosmwasm_std::{attr, from_binary, to_binary, Coin, CosmosMsg, Timestamp, Uint128, WasmMsg};
 use suberra_core::msg::ProductInstantiateMsg;
 
 use crate::mock_querier::mock_dependencies;
-use crate::msg::{QueryMsg, WorkPayload};
-use cosmwasm_std::testing::{mock_env, mock_info};
+use crate::msg::{DueSubscriptionsResponse, QueryMsg, WorkPayload};
+use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
 
 #[test]
 fn query_can_work() {
@@ -22,10 +27,22 @@ fn query_can_work() {
         initial_amount: Uint256::from(123u128),
         unit_interval_hour: 720u64,
         additional_grace_period_hour: None,
+        retry_schedule_hour: None,
+        max_pause_duration_hour: None,
+        max_pauses_per_year: None,
         admins: Vec::new(),
         mutable: false,
         uri: "{\"image_url\": \"www.google.com\" }".to_string(),
         factory_address: "factory".to_string(),
+        trial_period_hour: None,
+        plans: None,
+        max_amount_chargeable: None,
+        usage_tiers: None,
+        membership_nft: None,
+        billing_asset: None,
+        revenue_splits: None,
+        billing_calendar: None,
+        accept_any_payer: Some(true),
         owner: "creator".to_string(),
     };
 
@@ -54,7 +71,14 @@ fn query_can_work() {
 
     // user tries to subscribe
 
-    let msg = ExecuteMsg::Subscribe {};
+    let msg = ExecuteMsg::Subscribe {
+        plan_id: None,
+        coupon: None,
+        billing_cycles: None,
+        end_at: None,
+        beneficiary: None,
+        seats: None,
+    };
     let _ = contract::execute(deps.as_mut(), env.clone(), info_subscriber.clone(), msg);
 
     // fast forward to 300 hours later
@@ -95,3 +119,576 @@ fn query_can_work() {
 
     assert_eq!(result, true);
 }
+
+fn query_due_subscriptions(
+    deps: cosmwasm_std::Deps,
+    env: cosmwasm_std::Env,
+    before: Option<u64>,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Vec<String> {
+    let res: DueSubscriptionsResponse = from_binary(
+        &contract::query(
+            deps,
+            env,
+            QueryMsg::DueSubscriptions {
+                before,
+                start_after,
+                limit,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    res.subscribers
+}
+
+#[test]
+fn due_subscriptions() {
+    let mut deps = mock_dependencies(&[]);
+    let msg = ProductInstantiateMsg {
+        receiver_address: "receiver".to_string(),
+        unit_amount: Uint256::from(123u128),
+        initial_amount: Uint256::from(123u128),
+        unit_interval_hour: 720u64,
+        additional_grace_period_hour: None,
+        retry_schedule_hour: None,
+        max_pause_duration_hour: None,
+        max_pauses_per_year: None,
+        admins: Vec::new(),
+        mutable: false,
+        uri: "{\"image_url\": \"www.google.com\" }".to_string(),
+        factory_address: "factory".to_string(),
+        trial_period_hour: None,
+        plans: None,
+        max_amount_chargeable: None,
+        usage_tiers: None,
+        membership_nft: None,
+        billing_asset: None,
+        revenue_splits: None,
+        billing_calendar: None,
+        accept_any_payer: Some(true),
+        owner: "creator".to_string(),
+    };
+    let _res =
+        contract::instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+    let mut env = mock_env();
+    let start_timestamp = 1609459200;
+    let thirty_days_in_seconds = 720 * 60 * 60;
+
+    // subscribers subscribe 10 seconds apart
+    for (i, subscriber) in ["alice", "bob", "carol", "dave"].iter().enumerate() {
+        env.block.time = Timestamp::from_seconds(start_timestamp + 10 * i as u64);
+        let msg = ExecuteMsg::Subscribe {
+            plan_id: None,
+            coupon: None,
+            billing_cycles: None,
+            end_at: None,
+            beneficiary: None,
+            seats: None,
+        };
+        contract::execute(deps.as_mut(), env.clone(), mock_info(subscriber, &[]), msg).unwrap();
+    }
+
+    // cancelled subscriptions cannot be charged
+    contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("alice", &[]),
+        ExecuteMsg::Cancel {},
+    )
+    .unwrap();
+
+    // nothing is due before the first interval ends
+    assert_eq!(
+        query_due_subscriptions(deps.as_ref(), env.clone(), None, None, None),
+        Vec::<String>::new()
+    );
+
+    // bob and carol are due, dave is not due yet
+    env.block.time = Timestamp::from_seconds(start_timestamp + thirty_days_in_seconds + 25);
+    assert_eq!(
+        query_due_subscriptions(deps.as_ref(), env.clone(), None, None, None),
+        vec!["bob".to_string(), "carol".to_string()]
+    );
+
+    // paginated
+    assert_eq!(
+        query_due_subscriptions(deps.as_ref(), env.clone(), None, None, Some(1)),
+        vec!["bob".to_string()]
+    );
+    assert_eq!(
+        query_due_subscriptions(
+            deps.as_ref(),
+            env.clone(),
+            None,
+            Some("bob".to_string()),
+            Some(1)
+        ),
+        vec!["carol".to_string()]
+    );
+
+    // only subscriptions due at or before `before`
+    assert_eq!(
+        query_due_subscriptions(
+            deps.as_ref(),
+            env.clone(),
+            Some(start_timestamp + thirty_days_in_seconds + 10),
+            None,
+            None
+        ),
+        vec!["bob".to_string()]
+    );
+
+    // charged subscriptions are no longer due
+    contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("worker", &[]),
+        ExecuteMsg::Charge {
+            payer_address: "bob".to_string(),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        query_due_subscriptions(deps.as_ref(), env.clone(), None, None, None),
+        vec!["carol".to_string()]
+    );
+}
+
+#[test]
+fn due_subscriptions_leave_out_lapsed_subscriptions() {
+    let mut deps = mock_dependencies(&[]);
+
+    contract::instantiate(
+        deps.as_mut(),
+        env_at(START_TIMESTAMP),
+        mock_info("creator", &[]),
+        product_msg(),
+    )
+    .unwrap();
+
+    // dave subscribes for a single billing cycle, which is never charged
+    for (subscriber, billing_cycles) in [
+        ("alice", None),
+        ("bob", None),
+        ("carol", None),
+        ("dave", Some(1)),
+    ] {
+        execute(
+            deps.as_mut(),
+            env_at(START_TIMESTAMP),
+            subscriber,
+            ExecuteMsg::Subscribe {
+                plan_id: None,
+                coupon: None,
+                billing_cycles,
+                end_at: None,
+                beneficiary: None,
+                seats: None,
+            },
+        )
+        .unwrap();
+    }
+
+    // the payment of alice fails, and is retried a day later
+    let interval_end_at = START_TIMESTAMP + THIRTY_DAYS_IN_SECONDS;
+    charge(deps.as_mut(), env_at(interval_end_at), "alice").unwrap();
+    fail_payment(deps.as_mut(), env_at(interval_end_at), 0).unwrap();
+    assert_eq!(
+        query_due_subscriptions(deps.as_ref(), env_at(interval_end_at), None, None, None),
+        vec!["bob".to_string(), "carol".to_string()]
+    );
+
+    // past due subscriptions come first
+    let retry_at = interval_end_at + ONE_DAY_IN_SECONDS;
+    assert_eq!(
+        query_due_subscriptions(deps.as_ref(), env_at(retry_at), None, None, None),
+        vec!["alice".to_string(), "bob".to_string(), "carol".to_string()]
+    );
+    assert_eq!(
+        query_due_subscriptions(deps.as_ref(), env_at(retry_at), None, None, Some(1)),
+        vec!["alice".to_string()]
+    );
+    assert_eq!(
+        query_due_subscriptions(
+            deps.as_ref(),
+            env_at(retry_at),
+            None,
+            Some("alice".to_string()),
+            Some(1)
+        ),
+        vec!["bob".to_string()]
+    );
+    assert_eq!(
+        query_due_subscriptions(
+            deps.as_ref(),
+            env_at(retry_at),
+            None,
+            Some("bob".to_string()),
+            None
+        ),
+        vec!["carol".to_string()]
+    );
+
+    // bob and carol lapse after the grace period, while alice can still be retried
+    assert_eq!(
+        query_due_subscriptions(deps.as_ref(), env_at(retry_at + 1), None, None, None),
+        vec!["alice".to_string()]
+    );
+}
+
+#[test]
+fn batch_work() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_fee(0, Uint256::zero(), Uint256::zero(), 1);
+    let msg = ProductInstantiateMsg {
+        receiver_address: "receiver".to_string(),
+        unit_amount: Uint256::from(123u128),
+        initial_amount: Uint256::from(123u128),
+        unit_interval_hour: 720u64,
+        additional_grace_period_hour: None,
+        retry_schedule_hour: None,
+        max_pause_duration_hour: None,
+        max_pauses_per_year: None,
+        admins: Vec::new(),
+        mutable: false,
+        uri: "{\"image_url\": \"www.google.com\" }".to_string(),
+        factory_address: "factory".to_string(),
+        trial_period_hour: None,
+        plans: None,
+        max_amount_chargeable: None,
+        usage_tiers: None,
+        membership_nft: None,
+        billing_asset: None,
+        revenue_splits: None,
+        billing_calendar: None,
+        accept_any_payer: Some(true),
+        owner: "creator".to_string(),
+    };
+    let _res =
+        contract::instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+    let mut env = mock_env();
+    let start_timestamp = 1609459200;
+    let thirty_days_in_seconds = 720 * 60 * 60;
+    env.block.time = Timestamp::from_seconds(start_timestamp);
+
+    for subscriber in ["alice", "bob", "carol"].iter() {
+        let msg = ExecuteMsg::Subscribe {
+            plan_id: None,
+            coupon: None,
+            billing_cycles: None,
+            end_at: None,
+            beneficiary: None,
+            seats: None,
+        };
+        contract::execute(deps.as_mut(), env.clone(), mock_info(subscriber, &[]), msg).unwrap();
+    }
+
+    // cancelled subscriptions cannot be charged
+    contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("carol", &[]),
+        ExecuteMsg::Cancel {},
+    )
+    .unwrap();
+
+    let batch_payload = to_binary(&BatchWorkPayload {
+        payer_addresses: vec![
+            "alice".to_string(),
+            "bob".to_string(),
+            "carol".to_string(),
+            "unknown".to_string(),
+        ],
+    })
+    .unwrap();
+
+    // nothing can be charged before the interval ends
+    let can_work: bool = from_binary(
+        &contract::query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::CanWork {
+                payload: batch_payload.clone(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert!(!can_work);
+
+    let res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("worker", &[]),
+        ExecuteMsg::Work {
+            payload: batch_payload.clone(),
+        },
+    );
+    match res {
+        Err(ContractError::NoCharge {}) => {}
+        _ => panic!("Must return no charge error"),
+    }
+
+    env.block.time = Timestamp::from_seconds(start_timestamp + thirty_days_in_seconds);
+    let can_work: bool = from_binary(
+        &contract::query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::CanWork {
+                payload: batch_payload.clone(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert!(can_work);
+
+    // alice and bob are charged, carol and the unknown subscriber are skipped
+    let res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("worker", &[]),
+        ExecuteMsg::Work {
+            payload: batch_payload.clone(),
+        },
+    )
+    .unwrap();
+    assert_eq!(res.attributes[2], attr("charged", "alice,bob"));
+    assert_eq!(res.attributes[3], attr("skipped", "carol,unknown"));
+
+    // a single work receipt scaled to the number of charges, sent after their payments
+    assert_eq!(
+        res.messages.last().unwrap().msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+            funds: vec![],
+            msg: to_binary(&ExecuteMsg::SendWorkReceipt {
+                worker_address: "worker".to_string(),
+                units: 2,
+            })
+            .unwrap(),
+        })
+    );
+
+    // charged subscriptions are not charged again in the same interval
+    let res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("worker", &[]),
+        ExecuteMsg::Work {
+            payload: batch_payload,
+        },
+    );
+    match res {
+        Err(ContractError::NoCharge {}) => {}
+        _ => panic!("Must return no charge error"),
+    }
+
+    // batches are capped
+    let res = contract::execute(
+        deps.as_mut(),
+        env,
+        mock_info("worker", &[]),
+        ExecuteMsg::Work {
+            payload: to_binary(&BatchWorkPayload {
+                payer_addresses: vec!["alice".to_string(); 31],
+            })
+            .unwrap(),
+        },
+    );
+    match res {
+        Err(ContractError::BatchTooLarge {}) => {}
+        _ => panic!("Must return batch too large error"),
+    }
+}
+
+#[test]
+fn batch_work_skips_failing_charges() {
+    let mut deps = mock_dependencies(&[]);
+
+    contract::instantiate(
+        deps.as_mut(),
+        env_at(START_TIMESTAMP),
+        mock_info("creator", &[]),
+        product_msg(),
+    )
+    .unwrap();
+
+    execute(
+        deps.as_mut(),
+        env_at(START_TIMESTAMP),
+        "alice",
+        ExecuteMsg::Subscribe {
+            plan_id: None,
+            coupon: None,
+            billing_cycles: None,
+            end_at: None,
+            beneficiary: None,
+            seats: None,
+        },
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        env_at(START_TIMESTAMP),
+        "bob",
+        ExecuteMsg::Subscribe {
+            plan_id: None,
+            coupon: None,
+            billing_cycles: None,
+            end_at: None,
+            beneficiary: None,
+            seats: Some(2),
+        },
+    )
+    .unwrap();
+
+    // the minimum protocol fee is more than the charge of alice
+    deps.querier
+        .with_fee(100, Uint256::from(1500u128), Uint256::zero(), 1);
+
+    let charged_at = START_TIMESTAMP + THIRTY_DAYS_IN_SECONDS;
+    let res = execute(
+        deps.as_mut(),
+        env_at(charged_at),
+        "worker",
+        ExecuteMsg::Work {
+            payload: to_binary(&BatchWorkPayload {
+                payer_addresses: vec!["alice".to_string(), "bob".to_string()],
+            })
+            .unwrap(),
+        },
+    )
+    .unwrap();
+    assert_eq!(res.attributes[2], attr("charged", "bob"));
+    assert_eq!(res.attributes[3], attr("skipped", "alice"));
+
+    assert_eq!(res.events[0].ty, "charge_skipped");
+    assert!(res.events[0]
+        .attributes
+        .contains(&attr("subscriber", "alice")));
+    assert!(res.events[0]
+        .attributes
+        .contains(&attr("error", ContractError::InvalidFee {}.to_string())));
+    assert_eq!(res.events[1].ty, "charge");
+    assert!(res.events[1]
+        .attributes
+        .contains(&attr("subscriber", "bob")));
+    assert!(res.events[1].attributes.contains(&attr("amount", "2000")));
+
+    // the failed charge leaves the subscription of alice unchanged
+    let due: DueSubscriptionsResponse = from_binary(
+        &contract::query(
+            deps.as_ref(),
+            env_at(charged_at),
+            QueryMsg::DueSubscriptions {
+                before: None,
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(due.subscribers, vec!["alice".to_string()]);
+}
+
+#[test]
+fn work_receipt_excludes_failed_payments() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_fee(0, Uint256::zero(), Uint256::zero(), 1);
+
+    contract::instantiate(
+        deps.as_mut(),
+        env_at(START_TIMESTAMP),
+        mock_info("creator", &[]),
+        product_msg(),
+    )
+    .unwrap();
+
+    for subscriber in ["alice", "bob"] {
+        execute(
+            deps.as_mut(),
+            env_at(START_TIMESTAMP),
+            subscriber,
+            ExecuteMsg::Subscribe {
+                plan_id: None,
+                coupon: None,
+                billing_cycles: None,
+                end_at: None,
+                beneficiary: None,
+                seats: None,
+            },
+        )
+        .unwrap();
+    }
+
+    let charged_at = START_TIMESTAMP + THIRTY_DAYS_IN_SECONDS;
+    execute(
+        deps.as_mut(),
+        env_at(charged_at),
+        "worker",
+        ExecuteMsg::Work {
+            payload: to_binary(&BatchWorkPayload {
+                payer_addresses: vec!["alice".to_string(), "bob".to_string()],
+            })
+            .unwrap(),
+        },
+    )
+    .unwrap();
+
+    // only the contract can send the work receipt
+    let send_work_receipt = ExecuteMsg::SendWorkReceipt {
+        worker_address: "worker".to_string(),
+        units: 2,
+    };
+    match execute(
+        deps.as_mut(),
+        env_at(charged_at),
+        "worker",
+        send_work_receipt.clone(),
+    ) {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    // the payment of bob fails, so the worker is only rewarded for the charge of alice
+    fail_payment(deps.as_mut(), env_at(charged_at), 1).unwrap();
+    let res = execute(
+        deps.as_mut(),
+        env_at(charged_at),
+        MOCK_CONTRACT_ADDR,
+        send_work_receipt.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "job_registry".to_string(),
+            funds: vec![],
+            msg: to_binary(&JobsRegistryExecuteMsg::WorkReceipt {
+                worker_address: "worker".to_string(),
+                units: Some(1),
+            })
+            .unwrap(),
+        })
+    );
+
+    // no receipt is sent once every payment has failed
+    fail_payment(deps.as_mut(), env_at(charged_at), 0).unwrap();
+    let res = execute(
+        deps.as_mut(),
+        env_at(charged_at),
+        MOCK_CONTRACT_ADDR,
+        send_work_receipt,
+    )
+    .unwrap();
+    assert!(res.messages.is_empty());
+    assert!(res.attributes.contains(&attr("units", "0")));
+}
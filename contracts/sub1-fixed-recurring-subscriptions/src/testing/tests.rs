@@ -6,14 +6,14 @@ use crate::msg::{
     ConfigResponse, ExecuteMsg, QueryMsg, SubscriptionInfoResponse, SubscriptionsResponse,
     WorkPayload,
 };
-use crate::state::{Config, SubscriptionInfo};
+use crate::state::{Config, SubscriptionInfo, SubscriptionStatus};
 use admin_core::msg::AdminConfigResponse;
 use cosmwasm_bignumber::Uint256;
 use cosmwasm_std::testing::{mock_env, mock_info};
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::{attr, from_binary, to_binary, Addr, Api, Coin, Timestamp, Uint128};
 use suberra_core::msg::ProductInstantiateMsg;
-use suberra_core::subscriptions::Discount;
+use suberra_core::subscriptions::{Discount, DiscountType};
 
 const DEFAULT_GRACE_PERIOD: u64 = 86400; // 24 hours in seconds
 const THIRTY_DAYS_IN_SECONDS: u64 = 60 * 60 * 720;
@@ -28,6 +28,9 @@ fn proper_initialization() {
     let msg = ProductInstantiateMsg {
         receiver_address: "receiver".to_string(),
         additional_grace_period_hour: None,
+        retry_schedule_hour: None,
+        max_pause_duration_hour: None,
+        max_pauses_per_year: None,
         unit_amount: Uint256::from(123u128),
         initial_amount: Uint256::from(123u128),
         unit_interval_hour: 2592000u64,
@@ -35,6 +38,15 @@ fn proper_initialization() {
         mutable: false,
         uri: "{\"image_url\": \"www.google.com\" }".to_string(),
         factory_address: "factory".to_string(),
+        trial_period_hour: None,
+        plans: None,
+        max_amount_chargeable: None,
+        usage_tiers: None,
+        membership_nft: None,
+        billing_asset: None,
+        revenue_splits: None,
+        billing_calendar: None,
+        accept_any_payer: Some(true),
         owner: "creator".to_string(),
     };
     let info = mock_info("creator", &[]);
@@ -61,10 +73,22 @@ fn pause() {
         unit_amount: Uint256::from(123u128),
         unit_interval_hour: 2592000u64,
         additional_grace_period_hour: None,
+        retry_schedule_hour: None,
+        max_pause_duration_hour: None,
+        max_pauses_per_year: None,
         admins: Vec::new(),
         mutable: false,
         uri: "{\"image_url\": \"www.google.com\" }".to_string(),
         factory_address: "factory".to_string(),
+        trial_period_hour: None,
+        plans: None,
+        max_amount_chargeable: None,
+        usage_tiers: None,
+        membership_nft: None,
+        billing_asset: None,
+        revenue_splits: None,
+        billing_calendar: None,
+        accept_any_payer: Some(true),
         owner: "creator".to_string(),
     };
 
@@ -99,6 +123,19 @@ fn pause() {
         receiver_address: "receiver".to_string(),
         initial_amount: Uint256::from(123u128),
         additional_grace_period: 0,
+        trial_period_seconds: 0,
+        max_amount_chargeable: None,
+        retry_schedule_seconds: vec![86400, 259200, 604800],
+        max_pause_duration_seconds: 0,
+        max_pauses_per_year: 0,
+        usage_tiers: vec![],
+        usage_reporters: vec![],
+        billing_asset: None,
+        revenue_splits: vec![],
+        hooks: vec![],
+        billing_calendar: None,
+        price_changes: vec![],
+        accept_any_payer: true,
         unit_amount: Uint256::from(123u128),
         unit_interval_seconds: 2592000 * 60 * 60,
         uri: "{\"image_url\": \"www.google.com\" }".to_string(),
@@ -112,7 +149,14 @@ fn pause() {
         deps.as_mut(),
         mock_env(),
         info.clone(),
-        ExecuteMsg::Subscribe {},
+        ExecuteMsg::Subscribe {
+            plan_id: None,
+            coupon: None,
+            billing_cycles: None,
+            end_at: None,
+            beneficiary: None,
+            seats: None,
+        },
     );
     match res {
         Err(ContractError::Paused {}) => {}
@@ -132,10 +176,22 @@ fn freeze() {
         unit_amount: Uint256::from(123u128),
         unit_interval_hour: 2592000u64,
         additional_grace_period_hour: None,
+        retry_schedule_hour: None,
+        max_pause_duration_hour: None,
+        max_pauses_per_year: None,
         admins: Vec::new(),
         mutable: false,
         uri: "{\"image_url\": \"www.google.com\" }".to_string(),
         factory_address: "factory".to_string(),
+        trial_period_hour: None,
+        plans: None,
+        max_amount_chargeable: None,
+        usage_tiers: None,
+        membership_nft: None,
+        billing_asset: None,
+        revenue_splits: None,
+        billing_calendar: None,
+        accept_any_payer: Some(true),
         owner: "creator".to_string(),
     };
 
@@ -156,7 +212,14 @@ fn freeze() {
     let subscriber1 = mock_info("subscriber", &[]);
 
     // user tries to subscribe
-    let msg = ExecuteMsg::Subscribe {};
+    let msg = ExecuteMsg::Subscribe {
+        plan_id: None,
+        coupon: None,
+        billing_cycles: None,
+        end_at: None,
+        beneficiary: None,
+        seats: None,
+    };
     let res =
         contract::execute(deps.as_mut(), env.clone(), subscriber1.clone(), msg.clone()).unwrap();
     assert_eq!(res.messages.len(), 1);
@@ -175,7 +238,6 @@ fn freeze() {
     let msg = ExecuteMsg::ToggleFreeze {};
     let _res = contract::execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
-
     // state should now be set to paused
     let res = contract::query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
     let value: ConfigResponse = from_binary(&res).unwrap();
@@ -188,7 +250,14 @@ fn freeze() {
         deps.as_mut(),
         mock_env(),
         info.clone(),
-        ExecuteMsg::Subscribe {},
+        ExecuteMsg::Subscribe {
+            plan_id: None,
+            coupon: None,
+            billing_cycles: None,
+            end_at: None,
+            beneficiary: None,
+            seats: None,
+        },
     );
     match res {
         Err(ContractError::Frozen {}) => {}
@@ -282,10 +351,22 @@ fn unpause() {
         unit_amount: Uint256::from(123u128),
         unit_interval_hour: 2592000u64,
         additional_grace_period_hour: None,
+        retry_schedule_hour: None,
+        max_pause_duration_hour: None,
+        max_pauses_per_year: None,
         admins: Vec::new(),
         mutable: false,
         uri: "{\"image_url\": \"www.google.com\" }".to_string(),
         factory_address: "factory".to_string(),
+        trial_period_hour: None,
+        plans: None,
+        max_amount_chargeable: None,
+        usage_tiers: None,
+        membership_nft: None,
+        billing_asset: None,
+        revenue_splits: None,
+        billing_calendar: None,
+        accept_any_payer: Some(true),
     };
 
     let info = mock_info("creator", &[]);
@@ -322,10 +403,22 @@ fn simple_subscribe() {
         initial_amount: Uint256::from(123u128),
         unit_interval_hour: 720u64,
         additional_grace_period_hour: None,
+        retry_schedule_hour: None,
+        max_pause_duration_hour: None,
+        max_pauses_per_year: None,
         admins: Vec::new(),
         mutable: false,
         uri: "{\"image_url\": \"www.google.com\" }".to_string(),
         factory_address: "factory".to_string(),
+        trial_period_hour: None,
+        plans: None,
+        max_amount_chargeable: None,
+        usage_tiers: None,
+        membership_nft: None,
+        billing_asset: None,
+        revenue_splits: None,
+        billing_calendar: None,
+        accept_any_payer: Some(true),
     };
 
     let mut env = mock_env();
@@ -340,13 +433,21 @@ fn simple_subscribe() {
 
     // user tries to subscribe
 
-    let msg = ExecuteMsg::Subscribe {};
+    let msg = ExecuteMsg::Subscribe {
+        plan_id: None,
+        coupon: None,
+        billing_cycles: None,
+        end_at: None,
+        beneficiary: None,
+        seats: None,
+    };
     let res =
         contract::execute(deps.as_mut(), env.clone(), subscriber1.clone(), msg.clone()).unwrap();
     assert_eq!(
         res.attributes,
         vec![
             attr("initial_amount", "123"),
+            attr("charge_id", "1"),
             attr("method", "execute_subscribe"),
             attr("result", "subscribe_success"),
             attr("subscriber", "subscriber"),
@@ -363,6 +464,7 @@ fn simple_subscribe() {
             QueryMsg::Subscriptions {
                 limit: None,
                 start_after: None,
+                status: None,
             },
         )
         .unwrap(),
@@ -383,6 +485,7 @@ fn simple_subscribe() {
             QueryMsg::Subscriptions {
                 limit: None,
                 start_after: None,
+                status: None,
             },
         )
         .unwrap(),
@@ -424,7 +527,8 @@ fn simple_subscribe() {
             attr("module_contract_address", "cosmos2contract"),
             attr("subscriber", "subscriber"),
             attr("amount", "123"),
-            attr("periods", "1")
+            attr("periods", "1"),
+            attr("charge_id", "3"),
         ]
     );
 
@@ -442,13 +546,30 @@ fn simple_subscribe() {
     let subscriber_info: SubscriptionInfoResponse = from_binary(&res).unwrap();
     let expected_subscription = SubscriptionInfoResponse {
         subscriber: "subscriber".to_string(),
+        plan_id: 0,
         created_at: start_timestamp,
         last_charged: start_timestamp + THIRTY_DAYS_IN_SECONDS,
         interval_end_at: start_timestamp + 2 * THIRTY_DAYS_IN_SECONDS,
         is_cancelled: false,
         is_active: true,
+        status: SubscriptionStatus::Active,
         discount_per_interval: None,
+        trial_end_at: None,
         amount_chargeable: Some(Uint256::zero()),
+        total_paid: Uint256::from(246u128),
+        lifetime_paid: Uint256::from(246u128),
+        max_amount_chargeable: None,
+        is_past_due: false,
+        retry_count: 0,
+        next_retry_at: None,
+        is_paused: false,
+        resume_at: None,
+        term_end_at: None,
+        is_completed: false,
+        payer: "subscriber".to_string(),
+        prepaid_balance: Uint256::zero(),
+        seats: None,
+        team_owner: None,
     };
 
     // subscription should be cancelled
@@ -482,10 +603,22 @@ fn query_subscriber() {
         initial_amount: Uint256::from(123u128),
         unit_interval_hour: 720u64,
         additional_grace_period_hour: Some(720u64),
+        retry_schedule_hour: None,
+        max_pause_duration_hour: None,
+        max_pauses_per_year: None,
         admins: Vec::new(),
         mutable: false,
         uri: "{\"image_url\": \"www.google.com\" }".to_string(),
         factory_address: "factory".to_string(),
+        trial_period_hour: None,
+        plans: None,
+        max_amount_chargeable: None,
+        usage_tiers: None,
+        membership_nft: None,
+        billing_asset: None,
+        revenue_splits: None,
+        billing_calendar: None,
+        accept_any_payer: Some(true),
     };
 
     let info = mock_info("creator", &[]);
@@ -499,13 +632,21 @@ fn query_subscriber() {
     env.block.time = Timestamp::from_seconds(start_timestamp_seconds);
 
     // user tries to subscribe
-    let msg = ExecuteMsg::Subscribe {};
+    let msg = ExecuteMsg::Subscribe {
+        plan_id: None,
+        coupon: None,
+        billing_cycles: None,
+        end_at: None,
+        beneficiary: None,
+        seats: None,
+    };
     let res =
         contract::execute(deps.as_mut(), env.clone(), subscriber1.clone(), msg.clone()).unwrap();
     assert_eq!(
         res.attributes,
         vec![
             attr("initial_amount", "123"),
+            attr("charge_id", "1"),
             attr("method", "execute_subscribe"),
             attr("result", "subscribe_success"),
             attr("subscriber", "subscriber"),
@@ -587,6 +728,9 @@ fn subscribe_multiple_times() {
         owner: "creator".to_string(),
         receiver_address: "receiver".to_string(),
         additional_grace_period_hour: None,
+        retry_schedule_hour: None,
+        max_pause_duration_hour: None,
+        max_pauses_per_year: None,
         unit_amount: Uint256::from(123u128),
         initial_amount: Uint256::from(123u128),
         unit_interval_hour: 720u64,
@@ -594,6 +738,15 @@ fn subscribe_multiple_times() {
         mutable: false,
         uri: "{\"image_url\": \"www.google.com\" }".to_string(),
         factory_address: "factory".to_string(),
+        trial_period_hour: None,
+        plans: None,
+        max_amount_chargeable: None,
+        usage_tiers: None,
+        membership_nft: None,
+        billing_asset: None,
+        revenue_splits: None,
+        billing_calendar: None,
+        accept_any_payer: Some(true),
     };
 
     let info = mock_info("creator", &[]);
@@ -607,7 +760,14 @@ fn subscribe_multiple_times() {
 
     // user tries to subscribe for the first time
 
-    let msg = ExecuteMsg::Subscribe {};
+    let msg = ExecuteMsg::Subscribe {
+        plan_id: None,
+        coupon: None,
+        billing_cycles: None,
+        end_at: None,
+        beneficiary: None,
+        seats: None,
+    };
     let res = contract::execute(
         deps.as_mut(),
         env.clone(),
@@ -619,6 +779,7 @@ fn subscribe_multiple_times() {
         res.attributes,
         vec![
             attr("initial_amount", "123"),
+            attr("charge_id", "1"),
             attr("method", "execute_subscribe"),
             attr("result", "subscribe_success"),
             attr("subscriber", "subscriber"),
@@ -692,6 +853,7 @@ fn subscribe_multiple_times() {
         res.attributes,
         vec![
             attr("initial_amount", "123"),
+            attr("charge_id", "2"),
             attr("method", "execute_subscribe"),
             attr("result", "subscribe_success"),
             attr("subscriber", "subscriber"),
@@ -725,6 +887,9 @@ fn unpaid_subscriptions() {
         owner: "creator".to_string(),
         receiver_address: "receiver".to_string(),
         additional_grace_period_hour: None,
+        retry_schedule_hour: None,
+        max_pause_duration_hour: None,
+        max_pauses_per_year: None,
         unit_amount: Uint256::from(123u128),
         initial_amount: Uint256::from(123u128),
         unit_interval_hour: 720u64,
@@ -732,6 +897,15 @@ fn unpaid_subscriptions() {
         mutable: false,
         uri: "{\"image_url\": \"www.google.com\" }".to_string(),
         factory_address: "factory".to_string(),
+        trial_period_hour: None,
+        plans: None,
+        max_amount_chargeable: None,
+        usage_tiers: None,
+        membership_nft: None,
+        billing_asset: None,
+        revenue_splits: None,
+        billing_calendar: None,
+        accept_any_payer: Some(true),
     };
 
     let info = mock_info("creator", &[]);
@@ -746,7 +920,14 @@ fn unpaid_subscriptions() {
 
     // user tries to subscribe for the first time
 
-    let msg = ExecuteMsg::Subscribe {};
+    let msg = ExecuteMsg::Subscribe {
+        plan_id: None,
+        coupon: None,
+        billing_cycles: None,
+        end_at: None,
+        beneficiary: None,
+        seats: None,
+    };
     let res = contract::execute(
         deps.as_mut(),
         env.clone(),
@@ -758,6 +939,7 @@ fn unpaid_subscriptions() {
         res.attributes,
         vec![
             attr("initial_amount", "123"),
+            attr("charge_id", "1"),
             attr("method", "execute_subscribe"),
             attr("result", "subscribe_success"),
             attr("subscriber", "subscriber"),
@@ -829,10 +1011,22 @@ fn cancel_subscription() {
         initial_amount: Uint256::from(123u128),
         unit_interval_hour: 720u64,
         additional_grace_period_hour: Some(259200u64),
+        retry_schedule_hour: None,
+        max_pause_duration_hour: None,
+        max_pauses_per_year: None,
         admins: Vec::new(),
         mutable: false,
         uri: "{\"image_url\": \"www.google.com\" }".to_string(),
         factory_address: "factory".to_string(),
+        trial_period_hour: None,
+        plans: None,
+        max_amount_chargeable: None,
+        usage_tiers: None,
+        membership_nft: None,
+        billing_asset: None,
+        revenue_splits: None,
+        billing_calendar: None,
+        accept_any_payer: Some(true),
     };
 
     let info = mock_info("creator", &[]);
@@ -845,7 +1039,14 @@ fn cancel_subscription() {
     let start_timestamp = 1609459200;
     env.block.time = Timestamp::from_seconds(start_timestamp); // set to 1 January 2021 00:00:00 GMT
 
-    let msg = ExecuteMsg::Subscribe {};
+    let msg = ExecuteMsg::Subscribe {
+        plan_id: None,
+        coupon: None,
+        billing_cycles: None,
+        end_at: None,
+        beneficiary: None,
+        seats: None,
+    };
     let res = contract::execute(
         deps.as_mut(),
         env.clone(),
@@ -859,6 +1060,7 @@ fn cancel_subscription() {
         res.attributes,
         vec![
             attr("initial_amount", "123"),
+            attr("charge_id", "1"),
             attr("method", "execute_subscribe"),
             attr("result", "subscribe_success"),
             attr("subscriber", "subscriber"),
@@ -943,10 +1145,22 @@ fn remove_subscriber() {
         initial_amount: Uint256::from(123u128),
         unit_interval_hour: 720u64,
         additional_grace_period_hour: Some(259200u64),
+        retry_schedule_hour: None,
+        max_pause_duration_hour: None,
+        max_pauses_per_year: None,
         admins: Vec::new(),
         mutable: false,
         uri: "{\"image_url\": \"www.google.com\" }".to_string(),
         factory_address: "factory".to_string(),
+        trial_period_hour: None,
+        plans: None,
+        max_amount_chargeable: None,
+        usage_tiers: None,
+        membership_nft: None,
+        billing_asset: None,
+        revenue_splits: None,
+        billing_calendar: None,
+        accept_any_payer: Some(true),
     };
 
     let info = mock_info("creator", &[]);
@@ -959,7 +1173,14 @@ fn remove_subscriber() {
     let start_timestamp = 1609459200;
     env.block.time = Timestamp::from_seconds(start_timestamp); // set to 1 January 2021 00:00:00 GMT
 
-    let msg = ExecuteMsg::Subscribe {};
+    let msg = ExecuteMsg::Subscribe {
+        plan_id: None,
+        coupon: None,
+        billing_cycles: None,
+        end_at: None,
+        beneficiary: None,
+        seats: None,
+    };
     let res = contract::execute(
         deps.as_mut(),
         env.clone(),
@@ -973,6 +1194,7 @@ fn remove_subscriber() {
         res.attributes,
         vec![
             attr("initial_amount", "123"),
+            attr("charge_id", "1"),
             attr("method", "execute_subscribe"),
             attr("result", "subscribe_success"),
             attr("subscriber", "subscriber"),
@@ -1033,7 +1255,9 @@ fn remove_subscriber() {
         .unwrap(),
     )
     .unwrap();
-    assert_eq!(subscription, None);
+    let subscription = subscription.unwrap();
+    assert_eq!(subscription.status, SubscriptionStatus::Removed);
+    assert!(!subscription.is_active);
 }
 
 #[test]
@@ -1046,10 +1270,22 @@ fn modify_subscriber() {
         initial_amount: Uint256::from(123u128),
         unit_interval_hour: 720u64,
         additional_grace_period_hour: Some(259200u64),
+        retry_schedule_hour: None,
+        max_pause_duration_hour: None,
+        max_pauses_per_year: None,
         admins: Vec::new(),
         mutable: false,
         uri: "{\"image_url\": \"www.google.com\" }".to_string(),
         factory_address: "factory".to_string(),
+        trial_period_hour: None,
+        plans: None,
+        max_amount_chargeable: None,
+        usage_tiers: None,
+        membership_nft: None,
+        billing_asset: None,
+        revenue_splits: None,
+        billing_calendar: None,
+        accept_any_payer: Some(true),
     };
 
     let info = mock_info("creator", &[]);
@@ -1061,7 +1297,14 @@ fn modify_subscriber() {
     env.block.time = Timestamp::from_seconds(start_timestamp); // set to 1 January 2021 00:00:00 GMT
 
     let info_subscriber = mock_info("subscriber", &[]);
-    let msg = ExecuteMsg::Subscribe {};
+    let msg = ExecuteMsg::Subscribe {
+        plan_id: None,
+        coupon: None,
+        billing_cycles: None,
+        end_at: None,
+        beneficiary: None,
+        seats: None,
+    };
 
     let _res = contract::execute(
         deps.as_mut(),
@@ -1110,13 +1353,30 @@ fn modify_subscriber() {
     let subscriber_info: SubscriptionInfoResponse = from_binary(&res).unwrap();
     let expected_response: SubscriptionInfoResponse = SubscriptionInfoResponse {
         subscriber: "subscriber".to_string(),
+        plan_id: 0,
         created_at: 1609459200,
         last_charged: 1609459200,
         interval_end_at: 1612051200,
         is_active: true,
+        status: SubscriptionStatus::Active,
         is_cancelled: false,
         discount_per_interval: None,
+        trial_end_at: None,
         amount_chargeable: Some(Uint256::zero()),
+        total_paid: Uint256::from(123u128),
+        lifetime_paid: Uint256::from(123u128),
+        max_amount_chargeable: None,
+        is_past_due: false,
+        retry_count: 0,
+        next_retry_at: None,
+        is_paused: false,
+        resume_at: None,
+        term_end_at: None,
+        is_completed: false,
+        payer: "subscriber".to_string(),
+        prepaid_balance: Uint256::zero(),
+        seats: None,
+        team_owner: None,
     };
 
     assert_eq!(subscriber_info, expected_response);
@@ -1133,10 +1393,22 @@ fn test_discount() {
         initial_amount: Uint256::from(123u128),
         unit_interval_hour: 720u64,
         additional_grace_period_hour: Some(259200u64),
+        retry_schedule_hour: None,
+        max_pause_duration_hour: None,
+        max_pauses_per_year: None,
         admins: Vec::new(),
         mutable: false,
         uri: "{\"image_url\": \"www.google.com\" }".to_string(),
         factory_address: "factory".to_string(),
+        trial_period_hour: None,
+        plans: None,
+        max_amount_chargeable: None,
+        usage_tiers: None,
+        membership_nft: None,
+        billing_asset: None,
+        revenue_splits: None,
+        billing_calendar: None,
+        accept_any_payer: Some(true),
     };
 
     let info = mock_info("creator", &[]);
@@ -1148,7 +1420,14 @@ fn test_discount() {
     env.block.time = Timestamp::from_seconds(start_timestamp); // set to 1 January 2021 00:00:00 GMT
 
     let info_subscriber = mock_info("subscriber", &[]);
-    let msg = ExecuteMsg::Subscribe {};
+    let msg = ExecuteMsg::Subscribe {
+        plan_id: None,
+        coupon: None,
+        billing_cycles: None,
+        end_at: None,
+        beneficiary: None,
+        seats: None,
+    };
 
     let _res = contract::execute(
         deps.as_mut(),
@@ -1162,6 +1441,9 @@ fn test_discount() {
     let msg = ExecuteMsg::SetDiscount {
         discount: Some(Discount {
             amount: Uint256::from(23u128),
+            discount_type: DiscountType::Fixed,
+            expiry: None,
+            intervals: None,
         }),
         subscriber: "subscriber".to_string(),
     };
@@ -1201,7 +1483,8 @@ fn test_discount() {
             attr("module_contract_address", "cosmos2contract"),
             attr("subscriber", "subscriber"),
             attr("amount", "100"),
-            attr("periods", "1")
+            attr("periods", "1"),
+            attr("charge_id", "2"),
         ]
     );
 }
@@ -1217,10 +1500,22 @@ fn update_initial_amount_and_subscribe() {
         initial_amount: Uint256::from(123u128),
         unit_interval_hour: 720u64,
         additional_grace_period_hour: Some(259200u64),
+        retry_schedule_hour: None,
+        max_pause_duration_hour: None,
+        max_pauses_per_year: None,
         admins: Vec::new(),
         mutable: false,
         uri: "{\"image_url\": \"www.google.com\" }".to_string(),
         factory_address: "factory".to_string(),
+        trial_period_hour: None,
+        plans: None,
+        max_amount_chargeable: None,
+        usage_tiers: None,
+        membership_nft: None,
+        billing_asset: None,
+        revenue_splits: None,
+        billing_calendar: None,
+        accept_any_payer: Some(true),
     };
 
     let info = mock_info("creator", &[]);
@@ -1235,8 +1530,13 @@ fn update_initial_amount_and_subscribe() {
     let msg = ExecuteMsg::UpdateConfig {
         receiver_address: None,
         additional_grace_period_hour: None,
+        retry_schedule_hour: None,
+        max_pause_duration_hour: None,
+        max_pauses_per_year: None,
         initial_amount: Some(Uint256::from(100u128)), // change to 100
         uri: None,
+        revenue_splits: None,
+        accept_any_payer: None,
     };
 
     let unauth_info = mock_info("anyone", &[]);
@@ -1257,7 +1557,14 @@ fn update_initial_amount_and_subscribe() {
     );
 
     // user tries to subscribe
-    let msg = ExecuteMsg::Subscribe {};
+    let msg = ExecuteMsg::Subscribe {
+        plan_id: None,
+        coupon: None,
+        billing_cycles: None,
+        end_at: None,
+        beneficiary: None,
+        seats: None,
+    };
     let info_subscriber = mock_info("subscriber", &[]);
 
     let res = contract::execute(
@@ -1271,6 +1578,7 @@ fn update_initial_amount_and_subscribe() {
         res.attributes,
         vec![
             attr("initial_amount", "100"),
+            attr("charge_id", "1"),
             attr("method", "execute_subscribe"),
             attr("result", "subscribe_success"),
             attr("subscriber", "subscriber"),
@@ -1289,10 +1597,22 @@ fn update_admins() {
         initial_amount: Uint256::from(123u128),
         unit_interval_hour: 720u64,
         additional_grace_period_hour: Some(259200u64),
+        retry_schedule_hour: None,
+        max_pause_duration_hour: None,
+        max_pauses_per_year: None,
         admins: Vec::new(),
         mutable: false,
         uri: "{\"image_url\": \"www.google.com\" }".to_string(),
         factory_address: "factory".to_string(),
+        trial_period_hour: None,
+        plans: None,
+        max_amount_chargeable: None,
+        usage_tiers: None,
+        membership_nft: None,
+        billing_asset: None,
+        revenue_splits: None,
+        billing_calendar: None,
+        accept_any_payer: Some(true),
     };
 
     let info = mock_info("creator", &[]);
@@ -1360,10 +1680,22 @@ fn cancel_and_undo() {
         initial_amount: Uint256::from(123u128),
         unit_interval_hour: 720u64,
         additional_grace_period_hour: Some(259200u64),
+        retry_schedule_hour: None,
+        max_pause_duration_hour: None,
+        max_pauses_per_year: None,
         admins: Vec::new(),
         mutable: false,
         uri: "{\"image_url\": \"www.google.com\" }".to_string(),
         factory_address: "factory".to_string(),
+        trial_period_hour: None,
+        plans: None,
+        max_amount_chargeable: None,
+        usage_tiers: None,
+        membership_nft: None,
+        billing_asset: None,
+        revenue_splits: None,
+        billing_calendar: None,
+        accept_any_payer: Some(true),
     };
 
     let info = mock_info("creator", &[]);
@@ -1376,7 +1708,14 @@ fn cancel_and_undo() {
     let start_timestamp = 1609459200;
     env.block.time = Timestamp::from_seconds(start_timestamp); // set to 1 January 2021 00:00:00 GMT
 
-    let msg = ExecuteMsg::Subscribe {};
+    let msg = ExecuteMsg::Subscribe {
+        plan_id: None,
+        coupon: None,
+        billing_cycles: None,
+        end_at: None,
+        beneficiary: None,
+        seats: None,
+    };
     let res = contract::execute(
         deps.as_mut(),
         env.clone(),
@@ -1390,6 +1729,7 @@ fn cancel_and_undo() {
         res.attributes,
         vec![
             attr("initial_amount", "123"),
+            attr("charge_id", "1"),
             attr("method", "execute_subscribe"),
             attr("result", "subscribe_success"),
             attr("subscriber", "subscriber"),
@@ -1439,7 +1779,14 @@ fn cancel_and_undo() {
     let new_timestamp = start_timestamp + 200 * 60 * 60;
     env.block.time = Timestamp::from_seconds(new_timestamp); // set to 1 January 2021 00:00:00 GMT
 
-    let msg = ExecuteMsg::Subscribe {};
+    let msg = ExecuteMsg::Subscribe {
+        plan_id: None,
+        coupon: None,
+        billing_cycles: None,
+        end_at: None,
+        beneficiary: None,
+        seats: None,
+    };
     let res = contract::execute(
         deps.as_mut(),
         env.clone(),
@@ -1474,13 +1821,30 @@ fn cancel_and_undo() {
 
     let expected_subscription = SubscriptionInfoResponse {
         subscriber: "subscriber".to_string(),
+        plan_id: 0,
         created_at: start_timestamp,
         last_charged: start_timestamp,
         interval_end_at: start_timestamp + THIRTY_DAYS_IN_SECONDS,
         is_cancelled: false,
         is_active: true,
+        status: SubscriptionStatus::Active,
         discount_per_interval: None,
+        trial_end_at: None,
         amount_chargeable: Some(Uint256::zero()),
+        total_paid: Uint256::from(123u128),
+        lifetime_paid: Uint256::from(123u128),
+        max_amount_chargeable: None,
+        is_past_due: false,
+        retry_count: 0,
+        next_retry_at: None,
+        is_paused: false,
+        resume_at: None,
+        term_end_at: None,
+        is_completed: false,
+        payer: "subscriber".to_string(),
+        prepaid_balance: Uint256::zero(),
+        seats: None,
+        team_owner: None,
     };
 
     // subscription should be cancelled
@@ -1505,6 +1869,20 @@ fn test_compute_amount_chargeable() {
         unit_interval: Timestamp::from_seconds(60u64),
         uri: "{\"image_url\": \"www.google.com\" }".to_string(),
         factory_address: Addr::unchecked("factory"),
+        trial_period: 0,
+        max_amount_chargeable: None,
+        retry_schedule: vec![],
+        max_pause_duration: 0,
+        max_pauses_per_year: 0,
+        usage_tiers: vec![],
+        usage_reporters: vec![],
+        membership_nft: None,
+        billing_asset: None,
+        revenue_splits: vec![],
+        hooks: vec![],
+        billing_calendar: None,
+        price_changes: vec![],
+        accept_any_payer: false,
     };
 
     let subscription_info = SubscriptionInfo {
@@ -1514,10 +1892,25 @@ fn test_compute_amount_chargeable() {
         interval_end_at: Timestamp::from_seconds(100060),
         is_cancelled: false,
         discount_per_interval: None,
+        plan_id: 0,
+        trial_end_at: None,
+        total_paid: Uint256::zero(),
+        retry_count: 0,
+        next_retry_at: None,
+        resume_at: None,
+        pause_history: vec![],
+        term_end_at: None,
+        usage: vec![],
+        payer: None,
+        lifetime_paid: Uint256::zero(),
+        seats: None,
+        seat_proration: Uint256::zero(),
+        seat_credit: Uint256::zero(),
     };
     // charge after 10mins
     let amount_chargeable = contract::compute_amount_chargeable(
-        &state,
+        &state.default_plan(),
+        &[],
         &subscription_info,
         Timestamp::from_seconds(100659),
     );
@@ -1535,6 +1928,20 @@ fn test_compute_amount_chargeable() {
         additional_grace_period: 0,
         uri: "{\"image_url\": \"www.google.com\" }".to_string(),
         factory_address: Addr::unchecked("factory"),
+        trial_period: 0,
+        max_amount_chargeable: None,
+        retry_schedule: vec![],
+        max_pause_duration: 0,
+        max_pauses_per_year: 0,
+        usage_tiers: vec![],
+        usage_reporters: vec![],
+        membership_nft: None,
+        billing_asset: None,
+        revenue_splits: vec![],
+        hooks: vec![],
+        billing_calendar: None,
+        price_changes: vec![],
+        accept_any_payer: false,
     };
 
     let subscription_info = SubscriptionInfo {
@@ -1544,10 +1951,25 @@ fn test_compute_amount_chargeable() {
         interval_end_at: Timestamp::from_seconds(100300),
         is_cancelled: false,
         discount_per_interval: None,
+        plan_id: 0,
+        trial_end_at: None,
+        total_paid: Uint256::zero(),
+        retry_count: 0,
+        next_retry_at: None,
+        resume_at: None,
+        pause_history: vec![],
+        term_end_at: None,
+        usage: vec![],
+        payer: None,
+        lifetime_paid: Uint256::zero(),
+        seats: None,
+        seat_proration: Uint256::zero(),
+        seat_credit: Uint256::zero(),
     };
     // charge $0 after 1min
     let amount_chargeable = contract::compute_amount_chargeable(
-        &state,
+        &state.default_plan(),
+        &[],
         &subscription_info,
         Timestamp::from_seconds(100060),
     );
@@ -1555,7 +1977,8 @@ fn test_compute_amount_chargeable() {
 
     // charge $1 after 5min
     let amount_chargeable = contract::compute_amount_chargeable(
-        &state,
+        &state.default_plan(),
+        &[],
         &subscription_info,
         Timestamp::from_seconds(100300),
     );
@@ -1563,7 +1986,8 @@ fn test_compute_amount_chargeable() {
 
     // charge $2 after 11min
     let amount_chargeable = contract::compute_amount_chargeable(
-        &state,
+        &state.default_plan(),
+        &[],
         &subscription_info,
         Timestamp::from_seconds(100661),
     );
@@ -3,26 +3,36 @@ use crate::enumerable::{
     query_overdue_agreements,
 };
 use crate::error::ContractError;
-use crate::msg::{AgreementResponse, ExecuteMsg, InstantiateMsg, QueryMsg, WorkPayload};
+use crate::msg::{
+    AgreementResponse, BatchWorkPayload, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg,
+    WorkPayload,
+};
 use crate::state::{
-    agreements, increment_agreement_id, Agreement, AgreementStatus, Config, CONFIG,
+    agreements, increment_agreement_id, Agreement, AgreementStatus, Config, PendingTransfer,
+    CONFIG, PENDING_TRANSFERS,
 };
 use cosmwasm_bignumber::{Decimal256, Uint256};
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    attr, from_binary, to_binary, Addr, Attribute, Binary, BlockInfo, CosmosMsg, Deps, DepsMut,
-    Env, MessageInfo, Response, StdResult, Timestamp, WasmMsg,
+    attr, from_binary, to_binary, Addr, Attribute, Binary, BlockInfo, ContractResult, CosmosMsg,
+    Deps, DepsMut, Env, Event, MessageInfo, Reply, Response, StdResult, SubMsg, Timestamp, WasmMsg,
 };
 use cw2::set_contract_version;
 use cw_storage_plus::U64Key;
-use suberra_core::msg::{JobsRegistryExecuteMsg, MigrateMsg, SubWalletExecuteMsg};
+use suberra_core::asset::AssetInfo;
+use suberra_core::migration::{migrate_contract_version, Version};
+use suberra_core::msg::{JobsRegistryExecuteMsg, SubWalletExecuteMsg};
+use suberra_core::querier::query_subwallet_owner;
 use suberra_core::util::optional_addr_validate;
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:p2p_recurring_transfers";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+// maximum number of agreements that can be transferred in a single batched `Work` call
+const MAX_BATCH_SIZE: usize = 30;
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
@@ -49,6 +59,11 @@ pub fn instantiate(
         is_paused: false,
         is_frozen: false,
         job_registry_contract,
+        subwallet_factory_contract: optional_addr_validate(
+            deps.api,
+            msg.subwallet_factory_contract,
+        )?,
+        accept_any_payer: msg.accept_any_payer.unwrap_or(false),
         owner: info.sender.clone(),
         fee_address,
         minimum_amount_per_interval: msg.minimum_amount_per_interval,
@@ -78,20 +93,26 @@ pub fn execute(
             start_at,
             end_at,
             interval,
+            asset_info,
         } => try_create_agreement(
-            deps, env, info, receiver, amount, start_at, end_at, interval,
+            deps, env, info, receiver, amount, start_at, end_at, interval, asset_info,
         ),
         ExecuteMsg::Transfer { agreement_id } => try_transfer(deps, env, agreement_id, None),
         ExecuteMsg::CancelAgreement { agreement_id } => try_cancel(deps, env, info, agreement_id),
         ExecuteMsg::TerminateAgreement { agreement_id } => {
             try_terminate(deps, env, info, agreement_id)
         }
-        ExecuteMsg::Work { payload } => {
-            let payload: WorkPayload = from_binary(&payload).unwrap();
-            try_work(deps, env, info, payload.agreement_id)
-        }
+        ExecuteMsg::Work { payload } => match from_binary::<WorkPayload>(&payload) {
+            Ok(payload) => try_work(deps, env, info, payload.agreement_id),
+            Err(_) => {
+                let payload: BatchWorkPayload = from_binary(&payload)?;
+                try_batch_work(deps, env, info, payload.agreement_ids)
+            }
+        },
         ExecuteMsg::UpdateConfig {
             job_registry_contract,
+            subwallet_factory_contract,
+            accept_any_payer,
             minimum_interval,
             minimum_amount_per_interval,
             new_owner,
@@ -105,6 +126,8 @@ pub fn execute(
                 env,
                 info,
                 optional_addr_validate(api, job_registry_contract)?,
+                optional_addr_validate(api, subwallet_factory_contract)?,
+                accept_any_payer,
                 minimum_interval,
                 minimum_amount_per_interval,
                 optional_addr_validate(api, new_owner)?,
@@ -115,13 +138,53 @@ pub fn execute(
         }
         ExecuteMsg::ToggleFreeze {} => try_toggle_freeze(deps, env, info),
         ExecuteMsg::TogglePause {} => try_toggle_pause(deps, env, info),
+        ExecuteMsg::CollectTransfer { agreement_id } => {
+            try_collect_transfer(deps, env, info, agreement_id)
+        }
+        ExecuteMsg::SendWorkReceipt {
+            worker_address,
+            units,
+        } => try_send_work_receipt(deps, env, info, worker_address, units),
     }
 }
 
 #[allow(dead_code)]
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
-    Ok(Response::default())
+pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let previous_version =
+        migrate_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION, &[])?;
+
+    let mut config = CONFIG.load(deps.storage)?;
+    let mut attributes = vec![
+        attr("method", "migrate"),
+        attr("previous_version", previous_version.to_string()),
+        attr("version", CONTRACT_VERSION),
+    ];
+
+    if let Some(subwallet_factory_contract) = msg.subwallet_factory_contract {
+        config.subwallet_factory_contract =
+            Some(deps.api.addr_validate(&subwallet_factory_contract)?);
+        attributes.push(attr(
+            "subwallet_factory_contract",
+            subwallet_factory_contract,
+        ));
+    }
+    if let Some(accept_any_payer) = msg.accept_any_payer {
+        config.accept_any_payer = accept_any_payer;
+        attributes.push(attr("accept_any_payer", accept_any_payer.to_string()));
+    }
+
+    // agreements from before 0.3.0 were created by any account, so the payer check needs to be configured
+    if previous_version < Version::new(0, 3, 0)
+        && config.subwallet_factory_contract.is_none()
+        && !config.accept_any_payer
+    {
+        return Err(ContractError::SubwalletFactoryNotSet {});
+    }
+
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(attributes))
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -134,6 +197,7 @@ pub fn try_create_agreement(
     start_at: Option<u64>,
     end_at: Option<u64>,
     interval: u64,
+    asset_info: Option<AssetInfo>,
 ) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
 
@@ -151,6 +215,22 @@ pub fn try_create_agreement(
         return Err(ContractError::CannotSetOwnAccount {});
     }
 
+    // the sender pays for the agreement, so it must be a subwallet unless the contract accepts any payer
+    if !config.accept_any_payer {
+        let subwallet_factory_contract = config
+            .subwallet_factory_contract
+            .clone()
+            .ok_or(ContractError::SubwalletFactoryNotSet {})?;
+        if query_subwallet_owner(&deps.querier, subwallet_factory_contract, &info.sender)?.is_none()
+        {
+            return Err(ContractError::PayerNotSubwallet {});
+        }
+    }
+
+    if let Some(asset_info) = &asset_info {
+        asset_info.check(deps.api)?;
+    }
+
     let config: Config = CONFIG.load(deps.storage)?;
     if interval < config.minimum_interval {
         return Err(ContractError::InvalidParams {});
@@ -207,6 +287,7 @@ pub fn try_create_agreement(
         interval_due_at,
         last_charged: env.block.time,
         end_at: end_time,
+        asset_info,
     };
 
     // try charge, skips if no charge
@@ -243,17 +324,28 @@ fn attempt_charge(
     agreement.interval_due_at = agreement.interval_due_at.plus_seconds(agreement.interval);
     agreement.last_charged = env.block.time;
 
+    messages.extend(build_transfer_msgs(&config, agreement)?);
+
+    attributes.push(attr("amount", agreement.amount.to_string()));
+
+    Ok(())
+}
+
+// builds the messages that transfer the amount of the agreement from the subwallet of the owner, less the fee sent to the fee address
+fn build_transfer_msgs(config: &Config, agreement: &Agreement) -> StdResult<Vec<CosmosMsg>> {
+    let mut messages: Vec<CosmosMsg> = vec![];
     let mut transfer_amount = agreement.amount;
 
-    let fee_amount = compute_fees(&config, agreement);
+    let fee_amount = compute_fees(config, agreement);
     if fee_amount > Uint256::zero() {
         messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
             contract_addr: agreement.from.clone().into_string(),
             funds: vec![],
-            msg: to_binary(&SubWalletExecuteMsg::TransferAToken {
-                recipient: config.fee_address.to_string(),
-                amount: fee_amount.into(),
-            })?,
+            msg: to_binary(&SubWalletExecuteMsg::transfer(
+                &agreement.asset_info,
+                fee_amount.into(),
+                config.fee_address.to_string(),
+            ))?,
         }));
 
         transfer_amount = transfer_amount - fee_amount;
@@ -262,15 +354,14 @@ fn attempt_charge(
     messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
         contract_addr: agreement.from.clone().into_string(),
         funds: vec![],
-        msg: to_binary(&SubWalletExecuteMsg::TransferAToken {
-            recipient: agreement.to.clone().to_string(),
-            amount: transfer_amount.into(),
-        })?,
+        msg: to_binary(&SubWalletExecuteMsg::transfer(
+            &agreement.asset_info,
+            transfer_amount.into(),
+            agreement.to.to_string(),
+        ))?,
     }));
 
-    attributes.push(attr("amount", agreement.amount.to_string()));
-
-    Ok(())
+    Ok(messages)
 }
 
 /// toggles the freeze flag.  Can only be called by the owner.
@@ -449,17 +540,243 @@ pub fn try_work(
             funds: vec![],
             msg: to_binary(&JobsRegistryExecuteMsg::WorkReceipt {
                 worker_address: worker,
+                units: None,
             })?,
         })),
     )
 }
 
+/// try_batch_work transfers up to `MAX_BATCH_SIZE` agreements in a single call.
+/// Agreements that have nothing to transfer and transfers that fail are skipped instead of failing the whole batch.
+/// Every transfer is sent as a submessage that replies on error, so that the agreement of a failed transfer is restored in [`reply`].
+/// The attributes of every transfer are kept in a `transfer` event, and the error of every agreement that cannot be charged
+/// in a `transfer_skipped` event. A single work receipt scaled to the number of successful transfers is sent to the job registry.
+pub fn try_batch_work(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    agreement_ids: Vec<u64>,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    if config.is_frozen {
+        return Err(ContractError::Frozen {});
+    }
+
+    if config.job_registry_contract.is_none() {
+        return Err(ContractError::NoJobRegistry {});
+    }
+
+    if agreement_ids.len() > MAX_BATCH_SIZE {
+        return Err(ContractError::BatchTooLarge {});
+    }
+
+    PENDING_TRANSFERS.save(deps.storage, &vec![])?;
+
+    let mut response = Response::new();
+    let mut transferred: Vec<String> = vec![];
+    let mut skipped: Vec<String> = vec![];
+
+    for agreement_id in agreement_ids {
+        if !has_pending_charge(deps.as_ref(), &env, agreement_id)? {
+            skipped.push(agreement_id.to_string());
+            continue;
+        }
+
+        match charge_in_batch(deps.branch(), &env, agreement_id) {
+            Ok(transfer_response) => {
+                response = response
+                    .add_submessages(transfer_response.messages)
+                    .add_event(Event::new("transfer").add_attributes(transfer_response.attributes));
+                transferred.push(agreement_id.to_string());
+            }
+            Err(error) => {
+                response = response.add_event(Event::new("transfer_skipped").add_attributes(vec![
+                    attr("agreement_id", agreement_id.to_string()),
+                    attr("error", error.to_string()),
+                ]));
+                skipped.push(agreement_id.to_string());
+            }
+        }
+    }
+
+    if transferred.is_empty() {
+        return Err(ContractError::ZeroTransferableAmount {});
+    }
+
+    // the work receipt is sent after the transfers, so that only the transfers that went through are counted
+    Ok(response
+        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: env.contract.address.to_string(),
+            funds: vec![],
+            msg: to_binary(&ExecuteMsg::SendWorkReceipt {
+                worker_address: info.sender.to_string(),
+                units: transferred.len() as u64,
+            })?,
+        }))
+        .add_attributes(vec![
+            attr("method", "batch_work"),
+            attr("module_contract_address", env.contract.address.to_string()),
+            attr("transferred", transferred.join(",")),
+            attr("skipped", skipped.join(",")),
+        ]))
+}
+
+// charges the agreement for a batch, and sends the transfer as a submessage that replies on error.
+// The agreement before the charge is kept in `PENDING_TRANSFERS`, so that it can be restored if the transfer fails
+fn charge_in_batch(deps: DepsMut, env: &Env, agreement_id: u64) -> Result<Response, ContractError> {
+    let key = U64Key::from(agreement_id);
+    let mut agreement = match agreements().may_load(deps.storage, key.clone())? {
+        Some(v) => v,
+        None => return Err(ContractError::AgreementNotFound {}),
+    };
+    let previous_agreement = agreement.clone();
+
+    // the transfer messages are built again by `CollectTransfer`, so that the fee and the transfer are made in one submessage
+    let mut transfer_msgs: Vec<CosmosMsg> = vec![];
+    let mut attributes: Vec<Attribute> = vec![attr("agreement_id", agreement_id.to_string())];
+    attempt_charge(
+        &deps,
+        env.clone(),
+        &mut agreement,
+        &mut transfer_msgs,
+        &mut attributes,
+    )?;
+    agreements().save(deps.storage, key, &agreement)?;
+
+    let mut pending_transfers = PENDING_TRANSFERS.load(deps.storage)?;
+    let reply_id = pending_transfers.len() as u64;
+    pending_transfers.push(PendingTransfer {
+        agreement_id,
+        agreement: previous_agreement,
+        is_failed: false,
+    });
+    PENDING_TRANSFERS.save(deps.storage, &pending_transfers)?;
+
+    let transfer_msg = SubMsg::reply_on_error(
+        WasmMsg::Execute {
+            contract_addr: env.contract.address.to_string(),
+            funds: vec![],
+            msg: to_binary(&ExecuteMsg::CollectTransfer { agreement_id })?,
+        },
+        reply_id,
+    );
+
+    Ok(Response::new()
+        .add_submessage(transfer_msg)
+        .add_attributes(attributes))
+}
+
+/// Transfers the charge of an agreement from the subwallet of its owner, after it has been charged in a batch.
+/// Can only be called by the contract itself
+pub fn try_collect_transfer(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    agreement_id: u64,
+) -> Result<Response, ContractError> {
+    if info.sender != env.contract.address {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let config = CONFIG.load(deps.storage)?;
+    let agreement = agreements().load(deps.storage, U64Key::from(agreement_id))?;
+
+    Ok(Response::new()
+        .add_messages(build_transfer_msgs(&config, &agreement)?)
+        .add_attributes(vec![
+            attr("method", "collect_transfer"),
+            attr("agreement_id", agreement_id.to_string()),
+        ]))
+}
+
+/// Sends a work receipt for `units` transfers to the job registry, less the transfers of the batch that failed.
+/// No receipt is sent if every transfer has failed. Can only be called by the contract itself
+pub fn try_send_work_receipt(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    worker_address: String,
+    units: u64,
+) -> Result<Response, ContractError> {
+    if info.sender != env.contract.address {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let failed = PENDING_TRANSFERS
+        .load(deps.storage)?
+        .iter()
+        .filter(|pending_transfer| pending_transfer.is_failed)
+        .count() as u64;
+    let units = units.saturating_sub(failed);
+
+    let mut response = Response::new().add_attributes(vec![
+        attr("method", "send_work_receipt"),
+        attr("worker_address", worker_address.clone()),
+        attr("units", units.to_string()),
+    ]);
+
+    if units > 0 {
+        let job_registry_contract = match CONFIG.load(deps.storage)?.job_registry_contract {
+            Some(job_registry_contract) => job_registry_contract,
+            None => return Err(ContractError::NoJobRegistry {}),
+        };
+
+        response = response.add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: job_registry_contract.to_string(),
+            funds: vec![],
+            msg: to_binary(&JobsRegistryExecuteMsg::WorkReceipt {
+                worker_address,
+                units: Some(units),
+            })?,
+        }));
+    }
+
+    Ok(response)
+}
+
+/// Handles the failure of a transfer sent in a batch. The agreement is restored to its state before the charge,
+/// so that it can be transferred again, and the transfer is not counted in the work receipt
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    let error = match msg.result {
+        ContractResult::Err(error) => error,
+        // transfers only reply on error
+        ContractResult::Ok(_) => return Err(ContractError::InvalidReplyID {}),
+    };
+
+    let mut pending_transfers = PENDING_TRANSFERS.load(deps.storage)?;
+    let pending_transfer = match pending_transfers.get_mut(msg.id as usize) {
+        Some(pending_transfer) => {
+            pending_transfer.is_failed = true;
+            pending_transfer.clone()
+        }
+        None => return Err(ContractError::InvalidReplyID {}),
+    };
+    PENDING_TRANSFERS.save(deps.storage, &pending_transfers)?;
+
+    agreements().save(
+        deps.storage,
+        U64Key::from(pending_transfer.agreement_id),
+        &pending_transfer.agreement,
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("method", "transfer_skipped"),
+        attr("module_contract_address", env.contract.address.to_string()),
+        attr("agreement_id", pending_transfer.agreement_id.to_string()),
+        attr("error", error),
+    ]))
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn update_config(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
     job_registry_contract: Option<Addr>,
+    subwallet_factory_contract: Option<Addr>,
+    accept_any_payer: Option<bool>,
     new_minimum_interval: Option<u64>,
     new_minimum_amount_per_interval: Option<Uint256>,
     new_owner: Option<Addr>,
@@ -500,6 +817,19 @@ pub fn update_config(
         ))
     }
 
+    if let Some(subwallet_factory_contract) = subwallet_factory_contract {
+        config.subwallet_factory_contract = Some(subwallet_factory_contract.clone());
+        attributes.push(attr(
+            "subwallet_factory_contract",
+            subwallet_factory_contract.to_string(),
+        ))
+    }
+
+    if let Some(accept_any_payer) = accept_any_payer {
+        config.accept_any_payer = accept_any_payer;
+        attributes.push(attr("accept_any_payer", accept_any_payer.to_string()))
+    }
+
     if let Some(new_owner) = new_owner {
         config.owner = new_owner.clone();
         attributes.push(attr("owner", new_owner.to_string()))
@@ -624,10 +954,17 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::OverduedAgreements { start_after, limit } => {
             to_binary(&query_overdue_agreements(deps, env, start_after, limit)?)
         }
-        QueryMsg::CanWork { payload } => {
-            let work_payload: WorkPayload = from_binary(&payload).unwrap();
-            to_binary(&query_can_work(deps, env, work_payload.agreement_id)?)
-        }
+        QueryMsg::CanWork { payload } => match from_binary::<WorkPayload>(&payload) {
+            Ok(work_payload) => to_binary(&query_can_work(deps, env, work_payload.agreement_id)?),
+            Err(_) => {
+                let work_payload: BatchWorkPayload = from_binary(&payload)?;
+                to_binary(&query_can_work_batch(
+                    deps,
+                    env,
+                    work_payload.agreement_ids,
+                )?)
+            }
+        },
     }
 }
 
@@ -657,15 +994,36 @@ fn query_can_work(deps: Deps, env: Env, agreement_id: u64) -> StdResult<bool> {
         return Ok(false);
     }
 
+    has_pending_charge(deps, &env, agreement_id)
+}
+
+/// query_can_work_batch returns true if at least one of the agreements in the batch can be transferred
+fn query_can_work_batch(deps: Deps, env: Env, agreement_ids: Vec<u64>) -> StdResult<bool> {
+    let config = CONFIG.load(deps.storage)?;
+
+    if config.is_frozen || agreement_ids.len() > MAX_BATCH_SIZE {
+        return Ok(false);
+    }
+
+    for agreement_id in agreement_ids {
+        if has_pending_charge(deps, &env, agreement_id)? {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+/// returns true if the agreement exists and has a charge that can be transferred right now
+fn has_pending_charge(deps: Deps, env: &Env, agreement_id: u64) -> StdResult<bool> {
     let agreement = match agreements().may_load(deps.storage, U64Key::from(agreement_id))? {
         Some(v) => v,
         None => return Ok(false),
     };
 
     let status = compute_status(&agreement, &env.block);
-    let has_charge = has_charge(&agreement, status, &env.block);
 
-    Ok(has_charge)
+    Ok(has_charge(&agreement, status, &env.block))
 }
 
 fn query_config(deps: Deps) -> StdResult<Config> {
@@ -38,4 +38,16 @@ pub enum ContractError {
 
     #[error("P2P contract is paused.")]
     Paused {},
+
+    #[error("Batch exceeds the maximum number of entries")]
+    BatchTooLarge {},
+
+    #[error("Payer is not a subwallet")]
+    PayerNotSubwallet {},
+
+    #[error("Subwallet factory is not set")]
+    SubwalletFactoryNotSet {},
+
+    #[error("Invalid Reply ID")]
+    InvalidReplyID {},
 }
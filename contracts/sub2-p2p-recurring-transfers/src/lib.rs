@@ -8,3 +8,6 @@ pub use crate::error::ContractError;
 
 #[cfg(test)]
 mod testing;
+
+#[cfg(test)]
+mod mock_querier;
@@ -3,17 +3,32 @@ use cosmwasm_bignumber::Uint256;
 use cosmwasm_std::{Addr, Binary};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use suberra_core::asset::AssetInfo;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
     pub minimum_interval: u64,
     pub minimum_amount_per_interval: Uint256,
     pub job_registry_contract: Option<String>,
+    /// Only subwallets of the subwallet factory can create agreements if set
+    pub subwallet_factory_contract: Option<String>,
+    /// Accepts agreements from accounts that are not subwallets. Defaults to false
+    pub accept_any_payer: Option<bool>,
     pub fee_bps: Option<u64>,
     pub fee_address: Option<String>,
     pub max_fee: Option<Uint256>,
 }
 
+/// Migrating from before `0.3.0` requires either the subwallet factory or `accept_any_payer`,
+/// as agreements cannot be created otherwise
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateMsg {
+    /// Only subwallets of the subwallet factory can create agreements if set
+    pub subwallet_factory_contract: Option<String>,
+    /// Accepts agreements from accounts that are not subwallets
+    pub accept_any_payer: Option<bool>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
@@ -23,6 +38,8 @@ pub enum ExecuteMsg {
         start_at: Option<u64>,
         end_at: Option<u64>,
         interval: u64,
+        /// Native or cw20 asset the agreement is paid in. Defaults to aUST
+        asset_info: Option<AssetInfo>,
     },
     Transfer {
         agreement_id: u64,
@@ -38,6 +55,8 @@ pub enum ExecuteMsg {
     },
     UpdateConfig {
         job_registry_contract: Option<String>,
+        subwallet_factory_contract: Option<String>,
+        accept_any_payer: Option<bool>,
         minimum_interval: Option<u64>,
         minimum_amount_per_interval: Option<Uint256>,
         new_owner: Option<String>,
@@ -47,6 +66,17 @@ pub enum ExecuteMsg {
     },
     ToggleFreeze {},
     TogglePause {},
+    /// Transfers the charge of the agreement that has just been charged in a batch from the subwallet of the owner.
+    /// Only callable by the contract itself, so that the fee and the transfer to the receiver succeed or fail together
+    CollectTransfer {
+        agreement_id: u64,
+    },
+    /// Sends a work receipt for `units` transfers to the job registry, less the transfers of the batch that failed.
+    /// Only callable by the contract itself, after the transfers of the batch have been collected
+    SendWorkReceipt {
+        worker_address: String,
+        units: u64,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -92,6 +122,7 @@ pub struct AgreementResponse {
     pub end_at: Option<u64>,
     pub status: AgreementStatus,
     pub pending_charge: Uint256,
+    pub asset_info: Option<AssetInfo>,
 }
 
 impl AgreementResponse {
@@ -112,6 +143,7 @@ impl AgreementResponse {
             end_at: agreement.end_at.map(|e| e.seconds()),
             status: status.clone(),
             pending_charge: *pending_charge,
+            asset_info: agreement.asset_info.clone(),
         }
     }
 }
@@ -121,6 +153,12 @@ pub struct WorkPayload {
     pub agreement_id: u64,
 }
 
+/// Payload to transfer multiple agreements in a single `Work` call
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BatchWorkPayload {
+    pub agreement_ids: Vec<u64>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct AgreementsResponse {
     pub agreement_ids: Vec<u64>,
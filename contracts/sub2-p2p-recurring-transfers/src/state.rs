@@ -4,6 +4,7 @@ use serde::{Deserialize, Serialize};
 
 use cosmwasm_std::{Addr, StdResult, Storage, Timestamp};
 use cw_storage_plus::{Index, IndexList, IndexedMap, Item, MultiIndex, U64Key};
+use suberra_core::asset::AssetInfo;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
@@ -13,6 +14,12 @@ pub struct Config {
     /// once the contract is frozen, then no further transfers can be made and no agreements can be created. Intended to be a circuit-breaker measure
     pub is_frozen: bool,
     pub job_registry_contract: Option<Addr>,
+    /// agreements can only be created by subwallets of the subwallet factory if set
+    #[serde(default)]
+    pub subwallet_factory_contract: Option<Addr>,
+    /// agreements can be created by any account if set. Otherwise the subwallet factory must be set
+    #[serde(default)]
+    pub accept_any_payer: bool,
     pub minimum_interval: u64,
     pub minimum_amount_per_interval: Uint256,
     pub fee_bps: u64,
@@ -22,6 +29,21 @@ pub struct Config {
 
 pub const CONFIG: Item<Config> = Item::new("config");
 
+/// Stores an agreement that has been charged in the current batch
+/// - agreement_id: identifier of the charged agreement
+/// - agreement: the [`Agreement`] before the charge, which is restored if the transfer fails
+/// - is_failed: whether the transfer has failed, in which case it is not counted in the work receipt
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingTransfer {
+    pub agreement_id: u64,
+    pub agreement: Agreement,
+    pub is_failed: bool,
+}
+
+// Saves the transfers sent in the current batch. The index of a transfer is the ID of the reply sent if it fails.
+// Cleared before every batch, as replies are processed in the same transaction
+pub const PENDING_TRANSFERS: Item<Vec<PendingTransfer>> = Item::new("pending_transfers");
+
 // Incremental agreement_id, up only
 pub const AGREEMENT_ID: Item<u64> = Item::new("agreement_id");
 pub fn agreement_id(storage: &dyn Storage) -> StdResult<u64> {
@@ -89,4 +111,7 @@ pub struct Agreement {
     pub last_charged: Timestamp,
     pub start_at: Timestamp,
     pub end_at: Option<Timestamp>,
+    /// Asset the agreement is paid in. Agreements created before billing assets were added pay in aUST
+    #[serde(default)]
+    pub asset_info: Option<AssetInfo>,
 }
@@ -1,15 +1,20 @@
 use crate::contract;
 use crate::error::ContractError;
+use crate::mock_querier::mock_dependencies as mock_dependencies_with_subwallets;
 use crate::msg::{
-    AgreementResponse, AgreementsResponse, ExecuteMsg, InstantiateMsg, QueryMsg, WorkPayload,
+    AgreementResponse, AgreementsResponse, BatchWorkPayload, ExecuteMsg, InstantiateMsg,
+    MigrateMsg, QueryMsg, WorkPayload,
 };
 use crate::state::{AgreementStatus, Config};
 use cosmwasm_bignumber::Uint256;
 use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
 use cosmwasm_std::{
-    attr, coins, from_binary, to_binary, Addr, CosmosMsg, SubMsg, Timestamp, Uint128, WasmMsg,
+    attr, coins, from_binary, to_binary, Addr, ContractResult, CosmosMsg, Reply, SubMsg, Timestamp,
+    Uint128, WasmMsg,
 };
-use suberra_core::msg::SubWalletExecuteMsg;
+use cw2::set_contract_version;
+use suberra_core::asset::{Asset, AssetInfo};
+use suberra_core::msg::{JobsRegistryExecuteMsg, SubWalletExecuteMsg};
 
 const HOUR_SECONDS: u64 = 3600u64;
 const DAY_SECONDS: u64 = 86400u64;
@@ -20,6 +25,8 @@ fn proper_initialization() {
 
     let msg = InstantiateMsg {
         job_registry_contract: Some("job_registry".to_string()),
+        subwallet_factory_contract: None,
+        accept_any_payer: Some(true),
         minimum_interval: HOUR_SECONDS,
         minimum_amount_per_interval: Uint256::from(1_000_000u128),
         fee_bps: None,
@@ -44,6 +51,8 @@ fn create_agreement() {
 
     let msg = InstantiateMsg {
         job_registry_contract: Some("job_registry".to_string()),
+        subwallet_factory_contract: None,
+        accept_any_payer: Some(true),
         minimum_interval: HOUR_SECONDS,
         minimum_amount_per_interval: Uint256::from(1_000_000u128),
         fee_bps: None,
@@ -61,6 +70,7 @@ fn create_agreement() {
         start_at: None,
         end_at: None,
         interval: DAY_SECONDS,
+        asset_info: None,
     };
 
     let res = contract::execute(deps.as_mut(), env.clone(), info, msg).unwrap();
@@ -96,7 +106,8 @@ fn create_agreement() {
             end_at: None,
             status: AgreementStatus::Active,
             pending_charge: Uint256::zero(),
-            last_charged: start_time
+            last_charged: start_time,
+            asset_info: None,
         }
     );
 
@@ -108,6 +119,7 @@ fn create_agreement() {
         start_at: None,
         end_at: None,
         interval: 360,
+        asset_info: None,
     };
 
     let res = contract::execute(deps.as_mut(), env.clone(), info, msg).unwrap_err();
@@ -121,6 +133,7 @@ fn create_agreement() {
         start_at: Some(start_time + 1000),
         end_at: Some(start_time + 1000),
         interval: 3600,
+        asset_info: None,
     };
 
     let res = contract::execute(deps.as_mut(), env.clone(), info, msg).unwrap_err();
@@ -134,6 +147,7 @@ fn create_agreement() {
         start_at: Some(start_time + 1000),
         end_at: None,
         interval: DAY_SECONDS,
+        asset_info: None,
     };
 
     let res = contract::execute(deps.as_mut(), env.clone(), info, msg).unwrap();
@@ -159,7 +173,8 @@ fn create_agreement() {
             end_at: None,
             status: AgreementStatus::NotStarted,
             pending_charge: Uint256::zero(),
-            last_charged: start_time
+            last_charged: start_time,
+            asset_info: None,
         }
     );
 }
@@ -175,6 +190,8 @@ fn create_and_transfer() {
 
     let msg = InstantiateMsg {
         job_registry_contract: Some("job_registry".to_string()),
+        subwallet_factory_contract: None,
+        accept_any_payer: Some(true),
         minimum_interval: HOUR_SECONDS,
         minimum_amount_per_interval: Uint256::from(1_000_000u128),
         fee_bps: None,
@@ -192,6 +209,7 @@ fn create_and_transfer() {
         start_at: None,
         end_at: None,
         interval: HOUR_SECONDS,
+        asset_info: None,
     };
     let res = contract::execute(deps.as_mut(), env.clone(), info, msg);
     match res {
@@ -209,6 +227,7 @@ fn create_and_transfer() {
         start_at: None,
         end_at: None,
         interval: HOUR_SECONDS,
+        asset_info: None,
     };
     let _res = contract::execute(deps.as_mut(), env.clone(), info, msg).unwrap();
 
@@ -253,7 +272,8 @@ fn create_and_transfer() {
             end_at: None,
             status: AgreementStatus::Active,
             pending_charge: Uint256::zero(),
-            last_charged: start_time + HOUR_SECONDS
+            last_charged: start_time + HOUR_SECONDS,
+            asset_info: None,
         }
     );
 }
@@ -269,6 +289,8 @@ fn create_with_fees() {
 
     let msg = InstantiateMsg {
         job_registry_contract: Some("job_registry".to_string()),
+        subwallet_factory_contract: None,
+        accept_any_payer: Some(true),
         minimum_interval: HOUR_SECONDS,
         minimum_amount_per_interval: Uint256::from(1_000_000u128),
         fee_bps: Some(500u64), // 5% fee
@@ -287,6 +309,7 @@ fn create_with_fees() {
         start_at: None,
         end_at: None,
         interval: HOUR_SECONDS,
+        asset_info: None,
     };
     let res = contract::execute(deps.as_mut(), env.clone(), info, msg).unwrap();
 
@@ -364,6 +387,7 @@ fn create_with_fees() {
         start_at: None,
         end_at: None,
         interval: HOUR_SECONDS,
+        asset_info: None,
     };
     let res = contract::execute(deps.as_mut(), env.clone(), info, msg).unwrap();
 
@@ -403,6 +427,8 @@ fn create_and_cancel() {
 
     let msg = InstantiateMsg {
         job_registry_contract: Some("job_registry".to_string()),
+        subwallet_factory_contract: None,
+        accept_any_payer: Some(true),
         minimum_interval: HOUR_SECONDS,
         minimum_amount_per_interval: Uint256::from(1_000_000u128),
         fee_bps: None,
@@ -420,6 +446,7 @@ fn create_and_cancel() {
         start_at: None,
         end_at: None,
         interval: DAY_SECONDS,
+        asset_info: None,
     };
     contract::execute(deps.as_mut(), env.clone(), info, msg).unwrap();
 
@@ -459,6 +486,8 @@ fn test_multiple_agreements() {
 
     let msg = InstantiateMsg {
         job_registry_contract: Some("job_registry".to_string()),
+        subwallet_factory_contract: None,
+        accept_any_payer: Some(true),
         minimum_interval: HOUR_SECONDS,
         minimum_amount_per_interval: Uint256::from(1_000_000u128),
         fee_bps: None,
@@ -476,6 +505,7 @@ fn test_multiple_agreements() {
         start_at: None,
         end_at: None,
         interval: 86400,
+        asset_info: None,
     };
     let _res = contract::execute(deps.as_mut(), env.clone(), info, msg).unwrap();
 
@@ -487,6 +517,7 @@ fn test_multiple_agreements() {
         start_at: None,
         end_at: None,
         interval: 86400,
+        asset_info: None,
     };
     let _res = contract::execute(deps.as_mut(), env.clone(), info, msg).unwrap();
 
@@ -498,6 +529,7 @@ fn test_multiple_agreements() {
         start_at: None,
         end_at: None,
         interval: 86400,
+        asset_info: None,
     };
     let _res = contract::execute(deps.as_mut(), env.clone(), info, msg).unwrap();
 
@@ -574,6 +606,8 @@ fn pause() {
 
     let msg = InstantiateMsg {
         job_registry_contract: Some("job_registry".to_string()),
+        subwallet_factory_contract: None,
+        accept_any_payer: Some(true),
         minimum_interval: HOUR_SECONDS,
         minimum_amount_per_interval: Uint256::from(1_000_000u128),
         fee_bps: None,
@@ -593,6 +627,7 @@ fn pause() {
         start_at: Some(start_time + HOUR_SECONDS),
         end_at: Some(start_time + HOUR_SECONDS + DAY_SECONDS * 7),
         interval: DAY_SECONDS,
+        asset_info: None,
     };
     let _res = contract::execute(deps.as_mut(), env.clone(), info, msg).unwrap();
 
@@ -627,6 +662,7 @@ fn pause() {
         start_at: Some(start_time + DAY_SECONDS * 3 + HOUR_SECONDS),
         end_at: Some(start_time + DAY_SECONDS * 4),
         interval: DAY_SECONDS,
+        asset_info: None,
     };
     let res = contract::execute(deps.as_mut(), env.clone(), info, msg);
     match res {
@@ -674,7 +710,6 @@ fn pause() {
     let res = contract::query(deps.as_ref(), mock_env(), msg).unwrap();
     let config: Config = from_binary(&res).unwrap();
     assert_eq!(config.is_paused, false);
-
 }
 
 #[test]
@@ -687,6 +722,8 @@ fn freeze() {
 
     let msg = InstantiateMsg {
         job_registry_contract: Some("job_registry".to_string()),
+        subwallet_factory_contract: None,
+        accept_any_payer: Some(true),
         minimum_interval: HOUR_SECONDS,
         minimum_amount_per_interval: Uint256::from(1_000_000u128),
         fee_bps: None,
@@ -706,6 +743,7 @@ fn freeze() {
         start_at: Some(start_time + HOUR_SECONDS),
         end_at: Some(start_time + HOUR_SECONDS + DAY_SECONDS * 7),
         interval: DAY_SECONDS,
+        asset_info: None,
     };
     let _res = contract::execute(deps.as_mut(), env.clone(), info, msg).unwrap();
 
@@ -740,6 +778,7 @@ fn freeze() {
         start_at: Some(start_time + DAY_SECONDS * 3 + HOUR_SECONDS),
         end_at: Some(start_time + DAY_SECONDS * 4),
         interval: DAY_SECONDS,
+        asset_info: None,
     };
     let res = contract::execute(deps.as_mut(), env.clone(), info, msg);
     match res {
@@ -788,6 +827,8 @@ fn change_config() {
 
     let msg = InstantiateMsg {
         job_registry_contract: Some("job_registry".to_string()),
+        subwallet_factory_contract: None,
+        accept_any_payer: Some(true),
         minimum_interval: HOUR_SECONDS,
         minimum_amount_per_interval: Uint256::from(1_000_000u128),
         fee_bps: None,
@@ -806,6 +847,8 @@ fn change_config() {
         is_paused: false,
         is_frozen: false,
         job_registry_contract: Some(Addr::unchecked("job_registry")),
+        subwallet_factory_contract: None,
+        accept_any_payer: true,
         minimum_interval: HOUR_SECONDS,
         minimum_amount_per_interval: Uint256::from(1_000_000u128),
         fee_bps: 0u64,
@@ -817,6 +860,8 @@ fn change_config() {
     let msg = ExecuteMsg::UpdateConfig {
         new_owner: Some(String::from("the_new_owner")),
         job_registry_contract: Some(String::from("the_new_job_registry")),
+        subwallet_factory_contract: None,
+        accept_any_payer: None,
         minimum_interval: Some(DAY_SECONDS),
         minimum_amount_per_interval: None,
         fee_bps: Some(100u64),
@@ -842,6 +887,8 @@ fn change_config() {
         is_paused: false,
         is_frozen: false,
         job_registry_contract: Some(Addr::unchecked("job_registry")),
+        subwallet_factory_contract: None,
+        accept_any_payer: true,
         minimum_interval: HOUR_SECONDS,
         minimum_amount_per_interval: Uint256::from(1_000_000u128),
         fee_bps: 0u64,
@@ -862,6 +909,8 @@ fn change_config() {
         is_paused: false,
         is_frozen: false,
         job_registry_contract: Some(Addr::unchecked("the_new_job_registry")),
+        subwallet_factory_contract: None,
+        accept_any_payer: true,
         minimum_interval: DAY_SECONDS,
         minimum_amount_per_interval: Uint256::from(1_000_000u128),
         fee_bps: 100u64,
@@ -882,6 +931,8 @@ fn test_charge_lapsed_expiry() {
 
     let msg = InstantiateMsg {
         job_registry_contract: Some("job_registry".to_string()),
+        subwallet_factory_contract: None,
+        accept_any_payer: Some(true),
         minimum_interval: DAY_SECONDS,
         minimum_amount_per_interval: Uint256::from(1_000_000u128),
         fee_bps: None,
@@ -899,6 +950,7 @@ fn test_charge_lapsed_expiry() {
         start_at: Some(start_time + HOUR_SECONDS),
         end_at: Some(start_time + HOUR_SECONDS + DAY_SECONDS * 7),
         interval: DAY_SECONDS,
+        asset_info: None,
     };
     let _res = contract::execute(deps.as_mut(), env.clone(), info, msg).unwrap();
 
@@ -910,6 +962,7 @@ fn test_charge_lapsed_expiry() {
         start_at: Some(start_time + DAY_SECONDS * 3 + HOUR_SECONDS),
         end_at: Some(start_time + DAY_SECONDS * 4),
         interval: DAY_SECONDS,
+        asset_info: None,
     };
     let _res = contract::execute(deps.as_mut(), env.clone(), info, msg).unwrap();
 
@@ -937,7 +990,8 @@ fn test_charge_lapsed_expiry() {
             end_at: Some(start_time + HOUR_SECONDS + DAY_SECONDS * 7,),
             status: AgreementStatus::NotStarted,
             pending_charge: Uint256::zero(),
-            last_charged: start_time
+            last_charged: start_time,
+            asset_info: None,
         }
     );
 
@@ -967,7 +1021,8 @@ fn test_charge_lapsed_expiry() {
             end_at: Some(start_time + HOUR_SECONDS + 7 * DAY_SECONDS,),
             status: AgreementStatus::Active,
             pending_charge: Uint256::from(1000000u128),
-            last_charged: start_time
+            last_charged: start_time,
+            asset_info: None,
         }
     );
 
@@ -1023,7 +1078,8 @@ fn test_charge_lapsed_expiry() {
             end_at: Some(start_time + HOUR_SECONDS + DAY_SECONDS * 7,),
             status: AgreementStatus::Active,
             pending_charge: Uint256::from(0u128),
-            last_charged: start_time + HOUR_SECONDS
+            last_charged: start_time + HOUR_SECONDS,
+            asset_info: None,
         }
     );
 
@@ -1054,7 +1110,8 @@ fn test_charge_lapsed_expiry() {
             end_at: Some(start_time + HOUR_SECONDS + DAY_SECONDS * 7,),
             status: AgreementStatus::Lapsed,
             pending_charge: Uint256::from(0u128),
-            last_charged: start_time + HOUR_SECONDS
+            last_charged: start_time + HOUR_SECONDS,
+            asset_info: None,
         }
     );
 
@@ -1093,7 +1150,8 @@ fn test_charge_lapsed_expiry() {
             end_at: Some(start_time + DAY_SECONDS * 4),
             status: AgreementStatus::Expired,
             pending_charge: Uint256::from(0u128),
-            last_charged: start_time
+            last_charged: start_time,
+            asset_info: None,
         }
     );
 }
@@ -1108,6 +1166,8 @@ fn test_query_overdued() {
 
     let msg = InstantiateMsg {
         job_registry_contract: Some("job_registry".to_string()),
+        subwallet_factory_contract: None,
+        accept_any_payer: Some(true),
         minimum_interval: DAY_SECONDS,
         minimum_amount_per_interval: Uint256::from(1_000_000u128),
         fee_bps: None,
@@ -1125,6 +1185,7 @@ fn test_query_overdued() {
         start_at: Some(start_time + 1), // No initial amount charged
         end_at: None,
         interval: DAY_SECONDS,
+        asset_info: None,
     };
     contract::execute(deps.as_mut(), env.clone(), info, msg).unwrap();
 
@@ -1136,6 +1197,7 @@ fn test_query_overdued() {
         start_at: Some(start_time + 2 * HOUR_SECONDS),
         end_at: None,
         interval: DAY_SECONDS,
+        asset_info: None,
     };
     contract::execute(deps.as_mut(), env.clone(), info, msg).unwrap();
 
@@ -1147,6 +1209,7 @@ fn test_query_overdued() {
         start_at: Some(start_time + HOUR_SECONDS),
         end_at: None,
         interval: DAY_SECONDS,
+        asset_info: None,
     };
     contract::execute(deps.as_mut(), env.clone(), info, msg).unwrap();
 
@@ -1160,6 +1223,7 @@ fn test_query_overdued() {
         start_at: None,
         end_at: None,
         interval: DAY_SECONDS,
+        asset_info: None,
     };
     contract::execute(deps.as_mut(), env.clone(), info, msg).unwrap();
 
@@ -1243,3 +1307,510 @@ fn test_query_overdued() {
         );
     }
 }
+
+/// transfers multiple agreements in a single work call
+#[test]
+fn batch_work() {
+    let mut deps = mock_dependencies(&coins(2, "token"));
+    let mut env = mock_env();
+
+    let start_time = 1609459200u64;
+    env.block.time = Timestamp::from_seconds(start_time);
+
+    let msg = InstantiateMsg {
+        job_registry_contract: Some("job_registry".to_string()),
+        subwallet_factory_contract: None,
+        accept_any_payer: Some(true),
+        minimum_interval: HOUR_SECONDS,
+        minimum_amount_per_interval: Uint256::from(1_000_000u128),
+        fee_bps: None,
+        fee_address: None,
+        max_fee: None,
+    };
+    let info = mock_info("creator", &coins(2, "token"));
+    contract::instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    // agreement 1 and 2 are hourly, agreement 3 is daily
+    for (sender, interval) in [
+        ("alice", HOUR_SECONDS),
+        ("carol", HOUR_SECONDS),
+        ("dave", DAY_SECONDS),
+    ]
+    .iter()
+    {
+        let msg = ExecuteMsg::CreateAgreement {
+            receiver: String::from("bob"),
+            amount: Uint256::from(1_000_000u128),
+            start_at: None,
+            end_at: None,
+            interval: *interval,
+            asset_info: None,
+        };
+        contract::execute(deps.as_mut(), env.clone(), mock_info(sender, &[]), msg).unwrap();
+    }
+
+    let payload = to_binary(&BatchWorkPayload {
+        agreement_ids: vec![1, 2, 3, 4],
+    })
+    .unwrap();
+
+    // nothing to transfer yet
+    let res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("worker", &[]),
+        ExecuteMsg::Work {
+            payload: payload.clone(),
+        },
+    );
+    match res {
+        Err(ContractError::ZeroTransferableAmount {}) => {}
+        _ => panic!("Contract should return a zero transferable amount"),
+    }
+
+    // fast-forward 1 hour, only the hourly agreements are due
+    env.block.time = Timestamp::from_seconds(start_time + HOUR_SECONDS);
+    let can_work: bool = from_binary(
+        &contract::query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::CanWork {
+                payload: payload.clone(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert!(can_work);
+
+    let res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("worker", &[]),
+        ExecuteMsg::Work {
+            payload: payload.clone(),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("method", "batch_work"),
+            attr("module_contract_address", "cosmos2contract"),
+            attr("transferred", "1,2"),
+            attr("skipped", "3,4"),
+        ]
+    );
+
+    // one transfer per agreement that replies on error, and a single work receipt sent once the transfers are collected
+    assert_eq!(res.messages.len(), 3);
+    assert_eq!(
+        res.messages[0],
+        SubMsg::reply_on_error(
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "cosmos2contract".to_string(),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::CollectTransfer { agreement_id: 1 }).unwrap(),
+            }),
+            0
+        )
+    );
+    assert_eq!(
+        res.messages[2],
+        SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "cosmos2contract".to_string(),
+            funds: vec![],
+            msg: to_binary(&ExecuteMsg::SendWorkReceipt {
+                worker_address: "worker".to_string(),
+                units: 2,
+            })
+            .unwrap(),
+        }))
+    );
+    assert_eq!(res.events[0].ty, "transfer");
+    assert!(res.events[0]
+        .attributes
+        .contains(&attr("amount", "1000000")));
+
+    // the transfer is collected from the subwallet of the owner
+    let res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("cosmos2contract", &[]),
+        ExecuteMsg::CollectTransfer { agreement_id: 1 },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "alice".to_string(),
+            funds: vec![],
+            msg: to_binary(&SubWalletExecuteMsg::transfer(
+                &None,
+                Uint128::from(1_000_000u128),
+                "bob".to_string(),
+            ))
+            .unwrap(),
+        }))]
+    );
+
+    let can_work: bool = from_binary(
+        &contract::query(deps.as_ref(), env.clone(), QueryMsg::CanWork { payload }).unwrap(),
+    )
+    .unwrap();
+    assert!(!can_work);
+
+    // batches are capped
+    let res = contract::execute(
+        deps.as_mut(),
+        env,
+        mock_info("worker", &[]),
+        ExecuteMsg::Work {
+            payload: to_binary(&BatchWorkPayload {
+                agreement_ids: vec![1; 31],
+            })
+            .unwrap(),
+        },
+    );
+    match res {
+        Err(ContractError::BatchTooLarge {}) => {}
+        _ => panic!("Contract should return batch too large"),
+    }
+}
+
+// A failed transfer in a batch is restored and left out of the work receipt
+#[test]
+fn batch_work_skips_failed_transfers() {
+    let mut deps = mock_dependencies(&[]);
+    let mut env = mock_env();
+
+    let start_time = 1609459200u64;
+    env.block.time = Timestamp::from_seconds(start_time);
+
+    let msg = InstantiateMsg {
+        job_registry_contract: Some("job_registry".to_string()),
+        subwallet_factory_contract: None,
+        accept_any_payer: Some(true),
+        minimum_interval: HOUR_SECONDS,
+        minimum_amount_per_interval: Uint256::from(1_000_000u128),
+        fee_bps: None,
+        fee_address: None,
+        max_fee: None,
+    };
+    contract::instantiate(deps.as_mut(), env.clone(), mock_info("creator", &[]), msg).unwrap();
+
+    for sender in ["alice", "carol"] {
+        let msg = ExecuteMsg::CreateAgreement {
+            receiver: String::from("bob"),
+            amount: Uint256::from(1_000_000u128),
+            start_at: None,
+            end_at: None,
+            interval: HOUR_SECONDS,
+            asset_info: None,
+        };
+        contract::execute(deps.as_mut(), env.clone(), mock_info(sender, &[]), msg).unwrap();
+    }
+
+    env.block.time = Timestamp::from_seconds(start_time + HOUR_SECONDS);
+    contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("worker", &[]),
+        ExecuteMsg::Work {
+            payload: to_binary(&BatchWorkPayload {
+                agreement_ids: vec![1, 2],
+            })
+            .unwrap(),
+        },
+    )
+    .unwrap();
+
+    // only the contract can collect transfers and send work receipts
+    match contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("worker", &[]),
+        ExecuteMsg::CollectTransfer { agreement_id: 1 },
+    ) {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("Contract should return unauthorized"),
+    }
+    match contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("worker", &[]),
+        ExecuteMsg::SendWorkReceipt {
+            worker_address: "worker".to_string(),
+            units: 2,
+        },
+    ) {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("Contract should return unauthorized"),
+    }
+
+    // the subwallet of alice cannot pay, so the agreement is restored and can be transferred again
+    let res = contract::reply(
+        deps.as_mut(),
+        env.clone(),
+        Reply {
+            id: 0,
+            result: ContractResult::Err("insufficient funds".to_string()),
+        },
+    )
+    .unwrap();
+    assert!(res.attributes.contains(&attr("agreement_id", "1")));
+
+    let res = contract::query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::Agreement { agreement_id: 1 },
+    )
+    .unwrap();
+    let agreement: AgreementResponse = from_binary(&res).unwrap();
+    assert_eq!(agreement.interval_due_at, start_time + HOUR_SECONDS);
+    assert_eq!(agreement.last_charged, start_time);
+    assert_eq!(agreement.pending_charge, Uint256::from(1_000_000u128));
+
+    // the worker is only rewarded for the transfer that went through
+    let res = contract::execute(
+        deps.as_mut(),
+        env,
+        mock_info("cosmos2contract", &[]),
+        ExecuteMsg::SendWorkReceipt {
+            worker_address: "worker".to_string(),
+            units: 2,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "job_registry".to_string(),
+            funds: vec![],
+            msg: to_binary(&JobsRegistryExecuteMsg::WorkReceipt {
+                worker_address: "worker".to_string(),
+                units: Some(1),
+            })
+            .unwrap(),
+        }))]
+    );
+}
+
+// Agreements with a billing asset charge the subwallet with `TransferAsset`
+#[test]
+fn create_agreement_with_asset() {
+    let mut deps = mock_dependencies(&[]);
+    let mut env = mock_env();
+    let start_time = 1609459200u64;
+    env.block.time = Timestamp::from_seconds(start_time);
+
+    let msg = InstantiateMsg {
+        job_registry_contract: Some("job_registry".to_string()),
+        subwallet_factory_contract: None,
+        accept_any_payer: Some(true),
+        minimum_interval: HOUR_SECONDS,
+        minimum_amount_per_interval: Uint256::from(1_000_000u128),
+        fee_bps: None,
+        fee_address: None,
+        max_fee: None,
+    };
+    contract::instantiate(deps.as_mut(), env.clone(), mock_info("creator", &[]), msg).unwrap();
+
+    let asset_info = AssetInfo::Token {
+        contract_addr: Addr::unchecked("mir"),
+    };
+    let msg = ExecuteMsg::CreateAgreement {
+        receiver: String::from("bob"),
+        amount: Uint256::from(1000000u128),
+        start_at: None,
+        end_at: None,
+        interval: DAY_SECONDS,
+        asset_info: Some(asset_info.clone()),
+    };
+    let res = contract::execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), msg).unwrap();
+
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: String::from("alice"),
+            funds: vec![],
+            msg: to_binary(&SubWalletExecuteMsg::TransferAsset {
+                asset: Asset {
+                    info: asset_info.clone(),
+                    amount: Uint128::from(1000000u128),
+                },
+                recipient: String::from("bob"),
+            })
+            .unwrap(),
+        }))]
+    );
+
+    let res = contract::query(deps.as_ref(), env, QueryMsg::Agreement { agreement_id: 1 }).unwrap();
+    let val: AgreementResponse = from_binary(&res).unwrap();
+    assert_eq!(val.asset_info, Some(asset_info));
+}
+
+#[test]
+fn create_agreement_from_subwallet() {
+    let mut deps = mock_dependencies_with_subwallets(&[]);
+    deps.querier
+        .with_subwallets(&[("alice_subwallet", "alice")]);
+    let env = mock_env();
+
+    let msg = InstantiateMsg {
+        job_registry_contract: Some("job_registry".to_string()),
+        subwallet_factory_contract: Some("subwallet_factory".to_string()),
+        accept_any_payer: None,
+        minimum_interval: HOUR_SECONDS,
+        minimum_amount_per_interval: Uint256::from(1_000_000u128),
+        fee_bps: None,
+        fee_address: None,
+        max_fee: None,
+    };
+    contract::instantiate(deps.as_mut(), env.clone(), mock_info("creator", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::CreateAgreement {
+        receiver: String::from("bob"),
+        amount: Uint256::from(1000000u128),
+        start_at: None,
+        end_at: None,
+        interval: DAY_SECONDS,
+        asset_info: None,
+    };
+
+    // accounts that are not subwallets cannot be charged
+    let res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("alice", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::PayerNotSubwallet {}) => {}
+        _ => panic!("Must return payer not subwallet error"),
+    }
+
+    let res = contract::execute(deps.as_mut(), env, mock_info("alice_subwallet", &[]), msg);
+    assert!(res.is_ok());
+}
+
+#[test]
+fn create_agreement_requires_subwallet_factory() {
+    let mut deps = mock_dependencies(&[]);
+    let env = mock_env();
+
+    let msg = InstantiateMsg {
+        job_registry_contract: Some("job_registry".to_string()),
+        subwallet_factory_contract: None,
+        accept_any_payer: None,
+        minimum_interval: HOUR_SECONDS,
+        minimum_amount_per_interval: Uint256::from(1_000_000u128),
+        fee_bps: None,
+        fee_address: None,
+        max_fee: None,
+    };
+    contract::instantiate(deps.as_mut(), env.clone(), mock_info("creator", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::CreateAgreement {
+        receiver: String::from("bob"),
+        amount: Uint256::from(1000000u128),
+        start_at: None,
+        end_at: None,
+        interval: DAY_SECONDS,
+        asset_info: None,
+    };
+
+    // payers are not accepted without a subwallet factory to check them against
+    let res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("alice", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::SubwalletFactoryNotSet {}) => {}
+        _ => panic!("Must return subwallet factory not set error"),
+    }
+
+    // the owner can opt out of the check explicitly
+    contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        ExecuteMsg::UpdateConfig {
+            job_registry_contract: None,
+            subwallet_factory_contract: None,
+            accept_any_payer: Some(true),
+            minimum_interval: None,
+            minimum_amount_per_interval: None,
+            new_owner: None,
+            fee_bps: None,
+            fee_address: None,
+            max_fee: None,
+        },
+    )
+    .unwrap();
+
+    let res = contract::execute(deps.as_mut(), env, mock_info("alice", &[]), msg);
+    assert!(res.is_ok());
+}
+
+#[test]
+fn migrate_requires_payer_check() {
+    let mut deps = mock_dependencies(&[]);
+    let env = mock_env();
+
+    let msg = InstantiateMsg {
+        job_registry_contract: Some("job_registry".to_string()),
+        subwallet_factory_contract: None,
+        accept_any_payer: None,
+        minimum_interval: HOUR_SECONDS,
+        minimum_amount_per_interval: Uint256::from(1_000_000u128),
+        fee_bps: None,
+        fee_address: None,
+        max_fee: None,
+    };
+    contract::instantiate(deps.as_mut(), env.clone(), mock_info("creator", &[]), msg).unwrap();
+
+    // rewind to a contract from before the payer check was introduced
+    set_contract_version(
+        &mut deps.storage,
+        "crates.io:p2p_recurring_transfers",
+        "0.2.0",
+    )
+    .unwrap();
+
+    // agreements could not be created by anyone after the migration
+    let res = contract::migrate(
+        deps.as_mut(),
+        env.clone(),
+        MigrateMsg {
+            subwallet_factory_contract: None,
+            accept_any_payer: None,
+        },
+    );
+    match res {
+        Err(ContractError::SubwalletFactoryNotSet {}) => {}
+        _ => panic!("Must return subwallet factory not set error"),
+    }
+
+    let res = contract::migrate(
+        deps.as_mut(),
+        env.clone(),
+        MigrateMsg {
+            subwallet_factory_contract: Some("subwallet_factory".to_string()),
+            accept_any_payer: None,
+        },
+    )
+    .unwrap();
+    assert!(res
+        .attributes
+        .contains(&attr("subwallet_factory_contract", "subwallet_factory")));
+
+    let res = contract::query(deps.as_ref(), env, QueryMsg::Config {}).unwrap();
+    let config: Config = from_binary(&res).unwrap();
+    assert_eq!(
+        config.subwallet_factory_contract,
+        Some(Addr::unchecked("subwallet_factory"))
+    );
+}
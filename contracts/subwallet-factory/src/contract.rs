@@ -1,7 +1,10 @@
 use crate::error::ContractError;
 use crate::msg::{ExecuteMsg, InstantiateMsg};
 use crate::response::MsgInstantiateContractResponse;
-use crate::state::{read_config, retrieve_address, store_address, store_config};
+use crate::state::{
+    migrate_legacy_config, migrate_subwallet_owners, read_config, retrieve_address, retrieve_owner,
+    store_address, store_config,
+};
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
@@ -9,10 +12,15 @@ use cosmwasm_std::{
     Response, StdError, StdResult, SubMsg, WasmMsg,
 };
 
+use cw2::set_contract_version;
 use protobuf::Message;
+use suberra_core::migration::{migrate_contract_version, Version};
 use suberra_core::msg::{MigrateMsg, SubwalletInstantiateMsg};
 use suberra_core::subwallet_factory::{QueryMsg, SubwalletFactoryConfig as Config};
 
+const CONTRACT_NAME: &str = "crates.io:subwallet-factory";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
 /// A `reply` call code ID of sub-message.
 const INSTANTIATE_SUBWALLET_REPLY_ID: u64 = 1;
 
@@ -34,6 +42,7 @@ pub fn instantiate(
             aterra_token_addr: deps.api.addr_validate(msg.aterra_token_addr.as_str())?,
         },
     )?;
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     Ok(Response::new())
 }
 
@@ -65,15 +74,33 @@ pub fn execute(
 
 #[allow(dead_code)]
 #[cfg_attr(not(feature = "library"), entry_point)]
-/// Used for migration of contract. Returns the default object of type [`Response`].
+/// Used for migration of contract. Applies the upgrade steps of every version after the stored contract version.
+/// Refuses to migrate to an older version.
 /// ## Params
-/// * **_deps** is the object of type [`DepsMut`].
+/// * **deps** is the object of type [`DepsMut`].
 ///
 /// * **_env** is the object of type [`Env`].
 ///
 /// * **_msg** is the object of type [`MigrateMsg`].
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
-    Ok(Response::default())
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let previous_version =
+        migrate_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION, &[])?;
+
+    // the config was stored in a `cosmwasm_storage` singleton before the contract version was stored
+    if previous_version < Version::new(0, 2, 3) {
+        migrate_legacy_config(deps.storage)?;
+    }
+
+    // subwallets created before the reverse lookup was introduced are added to it
+    if previous_version < Version::new(0, 3, 0) {
+        migrate_subwallet_owners(deps.storage)?;
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("method", "migrate"),
+        attr("previous_version", previous_version.to_string()),
+        attr("version", CONTRACT_VERSION),
+    ]))
 }
 
 /// Updates the configs for the general settings
@@ -131,7 +158,6 @@ pub fn update_config(
     if let Some(new_aterra_token_addr) = new_aterra_token_addr {
         config.aterra_token_addr = deps.api.addr_validate(new_aterra_token_addr.as_str())?;
         attributes.push(attr("new_aterra_token_addr", new_aterra_token_addr));
-
     };
 
     store_config(deps.storage, &config)?;
@@ -242,6 +268,9 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::GetSubwalletAddress { owner_address } => {
             to_binary(&query_address(deps, owner_address)?)
         }
+        QueryMsg::GetSubwalletOwner { subwallet_address } => {
+            to_binary(&query_owner(deps, subwallet_address)?)
+        }
     }
 }
 
@@ -254,3 +283,8 @@ fn query_address(deps: Deps, address: String) -> StdResult<Option<String>> {
     let subwallet_address = res.map(|v| v.to_string());
     Ok(subwallet_address)
 }
+
+fn query_owner(deps: Deps, subwallet_address: String) -> StdResult<Option<String>> {
+    let res = retrieve_owner(deps.storage, &deps.api.addr_validate(&subwallet_address)?);
+    Ok(res.map(|v| v.to_string()))
+}
@@ -1,28 +1,56 @@
-use cosmwasm_std::{Addr, StdResult, Storage};
-use cosmwasm_storage::{bucket, bucket_read, ReadonlySingleton, Singleton};
+use cosmwasm_std::{Addr, Order, StdError, StdResult, Storage};
+use cosmwasm_storage::{singleton, singleton_read};
+use cw_storage_plus::{Item, Map};
 use suberra_core::subwallet_factory::SubwalletFactoryConfig as Config;
 
-pub static CONFIG_KEY: &[u8] = b"config";
-pub static ACCOUNTS_KEY: &[u8] = b"accounts";
+// key of the `cosmwasm_storage` singleton that stored the config of contracts instantiated before the factory stored its contract version
+static LEGACY_CONFIG_KEY: &[u8] = b"config";
+
+pub const CONFIG: Item<Config> = Item::new("config");
+// mapping between user address and its subwallet. Uses the same keys as the `cosmwasm_storage` bucket it replaces
+pub const ACCOUNTS: Map<&Addr, Addr> = Map::new("accounts");
+// reverse mapping between subwallet and its owner
+pub const SUBWALLET_OWNERS: Map<&Addr, Addr> = Map::new("subwallet_owners");
 
 pub fn store_config(storage: &mut dyn Storage, data: &Config) -> StdResult<()> {
-    Singleton::new(storage, CONFIG_KEY).save(data)
+    CONFIG.save(storage, data)
 }
 
 pub fn read_config(storage: &dyn Storage) -> StdResult<Config> {
-    ReadonlySingleton::new(storage, CONFIG_KEY).load()
+    CONFIG.load(storage)
 }
 
-// stores the mapping between user address and its subwallet
-pub fn store_address(storage: &mut dyn Storage, owner: &Addr, subwallet: &Addr) -> StdResult<()> {
-    bucket(storage, ACCOUNTS_KEY).save(owner.as_bytes(), subwallet)?;
+// moves the config from the legacy `cosmwasm_storage` singleton to `CONFIG`
+pub fn migrate_legacy_config(storage: &mut dyn Storage) -> StdResult<()> {
+    let config: Config = singleton_read(storage, LEGACY_CONFIG_KEY).load()?;
+    singleton::<Config>(storage, LEGACY_CONFIG_KEY).remove();
 
-    Ok(())
+    CONFIG.save(storage, &config)
+}
+
+// stores the mapping between user address and its subwallet, in both directions
+pub fn store_address(storage: &mut dyn Storage, owner: &Addr, subwallet: &Addr) -> StdResult<()> {
+    ACCOUNTS.save(storage, owner, subwallet)?;
+    SUBWALLET_OWNERS.save(storage, subwallet, owner)
 }
 
 pub fn retrieve_address(storage: &dyn Storage, owner: &Addr) -> Option<Addr> {
-    match bucket_read(storage, ACCOUNTS_KEY).load(owner.as_bytes()) {
-        Ok(v) => Some(v),
-        _ => None,
+    ACCOUNTS.may_load(storage, owner).ok().flatten()
+}
+
+pub fn retrieve_owner(storage: &dyn Storage, subwallet: &Addr) -> Option<Addr> {
+    SUBWALLET_OWNERS.may_load(storage, subwallet).ok().flatten()
+}
+
+// adds the subwallets created before the reverse mapping was introduced to `SUBWALLET_OWNERS`
+pub fn migrate_subwallet_owners(storage: &mut dyn Storage) -> StdResult<()> {
+    let accounts = ACCOUNTS
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    for (owner, subwallet) in accounts {
+        let owner = Addr::unchecked(String::from_utf8(owner).map_err(StdError::from)?);
+        SUBWALLET_OWNERS.save(storage, &subwallet, &owner)?;
     }
+    Ok(())
 }
@@ -4,10 +4,14 @@ mod tests {
     use crate::contract::{instantiate, query};
     use crate::error::ContractError;
     use crate::msg::{ExecuteMsg, InstantiateMsg};
+    use crate::state::{ACCOUNTS, SUBWALLET_OWNERS};
+    use suberra_core::msg::MigrateMsg;
     use suberra_core::subwallet_factory::{QueryMsg, SubwalletFactoryConfig as Config};
 
     use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-    use cosmwasm_std::{coins, from_binary, Addr};
+    use cosmwasm_std::{attr, coins, from_binary, Addr};
+    use cosmwasm_storage::{bucket, singleton};
+    use cw2::{get_contract_version, set_contract_version};
 
     #[test]
     fn proper_initialization() {
@@ -75,4 +79,123 @@ mod tests {
         };
         assert_eq!(config, expected_config);
     }
+
+    #[test]
+    fn migrate_legacy_storage() {
+        let mut deps = mock_dependencies(&[]);
+
+        // storage layout of a factory instantiated by a previous release
+        let legacy_config = Config {
+            owner: Addr::unchecked("deployer"),
+            aterra_token_addr: Addr::unchecked("aterra"),
+            anchor_market_contract: Addr::unchecked("anchor_market"),
+            subwallet_code_id: 17,
+        };
+        singleton(&mut deps.storage, b"config")
+            .save(&legacy_config)
+            .unwrap();
+        bucket(&mut deps.storage, b"accounts")
+            .save(b"user", &Addr::unchecked("subwallet"))
+            .unwrap();
+
+        let res = contract::migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("method", "migrate"),
+                attr("previous_version", "0.0.0"),
+                attr("version", env!("CARGO_PKG_VERSION")),
+            ]
+        );
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+        let config: Config = from_binary(&res).unwrap();
+        assert_eq!(config, legacy_config);
+
+        // accounts are readable without being moved
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetSubwalletAddress {
+                owner_address: "user".to_string(),
+            },
+        )
+        .unwrap();
+        let subwallet: Option<String> = from_binary(&res).unwrap();
+        assert_eq!(subwallet, Some("subwallet".to_string()));
+
+        // existing subwallets are added to the reverse lookup
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetSubwalletOwner {
+                subwallet_address: "subwallet".to_string(),
+            },
+        )
+        .unwrap();
+        let owner: Option<String> = from_binary(&res).unwrap();
+        assert_eq!(owner, Some("user".to_string()));
+
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetSubwalletOwner {
+                subwallet_address: "user".to_string(),
+            },
+        )
+        .unwrap();
+        let owner: Option<String> = from_binary(&res).unwrap();
+        assert_eq!(owner, None);
+
+        let version = get_contract_version(&deps.storage).unwrap();
+        assert_eq!(version.contract, "crates.io:subwallet-factory");
+        assert_eq!(version.version, env!("CARGO_PKG_VERSION"));
+
+        // a newer contract cannot be migrated to this version
+        set_contract_version(&mut deps.storage, "crates.io:subwallet-factory", "99.0.0").unwrap();
+        match contract::migrate(deps.as_mut(), mock_env(), MigrateMsg {}) {
+            Err(ContractError::Std(_)) => {}
+            _ => panic!("Contract should refuse to migrate to an older version"),
+        }
+    }
+
+    #[test]
+    fn migrate_subwallet_owners_once() {
+        let mut deps = mock_dependencies(&[]);
+        let env = mock_env();
+
+        let msg = InstantiateMsg {
+            subwallet_code_id: 17,
+            aterra_token_addr: "aterra".to_string(),
+            anchor_market_contract: "anchor_market".to_string(),
+        };
+        instantiate(deps.as_mut(), env.clone(), mock_info("creator", &[]), msg).unwrap();
+        ACCOUNTS
+            .save(
+                &mut deps.storage,
+                &Addr::unchecked("user"),
+                &Addr::unchecked("subwallet"),
+            )
+            .unwrap();
+
+        // the reverse lookup is built when migrating from a version without it
+        set_contract_version(&mut deps.storage, "crates.io:subwallet-factory", "0.2.3").unwrap();
+        contract::migrate(deps.as_mut(), env.clone(), MigrateMsg {}).unwrap();
+        assert_eq!(
+            SUBWALLET_OWNERS
+                .may_load(&deps.storage, &Addr::unchecked("subwallet"))
+                .unwrap(),
+            Some(Addr::unchecked("user"))
+        );
+
+        // later migrations do not scan the accounts again
+        SUBWALLET_OWNERS.remove(&mut deps.storage, &Addr::unchecked("subwallet"));
+        contract::migrate(deps.as_mut(), env, MigrateMsg {}).unwrap();
+        assert_eq!(
+            SUBWALLET_OWNERS
+                .may_load(&deps.storage, &Addr::unchecked("subwallet"))
+                .unwrap(),
+            None
+        );
+    }
 }
@@ -113,6 +113,17 @@ fn factory_creates_subwallet() {
         .unwrap();
 
     assert_eq!(res, "contract #2");
+
+    // subwallets are looked up by address
+    let msg = QueryMsg::GetSubwalletOwner {
+        subwallet_address: "contract #2".to_string(),
+    };
+    let res: Option<String> = app
+        .wrap()
+        .query_wasm_smart(&factory_instance, &msg)
+        .unwrap();
+
+    assert_eq!(res, Some(bob.to_string()));
 }
 
 /// instantiates subwallet_factory contract and stores subwallet_code returns the address of subwallet_factory and subwallet code id
@@ -5,7 +5,7 @@ use cosmwasm_schema::{export_schema, export_schema_with_title, remove_schemas, s
 
 use admin_core::msg::{AdminConfigResponse, InstantiateMsg};
 use subwallet::msg::{AllAllowancesResponse, ExecuteMsg, QueryMsg};
-use subwallet::state::Allowance;
+use subwallet::state::{Allowance, TokenAllowance};
 
 fn main() {
     let mut out_dir = current_dir().unwrap();
@@ -17,6 +17,7 @@ fn main() {
     export_schema_with_title(&schema_for!(ExecuteMsg), &out_dir, "ExecuteMsg");
     export_schema_with_title(&schema_for!(QueryMsg), &out_dir, "QueryMsg");
     export_schema(&schema_for!(Allowance), &out_dir);
+    export_schema(&schema_for!(TokenAllowance), &out_dir);
     export_schema(&schema_for!(AdminConfigResponse), &out_dir);
     export_schema(&schema_for!(AllAllowancesResponse), &out_dir);
 }
@@ -15,13 +15,15 @@ use cosmwasm_bignumber::Uint256;
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
     attr, to_binary, Addr, BankMsg, Binary, Coin, CosmosMsg, Deps, DepsMut, DistributionMsg, Empty,
-    Env, MessageInfo, Order, Response, StakingMsg, StdResult, Uint128, WasmMsg,
+    Env, MessageInfo, Order, Response, StakingMsg, StdError, StdResult, Uint128, WasmMsg,
 };
 use cw0::Expiration;
 use cw1::CanExecuteResponse;
 use cw2::set_contract_version;
 use cw20::Cw20ExecuteMsg;
 use cw_storage_plus::Bound;
+use suberra_core::asset::{Asset, AssetInfo};
+use suberra_core::migration::migrate_contract_version;
 
 use crate::error::ContractError;
 use crate::msg::{
@@ -30,8 +32,8 @@ use crate::msg::{
 };
 use crate::querier::{calculate_aust_amount, get_aust_balance, get_subwallet_factory_config};
 use crate::state::{
-    deduct_allowance, read_config, store_config, Allowance, Config, Permissions, ALLOWANCES,
-    PERMISSIONS,
+    deduct_allowance, read_config, store_config, Allowance, Config, Permissions, TokenAllowance,
+    ALLOWANCES, PERMISSIONS, TOKEN_ALLOWANCES,
 };
 use suberra_core::msg::{MigrateMsg, SubwalletInstantiateMsg};
 
@@ -94,6 +96,18 @@ pub fn execute(
             amount,
             expires,
         } => execute_decrease_allowance(deps, env, info, spender, amount, expires),
+        ExecuteMsg::IncreaseTokenAllowance {
+            spender,
+            token,
+            amount,
+            expires,
+        } => execute_increase_token_allowance(deps, env, info, spender, token, amount, expires),
+        ExecuteMsg::DecreaseTokenAllowance {
+            spender,
+            token,
+            amount,
+            expires,
+        } => execute_decrease_token_allowance(deps, env, info, spender, token, amount, expires),
         ExecuteMsg::SetPermissions {
             spender,
             permissions,
@@ -101,6 +115,9 @@ pub fn execute(
         ExecuteMsg::TransferAToken { amount, recipient } => {
             execute_transfer_atoken(deps, env, info, amount, recipient)
         }
+        ExecuteMsg::TransferAsset { asset, recipient } => {
+            execute_transfer_asset(deps, env, info, asset, recipient)
+        }
     }
 }
 
@@ -303,6 +320,120 @@ where
     Ok(res)
 }
 
+/// Increases the allowance in the cw20 `token` for a `spender` address.
+pub fn execute_increase_token_allowance<T>(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    spender: String,
+    token: String,
+    amount: Uint128,
+    expires: Option<Expiration>,
+) -> Result<Response<T>, ContractError>
+where
+    T: Clone + fmt::Debug + PartialEq + JsonSchema,
+{
+    check_is_admin_or_owner(deps.as_ref(), info.sender.clone())?;
+
+    let spender_addr = deps.api.addr_validate(&spender)?;
+    if info.sender == spender_addr {
+        return Err(ContractError::CannotSetOwnAccount {});
+    }
+    let token_addr = deps.api.addr_validate(&token)?;
+
+    TOKEN_ALLOWANCES.update::<_, ContractError>(
+        deps.storage,
+        (&spender_addr, &token_addr),
+        |allow| {
+            let prev_expires = allow
+                .as_ref()
+                .map(|allow| allow.expires)
+                .unwrap_or_default();
+
+            let mut allowance = allow
+                .filter(|allow| !allow.expires.is_expired(&env.block))
+                .unwrap_or_default();
+
+            if let Some(exp) = expires {
+                if exp.is_expired(&env.block) {
+                    return Err(ContractError::SettingExpiredAllowance(exp));
+                }
+
+                allowance.expires = exp;
+            } else if prev_expires.is_expired(&env.block) {
+                return Err(ContractError::SettingExpiredAllowance(prev_expires));
+            }
+
+            allowance.amount += amount;
+            Ok(allowance)
+        },
+    )?;
+
+    let res = Response::new()
+        .add_attribute("action", "increase_token_allowance")
+        .add_attribute("owner", info.sender)
+        .add_attribute("spender", spender)
+        .add_attribute("token", token)
+        .add_attribute("amount", amount);
+    Ok(res)
+}
+
+/// Decreases the allowance in the cw20 `token` for a `spender` address. The allowance is removed once it reaches zero
+pub fn execute_decrease_token_allowance<T>(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    spender: String,
+    token: String,
+    amount: Uint128,
+    expires: Option<Expiration>,
+) -> Result<Response<T>, ContractError>
+where
+    T: Clone + fmt::Debug + PartialEq + JsonSchema,
+{
+    check_is_admin_or_owner(deps.as_ref(), info.sender.clone())?;
+
+    let spender_addr = deps.api.addr_validate(&spender)?;
+    if info.sender == spender_addr {
+        return Err(ContractError::CannotSetOwnAccount {});
+    }
+    let token_addr = deps.api.addr_validate(&token)?;
+
+    let allowance = TOKEN_ALLOWANCES.update::<_, ContractError>(
+        deps.storage,
+        (&spender_addr, &token_addr),
+        |allow| {
+            let mut allowance = allow
+                .filter(|allow| !allow.expires.is_expired(&env.block))
+                .ok_or(ContractError::NoAllowance {})?;
+
+            if let Some(exp) = expires {
+                if exp.is_expired(&env.block) {
+                    return Err(ContractError::SettingExpiredAllowance(exp));
+                }
+
+                allowance.expires = exp;
+            }
+
+            // tolerates underflows, like the native allowances
+            allowance.amount = allowance.amount.saturating_sub(amount);
+            Ok(allowance)
+        },
+    )?;
+
+    if allowance.amount.is_zero() {
+        TOKEN_ALLOWANCES.remove(deps.storage, (&spender_addr, &token_addr));
+    }
+
+    let res = Response::new()
+        .add_attribute("method", "decrease_token_allowance")
+        .add_attribute("owner", info.sender)
+        .add_attribute("spender", spender)
+        .add_attribute("token", token)
+        .add_attribute("amount", amount);
+    Ok(res)
+}
+
 /// Transfers aUST to a receipient
 ///
 ///  * **deps** is the object of [`DepsMut`]
@@ -380,6 +511,89 @@ where
         .add_attributes(vec![attr("method", "transfer_atoken")]))
 }
 
+/// Transfers a native or cw20 asset to a receipient. The transfer is deducted from the native allowance
+/// in the asset's denom, or from the token allowance of the cw20 contract. Native assets are sent in full, and the
+/// tax is paid by the subwallet on top of the transfer, so that the recipient receives the exact amount
+///
+///  * **deps** is the object of [`DepsMut`]
+///
+///  * **_info** is the object of type [`MessageInfo`]
+///
+///  * **_env** is the object of type [`Env`]
+pub fn execute_transfer_asset(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    asset: Asset,
+    recipient: String,
+) -> Result<Response, ContractError> {
+    let cfg = ADMIN_CONFIG.load(deps.storage)?;
+
+    // transactions cannot be processed if the sender is not the owner and contract is frozen
+    if cfg.owner != info.sender && !cfg.mutable {
+        return Err(ContractError::Frozen {});
+    }
+
+    let recipient = deps.api.addr_validate(&recipient)?;
+
+    match &asset.info {
+        AssetInfo::NativeToken { denom } => deduct_allowance(
+            deps.storage,
+            env.clone(),
+            &info.sender,
+            vec![Coin {
+                denom: denom.to_string(),
+                amount: asset.amount,
+            }],
+        )?,
+        AssetInfo::Token { contract_addr } => {
+            TOKEN_ALLOWANCES.update::<_, ContractError>(
+                deps.storage,
+                (&info.sender, contract_addr),
+                |allow| {
+                    let mut allowance = allow.ok_or(ContractError::NoAllowance {})?;
+                    if allowance.expires.is_expired(&env.block) {
+                        return Err(ContractError::NoAllowance {});
+                    }
+
+                    // Decrease allowance
+                    allowance.amount = allowance
+                        .amount
+                        .checked_sub(asset.amount)
+                        .map_err(StdError::from)?;
+                    Ok(allowance)
+                },
+            )?;
+        }
+    }
+
+    let balance = asset
+        .info
+        .query_balance(deps.as_ref(), &env.contract.address)?;
+    if asset.amount > balance {
+        return Err(ContractError::InsufficientFunds {});
+    }
+
+    let transfer_msg = match &asset.info {
+        AssetInfo::NativeToken { denom } => CosmosMsg::Bank(BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: vec![Coin {
+                denom: denom.to_string(),
+                amount: asset.amount,
+            }],
+        }),
+        AssetInfo::Token { .. } => asset.clone().into_msg(deps.as_ref(), recipient.clone())?,
+    };
+
+    Ok(Response::new()
+        .add_message(transfer_msg)
+        .add_attributes(vec![
+            attr("method", "transfer_asset"),
+            attr("recipient", recipient),
+            attr("asset", asset.to_string()),
+        ]))
+}
+
 pub fn execute_set_permissions<T>(
     deps: DepsMut,
     _env: Env,
@@ -427,6 +641,9 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::AdminList {} => to_binary(&query_admin_list(deps)?),
         QueryMsg::Owner {} => to_binary(&query_owner(deps)?),
         QueryMsg::Allowance { spender } => to_binary(&query_allowance(deps, env, spender)?),
+        QueryMsg::TokenAllowance { spender, token } => {
+            to_binary(&query_token_allowance(deps, env, spender, token)?)
+        }
         QueryMsg::Permissions { spender } => to_binary(&query_permissions(deps, spender)?),
         QueryMsg::CanExecute { sender, msg } => {
             to_binary(&query_can_execute(deps, env, sender, msg)?)
@@ -452,6 +669,23 @@ pub fn query_allowance(deps: Deps, env: Env, spender: String) -> StdResult<Allow
     Ok(allow)
 }
 
+// if the subkey has no allowance in the token, return an empty struct (not an error)
+pub fn query_token_allowance(
+    deps: Deps,
+    env: Env,
+    spender: String,
+    token: String,
+) -> StdResult<TokenAllowance> {
+    let spender = deps.api.addr_validate(&spender)?;
+    let token = deps.api.addr_validate(&token)?;
+    let allow = TOKEN_ALLOWANCES
+        .may_load(deps.storage, (&spender, &token))?
+        .filter(|allow| !allow.expires.is_expired(&env.block))
+        .unwrap_or_default();
+
+    Ok(allow)
+}
+
 // if the subkey has no permissions, return an empty struct (not an error)
 pub fn query_permissions(deps: Deps, spender: String) -> StdResult<Permissions> {
     let spender = deps.api.addr_validate(&spender)?;
@@ -514,8 +748,17 @@ fn can_execute(deps: Deps, env: Env, sender: String, msg: CosmosMsg) -> StdResul
 
 #[allow(dead_code)]
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
-    Ok(Response::default())
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let previous_version =
+        migrate_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION, &[])?;
+
+    // no storage changes to apply yet. Upgrade steps are gated on `previous_version`
+
+    Ok(Response::new().add_attributes(vec![
+        attr("method", "migrate"),
+        attr("previous_version", previous_version.to_string()),
+        attr("version", CONTRACT_VERSION),
+    ]))
 }
 
 const MAX_LIMIT: u32 = 30;
@@ -6,6 +6,7 @@ use cosmwasm_std::{Coin, CosmosMsg, Empty, Uint128};
 use cw0::{Expiration, NativeBalance};
 
 use crate::state::Permissions;
+use suberra_core::asset::Asset;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
@@ -36,6 +37,20 @@ where
         amount: Coin,
         expires: Option<Expiration>,
     },
+    /// Add an allowance in the cw20 `token` to a given subkey (subkey must not be admin)
+    IncreaseTokenAllowance {
+        spender: String,
+        token: String,
+        amount: Uint128,
+        expires: Option<Expiration>,
+    },
+    /// Decreases an allowance in the cw20 `token` for a given subkey (subkey must not be admin)
+    DecreaseTokenAllowance {
+        spender: String,
+        token: String,
+        amount: Uint128,
+        expires: Option<Expiration>,
+    },
 
     // Setups up permissions for a given subkey.
     SetPermissions {
@@ -45,6 +60,9 @@ where
 
     /// Transfers aUST to a recipient
     TransferAToken { amount: Uint128, recipient: String },
+
+    /// Transfers a native or cw20 asset to a recipient
+    TransferAsset { asset: Asset, recipient: String },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -63,6 +81,12 @@ where
     Allowance {
         spender: String,
     },
+    /// Get the current allowance in the cw20 `token` for the given subkey
+    /// Returns crate::state::TokenAllowance
+    TokenAllowance {
+        spender: String,
+        token: String,
+    },
     /// Get the current permissions for the given subkey (how much it can spend)
     /// Returns PermissionsInfo
     Permissions {
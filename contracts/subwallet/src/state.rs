@@ -1,6 +1,6 @@
 // use crate::allowance::PeriodicAllowance;
 use crate::error::ContractError;
-use cosmwasm_std::{Addr, Coin, Env, StdResult, Storage};
+use cosmwasm_std::{Addr, Coin, Env, StdResult, Storage, Uint128};
 use cosmwasm_storage::{ReadonlySingleton, Singleton};
 use cw0::{Expiration, NativeBalance};
 use cw_storage_plus::Map;
@@ -71,8 +71,18 @@ impl Allowance {
     }
 }
 
+// Allowance of a spender in a cw20 token. Token allowances are kept apart from the native allowances and keyed by
+// the token contract, so that a token address can never be mistaken for a native denom
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct TokenAllowance {
+    pub amount: Uint128,
+    pub expires: Expiration,
+}
+
 pub const PERMISSIONS: Map<&Addr, Permissions> = Map::new("permissions");
 pub const ALLOWANCES: Map<&Addr, Allowance> = Map::new("allowances");
+// (spender, token contract) -> allowance in the token
+pub const TOKEN_ALLOWANCES: Map<(&Addr, &Addr), TokenAllowance> = Map::new("token_allowances");
 // pub const PERIODIC_ALLOWANCES: Map<&Addr, PeriodicAllowance> = Map::new("periodic_allowances");
 
 // helper function to decrease the allowance for the user.]
@@ -1807,6 +1807,277 @@ mod tests {
         }
     }
 
+    mod transfer_asset {
+        use cosmwasm_std::testing::MOCK_CONTRACT_ADDR;
+        use cosmwasm_std::{Addr, BankMsg, Uint128};
+        use suberra_core::asset::{Asset, AssetInfo};
+
+        use crate::contract::query_token_allowance;
+        use crate::error::ContractError;
+        use crate::state::TokenAllowance;
+
+        use super::*;
+
+        const DENOM: &str = "uluna";
+
+        fn transfer_msg(amount: u128) -> ExecuteMsg {
+            ExecuteMsg::TransferAsset {
+                asset: Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: DENOM.to_string(),
+                    },
+                    amount: Uint128::new(amount),
+                },
+                recipient: SPENDER2.to_string(),
+            }
+        }
+
+        #[test]
+        fn with_allowance() {
+            let Suite { mut deps, .. } = SuiteConfig::new()
+                .with_allowance(SPENDER1, coin(10, DENOM))
+                .init();
+            deps.querier
+                .update_balance(MOCK_CONTRACT_ADDR, coins(100, DENOM));
+
+            let rsp = execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(SPENDER1, &[]),
+                transfer_msg(6),
+            )
+            .unwrap();
+
+            assert_eq!(
+                rsp.messages,
+                vec![SubMsg::new(BankMsg::Send {
+                    to_address: SPENDER2.to_string(),
+                    amount: coins(6, DENOM),
+                })]
+            );
+            assert_eq!(
+                query_allowance(deps.as_ref(), mock_env(), SPENDER1.to_owned())
+                    .unwrap()
+                    .balance,
+                NativeBalance(coins(4, DENOM))
+            );
+        }
+
+        #[test]
+        fn taxed_denom_is_delivered_in_full() {
+            let Suite { mut deps, .. } = SuiteConfig::new()
+                .with_allowance(SPENDER1, coin(1000, "uusd"))
+                .init();
+            deps.querier
+                .update_balance(MOCK_CONTRACT_ADDR, coins(1000, "uusd"));
+
+            // a charge split between the merchant and the protocol fee
+            for (recipient, amount) in [(SPENDER2, 990u128), (SPENDER3, 10u128)] {
+                let rsp = execute(
+                    deps.as_mut(),
+                    mock_env(),
+                    mock_info(SPENDER1, &[]),
+                    ExecuteMsg::TransferAsset {
+                        asset: Asset {
+                            info: AssetInfo::NativeToken {
+                                denom: "uusd".to_string(),
+                            },
+                            amount: Uint128::new(amount),
+                        },
+                        recipient: recipient.to_string(),
+                    },
+                )
+                .unwrap();
+
+                // the tax is not taken out of the amount that the recipient receives
+                assert_eq!(
+                    rsp.messages,
+                    vec![SubMsg::new(BankMsg::Send {
+                        to_address: recipient.to_string(),
+                        amount: coins(amount, "uusd"),
+                    })]
+                );
+            }
+
+            assert_eq!(
+                query_allowance(deps.as_ref(), mock_env(), SPENDER1.to_owned())
+                    .unwrap()
+                    .balance,
+                NativeBalance(vec![])
+            );
+        }
+
+        #[test]
+        fn native_allowance_does_not_cover_token() {
+            // a native denom named like the token contract is a different asset
+            let Suite { mut deps, .. } = SuiteConfig::new()
+                .with_allowance(SPENDER1, coin(10, TOKEN))
+                .init();
+
+            let msg = ExecuteMsg::TransferAsset {
+                asset: Asset {
+                    info: AssetInfo::Token {
+                        contract_addr: Addr::unchecked(TOKEN),
+                    },
+                    amount: Uint128::new(6),
+                },
+                recipient: SPENDER2.to_string(),
+            };
+            let err =
+                execute(deps.as_mut(), mock_env(), mock_info(SPENDER1, &[]), msg).unwrap_err();
+            assert_eq!(err, ContractError::NoAllowance {});
+
+            assert_eq!(
+                query_allowance(deps.as_ref(), mock_env(), SPENDER1.to_owned())
+                    .unwrap()
+                    .balance,
+                NativeBalance(coins(10, TOKEN))
+            );
+        }
+
+        #[test]
+        fn token_allowance() {
+            let Suite { mut deps, owner } = SuiteConfig::new().init();
+
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                owner.clone(),
+                ExecuteMsg::IncreaseTokenAllowance {
+                    spender: SPENDER1.to_string(),
+                    token: TOKEN.to_string(),
+                    amount: Uint128::new(10),
+                    expires: None,
+                },
+            )
+            .unwrap();
+
+            // the token allowance is not part of the native allowance
+            assert_eq!(
+                query_allowance(deps.as_ref(), mock_env(), SPENDER1.to_owned()).unwrap(),
+                Allowance::default()
+            );
+            assert_eq!(
+                query_token_allowance(
+                    deps.as_ref(),
+                    mock_env(),
+                    SPENDER1.to_owned(),
+                    TOKEN.to_owned()
+                )
+                .unwrap(),
+                TokenAllowance {
+                    amount: Uint128::new(10),
+                    expires: Expiration::Never {},
+                }
+            );
+
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                owner.clone(),
+                ExecuteMsg::DecreaseTokenAllowance {
+                    spender: SPENDER1.to_string(),
+                    token: TOKEN.to_string(),
+                    amount: Uint128::new(4),
+                    expires: None,
+                },
+            )
+            .unwrap();
+            assert_eq!(
+                query_token_allowance(
+                    deps.as_ref(),
+                    mock_env(),
+                    SPENDER1.to_owned(),
+                    TOKEN.to_owned()
+                )
+                .unwrap()
+                .amount,
+                Uint128::new(6)
+            );
+
+            // spenders cannot change token allowances
+            let err = execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(SPENDER1, &[]),
+                ExecuteMsg::IncreaseTokenAllowance {
+                    spender: SPENDER2.to_string(),
+                    token: TOKEN.to_string(),
+                    amount: Uint128::new(10),
+                    expires: None,
+                },
+            )
+            .unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
+
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                owner,
+                ExecuteMsg::DecreaseTokenAllowance {
+                    spender: SPENDER1.to_string(),
+                    token: TOKEN.to_string(),
+                    amount: Uint128::new(10),
+                    expires: None,
+                },
+            )
+            .unwrap();
+            assert_eq!(
+                query_token_allowance(
+                    deps.as_ref(),
+                    mock_env(),
+                    SPENDER1.to_owned(),
+                    TOKEN.to_owned()
+                )
+                .unwrap(),
+                TokenAllowance::default()
+            );
+        }
+
+        #[test]
+        fn not_enough_allowance() {
+            let Suite { mut deps, .. } = SuiteConfig::new()
+                .with_allowance(SPENDER1, coin(10, DENOM))
+                .init();
+            deps.querier
+                .update_balance(MOCK_CONTRACT_ADDR, coins(100, DENOM));
+
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(SPENDER1, &[]),
+                transfer_msg(20),
+            )
+            .unwrap_err();
+
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(SPENDER3, &[]),
+                transfer_msg(1),
+            )
+            .unwrap_err();
+        }
+
+        #[test]
+        fn insufficient_funds() {
+            let Suite { mut deps, .. } = SuiteConfig::new()
+                .with_allowance(SPENDER1, coin(10, DENOM))
+                .init();
+            deps.querier
+                .update_balance(MOCK_CONTRACT_ADDR, coins(5, DENOM));
+
+            let err = execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(SPENDER1, &[]),
+                transfer_msg(6),
+            )
+            .unwrap_err();
+            assert_eq!(err, ContractError::InsufficientFunds {});
+        }
+    }
+
     // tests permissions and allowances are independent features and does not affect each other
     #[test]
     fn permissions_allowances_independent() {
@@ -6,7 +6,7 @@ use crate::query::{
     query_all_streams_by_token,
 };
 use crate::state::{increment_stream_id, streams, Stream};
-use crate::token::{Asset, AssetInfo};
+use suberra_core::asset::{Asset, AssetInfo};
 
 use cosmwasm_std::{
     attr, from_binary, to_binary, Addr, Binary, Decimal, Deps, DepsMut, Env, MessageInfo, Response,
@@ -16,6 +16,7 @@ use cosmwasm_std::{entry_point, Uint128};
 use cw2::set_contract_version;
 use cw20::Cw20ReceiveMsg;
 use cw_storage_plus::U64Key;
+use suberra_core::migration::migrate_contract_version;
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:p2p_recurring_transfers";
@@ -379,6 +380,15 @@ pub fn query_stream_balance_of(
 
 #[allow(dead_code)]
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
-    Ok(Response::default())
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let previous_version =
+        migrate_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION, &[])?;
+
+    // no storage changes to apply yet. Upgrade steps are gated on `previous_version`
+
+    Ok(Response::new().add_attributes(vec![
+        attr("method", "migrate"),
+        attr("previous_version", previous_version.to_string()),
+        attr("version", CONTRACT_VERSION),
+    ]))
 }
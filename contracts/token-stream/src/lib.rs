@@ -3,7 +3,6 @@ mod error;
 pub mod msg;
 pub mod query;
 pub mod state;
-pub mod token;
 
 pub use crate::error::ContractError;
 
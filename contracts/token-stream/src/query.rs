@@ -3,10 +3,10 @@ use std::convert::TryInto;
 use crate::{
     msg::StreamsResponse,
     state::{streams, Stream},
-    token::AssetInfo,
 };
 use cosmwasm_std::{Addr, Deps, Order, StdResult};
 use cw_storage_plus::{Bound, MultiIndex, U64Key};
+use suberra_core::asset::AssetInfo;
 
 // settings for pagination
 const MAX_LIMIT: u32 = 30;
@@ -3,7 +3,7 @@ use crate::error::ContractError;
 use crate::mock_querier::mock_dependencies;
 use crate::msg::{Cw20HookMsg, ExecuteMsg, InstantiateMsg, QueryMsg, StreamsResponse};
 use crate::state::Stream;
-use crate::token::{Asset, AssetInfo};
+use suberra_core::asset::{Asset, AssetInfo};
 
 use cosmwasm_std::testing::{mock_env, mock_info};
 use cosmwasm_std::{
@@ -3,10 +3,10 @@ use serde::{Deserialize, Serialize};
 use std::fmt;
 
 use cosmwasm_std::{
-    to_binary, Addr, BankMsg, Coin, CosmosMsg, Decimal, Deps, MessageInfo, StdError, StdResult,
-    Uint128, WasmMsg,
+    to_binary, Addr, Api, BankMsg, Coin, CosmosMsg, Decimal, Deps, MessageInfo, QueryRequest,
+    StdError, StdResult, Uint128, WasmMsg, WasmQuery,
 };
-use cw20::Cw20ExecuteMsg;
+use cw20::{BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg};
 use terra_cosmwasm::TerraQuerier;
 
 static DECIMAL_FRACTION: Uint128 = Uint128::new(1_000_000_000_000_000_000u128);
@@ -55,7 +55,7 @@ impl Asset {
         if let AssetInfo::NativeToken { denom } = &self.info {
             Ok(Coin {
                 denom: denom.to_string(),
-                amount: (amount.checked_sub(self.compute_tax(deps).unwrap())).unwrap(),
+                amount: amount.checked_sub(self.compute_tax(deps)?)?,
             })
         } else {
             Err(StdError::generic_err("cannot deduct tax from token asset"))
@@ -154,4 +154,69 @@ impl AssetInfo {
             }
         }
     }
+    /// Validates the cw20 contract address of a token asset
+    pub fn check(&self, api: &dyn Api) -> StdResult<()> {
+        if let AssetInfo::Token { contract_addr } = self {
+            api.addr_validate(contract_addr.as_str())?;
+        }
+        Ok(())
+    }
+
+    /// Returns the balance of this asset held by `address`
+    pub fn query_balance(&self, deps: Deps, address: &Addr) -> StdResult<Uint128> {
+        match self {
+            AssetInfo::NativeToken { denom } => {
+                Ok(deps.querier.query_balance(address, denom)?.amount)
+            }
+            AssetInfo::Token { contract_addr } => {
+                let res: BalanceResponse =
+                    deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+                        contract_addr: contract_addr.to_string(),
+                        msg: to_binary(&Cw20QueryMsg::Balance {
+                            address: address.to_string(),
+                        })?,
+                    }))?;
+                Ok(res.balance)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::mock_dependencies;
+
+    #[test]
+    fn deduct_tax_returns_query_errors() {
+        // the mock querier does not answer the treasury queries of terra
+        let deps = mock_dependencies(&[]);
+        let asset = Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::new(1000),
+        };
+
+        assert!(asset.deduct_tax(deps.as_ref()).is_err());
+    }
+
+    #[test]
+    fn deduct_tax_is_free_for_luna() {
+        let deps = mock_dependencies(&[]);
+        let asset = Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uluna".to_string(),
+            },
+            amount: Uint128::new(1000),
+        };
+
+        assert_eq!(
+            asset.deduct_tax(deps.as_ref()).unwrap(),
+            Coin {
+                denom: "uluna".to_string(),
+                amount: Uint128::new(1000),
+            }
+        );
+    }
 }
@@ -0,0 +1,220 @@
+use crate::subscriptions::{BillingCalendar, CalendarPeriod};
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+impl BillingCalendar {
+    /// Returns true if the day of the month and the month of the calendar exist
+    pub fn is_valid(&self) -> bool {
+        (1..=31).contains(&self.day_of_month) && matches!(self.month, None | Some(1..=12))
+    }
+
+    /// Returns the first period end after `timestamp`
+    pub fn next_period_end(&self, timestamp: u64) -> u64 {
+        let mut month = self.aligned_month(month_of(timestamp));
+        while self.period_end_in(month) <= timestamp {
+            month += self.period_months();
+        }
+
+        self.period_end_in(month)
+    }
+
+    /// Returns the last period end before `timestamp`
+    pub fn previous_period_end(&self, timestamp: u64) -> u64 {
+        let mut month = self.aligned_month(month_of(timestamp));
+        while self.period_end_in(month) >= timestamp && month >= self.period_months() {
+            month -= self.period_months();
+        }
+
+        self.period_end_in(month)
+    }
+
+    /// Returns the end of the `periods`-th period after `timestamp`. The first period is partial if `timestamp` is not a period end
+    pub fn period_end_after(&self, timestamp: u64, periods: u64) -> u64 {
+        if periods == 0 {
+            return timestamp;
+        }
+
+        let first_end = self.next_period_end(timestamp);
+        self.period_end_in(month_of(first_end) + (periods - 1) * self.period_months())
+    }
+
+    /// Returns the number of period ends after `start` and before `end`
+    pub fn period_ends_between(&self, start: u64, end: u64) -> u64 {
+        let first_end = self.next_period_end(start);
+        if first_end >= end {
+            return 0;
+        }
+
+        let last_end = self.previous_period_end(end);
+        (month_of(last_end) - month_of(first_end)) / self.period_months() + 1
+    }
+
+    /// Returns the number of months in every period
+    pub fn period_months(&self) -> u64 {
+        match self.period {
+            CalendarPeriod::Monthly => 1,
+            CalendarPeriod::Quarterly => 3,
+            CalendarPeriod::Yearly => 12,
+        }
+    }
+
+    // returns the last month before or at `month` that a period ends in. Months are counted from January 1970
+    fn aligned_month(&self, month: u64) -> u64 {
+        let first_month = match self.period {
+            CalendarPeriod::Monthly => 0,
+            _ => u64::from(self.month.unwrap_or(1)) - 1,
+        };
+        let offset = (month + 12 - first_month) % self.period_months();
+        month.saturating_sub(offset)
+    }
+
+    // returns the period end in `month`, clamped to the last day of short months
+    fn period_end_in(&self, month: u64) -> u64 {
+        let year = 1970 + month / 12;
+        let month_of_year = month % 12 + 1;
+        let day = u64::from(self.day_of_month).min(days_in_month(year, month_of_year));
+        (days_from_civil(year, month_of_year) + day - 1) * SECONDS_PER_DAY
+    }
+}
+
+// returns the month of `timestamp`, counted from January 1970
+fn month_of(timestamp: u64) -> u64 {
+    let (year, month) = civil_from_days(timestamp / SECONDS_PER_DAY);
+    (year - 1970) * 12 + month - 1
+}
+
+fn is_leap_year(year: u64) -> bool {
+    match (year % 4, year % 100, year % 400) {
+        (_, _, 0) => true,
+        (_, 0, _) => false,
+        (0, _, _) => true,
+        _ => false,
+    }
+}
+
+fn days_in_month(year: u64, month: u64) -> u64 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// returns the number of days from 1 January 1970 to the first day of `month` in `year`
+fn days_from_civil(year: u64, month: u64) -> u64 {
+    let days_before_year: u64 = (1970..year)
+        .map(|year| match is_leap_year(year) {
+            true => 366,
+            false => 365,
+        })
+        .sum();
+    let days_before_month: u64 = (1..month).map(|month| days_in_month(year, month)).sum();
+    days_before_year + days_before_month
+}
+
+// returns the year and the month of the day that is `days` after 1 January 1970
+fn civil_from_days(mut days: u64) -> (u64, u64) {
+    let mut year = 1970;
+    loop {
+        let days_in_year = match is_leap_year(year) {
+            true => 366,
+            false => 365,
+        };
+        if days < days_in_year {
+            break;
+        }
+        days -= days_in_year;
+        year += 1;
+    }
+
+    let mut month = 1;
+    while days >= days_in_month(year, month) {
+        days -= days_in_month(year, month);
+        month += 1;
+    }
+
+    (year, month)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 1 January 2021 00:00:00 UTC
+    const JAN_1_2021: u64 = 1609459200;
+
+    fn new_calendar(
+        period: CalendarPeriod,
+        day_of_month: u8,
+        month: Option<u8>,
+    ) -> BillingCalendar {
+        BillingCalendar {
+            period,
+            day_of_month,
+            month,
+            prorate_first_period: false,
+        }
+    }
+
+    fn day(days_after_jan_1_2021: u64) -> u64 {
+        JAN_1_2021 + days_after_jan_1_2021 * SECONDS_PER_DAY
+    }
+
+    #[test]
+    fn monthly_periods_are_clamped_to_short_months() {
+        let calendar = new_calendar(CalendarPeriod::Monthly, 31, None);
+
+        // 31 January, 28 February, 31 March 2021
+        assert_eq!(calendar.next_period_end(JAN_1_2021), day(30));
+        assert_eq!(calendar.next_period_end(day(30)), day(58));
+        assert_eq!(calendar.next_period_end(day(58)), day(89));
+        assert_eq!(calendar.period_end_after(JAN_1_2021, 3), day(89));
+        assert_eq!(calendar.previous_period_end(day(89)), day(58));
+        assert_eq!(calendar.previous_period_end(day(59)), day(58));
+
+        // 29 February 2024
+        let feb_1_2024 = 1706745600;
+        assert_eq!(
+            calendar.next_period_end(feb_1_2024),
+            feb_1_2024 + 28 * SECONDS_PER_DAY
+        );
+    }
+
+    #[test]
+    fn quarterly_and_yearly_periods() {
+        // 15 February, 15 May, 15 August and 15 November
+        let calendar = new_calendar(CalendarPeriod::Quarterly, 15, Some(2));
+        assert_eq!(calendar.next_period_end(JAN_1_2021), day(45));
+        assert_eq!(calendar.next_period_end(day(45)), day(134));
+        assert_eq!(
+            calendar.previous_period_end(day(45)),
+            JAN_1_2021 - 47 * SECONDS_PER_DAY
+        );
+
+        // 1 January
+        let calendar = new_calendar(CalendarPeriod::Yearly, 1, None);
+        assert_eq!(calendar.next_period_end(JAN_1_2021 - 1), JAN_1_2021);
+        assert_eq!(calendar.next_period_end(JAN_1_2021), day(365));
+        assert_eq!(calendar.period_end_after(JAN_1_2021, 2), day(365 + 365));
+    }
+
+    #[test]
+    fn period_ends_between() {
+        let calendar = new_calendar(CalendarPeriod::Monthly, 1, None);
+
+        // 1 February and 1 March 2021
+        assert_eq!(calendar.period_ends_between(JAN_1_2021, day(59)), 1);
+        assert_eq!(calendar.period_ends_between(JAN_1_2021, day(60)), 2);
+        assert_eq!(calendar.period_ends_between(day(10), day(31)), 0);
+        assert_eq!(calendar.period_ends_between(day(10), day(10)), 0);
+    }
+
+    #[test]
+    fn invalid_calendars() {
+        assert!(new_calendar(CalendarPeriod::Monthly, 1, None).is_valid());
+        assert!(!new_calendar(CalendarPeriod::Monthly, 0, None).is_valid());
+        assert!(!new_calendar(CalendarPeriod::Monthly, 32, None).is_valid());
+        assert!(!new_calendar(CalendarPeriod::Yearly, 1, Some(13)).is_valid());
+    }
+}
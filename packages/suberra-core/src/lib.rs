@@ -1,3 +1,6 @@
+pub mod asset;
+pub mod calendar;
+pub mod migration;
 pub mod msg;
 pub mod product_factory;
 pub mod querier;
@@ -0,0 +1,118 @@
+use cosmwasm_std::{StdError, StdResult, Storage};
+use cw2::{get_contract_version, set_contract_version};
+pub use semver::Version;
+
+/// Version of a contract that was instantiated before it stored a cw2 contract version of its own
+pub const UNVERSIONED: Version = Version::new(0, 0, 0);
+
+/// Checks the cw2 contract version of a contract that is being migrated, and stores `new_version` in its place.
+/// Returns the version that the contract is migrated from, so that the upgrade steps of every later version can be applied.
+///
+/// Contracts that did not store a version, or stored it under one of the `legacy_names`, are [`UNVERSIONED`].
+/// Fails if the stored version belongs to another contract, or is newer than `new_version`
+pub fn migrate_contract_version(
+    storage: &mut dyn Storage,
+    contract_name: &str,
+    new_version: &str,
+    legacy_names: &[&str],
+) -> StdResult<Version> {
+    let new_version = parse_version(new_version)?;
+
+    let previous_version = match get_contract_version(storage) {
+        Ok(stored) if stored.contract == contract_name => parse_version(&stored.version)?,
+        Ok(stored) if legacy_names.contains(&stored.contract.as_str()) => UNVERSIONED,
+        Ok(stored) => {
+            return Err(StdError::generic_err(format!(
+                "Cannot migrate from a different contract: {}",
+                stored.contract
+            )))
+        }
+        Err(StdError::NotFound { .. }) => UNVERSIONED,
+        Err(err) => return Err(err),
+    };
+
+    if previous_version > new_version {
+        return Err(StdError::generic_err(format!(
+            "Cannot migrate from version {} to an older version {}",
+            previous_version, new_version
+        )));
+    }
+
+    set_contract_version(storage, contract_name, new_version.to_string())?;
+
+    Ok(previous_version)
+}
+
+fn parse_version(version: &str) -> StdResult<Version> {
+    Version::parse(version.trim_start_matches('v'))
+        .map_err(|_| StdError::generic_err(format!("Invalid contract version: {}", version)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::MockStorage;
+
+    #[test]
+    fn unversioned_contracts_are_migrated() {
+        let mut storage = MockStorage::new();
+        let version =
+            migrate_contract_version(&mut storage, "crates.io:sub1", "0.3.0", &[]).unwrap();
+        assert_eq!(version, UNVERSIONED);
+        assert_eq!(get_contract_version(&storage).unwrap().version, "0.3.0");
+
+        // contracts that stored the version of a library they instantiate are unversioned
+        let mut storage = MockStorage::new();
+        set_contract_version(&mut storage, "crates.io:admin-core", "0.2.1").unwrap();
+        let version = migrate_contract_version(
+            &mut storage,
+            "crates.io:sub1",
+            "0.3.0",
+            &["crates.io:admin-core"],
+        )
+        .unwrap();
+        assert_eq!(version, UNVERSIONED);
+        assert_eq!(
+            get_contract_version(&storage).unwrap().contract,
+            "crates.io:sub1"
+        );
+    }
+
+    #[test]
+    fn versioned_contracts_are_migrated() {
+        let mut storage = MockStorage::new();
+        set_contract_version(&mut storage, "crates.io:sub1", "0.2.1").unwrap();
+
+        let version =
+            migrate_contract_version(&mut storage, "crates.io:sub1", "0.3.0", &[]).unwrap();
+        assert_eq!(version, Version::new(0, 2, 1));
+
+        // migrating to the same version is allowed
+        let version =
+            migrate_contract_version(&mut storage, "crates.io:sub1", "0.3.0", &[]).unwrap();
+        assert_eq!(version, Version::new(0, 3, 0));
+    }
+
+    #[test]
+    fn invalid_migrations() {
+        let mut storage = MockStorage::new();
+        set_contract_version(&mut storage, "crates.io:sub1", "0.3.0").unwrap();
+
+        // downgrades are refused
+        let err =
+            migrate_contract_version(&mut storage, "crates.io:sub1", "0.2.1", &[]).unwrap_err();
+        assert_eq!(
+            err,
+            StdError::generic_err("Cannot migrate from version 0.3.0 to an older version 0.2.1")
+        );
+
+        // another contract cannot be migrated
+        let err =
+            migrate_contract_version(&mut storage, "crates.io:sub2", "0.3.0", &[]).unwrap_err();
+        assert_eq!(
+            err,
+            StdError::generic_err("Cannot migrate from a different contract: crates.io:sub1")
+        );
+        assert_eq!(get_contract_version(&storage).unwrap().version, "0.3.0");
+    }
+}
@@ -1,5 +1,9 @@
+use crate::asset::{Asset, AssetInfo};
+use crate::subscriptions::{
+    BillingCalendar, MembershipNftInfo, PlanInfo, PriceTier, RevenueSplitInfo,
+};
 use cosmwasm_bignumber::Uint256;
-use cosmwasm_std::Uint128;
+use cosmwasm_std::{to_binary, CosmosMsg, StdResult, Uint128, WasmMsg};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -12,13 +16,23 @@ pub struct SubwalletInstantiateMsg {
     pub subwallet_factory_addr: String,
 }
 
-//  - receiver_address: address that will receive the payments
+//  - receiver_address: address that will receive the payments. The primary receiver if the revenue is split
 //  - job_registry_contact: Contract address of the job_registry. Required for automation
 //  - unit_amount: Amount to be charged in every billing cycle
 //  - initial_amount: initial_amount that must be transferred to the receiver for the subscription to be created. Common in most services
 //  - unit_interval_hour: Duration of the billing cycle in hours
+//  - trial_period_hour: (Optional) Duration of the free trial in hours. No charges are made during the trial
+//  - plans: Additional pricing plans offered by the product. The top-level pricing fields describe the default plan
 //  - max_amount_chargeable: Maximum amount that will be chargeable to the subscriber.
+//  - usage_tiers: (Optional) Graduated unit prices of the usage reported in every billing cycle. Makes the product metered, with the unit amount as the base fee
+//  - membership_nft: (Optional) Mints a cw721 membership token for every subscription if set
+//  - billing_asset: (Optional) Native or cw20 asset that subscriptions are paid in. Defaults to aUST, for amounts denominated in UST
+//  - revenue_splits: (Optional) Co-receivers of the product with their share of the revenue in basis points. The primary receiver gets the rest
+//  - accept_any_payer: (Optional) Accepts payers that are not subwallets of the subwallet factory. Defaults to false
 //  - additional_grace_period_hour: Amount of time (in hours) that a subscription should still be active despite payment is due
+//  - retry_schedule_hour: (Optional) Delays in hours before each retry of a failed charge. The subscription is cancelled after the final retry fails
+//  - max_pause_duration_hour: (Optional) Maximum duration in hours of a pause requested by a subscriber. Subscribers cannot pause if unset
+//  - max_pauses_per_year: (Optional) Maximum number of pauses a subscriber can request in any 365 days. Subscribers cannot pause if unset
 //  - uri : Metadata for the subscription
 //  - admins: List of admins that have the rights to manage some features of the product contracts
 //  - mutable: States if the contract is mutable
@@ -30,7 +44,19 @@ pub struct ProductInstantiateMsg {
     pub unit_amount: Uint256,
     pub initial_amount: Uint256,
     pub unit_interval_hour: u64,
+    pub trial_period_hour: Option<u64>,
+    pub plans: Option<Vec<PlanInfo>>,
+    pub max_amount_chargeable: Option<Uint256>,
+    pub usage_tiers: Option<Vec<PriceTier>>,
+    pub membership_nft: Option<MembershipNftInfo>,
+    pub billing_asset: Option<AssetInfo>,
+    pub revenue_splits: Option<Vec<RevenueSplitInfo>>,
+    pub billing_calendar: Option<BillingCalendar>,
+    pub accept_any_payer: Option<bool>,
     pub additional_grace_period_hour: Option<u64>,
+    pub retry_schedule_hour: Option<Vec<u64>>,
+    pub max_pause_duration_hour: Option<u64>,
+    pub max_pauses_per_year: Option<u32>,
     pub uri: String,
     pub owner: String,
     pub admins: Vec<String>,
@@ -43,6 +69,24 @@ pub struct ProductInstantiateMsg {
 pub enum SubWalletExecuteMsg {
     TransferUST { amount: Uint128, recipient: String },
     TransferAToken { amount: Uint128, recipient: String },
+    TransferAsset { asset: Asset, recipient: String },
+}
+
+impl SubWalletExecuteMsg {
+    /// Returns the message that makes a subwallet transfer `amount` of the billing asset to `recipient`.
+    /// Payments without a billing asset are made in aUST, for an `amount` denominated in UST
+    pub fn transfer(billing_asset: &Option<AssetInfo>, amount: Uint128, recipient: String) -> Self {
+        match billing_asset {
+            Some(info) => SubWalletExecuteMsg::TransferAsset {
+                asset: Asset {
+                    info: info.clone(),
+                    amount,
+                },
+                recipient,
+            },
+            None => SubWalletExecuteMsg::TransferAToken { amount, recipient },
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -54,6 +98,7 @@ pub struct JobsRegistryInstantiateMsg {}
 pub enum JobsRegistryExecuteMsg {
     WorkReceipt {
         worker_address: String,
+        units: Option<u64>,
     },
     AddJob {
         contract_address: String,
@@ -61,6 +106,53 @@ pub enum JobsRegistryExecuteMsg {
     },
 }
 
+/// SubscriptionHookMsg is sent by a product to the hook contracts registered on it when a subscription changes.
+/// Hook contracts receive it wrapped in `{"subscription_hook": ...}`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SubscriptionHookMsg {
+    Subscribed {
+        subscriber: String,
+        plan_id: u64,
+    },
+    CancellationUndone {
+        subscriber: String,
+    },
+    Cancelled {
+        subscriber: String,
+    },
+    Charged {
+        subscriber: String,
+        amount: Uint256,
+    },
+    /// The subscription is cancelled after the final retry of a failed charge
+    Lapsed {
+        subscriber: String,
+    },
+    Removed {
+        subscriber: String,
+    },
+}
+
+impl SubscriptionHookMsg {
+    /// Returns the message that delivers this callback to the hook contract `contract_addr`
+    pub fn into_cosmos_msg(self, contract_addr: String) -> StdResult<CosmosMsg> {
+        Ok(WasmMsg::Execute {
+            contract_addr,
+            msg: to_binary(&SubscriptionHookExecuteMsg::SubscriptionHook(self))?,
+            funds: vec![],
+        }
+        .into())
+    }
+}
+
+/// SubscriptionHookExecuteMsg is the execute message that hook contracts must accept
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SubscriptionHookExecuteMsg {
+    SubscriptionHook(SubscriptionHookMsg),
+}
+
 /// We currently take no arguments for migrations
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct MigrateMsg {}
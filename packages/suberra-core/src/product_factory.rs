@@ -1,3 +1,7 @@
+use crate::asset::AssetInfo;
+use crate::subscriptions::{
+    BillingCalendar, MembershipNftInfo, PlanInfo, PriceTier, RevenueSplitInfo,
+};
 use cosmwasm_bignumber::Uint256;
 use cosmwasm_std::Addr;
 use schemars::JsonSchema;
@@ -12,12 +16,23 @@ pub struct InstantiateMsg {
     pub min_protocol_fee: Uint256,
     pub min_amount_per_interval: Uint256,
     pub min_unit_interval_hour: u64,
+    pub min_price_change_notice_hour: u64,
     pub fee_address: String,
     pub job_registry_address: String,
+    pub subwallet_factory_address: String,
+}
+
+/// ## Description
+/// This structure describes the migration settings of the contract.
+/// `subwallet_factory_address` is required to migrate a factory from before `0.3.0` that has no subwallet factory yet
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateMsg {
+    pub subwallet_factory_address: Option<String>,
 }
 
 /// ## Description
 /// This structure describes the execute messages of the contract.
+#[allow(clippy::large_enum_variant)]
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
@@ -32,8 +47,10 @@ pub enum ExecuteMsg {
         new_min_protocol_fee: Option<Uint256>,
         new_min_amount_per_interval: Option<Uint256>,
         new_min_unit_interval_hour: Option<u64>,
+        new_min_price_change_notice_hour: Option<u64>,
         new_fee_address: Option<String>,
         new_job_registry_address: Option<String>,
+        new_subwallet_factory_address: Option<String>,
     },
 }
 
@@ -52,12 +69,23 @@ pub enum QueryMsg {
 
 /// # Description
 /// Execute message used for creating a product through the product_factory
-// - receiver_address: address that will receive the payments
+// - receiver_address: address that will receive the payments. The primary receiver if the revenue is split
 // - unit_amount: Amount to be charged in every billing cycle
 // - initial_amount: initial_amount that must be transferred to the receiver for the subscription to be created. Common in most services
 // - unit_interval_hour: Duration of the billing cycle in hours
+// - trial_period_hour: (Optional) Duration of the free trial in hours. No charges are made during the trial
+// - plans: Additional pricing plans offered by the product. The top-level pricing fields describe the default plan
 // - max_amount_chargeable: Maximum amount that will be chargeable to the subscriber.
+// - usage_tiers: (Optional) Graduated unit prices of the usage reported in every billing cycle. Makes the product metered, with the unit amount as the base fee
+// - membership_nft: (Optional) Mints a cw721 membership token for every subscription if set
+// - billing_asset: (Optional) Native or cw20 asset that subscriptions are paid in. Defaults to aUST, for amounts denominated in UST
+// - revenue_splits: (Optional) Co-receivers of the product with their share of the revenue in basis points. The primary receiver gets the rest
+// - billing_calendar: (Optional) Anchors the intervals of the default plan to the UTC calendar instead of `unit_interval_hour`
+// - accept_any_payer: (Optional) Accepts payers that are not subwallets of the subwallet factory. Defaults to false
 // - additional_grace_period_hour: Amount of time (in hours) that a subscription should still be active despite payment is due
+// - retry_schedule_hour: (Optional) Delays in hours before each retry of a failed charge. The subscription is cancelled after the final retry fails
+// - max_pause_duration_hour: (Optional) Maximum duration in hours of a pause requested by a subscriber. Subscribers cannot pause if unset
+// - max_pauses_per_year: (Optional) Maximum number of pauses a subscriber can request in any 365 days. Subscribers cannot pause if unset
 // - uri : Metadata for the subscription
 // - admins: List of admins that have the rights to manage some features of the product contracts
 // - mutable: States if the contract is mutable
@@ -68,8 +96,19 @@ pub struct CreateProductExecuteMsg {
     pub unit_amount: Uint256,
     pub initial_amount: Uint256,
     pub unit_interval_hour: u64,
+    pub trial_period_hour: Option<u64>,
+    pub plans: Option<Vec<PlanInfo>>,
     pub max_amount_chargeable: Option<Uint256>,
+    pub usage_tiers: Option<Vec<PriceTier>>,
+    pub membership_nft: Option<MembershipNftInfo>,
+    pub billing_asset: Option<AssetInfo>,
+    pub revenue_splits: Option<Vec<RevenueSplitInfo>>,
+    pub billing_calendar: Option<BillingCalendar>,
+    pub accept_any_payer: Option<bool>,
     pub additional_grace_period_hour: Option<u64>,
+    pub retry_schedule_hour: Option<Vec<u64>>,
+    pub max_pause_duration_hour: Option<u64>,
+    pub max_pauses_per_year: Option<u32>,
     pub uri: String,
     pub admins: Vec<String>,
     pub mutable: bool,
@@ -91,10 +130,16 @@ pub struct ConfigResponse {
     pub min_amount_per_interval: Uint256,
     /// minimum unit interval in hours
     pub min_unit_interval_hour: u64,
+    /// minimum notice in hours between scheduling a price change of a product and the change taking effect
+    #[serde(default)]
+    pub min_price_change_notice_hour: u64,
     /// fee address
     pub fee_address: String,
     /// job registry address
     pub job_registry_address: String,
+    /// subwallet factory address, used by products to check that their payers are subwallets. Unset for factories instantiated before it was introduced
+    #[serde(default)]
+    pub subwallet_factory_address: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
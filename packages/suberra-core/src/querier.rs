@@ -1,6 +1,7 @@
 use crate::product_factory::{
     ConfigResponse as ProductFactoryConfigResponse, QueryMsg as FactoryQueryMsg,
 };
+use crate::subwallet_factory::QueryMsg as SubwalletFactoryQueryMsg;
 use cosmwasm_std::{to_binary, Addr, QuerierWrapper, QueryRequest, StdResult, WasmQuery};
 
 pub fn query_product_factory_config(
@@ -12,3 +13,17 @@ pub fn query_product_factory_config(
         msg: to_binary(&FactoryQueryMsg::Config {})?,
     }))
 }
+
+/// Returns the owner of `subwallet`, or `None` if it was not created by `subwallet_factory`
+pub fn query_subwallet_owner(
+    querier: &QuerierWrapper,
+    subwallet_factory: Addr,
+    subwallet: &Addr,
+) -> StdResult<Option<String>> {
+    querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: subwallet_factory.to_string(),
+        msg: to_binary(&SubwalletFactoryQueryMsg::GetSubwalletOwner {
+            subwallet_address: subwallet.to_string(),
+        })?,
+    }))
+}
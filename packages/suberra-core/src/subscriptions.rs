@@ -3,10 +3,102 @@ use cosmwasm_bignumber::Uint256;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+/// Plan ID that is reserved for the plan described by the top-level pricing fields of the product
+pub const DEFAULT_PLAN_ID: u64 = 0;
+
+/// DiscountType describes how the `amount` of a [`Discount`] is applied
+/// * `Fixed`: `amount` is deducted from the unit amount of every discounted interval
+/// * `Bps`: `amount` is the percentage (in basis points) of the unit amount that is deducted from every discounted interval
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DiscountType {
+    #[default]
+    Fixed,
+    Bps,
+}
+
 /// Discount Struct to store Discount information per subscriber
-/// * `amount`: Discount amount to be applied.
-/// * `expiry`: Optional unix timestamp (seconds). If specified, discount is no longer applied when Discount expires
+/// * `amount`: Discount amount to be applied. Interpreted according to `discount_type`
+/// * `discount_type`: Whether `amount` is a fixed amount or basis points. Defaults to a fixed amount
+/// * `expiry`: Optional unix timestamp (seconds). If specified, discount is no longer applied to intervals that start at or after the expiry
+/// * `intervals`: Optional number of intervals that the discount is applied to. Decreases every time a discounted interval is charged
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Discount {
     pub amount: Uint256,
+    #[serde(default)]
+    pub discount_type: DiscountType,
+    pub expiry: Option<u64>,
+    pub intervals: Option<u64>,
+}
+
+/// PlanInfo describes a pricing plan that is offered by a product
+/// * `plan_id`: Identifier of the plan. Must be unique within a product and cannot be [`DEFAULT_PLAN_ID`]
+/// * `unit_amount`: Amount to be charged in every billing cycle
+/// * `initial_amount`: Amount that must be transferred to the receiver for the subscription to be created
+/// * `unit_interval_hour`: Duration of the billing cycle in hours
+/// * `trial_period_hour`: (Optional) Duration of the free trial in hours. Defaults to the trial period of the product
+/// * `billing_calendar`: (Optional) Anchors the intervals of the plan to the UTC calendar instead of `unit_interval_hour`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PlanInfo {
+    pub plan_id: u64,
+    pub unit_amount: Uint256,
+    pub initial_amount: Uint256,
+    pub unit_interval_hour: u64,
+    pub trial_period_hour: Option<u64>,
+    #[serde(default)]
+    pub billing_calendar: Option<BillingCalendar>,
+}
+
+/// CalendarPeriod describes the length of the intervals of a [`BillingCalendar`]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CalendarPeriod {
+    Monthly,
+    Quarterly,
+    Yearly,
+}
+
+/// BillingCalendar anchors the intervals of a plan to the UTC calendar instead of a fixed duration.
+/// Intervals end at 00:00 UTC on `day_of_month`, or on the last day of months that are too short
+/// * `period`: Length of every interval
+/// * `day_of_month`: Day of the month that intervals end on, from 1 to 31
+/// * `month`: (Optional) Month that quarterly and yearly intervals end in, from 1 to 12. Defaults to January. Ignored by monthly calendars
+/// * `prorate_first_period`: Whether the initial amount is prorated over the partial period between subscribing and the first period end
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BillingCalendar {
+    pub period: CalendarPeriod,
+    pub day_of_month: u8,
+    pub month: Option<u8>,
+    pub prorate_first_period: bool,
+}
+
+/// PriceTier describes the unit price of usage within a tier of a metered product. Tiers are graduated:
+/// every unit of usage in an interval is priced by the tier that it falls in
+/// * `up_to`: (Optional) Last unit of usage in the interval covered by the tier. The last tier has no upper bound
+/// * `unit_price`: Amount charged for every unit of usage in the tier
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PriceTier {
+    pub up_to: Option<u64>,
+    pub unit_price: Uint256,
+}
+
+/// RevenueSplitInfo describes the share of the merchant revenue of a product that is paid to a co-receiver.
+/// The primary receiver of the product gets the rest of the revenue
+/// * `receiver`: Address of the co-receiver
+/// * `share_bps`: Share of the merchant amount of every payment in basis points
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RevenueSplitInfo {
+    pub receiver: String,
+    pub share_bps: u64,
+}
+
+/// MembershipNftInfo describes the cw721 membership tokens that a product mints for its subscriptions
+/// * `name`: Name of the token collection
+/// * `symbol`: Symbol of the token collection
+/// * `transferable`: Whether the owner of a membership token can transfer it to another address
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MembershipNftInfo {
+    pub name: String,
+    pub symbol: String,
+    pub transferable: bool,
 }
@@ -15,5 +15,11 @@ pub struct SubwalletFactoryConfig {
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     Config {},
-    GetSubwalletAddress { owner_address: String },
+    GetSubwalletAddress {
+        owner_address: String,
+    },
+    /// Reverse lookup of `GetSubwalletAddress`. Returns the owner of the subwallet, or `null` if the address is not a subwallet of the factory
+    GetSubwalletOwner {
+        subwallet_address: String,
+    },
 }
@@ -212,6 +212,7 @@ async function initContracts(client: Client) {
   console.log(`Attempting to upload subwallet_factory(1)`)
 
   const deployedState = readSuberraContracts(client.terra.config.chainID);
+  let subwallet_factory_contract = deployedState["subwallet_factory_contract"];
   console.log(deployedState);
   console.log(config);
   if (
@@ -220,7 +221,7 @@ async function initContracts(client: Client) {
     config.aterra_token_contract
   ) {
     console.log(`Attempting to upload subwallet_factory`)
-    await initContract(client, "subwallet_factory", {
+    subwallet_factory_contract = await initContract(client, "subwallet_factory", {
       subwallet_code_id: deployedState["subwallet_code_id"],
       anchor_market_contract: config.anchor_market_contract,
       aterra_token_addr: config.aterra_token_contract,
@@ -233,7 +234,8 @@ async function initContracts(client: Client) {
   // deploys the product subscription contract
   if (
     deployedState["subscription_product_code_id"] &&
-    deployedState["jobs_registry_contract"]
+    deployedState["jobs_registry_contract"] &&
+    subwallet_factory_contract
   ) {
     await initContract(client, "product_factory", {
       product_code_id: deployedState["subscription_product_code_id"],
@@ -241,8 +243,10 @@ async function initContracts(client: Client) {
       min_protocol_fee: "0", // $0
       min_amount_per_interval: "4000000", // $4
       min_unit_interval_hour: 24,
+      min_price_change_notice_hour: 720, // 30 days
       fee_address: client.wallet.key.accAddress, // Send to self
       job_registry_address: deployedState["jobs_registry_contract"],
+      subwallet_factory_address: subwallet_factory_contract,
     });
   }
 
@@ -252,6 +256,7 @@ async function initContracts(client: Client) {
       minimum_interval: 86400, // 1 day
       minimum_amount_per_interval: "10000000", // $10
       job_registry_contract: deployedState["jobs_registry_contract"],
+      subwallet_factory_contract,
       fee_bps: 0, // 0% fees
       // fee_address: client.wallet.key.accAddress, // omitted as it defaults to deployer
       max_fee: "1000000", // 1 usd